@@ -2,6 +2,8 @@ import { invoke } from '@tauri-apps/api/core';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import type {
   Settings,
+  ChannelMode,
+  CaptureMode,
   MicStatus,
   ModelStatus,
   LlmModelStatus,
@@ -9,22 +11,43 @@ import type {
   HistoryEntry,
   HistoryPage,
   HistoryStats,
+  CorruptModelNotice,
+  AppContext,
+  UsageStats,
+  LanguageOption,
+  SttMode,
+  SttProvider,
+  DbHealth,
+  DbRepair,
+  TrashEntry,
   DownloadProgress,
+  DownloadKind,
+  DownloadQueueProgress,
   TestPolishResult,
+  MatchedRule,
   GeneratedRule,
   PromptRule,
   WhisperModelInfo,
   SystemInfo,
+  Diagnostics,
   WhisperModelId,
+  WhisperModel,
   PolishModelInfo,
   PolishModel,
+  PolishMode,
+  CloudProvider,
   Qwen3AsrModelInfo,
   Qwen3AsrModelId,
   TranscriptionPartialPayload,
+  PastePendingPayload,
+  RateLimitedPayload,
+  MicDeviceChangedPayload,
   MeetingNote,
   PolishedMeetingNote,
   DataRootCheckResult,
   DataRootMigrationProgress,
+  OverlayStyle,
+  WhisperThreadBenchmark,
 } from './types';
 
 // ── Settings ──
@@ -34,20 +57,48 @@ export const getSettings = () => invoke<Settings>('get_settings');
 export const saveSettings = (newSettings: Settings) =>
   invoke<void>('save_settings', { newSettings });
 
+export const updateSttMode = (mode: SttMode) =>
+  invoke<void>('set_stt_mode', { mode });
+
+export const updateSttProvider = (provider: SttProvider) =>
+  invoke<void>('set_stt_provider', { provider });
+
+export const updatePolishMode = (mode: PolishMode) =>
+  invoke<void>('set_polish_mode', { mode });
+
 export const updateHotkey = (hotkey: string) =>
   invoke<void>('update_hotkey', { hotkey });
 
 export const updateEditHotkey = (hotkey: string) =>
   invoke<void>('update_edit_hotkey', { hotkey });
 
+export const updateRepeatHotkey = (hotkey: string) =>
+  invoke<void>('update_repeat_hotkey', { hotkey });
+
 export const updateMeetingHotkey = (hotkey: string | null) =>
   invoke<void>('update_meeting_hotkey', { hotkey });
 
+export const setPasteRawOnModifier = (enabled: boolean) =>
+  invoke<void>('set_paste_raw_on_modifier', { enabled });
+
 export const resetSettings = () => invoke<void>('reset_settings');
 
+export const restartOnboarding = () => invoke<void>('restart_onboarding');
+
+export const exportSettings = () => invoke<string>('export_settings');
+
+export const importSettings = (json: string) =>
+  invoke<string[]>('import_settings', { json });
+
 export const getDefaultPromptRules = (language?: string) =>
   invoke<PromptRule[]>('get_default_prompt_rules', { language: language ?? null });
 
+export const getDefaultFillerWords = (language: string) =>
+  invoke<string[]>('get_default_filler_words', { language });
+
+export const listSupportedLanguages = (provider: SttProvider) =>
+  invoke<LanguageOption[]>('list_supported_languages', { provider });
+
 // ── Recording ──
 
 export const startRecording = () => invoke<void>('start_recording');
@@ -56,6 +107,8 @@ export const stopRecording = () => invoke<string>('stop_recording');
 
 export const cancelRecording = () => invoke<void>('cancel_recording');
 
+export const cancelProcessing = () => invoke<void>('cancel_processing');
+
 export const setTestMode = (enabled: boolean) =>
   invoke<void>('set_test_mode', { enabled });
 
@@ -67,6 +120,8 @@ export const setContextOverride = (appName: string, bundleId: string, url: strin
 
 export const triggerUndo = () => invoke<void>('trigger_undo');
 
+export const dismissOverlay = () => invoke<void>('dismiss_overlay');
+
 export const setEditTextOverride = (text: string) =>
   invoke<void>('set_edit_text_override', { text });
 
@@ -74,19 +129,45 @@ export const setEditTextOverride = (text: string) =>
 
 export const getHistoryStats = () => invoke<HistoryStats>('get_history_stats');
 
+export const getUsageStats = (fromMs: number | null, toMs: number | null) =>
+  invoke<UsageStats>('get_usage_stats', { fromMs, toMs });
+
 export const getHistory = () => invoke<HistoryEntry[]>('get_history');
 
 export const getHistoryPage = (beforeTimestamp?: number, limit?: number) =>
   invoke<HistoryPage>('get_history_page', { beforeTimestamp: beforeTimestamp ?? null, limit: limit ?? null });
 
+export const getHistoryByTag = (tag: string, beforeTimestamp?: number, limit?: number) =>
+  invoke<HistoryPage>('get_history_by_tag', { tag, beforeTimestamp: beforeTimestamp ?? null, limit: limit ?? null });
+
+export const setEntryTags = (id: string, tags: string[]) => invoke<string[]>('set_entry_tags', { id, tags });
+
 export const deleteHistoryEntry = (id: string) =>
   invoke<void>('delete_history_entry', { id });
 
 export const exportHistoryAudio = (id: string) =>
   invoke<string>('export_history_audio', { id });
 
+export const exportHistorySrt = (id: string) =>
+  invoke<string>('export_history_srt', { id });
+
+export const repolishHistoryEntry = (id: string) =>
+  invoke<string>('repolish_history_entry', { id });
+
+export const copyEntryRaw = (id: string) => invoke<void>('copy_entry_raw', { id });
+
 export const clearAllHistory = () => invoke<void>('clear_all_history');
 
+export const getHistoryTrash = () => invoke<TrashEntry[]>('get_history_trash');
+
+export const restoreHistoryEntry = (id: string) => invoke<void>('restore_history_entry', { id });
+
+export const emptyHistoryTrash = () => invoke<void>('empty_history_trash');
+
+export const verifyHistoryDb = () => invoke<DbHealth>('verify_history_db');
+
+export const repairHistoryDb = () => invoke<DbRepair>('repair_history_db');
+
 export const getHistoryStoragePath = () => invoke<string>('get_history_storage_path');
 
 export const getAppIcon = (bundleId: string) =>
@@ -100,11 +181,33 @@ export const saveApiKey = (provider: string, key: string) =>
 export const getApiKey = (provider: string) =>
   invoke<string>('get_api_key', { provider });
 
+export const listStoredCredentials = () =>
+  invoke<string[]>('list_stored_credentials');
+
+export const clearAllCredentials = () => invoke<void>('clear_all_credentials');
+
 // ── Polish ──
 
 export const testPolish = (testText: string, customPrompt: string) =>
   invoke<TestPolishResult>('test_polish', { testText, customPrompt });
 
+export const testEdit = (selectedText: string, instruction: string, editPrompt: string) =>
+  invoke<TestPolishResult>('test_edit', { selectedText, instruction, editPrompt });
+
+export const previewSystemPrompt = (appName: string, bundleId: string, url: string) =>
+  invoke<string>('preview_system_prompt', { appName, bundleId, url });
+
+export const matchPromptRule = (appName: string, bundleId: string, url: string) =>
+  invoke<MatchedRule | null>('match_prompt_rule', { appName, bundleId, url });
+
+export const getCurrentContext = () => invoke<AppContext>('get_current_context');
+
+export const setNextPolishModel = (model: string | null) =>
+  invoke<void>('set_next_polish_model', { model });
+
+export const listCloudModels = (provider: CloudProvider, apiKey: string, endpoint: string) =>
+  invoke<string[]>('list_cloud_models', { provider, apiKey, endpoint });
+
 export const generateRuleFromDescription = (description: string) =>
   invoke<GeneratedRule>('generate_rule_from_description', { description });
 
@@ -118,6 +221,9 @@ export const checkLlmModelStatus = () => invoke<LlmModelStatus>('check_llm_model
 
 export const downloadLlmModel = () => invoke<void>('download_llm_model');
 
+export const cancelDownload = (kind: DownloadKind) =>
+  invoke<void>('cancel_download', { kind });
+
 // ── Mic & Permissions ──
 
 export const getMicStatus = () => invoke<MicStatus>('get_mic_status');
@@ -125,6 +231,21 @@ export const getMicStatus = () => invoke<MicStatus>('get_mic_status');
 export const setMicDevice = (deviceName: string | null) =>
   invoke<void>('set_mic_device', { deviceName });
 
+export const setChannelMode = (mode: ChannelMode) =>
+  invoke<void>('set_channel_mode', { mode });
+
+export const setCaptureMode = (mode: CaptureMode) =>
+  invoke<void>('set_capture_mode', { mode });
+
+export const onMicDeviceChanged = (cb: (payload: MicDeviceChangedPayload) => void): Promise<UnlistenFn> =>
+  listen<MicDeviceChangedPayload>('mic-device-changed', (e) => cb(e.payload));
+
+export const startMicMonitor = () => invoke<void>('start_mic_monitor');
+
+export const getCurrentInputLevel = () => invoke<number>('get_current_input_level');
+
+export const stopMicMonitor = () => invoke<void>('stop_mic_monitor');
+
 export const checkPermissions = () => invoke<PermissionStatus>('check_permissions');
 
 export const openPermissionSettings = (permissionType: string) =>
@@ -138,14 +259,32 @@ export const onRecordingStatus = (cb: (status: string) => void): Promise<Unliste
 export const onRecordingMaxDuration = (cb: (secs: number) => void): Promise<UnlistenFn> =>
   listen<number>('recording-max-duration', (e) => cb(e.payload));
 
+export const onOverlayStyle = (cb: (style: OverlayStyle) => void): Promise<UnlistenFn> =>
+  listen<OverlayStyle>('overlay-style', (e) => cb(e.payload));
+
 export const onAudioLevels = (cb: (levels: number[]) => void): Promise<UnlistenFn> =>
   listen<number[]>('audio-levels', (e) => cb(e.payload));
 
+export const onMicLevel = (cb: (level: number) => void): Promise<UnlistenFn> =>
+  listen<number>('mic-level', (e) => cb(e.payload));
+
 export const onTranscriptionPartial = (
   cb: (payload: TranscriptionPartialPayload) => void,
 ): Promise<UnlistenFn> =>
   listen<TranscriptionPartialPayload>('transcription-partial', (e) => cb(e.payload));
 
+export const onPastePending = (cb: (payload: PastePendingPayload) => void): Promise<UnlistenFn> =>
+  listen<PastePendingPayload>('paste-pending', (e) => cb(e.payload));
+
+export const onRateLimited = (cb: (payload: RateLimitedPayload) => void): Promise<UnlistenFn> =>
+  listen<RateLimitedPayload>('rate-limited', (e) => cb(e.payload));
+
+export const onPolishReasoning = (cb: (text: string) => void): Promise<UnlistenFn> =>
+  listen<string>('polish-reasoning', (e) => cb(e.payload));
+
+export const onPolishStream = (cb: (text: string) => void): Promise<UnlistenFn> =>
+  listen<string>('polish-stream', (e) => cb(e.payload));
+
 export const onTranscriptionResult = (cb: (text: string) => void): Promise<UnlistenFn> =>
   listen<string>('transcription-result', (e) => cb(e.payload));
 
@@ -162,6 +301,20 @@ export const onLlmModelDownloadProgress = (
 ): Promise<UnlistenFn> =>
   listen<DownloadProgress>('llm-model-download-progress', (e) => cb(e.payload));
 
+export const onDownloadQueueProgress = (
+  cb: (p: DownloadQueueProgress) => void,
+): Promise<UnlistenFn> =>
+  listen<DownloadQueueProgress>('download-queue-progress', (e) => cb(e.payload));
+
+export const onShowSetup = (cb: () => void): Promise<UnlistenFn> =>
+  listen<null>('show-setup', () => cb());
+
+export const onContextUnavailable = (cb: () => void): Promise<UnlistenFn> =>
+  listen('context-unavailable', () => cb());
+
+export const onPolishModelCorrupt = (cb: (notice: CorruptModelNotice) => void): Promise<UnlistenFn> =>
+  listen<CorruptModelNotice>('polish-model-corrupt', (e) => cb(e.payload));
+
 export const onVoiceRuleStatus = (cb: (status: string) => void): Promise<UnlistenFn> =>
   listen<string>('voice-rule-status', (e) => cb(e.payload));
 
@@ -179,10 +332,15 @@ export const listWhisperModels = () =>
 export const getSystemInfo = () =>
   invoke<SystemInfo>('get_system_info');
 
+export const getDiagnostics = () => invoke<Diagnostics>('get_diagnostics');
+
 export const getWhisperModelRecommendation = () =>
   invoke<WhisperModelId>('get_whisper_model_recommendation');
 
-export const switchWhisperModel = (model: WhisperModelId) =>
+export const benchmarkWhisperThreads = () =>
+  invoke<WhisperThreadBenchmark>('benchmark_whisper_threads');
+
+export const switchWhisperModel = (model: WhisperModel) =>
   invoke<void>('switch_whisper_model', { model });
 
 export const downloadWhisperModel = (model: WhisperModelId) =>
@@ -193,6 +351,14 @@ export const onWhisperModelDownloadProgress = (
 ): Promise<UnlistenFn> =>
   listen<DownloadProgress>('whisper-model-download-progress', (e) => cb(e.payload));
 
+export const onWhisperCoremlStatus = (
+  cb: (s: { status: 'downloading' | 'complete' | 'unavailable' | 'error'; message: string }) => void,
+): Promise<UnlistenFn> =>
+  listen<{ status: 'downloading' | 'complete' | 'unavailable' | 'error'; message: string }>(
+    'whisper-coreml-status',
+    (e) => cb(e.payload),
+  );
+
 // ── Polish Models ──
 
 export const listPolishModels = () =>
@@ -235,6 +401,8 @@ export const isDevMode = () => invoke<boolean>('is_dev_mode');
 
 export const exportDiagnosticLog = () => invoke<string>('export_diagnostic_log');
 
+export const getLogPath = () => invoke<string>('get_log_path');
+
 // ── Qwen3-ASR Model ──
 
 export const listQwen3AsrModels = () =>
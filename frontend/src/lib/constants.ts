@@ -9,12 +9,13 @@ export const DEFAULT_MEETING_HOTKEY = 'Alt+KeyM';
 // ── Modifier display symbols (platform-aware) ──
 
 const isMac = typeof navigator !== 'undefined' && navigator.platform?.toLowerCase().includes('mac');
+const isWindows = typeof navigator !== 'undefined' && navigator.platform?.toLowerCase().startsWith('win');
 
 export const MODIFIER_SYMBOLS: Record<string, string> = isMac
   ? { Alt: '⌥', Control: '⌃', Shift: '⇧', Super: '⌘' }
   : { Alt: 'Alt', Control: 'Ctrl', Shift: 'Shift', Super: 'Win' };
 
-export { isMac };
+export { isMac, isWindows };
 
 export function formatHotkeyDisplay(hotkeyStr: string): string {
   return hotkeyStr
@@ -83,6 +84,14 @@ export const CLOUD_PROVIDERS: Record<CloudProvider, CloudProviderMeta> = {
     ],
     apiKeyUrl: 'https://cloud.sambanova.ai/apis',
   },
+  anthropic: {
+    models: [
+      { id: 'claude-3-5-haiku-latest', name: 'Claude 3.5 Haiku' },
+      { id: 'claude-haiku-4-5', name: 'Claude Haiku 4.5' },
+      { id: 'claude-sonnet-4-5', name: 'Claude Sonnet 4.5' },
+    ],
+    apiKeyUrl: 'https://console.anthropic.com/settings/keys',
+  },
   custom: {
     models: [],
   },
@@ -126,6 +135,7 @@ export const CLOUD_PROVIDER_LABELS: Record<CloudProvider, string> = {
   open_ai: 'OpenAI',
   gemini: 'Gemini',
   samba_nova: 'SambaNova',
+  anthropic: 'Anthropic',
   custom: 'Custom',
 };
 
@@ -6,14 +6,26 @@ import type {
   SttCloudConfig,
   PromptRule,
   DictionaryConfig,
+  NamedDictionary,
   PolishModel,
   PolishMode,
   SttMode,
+  SessionMode,
+  HotkeyMode,
+  WhisperQuality,
   CloudProvider,
   SttProvider,
-  WhisperModelId,
+  WhisperModel,
   LocalSttEngine,
   Qwen3AsrModelId,
+  HistoryAudioFormat,
+  HistoryAudioBitDepth,
+  OverlayStyle,
+  EditScope,
+  LocalApiConfig,
+  TrailingBehavior,
+  LogLevel,
+  InsertionMethod,
 } from '../types';
 import * as api from '../api';
 import { DEFAULT_HOTKEY } from '../constants';
@@ -28,27 +40,79 @@ let settings = $state<Settings>({
     model: 'phi4_mini',
     custom_prompt: null,
     mode: 'local',
-    cloud: { provider: 'groq', api_key: '', endpoint: '', model_id: 'qwen/qwen3-32b' },
+    cloud: { provider: 'groq', api_key: '', endpoint: '', model_id: 'qwen/qwen3-32b', stream: false },
     prompt_rules: {},
-    dictionary: { enabled: true, entries: [] },
+    dictionary: { enabled: true },
+    dictionaries: [{ name: 'Personal', enabled: true, entries: [] }],
     reasoning: false,
+    preserve_markup: false,
+    translate_to: null,
+    context_aware: true,
+    context_from_history: 0,
+    hallucination_ratio: 3,
+    hallucination_abs: 200,
+    edit_system_prompt: null,
   },
   history_retention_days: 0,
   language: null,
   stt: {
     mode: 'local',
-    cloud: { provider: 'deepgram', api_key: '', endpoint: '', model_id: 'whisper', language: 'auto' },
+    cloud: { provider: 'deepgram', api_key: '', endpoint: '', model_id: 'whisper', language: 'auto', verbose: false, cloud_native_rate: false, compress_upload: false, stt_prompt: '' },
     whisper_model: 'large_v3_turbo',
     local_engine: 'whisper',
     qwen3_asr_model: 'qwen3_asr1_7_b',
     language: 'auto',
+    chunk_threshold_secs: 60,
+    whisper_quality: 'fast',
   },
   edit_hotkey: null,
   onboarding_completed: false,
   mic_device: null,
+  channel_mode: 'average',
+  capture_mode: 'microphone',
   meeting_hotkey: null,
   idle_mic_timeout_secs: 0,
   record_meeting_audio: false,
+  idle_unload_minutes: 0,
+  input_gain_db: 0,
+  auto_gain: false,
+  paste_raw_on_modifier: false,
+  auto_stop_silence_ms: 0,
+  history_audio_format: 'wav',
+  history_audio_bit_depth: '16',
+  overlay_auto_hide_ms: 1500,
+  remove_fillers: false,
+  filler_words: [],
+  force_cpu: false,
+  verify_target_on_paste: false,
+  overlay_style: 'full',
+  whisper_threads: null,
+  edit_scope: 'selection',
+  history_max_entries: 0,
+  history_max_audio_bytes: 0,
+  history_trash_retention_days: 30,
+  repeat_paste_hotkey: null,
+  normalize_numbers: false,
+  smart_capitalize: false,
+  overlay_show_reasoning: false,
+  whisper_coreml: false,
+  local_api: { enabled: false, port: 47821, token: '' },
+  trailing_behavior: 'as_is',
+  visualizer_gain: 1.0,
+  max_processing_secs: 120,
+  plain_text_only: true,
+  hotkey_debounce_ms: 300,
+  log_level: 'info',
+  insertion_method: 'clipboard_paste',
+  keystroke_delay_ms: 0,
+  paste_grace_ms: 0,
+  session_mode: 'single',
+  queue_dictations: false,
+  http_proxy: null,
+  max_recording_secs: 120,
+  hotkey_mode: 'toggle',
+  cloud_max_retries: 3,
+  silence_threshold_db: -40,
 });
 
 export function getSettings(): Settings {
@@ -166,6 +230,30 @@ export function setPolishReasoning(reasoning: boolean) {
   settings.polish.reasoning = reasoning;
 }
 
+export function setPolishPreserveMarkup(preserve: boolean) {
+  settings.polish.preserve_markup = preserve;
+}
+
+export function setPolishTranslateTo(language: string | null) {
+  settings.polish.translate_to = language;
+}
+
+export function setPolishContextAware(enabled: boolean) {
+  settings.polish.context_aware = enabled;
+}
+
+export function setPolishContextFromHistory(count: number) {
+  settings.polish.context_from_history = count;
+}
+
+export function setPolishHallucinationRatio(ratio: number) {
+  settings.polish.hallucination_ratio = ratio;
+}
+
+export function setPolishEditSystemPrompt(prompt: string | null) {
+  settings.polish.edit_system_prompt = prompt;
+}
+
 export function setPolishCloudProvider(provider: CloudProvider) {
   settings.polish.cloud.provider = provider;
 }
@@ -182,6 +270,10 @@ export function setPolishCloudModelId(modelId: string) {
   settings.polish.cloud.model_id = modelId;
 }
 
+export function setPolishCloudStream(stream: boolean) {
+  settings.polish.cloud.stream = stream;
+}
+
 export function setSttMode(mode: SttMode) {
   settings.stt.mode = mode;
 }
@@ -202,6 +294,22 @@ export function setSttCloudModelId(modelId: string) {
   settings.stt.cloud.model_id = modelId;
 }
 
+export function setSttCloudNativeRate(enabled: boolean) {
+  settings.stt.cloud.cloud_native_rate = enabled;
+}
+
+export function setSttCloudCompressUpload(enabled: boolean) {
+  settings.stt.cloud.compress_upload = enabled;
+}
+
+export function setSttCloudPrompt(prompt: string) {
+  settings.stt.cloud.stt_prompt = prompt;
+}
+
+export function setSttWhisperQuality(quality: WhisperQuality) {
+  settings.stt.whisper_quality = quality;
+}
+
 export function setSttLanguage(lang: string) {
   settings.stt.language = lang;
   settings.stt.cloud.language = lang;
@@ -212,7 +320,7 @@ export function setSttCloudLanguage(lang: string) {
   settings.stt.language = lang;
 }
 
-export function setSttWhisperModel(model: WhisperModelId) {
+export function setSttWhisperModel(model: WhisperModel) {
   settings.stt.whisper_model = model;
 }
 
@@ -241,6 +349,154 @@ export function setRecordMeetingAudio(v: boolean) {
   settings.record_meeting_audio = v;
 }
 
+export function setIdleUnloadMinutes(v: number) {
+  settings.idle_unload_minutes = v;
+}
+
+export function setInputGainDb(v: number) {
+  settings.input_gain_db = v;
+}
+
+export function setAutoGain(v: boolean) {
+  settings.auto_gain = v;
+}
+
+export function setAutoStopSilenceMs(v: number) {
+  settings.auto_stop_silence_ms = v;
+}
+
+export function setHistoryAudioFormat(v: HistoryAudioFormat) {
+  settings.history_audio_format = v;
+}
+
+export function setHistoryAudioBitDepth(v: HistoryAudioBitDepth) {
+  settings.history_audio_bit_depth = v;
+}
+
+export function setOverlayAutoHideMs(v: number) {
+  settings.overlay_auto_hide_ms = v;
+}
+
+export function setRemoveFillers(v: boolean) {
+  settings.remove_fillers = v;
+}
+
+export function setFillerWords(v: string[]) {
+  settings.filler_words = v;
+}
+
+export function setForceCpu(v: boolean) {
+  settings.force_cpu = v;
+}
+
+export function setVerifyTargetOnPaste(v: boolean) {
+  settings.verify_target_on_paste = v;
+}
+
+export function setOverlayStyle(v: OverlayStyle) {
+  settings.overlay_style = v;
+}
+
+export function setWhisperThreads(v: number | null) {
+  settings.whisper_threads = v;
+}
+
+export function setEditScope(v: EditScope) {
+  settings.edit_scope = v;
+}
+
+export function setHistoryMaxEntries(v: number) {
+  settings.history_max_entries = v;
+}
+
+export function setHistoryMaxAudioBytes(v: number) {
+  settings.history_max_audio_bytes = v;
+}
+
+export function setRepeatPasteHotkey(v: string | null) {
+  settings.repeat_paste_hotkey = v;
+}
+
+export function setNormalizeNumbers(v: boolean) {
+  settings.normalize_numbers = v;
+}
+
+export function setSmartCapitalize(v: boolean) {
+  settings.smart_capitalize = v;
+}
+
+export function setOverlayShowReasoning(v: boolean) {
+  settings.overlay_show_reasoning = v;
+}
+
+export function setWhisperCoreml(v: boolean) {
+  settings.whisper_coreml = v;
+}
+
+export function setLocalApi(v: LocalApiConfig) {
+  settings.local_api = v;
+}
+
+export function setTrailingBehavior(v: TrailingBehavior) {
+  settings.trailing_behavior = v;
+}
+
+export function setVisualizerGain(v: number) {
+  settings.visualizer_gain = v;
+}
+
+export function setMaxProcessingSecs(v: number) {
+  settings.max_processing_secs = v;
+}
+
+export function setPlainTextOnly(v: boolean) {
+  settings.plain_text_only = v;
+}
+
+export function setHotkeyDebounceMs(v: number) {
+  settings.hotkey_debounce_ms = v;
+}
+
+export function setLogLevel(v: LogLevel) {
+  settings.log_level = v;
+}
+
+export function setInsertionMethod(v: InsertionMethod) {
+  settings.insertion_method = v;
+}
+
+export function setKeystrokeDelayMs(v: number) {
+  settings.keystroke_delay_ms = v;
+}
+
+export function setPasteGraceMs(v: number) {
+  settings.paste_grace_ms = v;
+}
+
+export function setSessionMode(v: SessionMode) {
+  settings.session_mode = v;
+}
+
+export function setQueueDictations(v: boolean) {
+  settings.queue_dictations = v;
+}
+
+export function setHttpProxy(v: string | null) {
+  settings.http_proxy = v;
+}
+
+export function setMaxRecordingSecs(v: number) {
+  settings.max_recording_secs = v;
+}
+
+export function setHotkeyMode(v: HotkeyMode) {
+  settings.hotkey_mode = v;
+}
+
+export function setSilenceThresholdDb(v: number) {
+  settings.silence_threshold_db = v;
+}
+
 // ── Prompt rules ──
 
 export function getCurrentRules(): PromptRule[] {
@@ -275,6 +531,18 @@ export function setDictionaryEnabled(enabled: boolean) {
   settings.polish.dictionary.enabled = enabled;
 }
 
+export function getDictionaries(): NamedDictionary[] {
+  return settings.polish.dictionaries;
+}
+
+export function addDictionary(name: string) {
+  settings.polish.dictionaries.push({ name, enabled: true, entries: [] });
+}
+
+export function removeDictionary(index: number) {
+  settings.polish.dictionaries.splice(index, 1);
+}
+
 export function setCustomPrompt(prompt: string | null) {
   settings.polish.custom_prompt = prompt;
 }
@@ -4,12 +4,22 @@ export type SttMode = 'local' | 'cloud';
 
 export type SttProvider = 'deepgram' | 'groq' | 'open_ai' | 'azure' | 'custom';
 
+export interface LanguageOption {
+  code: string;
+  name: string;
+}
+
 export interface SttCloudConfig {
   provider: SttProvider;
   api_key: string;
   endpoint: string;
   model_id: string;
   language: string;
+  verbose: boolean;
+  response_text_path?: string | null;
+  cloud_native_rate: boolean;
+  compress_upload: boolean;
+  stt_prompt: string;
 }
 
 export type WhisperModelId =
@@ -20,6 +30,11 @@ export type WhisperModelId =
   | 'base'
   | 'large_v3_turbo_zh_tw';
 
+/** A user-supplied GGML file outside the models directory. */
+export type CustomWhisperModel = { custom: { path: string } };
+
+export type WhisperModel = WhisperModelId | CustomWhisperModel;
+
 export type LocalSttEngine = 'whisper' | 'qwen3_asr';
 
 export type Qwen3AsrModelId = 'qwen3_asr1_7_b' | 'qwen3_asr0_6_b';
@@ -43,6 +58,13 @@ export interface WhisperModelInfo {
   downloaded: boolean;
   file_size_on_disk: number;
   is_active: boolean;
+  coreml_downloaded: boolean;
+}
+
+export interface WhisperThreadBenchmark {
+  recommended: number;
+  auto_threads: number;
+  runs: [number, number][];
 }
 
 export interface SystemInfo {
@@ -51,6 +73,7 @@ export interface SystemInfo {
   is_apple_silicon: boolean;
   gpu_vram_bytes: number;
   has_cuda: boolean;
+  has_coreml: boolean;
   os: string;
   arch: string;
 }
@@ -58,12 +81,16 @@ export interface SystemInfo {
 export interface SttConfig {
   mode: SttMode;
   cloud: SttCloudConfig;
-  whisper_model: WhisperModelId;
+  whisper_model: WhisperModel;
   local_engine: LocalSttEngine;
   qwen3_asr_model: Qwen3AsrModelId;
   language: string;
+  chunk_threshold_secs: number;
+  whisper_quality: WhisperQuality;
 }
 
+export type WhisperQuality = 'fast' | 'balanced' | 'accurate';
+
 // ── Polish ──
 
 export type PolishMode = 'local' | 'cloud';
@@ -75,6 +102,7 @@ export type CloudProvider =
   | 'open_ai'
   | 'gemini'
   | 'samba_nova'
+  | 'anthropic'
   | 'custom';
 
 export type PolishModel = 'phi4_mini' | 'ministral3b' | 'ministral14b' | 'qwen3_4b' | 'qwen3_8b';
@@ -106,6 +134,10 @@ export interface PromptRule {
   enabled: boolean;
   icon?: string;
   alt_matches?: MatchCondition[];
+  /** STT language used when this rule matches ("multi" = auto-detect). */
+  stt_language_override?: string | null;
+  /** Paste (true) or copy only (false) when this rule matches; unset = global setting. */
+  auto_paste_override?: boolean | null;
 }
 
 export interface DictionaryEntry {
@@ -115,6 +147,11 @@ export interface DictionaryEntry {
 
 export interface DictionaryConfig {
   enabled: boolean;
+}
+
+export interface NamedDictionary {
+  name: string;
+  enabled: boolean;
   entries: DictionaryEntry[];
 }
 
@@ -123,6 +160,7 @@ export interface CloudConfig {
   api_key: string;
   endpoint: string;
   model_id: string;
+  stream: boolean;
 }
 
 export interface PolishConfig {
@@ -133,11 +171,43 @@ export interface PolishConfig {
   cloud: CloudConfig;
   prompt_rules: Record<string, PromptRule[]>;
   dictionary: DictionaryConfig;
+  dictionaries: NamedDictionary[];
   reasoning: boolean;
+  preserve_markup: boolean;
+  translate_to: string | null;
+  context_aware: boolean;
+  context_from_history: number;
+  hallucination_ratio: number;
+  hallucination_abs: number;
+  edit_system_prompt: string | null;
 }
 
 // ── Settings ──
 
+export type HistoryAudioFormat = 'wav' | 'mp3' | 'opus' | 'none';
+
+export type HistoryAudioBitDepth = '16' | '24' | '32f';
+
+export type OverlayStyle = 'full' | 'compact' | 'dot_only';
+
+export type EditScope = 'selection' | 'clipboard' | 'select_all';
+
+export type TrailingBehavior = 'trim' | 'add_space' | 'add_newline' | 'as_is';
+
+export type SessionMode = 'single' | 'continuous';
+
+export type HotkeyMode = 'toggle' | 'push_to_talk';
+
+export type InsertionMethod = 'clipboard_paste' | 'accessibility' | 'type_keystrokes';
+
+export type LogLevel = 'error' | 'warn' | 'info' | 'debug' | 'trace';
+
+export interface LocalApiConfig {
+  enabled: boolean;
+  port: number;
+  token: string;
+}
+
 export interface Settings {
   hotkey: string;
   auto_paste: boolean;
@@ -148,17 +218,64 @@ export interface Settings {
   edit_hotkey: string | null;
   onboarding_completed: boolean;
   mic_device: string | null;
+  channel_mode: ChannelMode;
+  capture_mode: CaptureMode;
   meeting_hotkey: string | null;
   idle_mic_timeout_secs: number;
   record_meeting_audio: boolean;
+  idle_unload_minutes: number;
+  input_gain_db: number;
+  auto_gain: boolean;
+  paste_raw_on_modifier: boolean;
+  auto_stop_silence_ms: number;
+  history_audio_format: HistoryAudioFormat;
+  history_audio_bit_depth: HistoryAudioBitDepth;
+  overlay_auto_hide_ms: number;
+  remove_fillers: boolean;
+  filler_words: string[];
+  force_cpu: boolean;
+  verify_target_on_paste: boolean;
+  overlay_style: OverlayStyle;
+  whisper_threads: number | null;
+  edit_scope: EditScope;
+  history_max_entries: number;
+  history_max_audio_bytes: number;
+  history_trash_retention_days: number;
+  repeat_paste_hotkey: string | null;
+  normalize_numbers: boolean;
+  smart_capitalize: boolean;
+  overlay_show_reasoning: boolean;
+  whisper_coreml: boolean;
+  local_api: LocalApiConfig;
+  trailing_behavior: TrailingBehavior;
+  visualizer_gain: number;
+  max_processing_secs: number;
+  plain_text_only: boolean;
+  hotkey_debounce_ms: number;
+  log_level: LogLevel;
+  insertion_method: InsertionMethod;
+  keystroke_delay_ms: number;
+  paste_grace_ms: number;
+  session_mode: SessionMode;
+  queue_dictations: boolean;
+  http_proxy: string | null;
+  max_recording_secs: number;
+  hotkey_mode: HotkeyMode;
+  cloud_max_retries: number;
+  silence_threshold_db: number;
   data_root?: string | null;
 }
 
+export type ChannelMode = 'average' | 'left' | 'right' | { channel: number };
+
+export type CaptureMode = 'microphone' | 'system_audio';
+
 export interface DataRootCheckResult {
   has_enough_space: boolean;
   already_has_data: boolean;
   free_bytes: number;
   data_size_bytes: number;
+  is_writable: boolean;
 }
 
 export interface DataRootMigrationProgress {
@@ -179,6 +296,7 @@ export interface HistoryEntry {
   polish_model: string;
   duration_secs: number;
   has_audio: boolean;
+  audio_format: HistoryAudioFormat;
   stt_elapsed_ms: number;
   polish_elapsed_ms: number | null;
   total_elapsed_ms: number;
@@ -186,6 +304,21 @@ export interface HistoryEntry {
   bundle_id: string;
   chars_per_sec: number;
   word_count: number;
+  confidence?: number;
+  source_language?: string;
+  target_language?: string;
+  tags: string[];
+  /** Per-word timings of `raw_text`, from providers that report them (Deepgram). */
+  word_timings?: WordTiming[];
+  app_context?: AppContext;
+  /** Why polish fell back to the raw text, if it did. */
+  fallback_reason?: string;
+}
+
+export interface WordTiming {
+  word: string;
+  start_secs: number;
+  end_secs: number;
 }
 
 export interface HistoryPage {
@@ -205,6 +338,41 @@ export interface HistoryStats {
   local_polish_output_chars: number;
 }
 
+export interface CorruptModelNotice {
+  model: PolishModel;
+  failures: number;
+  error: string;
+}
+
+export interface ModelUsage {
+  model: string;
+  entries: number;
+  duration_secs: number;
+}
+
+export interface UsageStats {
+  total_entries: number;
+  total_duration_secs: number;
+  total_chars: number;
+  avg_total_elapsed_ms: number;
+  stt_models: ModelUsage[];
+  polish_models: ModelUsage[];
+}
+
+export interface DbHealth {
+  ok: boolean;
+  problems: string[];
+}
+
+export interface TrashEntry extends HistoryEntry {
+  deleted_at: number;
+}
+
+export interface DbRepair {
+  recovered_rows: number;
+  quarantined_path: string;
+}
+
 // ── API responses ──
 
 export interface MicStatus {
@@ -213,6 +381,12 @@ export interface MicStatus {
   devices: string[];
 }
 
+/** The chosen input device was missing; recording used `actual` instead. */
+export interface MicDeviceChangedPayload {
+  requested: string;
+  actual: string | null;
+}
+
 export interface ModelStatus {
   engine: string;
   model_exists: boolean;
@@ -229,6 +403,68 @@ export interface PermissionStatus {
   accessibility: boolean;
 }
 
+export interface ModelFileStatus {
+  kind: 'whisper' | 'qwen3_asr' | 'polish' | 'vad';
+  name: string;
+  downloaded: boolean;
+  size_bytes: number;
+}
+
+export interface Diagnostics {
+  app_version: string;
+  os: string;
+  os_version: string;
+  arch: string;
+  mic: MicStatus;
+  permissions: PermissionStatus;
+  stt_mode: SttMode;
+  stt_model: string;
+  polish_enabled: boolean;
+  polish_mode: PolishMode;
+  polish_model: string;
+  models: ModelFileStatus[];
+  keychain_ok: boolean;
+  keychain_error: string | null;
+  log_path: string;
+  log_tail: string[];
+}
+
+export type SumiErrorKind =
+  | 'missing_api_key'
+  | 'auth'
+  | 'rate_limited'
+  | 'network'
+  | 'model_not_found'
+  | 'config'
+  | 'no_speech'
+  | 'cancelled'
+  | 'provider'
+  | 'other';
+
+/** Typed error rejected by cloud-backed commands (`test_polish`, `generate_rule_from_description`). */
+export interface SumiError {
+  kind: SumiErrorKind;
+  message: string;
+  /** Only on `rate_limited`, when the provider said when to retry. */
+  retry_after_secs?: number;
+}
+
+/** Frontmost app as context detection sees it (`get_current_context`). */
+export interface AppContext {
+  app_name: string;
+  bundle_id: string;
+  url: string;
+  /** Terminal app name when `app_name` was replaced by the CLI tool running in it. */
+  terminal_host: string;
+}
+
+/** Rule `match_prompt_rule` reports for a synthetic app context. */
+export interface MatchedRule {
+  name: string;
+  /** Index into the rule's `alt_matches`; null when the primary condition matched. */
+  alt_index: number | null;
+}
+
 export interface TestPolishResult {
   current_result: string;
   edited_result: string;
@@ -241,14 +477,33 @@ export interface GeneratedRule {
   prompt: string;
 }
 
+/** Prefix of a model download's `<kind>-download-progress` event. */
+export type DownloadKind =
+  | 'model'
+  | 'llm-model'
+  | 'polish-model'
+  | 'whisper-model'
+  | 'vad-model'
+  | 'qwen3-asr'
+  | 'diarization-model'
+  | 'segmentation-model';
+
 export interface DownloadProgress {
-  status: 'downloading' | 'complete' | 'error';
+  status: 'downloading' | 'complete' | 'error' | 'cancelled';
   downloaded?: number;
   total?: number;
   message?: string;
   current_file?: string;
 }
 
+export interface DownloadQueueProgress {
+  downloads: Record<string, { kind: DownloadKind; downloaded: number; total: number }>;
+  count: number;
+  downloaded: number;
+  total: number;
+  percent: number;
+}
+
 // ── Meeting Notes ──
 
 export interface MeetingNote {
@@ -284,6 +539,15 @@ export type Page =
 
 // ── Streaming ──
 
+export interface PastePendingPayload {
+  text: string;
+  grace_ms: number;
+}
+
+export interface RateLimitedPayload {
+  retry_after_secs: number | null;
+}
+
 export interface TranscriptionPartialPayload {
   text: string;
 }
@@ -291,6 +555,7 @@ export interface TranscriptionPartialPayload {
 // ── Overlay ──
 
 export type OverlayStatus =
+  | 'idle'
   | 'preparing'
   | 'recording'
   | 'edit_recording'
@@ -301,6 +566,9 @@ export type OverlayStatus =
   | 'polishing'
   | 'pasted'
   | 'copied'
+  | 'copied_target_changed'
+  | 'copied_typing_incomplete'
+  | 'needs-accessibility'
   | 'error'
   | 'edited'
   | 'edit_requires_polish';
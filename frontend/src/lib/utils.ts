@@ -1,3 +1,5 @@
+import type { SumiError } from './types';
+
 /** Format bytes as human-readable size string (e.g. "1.5 GB", "800 MB"). */
 export function formatSize(bytes: number): string {
   if (bytes >= 1_073_741_824) return (bytes / 1_073_741_824).toFixed(1) + ' GB';
@@ -8,3 +10,8 @@ export function formatSize(bytes: number): string {
 export function camelCase(id: string): string {
   return id.replace(/_([a-z0-9])/g, (_, c) => c.toUpperCase());
 }
+
+/** Narrow a rejected `invoke` value to a typed backend error. */
+export function isSumiError(e: unknown): e is SumiError {
+  return typeof e === 'object' && e !== null && 'kind' in e && 'message' in e;
+}
@@ -1,19 +1,173 @@
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
-    mpsc, Arc, Mutex,
+    mpsc, Arc, Mutex, OnceLock, RwLock,
 };
 use std::time::{Duration, Instant};
 
-use crate::stt::{LocalSttEngine, SttConfig, SttMode};
+use crate::error::SumiError;
+use crate::stt::{LocalSttEngine, SttConfig, SttMode, WordTiming};
 use crate::transcribe::transcribe_with_cached_whisper;
 
+/// How multi-channel input frames are folded into the mono capture buffer.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelMode {
+    /// Average all channels (the historical behaviour).
+    #[default]
+    Average,
+    Left,
+    Right,
+    /// Zero-based channel index, for mic arrays and multi-input interfaces.
+    Channel(usize),
+}
+
+static CHANNEL_MODE: OnceLock<RwLock<ChannelMode>> = OnceLock::new();
+
+fn channel_mode_lock() -> &'static RwLock<ChannelMode> {
+    CHANNEL_MODE.get_or_init(|| RwLock::new(ChannelMode::default()))
+}
+
+/// Set the channel layout used by streams opened from now on.  Called once
+/// after `load_settings()` and whenever the user changes the setting; the
+/// caller closes the current stream so the next recording picks it up.
+pub fn set_channel_mode(mode: ChannelMode) {
+    if let Ok(mut guard) = channel_mode_lock().write() {
+        *guard = mode;
+    }
+}
+
+fn current_channel_mode() -> ChannelMode {
+    channel_mode_lock().read().map(|g| g.clone()).unwrap_or_default()
+}
+
+/// Where the capture stream gets its audio from.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureMode {
+    /// The selected (or default) input device.
+    #[default]
+    Microphone,
+    /// Whatever is playing on the default output device, for transcribing
+    /// calls and videos.  Implemented on Windows via WASAPI loopback.  macOS
+    /// has no OS-level loopback: install a virtual device such as BlackHole,
+    /// route output to it, and pick it as the input device instead.
+    SystemAudio,
+}
+
+static CAPTURE_MODE: RwLock<CaptureMode> = RwLock::new(CaptureMode::Microphone);
+
+/// Set the capture source used by streams opened from now on.  Same contract
+/// as [`set_channel_mode`]: the caller closes the current stream.
+pub fn set_capture_mode(mode: CaptureMode) {
+    if let Ok(mut guard) = CAPTURE_MODE.write() {
+        *guard = mode;
+    }
+}
+
+/// Whether the next stream should loop back the default output device.
+/// Only WASAPI can open an output device as an input; elsewhere the setting
+/// falls back to the microphone.
+pub(crate) fn loopback_capture() -> bool {
+    cfg!(target_os = "windows") && current_capture_mode() == CaptureMode::SystemAudio
+}
+
+fn current_capture_mode() -> CaptureMode {
+    CAPTURE_MODE.read().map(|g| *g).unwrap_or_default()
+}
+
+/// Fold one interleaved frame into a single mono sample according to `mode`.
+/// A channel index the device doesn't have falls back to averaging.
+#[inline]
+fn mix_frame<T: Copy>(frame: &[T], mode: &ChannelMode, to_f32: impl Fn(T) -> f32) -> f32 {
+    let pick = match mode {
+        ChannelMode::Average => None,
+        ChannelMode::Left => Some(0),
+        ChannelMode::Right => Some(1),
+        ChannelMode::Channel(i) => Some(*i),
+    };
+    match pick.and_then(|i| frame.get(i)) {
+        Some(&s) => to_f32(s),
+        None => frame.iter().map(|&s| to_f32(s)).sum::<f32>() / frame.len() as f32,
+    }
+}
+
+/// How much audio before the hotkey press is kept and prepended to a new
+/// recording, so a fast start doesn't clip the first phoneme.
+const PRE_ROLL_MS: usize = 300;
+
+/// Bounded ring of the most recent mono samples captured while *not*
+/// recording.  `do_start_recording` drains it into the buffer when the
+/// recording flag flips.
+#[derive(Default)]
+pub(crate) struct PreRoll {
+    samples: VecDeque<f32>,
+    capacity: usize,
+}
+
+impl PreRoll {
+    fn new(capacity: usize) -> Self {
+        Self { samples: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    fn push(&mut self, sample: f32) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    fn extend_frames<T: Copy>(
+        &mut self,
+        data: &[T],
+        channels: usize,
+        mode: &ChannelMode,
+        to_f32: impl Fn(T) -> f32,
+    ) {
+        if channels == 1 {
+            for &s in data {
+                self.push(to_f32(s));
+            }
+        } else {
+            for chunk in data.chunks(channels) {
+                self.push(mix_frame(chunk, mode, &to_f32));
+            }
+        }
+    }
+
+    fn drain_into(&mut self, buf: &mut Vec<f32>) {
+        buf.extend(self.samples.drain(..));
+    }
+
+    fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// RMS of the newest `window` samples (0.0 when the ring is empty).
+    fn recent_rms(&self, window: usize) -> f32 {
+        let n = window.min(self.samples.len());
+        if n == 0 {
+            return 0.0;
+        }
+        let sum: f32 = self.samples.iter().rev().take(n).map(|&s| s * s).sum();
+        (sum / n as f32).sqrt()
+    }
+}
+
 /// Commands sent from [`AudioThreadControl`] to the audio thread.
 enum AudioCmd {
     /// Pause the cpal stream (CoreAudio stops capturing, mic indicator goes away).
     Pause,
     /// Resume the cpal stream.  The reply channel signals `true` on success.
     Resume(mpsc::Sender<bool>),
+    /// Re-read the device's current input config.  Replies with its sample
+    /// rate, or `None` if the device can no longer be queried.
+    Probe(mpsc::Sender<Option<u32>>),
     /// Stop the audio thread entirely (thread exits, stream is dropped).
     Stop,
 }
@@ -26,8 +180,18 @@ pub struct AudioThreadControl {
     /// The resolved device name this stream was opened on (after BT-avoidance).
     /// `None` means cpal's system default was used at open time.
     pub device_name: Option<String>,
+    /// The user's chosen device when it was not found and the stream fell
+    /// back to the system default.  Taken by [`take_device_fallback`].
+    missing_device: Mutex<Option<String>>,
     /// True while the stream is paused (idle timeout).
     paused: Arc<AtomicBool>,
+    /// Rolling pre-roll filled by the callback between recordings.
+    pre_roll: Arc<Mutex<PreRoll>>,
+    /// Rate the stream was opened at; every sample in `buffer` is at this rate.
+    pub sample_rate: u32,
+    /// When the current recording started and how many pre-roll samples
+    /// seeded it.  Used to sanity-check the captured length on stop.
+    recording_started: Mutex<Option<(Instant, usize)>>,
 }
 
 impl AudioThreadControl {
@@ -67,23 +231,45 @@ impl AudioThreadControl {
         self.stream_alive.load(Ordering::Relaxed)
     }
 
+    /// Ask the device for its current input rate.  Returns the new rate when
+    /// it no longer matches the one the stream was opened at — e.g. Bluetooth
+    /// headsets drop to 16/24 kHz when their mic profile activates, without
+    /// the stream erroring.  `None` when unchanged or the probe timed out.
+    pub fn probe_rate_change(&self) -> Option<u32> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.cmd_tx.send(AudioCmd::Probe(reply_tx)).ok()?;
+        let current = reply_rx.recv_timeout(Duration::from_millis(200)).ok()??;
+        (current != self.sample_rate).then_some(current)
+    }
+
     pub fn is_paused(&self) -> bool {
         self.paused.load(Ordering::SeqCst)
     }
+
+    /// RMS level of the newest `window` samples captured while not recording.
+    /// Used by the mic level monitor; reads the pre-roll ring so the callback
+    /// needs no extra buffer.
+    pub fn input_level(&self, window: usize) -> f32 {
+        self.pre_roll.lock().map(|r| r.recent_rms(window)).unwrap_or(0.0)
+    }
 }
 
 /// Spawn a persistent audio thread that builds and immediately starts the cpal
 /// input stream.  The stream runs for the entire app lifetime — the callback
-/// checks `is_recording` atomically and discards samples when false.
+/// checks `is_recording` atomically; when false, samples only feed the
+/// bounded [`PreRoll`] ring and are otherwise discarded.
 ///
 /// If `device_name` is Some, the named device is used; falls back to the
 /// system default if not found.  When `device_name` is None (Auto mode),
 /// `resolve_input_device` is applied first so Bluetooth inputs are avoided.
+/// In [`CaptureMode::SystemAudio`] (Windows only) the default output device
+/// is looped back instead and `device_name` is ignored.
 pub fn spawn_audio_thread(
     buffer: Arc<Mutex<Vec<f32>>>,
     is_recording: Arc<AtomicBool>,
     device_name: Option<String>,
 ) -> Result<(u32, AudioThreadControl), String> {
+    let requested_device = device_name.clone();
     // Apply Bluetooth avoidance when in Auto mode (device_name == None).
     let device_name = crate::audio_devices::resolve_input_device(device_name);
     // Shared flag: set to false by the error callback when the stream dies.
@@ -98,11 +284,27 @@ pub fn spawn_audio_thread(
 
     let buf_for_thread = Arc::clone(&buffer);
     let rec_for_thread = Arc::clone(&is_recording);
+    let pre_roll = Arc::new(Mutex::new(PreRoll::default()));
+    let pre_for_thread = Arc::clone(&pre_roll);
+
+    let loopback = loopback_capture();
+    if !loopback && current_capture_mode() == CaptureMode::SystemAudio {
+        tracing::warn!("System audio capture is only supported on Windows; using the microphone");
+    }
 
     std::thread::spawn(move || {
         let host = cpal::default_host();
 
-        let device = if let Some(ref name) = device_name {
+        let device = if loopback {
+            // WASAPI treats an input stream on a render device as loopback.
+            match host.default_output_device() {
+                Some(d) => d,
+                None => {
+                    let _ = init_tx.send(Err("No output device found for system audio capture".to_string()));
+                    return;
+                }
+            }
+        } else if let Some(ref name) = device_name {
             let found = host
                 .input_devices()
                 .ok()
@@ -133,7 +335,10 @@ pub fn spawn_audio_thread(
         // Record the ACTUAL device name (may differ from `device_name` on fallback).
         let actual_device_name = device.name().ok();
 
-        let config = match device.default_input_config() {
+        let input_config = |device: &cpal::Device| {
+            if loopback { device.default_output_config() } else { device.default_input_config() }
+        };
+        let config = match input_config(&device) {
             Ok(c) => c,
             Err(e) => {
                 let _ = init_tx.send(Err(format!("Failed to get input config: {}", e)));
@@ -143,10 +348,16 @@ pub fn spawn_audio_thread(
 
         let sample_rate = config.sample_rate().0;
         let channels = config.channels() as usize;
+        let channel_mode = current_channel_mode();
+        if let Ok(mut ring) = pre_for_thread.lock() {
+            *ring = PreRoll::new(sample_rate as usize * PRE_ROLL_MS / 1000);
+        }
 
         let stream = {
             let buf = Arc::clone(&buf_for_thread);
             let rec = Arc::clone(&rec_for_thread);
+            let pre = Arc::clone(&pre_for_thread);
+            let mode = channel_mode.clone();
             match config.sample_format() {
                 cpal::SampleFormat::F32 => {
                     let rec_err = Arc::clone(&rec_for_thread);
@@ -155,7 +366,13 @@ pub fn spawn_audio_thread(
                         &config.into(),
                         move |data: &[f32], _: &cpal::InputCallbackInfo| {
                             if !rec.load(Ordering::Relaxed) {
-                                return;
+                                let Ok(mut ring) = pre.lock() else { return };
+                                // Re-check under the lock: do_start_recording
+                                // flips the flag while holding it.
+                                if !rec.load(Ordering::SeqCst) {
+                                    ring.extend_frames(data, channels, &mode, |s| s);
+                                    return;
+                                }
                             }
                             let mut buf = match buf.lock() {
                                 Ok(b) => b,
@@ -170,7 +387,7 @@ pub fn spawn_audio_thread(
                                 buf.extend_from_slice(data);
                             } else {
                                 for chunk in data.chunks(channels) {
-                                    buf.push(chunk.iter().sum::<f32>() / channels as f32);
+                                    buf.push(mix_frame(chunk, &mode, |s| s));
                                 }
                             }
                         },
@@ -185,13 +402,19 @@ pub fn spawn_audio_thread(
                 cpal::SampleFormat::I16 => {
                     let buf = Arc::clone(&buf_for_thread);
                     let rec = Arc::clone(&rec_for_thread);
+                    let pre = Arc::clone(&pre_for_thread);
+                    let mode = channel_mode.clone();
                     let rec_err = Arc::clone(&rec_for_thread);
                     let alive_err = Arc::clone(&alive_for_thread);
                     device.build_input_stream(
                         &config.into(),
                         move |data: &[i16], _: &cpal::InputCallbackInfo| {
                             if !rec.load(Ordering::Relaxed) {
-                                return;
+                                let Ok(mut ring) = pre.lock() else { return };
+                                if !rec.load(Ordering::SeqCst) {
+                                    ring.extend_frames(data, channels, &mode, |s| s as f32 / i16::MAX as f32);
+                                    return;
+                                }
                             }
                             let mut buf = match buf.lock() {
                                 Ok(b) => b,
@@ -206,13 +429,7 @@ pub fn spawn_audio_thread(
                                 buf.extend(data.iter().map(|&s| s as f32 / i16::MAX as f32));
                             } else {
                                 for chunk in data.chunks(channels) {
-                                    buf.push(
-                                        chunk
-                                            .iter()
-                                            .map(|&s| s as f32 / i16::MAX as f32)
-                                            .sum::<f32>()
-                                            / channels as f32,
-                                    );
+                                    buf.push(mix_frame(chunk, &mode, |s| s as f32 / i16::MAX as f32));
                                 }
                             }
                         },
@@ -245,8 +462,8 @@ pub fn spawn_audio_thread(
         }
 
         tracing::info!(
-            "Audio stream always-on: {} Hz, {} ch (device: {:?})",
-            sample_rate, channels, actual_device_name,
+            "Audio stream always-on: {} Hz, {} ch, {:?} (device: {:?})",
+            sample_rate, channels, channel_mode, actual_device_name,
         );
         let _ = init_tx.send(Ok((sample_rate, actual_device_name)));
 
@@ -261,6 +478,10 @@ pub fn spawn_audio_thread(
                     }
                 }
                 Ok(AudioCmd::Resume(reply)) => {
+                    // Whatever is in the ring predates the pause.
+                    if let Ok(mut ring) = pre_for_thread.lock() {
+                        ring.clear();
+                    }
                     let ok = match stream.play() {
                         Ok(()) => {
                             tracing::info!("Audio stream resumed");
@@ -274,6 +495,9 @@ pub fn spawn_audio_thread(
                     };
                     let _ = reply.send(ok);
                 }
+                Ok(AudioCmd::Probe(reply)) => {
+                    let _ = reply.send(input_config(&device).ok().map(|c| c.sample_rate().0));
+                }
                 Ok(AudioCmd::Stop) | Err(_) => {
                     tracing::info!("Audio thread stopping");
                     break;
@@ -286,8 +510,34 @@ pub fn spawn_audio_thread(
         .recv_timeout(std::time::Duration::from_secs(5))
         .map_err(|_| "Audio thread init timed out".to_string())??;
 
+    let missing_device = requested_device
+        .filter(|name| !loopback && actual_device_name.as_deref() != Some(name.as_str()));
     let paused = Arc::new(AtomicBool::new(false));
-    Ok((sample_rate, AudioThreadControl { cmd_tx, stream_alive, device_name: actual_device_name, paused }))
+    Ok((
+        sample_rate,
+        AudioThreadControl {
+            cmd_tx,
+            stream_alive,
+            device_name: actual_device_name,
+            missing_device: Mutex::new(missing_device),
+            paused,
+            pre_roll,
+            sample_rate,
+            recording_started: Mutex::new(None),
+        },
+    ))
+}
+
+/// The explicitly chosen input device the open stream could not find, and
+/// the device it fell back to.  Reported once per stream, so the caller can
+/// tell the user without repeating itself on every recording.
+pub fn take_device_fallback(
+    audio_thread: &Mutex<Option<AudioThreadControl>>,
+) -> Option<(String, Option<String>)> {
+    let at = audio_thread.lock().ok()?;
+    let ctrl = at.as_ref()?;
+    let missing = ctrl.missing_device.lock().ok()?.take()?;
+    Some((missing, ctrl.device_name.clone()))
 }
 
 /// Attempt to reconnect the microphone when `mic_available` is false.
@@ -332,9 +582,9 @@ pub fn close_audio_stream(
     tracing::info!("Mic stream closed (on-demand)");
 }
 
-/// Start recording — opens the mic stream if not already running (on-demand model).
-pub fn do_start_recording(
-    is_recording: &AtomicBool,
+/// Make sure the mic stream is running: resume it after an idle pause, or
+/// reopen it when it is closed or dead.  Does not touch `is_recording`.
+pub fn ensure_stream_open(
     mic_available: &AtomicBool,
     reconnecting: &AtomicBool,
     sample_rate: &Mutex<Option<u32>>,
@@ -343,7 +593,6 @@ pub fn do_start_recording(
     audio_thread: &Mutex<Option<AudioThreadControl>>,
     device_name: Option<String>,
 ) -> Result<(), String> {
-    // ── Step 1: ensure stream is alive ───────────────────────────────────
     let stream_dead = audio_thread.lock().ok()
         .and_then(|at| at.as_ref().map(|c| !c.is_alive()))
         .unwrap_or(false);
@@ -397,8 +646,25 @@ pub fn do_start_recording(
             }
         }
         // Freshly resumed/reconnected stream is already on the correct device;
-        // the mismatch check below will be a no-op.
+        // the caller's mismatch check will be a no-op.
     }
+    Ok(())
+}
+
+/// Start recording — opens the mic stream if not already running (on-demand model).
+#[allow(clippy::too_many_arguments)]
+pub fn do_start_recording(
+    is_recording: &AtomicBool,
+    mic_available: &AtomicBool,
+    reconnecting: &AtomicBool,
+    sample_rate: &Mutex<Option<u32>>,
+    buffer: &Arc<Mutex<Vec<f32>>>,
+    is_recording_arc: &Arc<AtomicBool>,
+    audio_thread: &Mutex<Option<AudioThreadControl>>,
+    device_name: Option<String>,
+) -> Result<(), String> {
+    // ── Step 1: ensure stream is alive ───────────────────────────────────
+    ensure_stream_open(mic_available, reconnecting, sample_rate, buffer, is_recording_arc, audio_thread, device_name.clone())?;
 
     // ── Step 2: device-mismatch guard (BT hotplug race defence) ─────────
     // resolve_input_device reflects the *current* system state.  If BT
@@ -407,7 +673,10 @@ pub fn do_start_recording(
     // wanted device is now Some("MacBook Pro Microphone") — a mismatch.
     // Reconnect *before* setting is_recording so the race window is zero.
     let wanted = crate::audio_devices::resolve_input_device(device_name.clone());
-    if wanted.is_some() {
+    let mut restart = false;
+    // A loopback stream is open on the output device, so its name never
+    // matches the wanted input.
+    if wanted.is_some() && !loopback_capture() {
         let current = audio_thread.lock().ok()
             .and_then(|g| g.as_ref().map(|c| c.device_name.clone()))
             .flatten();
@@ -416,14 +685,27 @@ pub fn do_start_recording(
                 "Stream device mismatch (stream={:?}, wanted={:?}) — reconnecting before recording",
                 current, wanted
             );
-            {
-                let mut at = audio_thread.lock().map_err(|e| e.to_string())?;
-                if let Some(ctrl) = at.take() { ctrl.stop(); }
-            }
-            mic_available.store(false, Ordering::SeqCst);
-            try_reconnect_audio(mic_available, sample_rate, buffer, is_recording_arc, audio_thread, device_name)?;
+            restart = true;
         }
     }
+    // Same device, different format: the stream keeps delivering samples at
+    // the new rate while we would resample from the old one (chipmunk audio).
+    if !restart {
+        let changed = audio_thread.lock().ok()
+            .and_then(|g| g.as_ref().and_then(|c| c.probe_rate_change().map(|new| (c.sample_rate, new))));
+        if let Some((old, new)) = changed {
+            tracing::warn!("Input sample rate changed ({} Hz → {} Hz) — reconnecting before recording", old, new);
+            restart = true;
+        }
+    }
+    if restart {
+        {
+            let mut at = audio_thread.lock().map_err(|e| e.to_string())?;
+            if let Some(ctrl) = at.take() { ctrl.stop(); }
+        }
+        mic_available.store(false, Ordering::SeqCst);
+        try_reconnect_audio(mic_available, sample_rate, buffer, is_recording_arc, audio_thread, device_name)?;
+    }
 
     // ── Step 3: flip the recording flag ──────────────────────────────────
     // Hold the audio_thread lock while setting is_recording so that the
@@ -434,27 +716,37 @@ pub fn do_start_recording(
         // If the stream was torn down between Step 2 and now (e.g. idle
         // watcher closed it), return an error.  The caller will show an
         // error state and the next hotkey press will reconnect.
-        if at.is_none() {
+        let Some(ctrl) = at.as_ref() else {
             return Err("mic_not_ready".to_string());
-        }
+        };
         if is_recording.load(Ordering::SeqCst) {
             return Err("Already recording".to_string());
         }
-        buffer.lock().map_err(|e| e.to_string())?.clear();
+        // Seed the buffer with the pre-roll and flip the flag while holding
+        // the ring lock, so no callback lands between the two.
+        let mut ring = ctrl.pre_roll.lock().map_err(|e| e.to_string())?;
+        let mut buf = buffer.lock().map_err(|e| e.to_string())?;
+        buf.clear();
+        ring.drain_into(&mut buf);
+        if let Ok(mut started) = ctrl.recording_started.lock() {
+            *started = Some((Instant::now(), buf.len()));
+        }
         is_recording.store(true, Ordering::SeqCst);
     }
 
     Ok(())
 }
 
-/// Stop recording, transcribe, and return the text + 16 kHz samples for history.
+/// Stop recording, transcribe, and return the text, the 16 kHz samples and
+/// any word timings for history.  `cancel` abandons a cloud upload.
 pub fn do_stop_recording(
     state: &crate::AppState,
     stt_config: &SttConfig,
     language: &str,
     dictionary_terms: &[String],
-) -> Result<(String, Vec<f32>), String> {
-    let sample_rate = state.sample_rate
+    cancel: Option<&AtomicBool>,
+) -> Result<(String, Vec<f32>, Option<Vec<WordTiming>>), SumiError> {
+    let mut sample_rate = state.sample_rate
         .lock()
         .map_err(|e| e.to_string())?
         .ok_or_else(|| "No microphone available".to_string())?;
@@ -463,7 +755,7 @@ pub fn do_stop_recording(
         .compare_exchange(true, false, Ordering::SeqCst, Ordering::SeqCst)
         .is_err()
     {
-        return Err("Not currently recording".to_string());
+        return Err("Not currently recording".into());
     }
     // Wake the streaming feeder immediately so it exits its 2 s sleep and
     // starts post-loop work (trailing feed + finish_streaming) right away,
@@ -512,7 +804,28 @@ pub fn do_stop_recording(
     };
 
     if samples.is_empty() {
-        return Err("No audio captured".to_string());
+        return Err("No audio captured".into());
+    }
+
+    // The buffer was filled by the stream in `audio_thread`; trust its rate
+    // over `state.sample_rate` if the two ever diverge.
+    let capture = state.audio_thread.lock().ok().and_then(|g| {
+        g.as_ref().map(|c| (c.sample_rate, c.recording_started.lock().ok().and_then(|mut s| s.take())))
+    });
+    if let Some((stream_rate, started)) = capture {
+        if stream_rate != sample_rate {
+            tracing::warn!("Stored sample rate {} Hz differs from stream rate {} Hz; using the stream's", sample_rate, stream_rate);
+            sample_rate = stream_rate;
+        }
+        if let Some((at, pre_roll)) = started {
+            let expected_secs = at.elapsed().as_secs_f64() + pre_roll as f64 / sample_rate as f64;
+            if let Some(ratio) = implausible_capture(samples.len(), sample_rate, expected_secs) {
+                tracing::warn!(
+                    "Captured audio is {:.2}× the recording's wall-clock length at {} Hz — the input format may have changed mid-recording",
+                    ratio, sample_rate
+                );
+            }
+        }
     }
 
     tracing::info!(
@@ -522,6 +835,53 @@ pub fn do_stop_recording(
         sample_rate,
     );
 
+    transcribe_capture(state, stt_config, language, dictionary_terms, samples, sample_rate, qwen3_streaming_result, cancel)
+}
+
+/// Cut what a continuous dictation has captured so far out of the buffer
+/// while the stream keeps recording.  Returns the samples and their rate,
+/// or `None` if nothing arrived since the previous chunk.
+pub fn take_recorded_chunk(state: &crate::AppState) -> Option<(Vec<f32>, u32)> {
+    let sample_rate = state
+        .audio_thread
+        .lock()
+        .ok()
+        .and_then(|g| g.as_ref().map(|c| c.sample_rate))
+        .or_else(|| state.sample_rate.lock().ok().and_then(|r| *r))?;
+    let samples = std::mem::take(&mut *state.buffer.lock().ok()?);
+    (!samples.is_empty()).then_some((samples, sample_rate))
+}
+
+/// Transcribe a capture recorded at `sample_rate`: resample, apply input
+/// gain, trim silence and run the configured STT engine.  A Qwen3-ASR
+/// `qwen3_streaming_result` from the live-preview feeder replaces the batch
+/// pass.  Returns the text, the 16 kHz samples for history and, for
+/// providers that report them, word timings relative to those samples.
+/// A cloud upload is abandoned as soon as `cancel` fires.
+#[allow(clippy::too_many_arguments)]
+pub fn transcribe_capture(
+    state: &crate::AppState,
+    stt_config: &SttConfig,
+    language: &str,
+    dictionary_terms: &[String],
+    samples: Vec<f32>,
+    sample_rate: u32,
+    qwen3_streaming_result: Option<String>,
+    cancel: Option<&AtomicBool>,
+) -> Result<(String, Vec<f32>, Option<Vec<WordTiming>>), SumiError> {
+    if let Ok(mut c) = state.last_stt_confidence.lock() {
+        *c = None;
+    }
+    state.touch_model_use();
+
+    // Cloud upload at the device rate keeps a copy of the capture from before
+    // the 16 kHz downsample; the 16 kHz buffer still drives trimming and history.
+    let mut native = (stt_config.mode == SttMode::Cloud
+        && stt_config.cloud.cloud_native_rate
+        && stt_config.cloud.provider.accepts_native_rate()
+        && sample_rate != 16000)
+        .then(|| samples.clone());
+
     let t0 = Instant::now();
     let mut samples_16k = if sample_rate != 16000 {
         let resampled = resample(&samples, sample_rate, 16000);
@@ -531,6 +891,20 @@ pub fn do_stop_recording(
         samples
     };
 
+    // ── Input gain / normalization ──────────────────────────────────────
+    // Applied before silence detection so quiet mics don't trip the trimmer.
+    // The returned samples (saved to history) carry the same gain, so
+    // playback matches what the STT engine heard.
+    let (input_gain_db, auto_gain) = state
+        .settings
+        .lock()
+        .map(|s| (s.input_gain_db, s.auto_gain))
+        .unwrap_or((0.0, false));
+    apply_input_gain(&mut samples_16k, input_gain_db, auto_gain);
+    if let Some(native) = native.as_mut() {
+        apply_input_gain(native, input_gain_db, auto_gain);
+    }
+
     // ── VAD or RMS trimming ─────────────────────────────────────────────
     // Skip Silero VAD for cloud STT — cloud providers handle silence/speech
     // detection server-side, and the ggml VAD backend creates a disposable
@@ -538,25 +912,28 @@ pub fn do_stop_recording(
     // seconds of overhead on Windows due to repeated kernel calls.
     let use_silero = crate::settings::vad_model_path().exists()
         && stt_config.mode != SttMode::Cloud;
+    // Offsets where VAD joined speech segments; cut points for chunked Whisper.
+    let mut speech_boundaries = Vec::new();
 
     if use_silero {
         // Use Silero VAD to extract speech segments
         match crate::transcribe::filter_with_vad(&state.vad_ctx, &samples_16k) {
-            Ok(speech) if speech.is_empty() => {
+            Ok((speech, _)) if speech.is_empty() => {
                 tracing::info!("VAD: no speech segments found");
-                return Err("no_speech".to_string());
+                return Err(SumiError::no_speech());
             }
-            Ok(speech) => {
+            Ok((speech, boundaries)) => {
                 tracing::info!(
                     "VAD filtered: {:.2}s → {:.2}s",
                     samples_16k.len() as f64 / 16000.0,
                     speech.len() as f64 / 16000.0,
                 );
                 samples_16k = speech;
+                speech_boundaries = boundaries;
             }
             Err(e) => {
                 tracing::warn!("VAD failed ({}), falling back to RMS trimming", e);
-                rms_trim_silence(&mut samples_16k)?;
+                rms_trim_silence(&mut samples_16k, silence_threshold())?;
             }
         }
     } else {
@@ -565,14 +942,31 @@ pub fn do_stop_recording(
         } else {
             tracing::debug!("VAD model not downloaded, using RMS trimming");
         }
-        rms_trim_silence(&mut samples_16k)?;
+        let kept = rms_trim_silence(&mut samples_16k, silence_threshold())?;
+        if let Some(native) = native.as_mut() {
+            // Apply the same cut, scaled from 16 kHz to the device rate.
+            let scale = |i: usize| (i as u64 * sample_rate as u64 / 16000) as usize;
+            let end = scale(kept.end).min(native.len());
+            native.truncate(end);
+            native.drain(..scale(kept.start).min(end));
+        }
     }
 
     let stt_start = Instant::now();
+    let mut word_timings = None;
     let text = match stt_config.mode {
         SttMode::Local => match stt_config.local_engine {
             LocalSttEngine::Whisper => {
-                let result = transcribe_with_cached_whisper(&state.whisper_ctx, &samples_16k, &stt_config.whisper_model, language, dictionary_terms)?;
+                let result = transcribe_with_cached_whisper(
+                    &state.whisper_ctx,
+                    &samples_16k,
+                    &stt_config.whisper_model,
+                    language,
+                    dictionary_terms,
+                    stt_config.chunk_threshold_secs,
+                    &speech_boundaries,
+                    stt_config.whisper_quality,
+                )?;
                 tracing::info!("[timing] STT (local whisper): {:.0?}", stt_start.elapsed());
                 result
             }
@@ -581,9 +975,9 @@ pub fn do_stop_recording(
                 if let Some(text) = qwen3_streaming_result {
                     tracing::info!("[timing] STT (local qwen3-asr streaming): {:.0?}", stt_start.elapsed());
                     return if text.is_empty() {
-                        Err("no_speech".to_string())
+                        Err(SumiError::no_speech())
                     } else {
-                        Ok((text, samples_16k))
+                        Ok((text, samples_16k, None))
                     };
                 }
 
@@ -603,34 +997,168 @@ pub fn do_stop_recording(
             }
         },
         SttMode::Cloud => {
-            let result = crate::stt::run_cloud_stt(&stt_config.cloud, &samples_16k, &state.http_client, None)?;
+            let prompt = crate::stt::cloud_bias_prompt(&stt_config.cloud, dictionary_terms);
+            let result = match &native {
+                Some(native) => crate::stt::run_cloud_stt_detailed(&stt_config.cloud, native, sample_rate, &state.http_client(), prompt.as_deref(), cancel),
+                None => crate::stt::run_cloud_stt_detailed(&stt_config.cloud, &samples_16k, 16000, &state.http_client(), prompt.as_deref(), cancel),
+            }?;
             tracing::info!("[timing] STT (cloud {}): {:.0?}", stt_config.cloud.provider.as_key(), stt_start.elapsed());
-            result
+            if let Ok(mut c) = state.last_stt_confidence.lock() {
+                *c = result.confidence;
+            }
+            word_timings = result.word_timings;
+            result.text
         }
     };
 
     if text.is_empty() {
-        Err("no_speech".to_string())
+        Err(SumiError::no_speech())
     } else {
-        Ok((text, samples_16k))
+        Ok((text, samples_16k, word_timings))
     }
 }
 
+/// Ratio of captured to expected audio length when it is off by more than
+/// ~25% — the signature of samples arriving at a different rate than the one
+/// we resample from.  Very short recordings are skipped; scheduling jitter
+/// dominates them.
+fn implausible_capture(captured_samples: usize, sample_rate: u32, expected_secs: f64) -> Option<f64> {
+    if expected_secs < 1.0 || sample_rate == 0 {
+        return None;
+    }
+    let ratio = captured_samples as f64 / sample_rate as f64 / expected_secs;
+    (!(0.8..=1.25).contains(&ratio)).then_some(ratio)
+}
+
 /// RMS (root mean square) energy of an audio slice.
 #[inline]
 pub(crate) fn rms(samples: &[f32]) -> f32 {
     (samples.iter().map(|&s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
 }
 
-/// Strip leading/trailing silence using RMS, and reject near-silent audio.
-fn rms_trim_silence(samples_16k: &mut Vec<f32>) -> Result<(), String> {
-    const SILENCE_RMS_THRESHOLD: f32 = 0.01;
+/// Peak level targeted by `auto_gain` normalization (-3 dBFS).
+const NORMALIZE_TARGET_PEAK: f32 = 0.707_945_8;
+/// Upper bound on the normalization boost so near-silent noise is not
+/// amplified into something that looks like speech.
+const NORMALIZE_MAX_GAIN_DB: f32 = 30.0;
+
+#[inline]
+fn db_to_linear(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// Apply a fixed `gain_db` and, when `auto_gain` is set, peak-normalize to
+/// -3 dBFS.  Output is clamped to `[-1, 1]` so the boost never clips.
+pub(crate) fn apply_input_gain(samples: &mut [f32], gain_db: f32, auto_gain: bool) {
+    let mut gain = if gain_db != 0.0 { db_to_linear(gain_db) } else { 1.0 };
+
+    if auto_gain {
+        let peak = samples.iter().fold(0.0f32, |m, &s| m.max(s.abs())) * gain;
+        if peak > 0.0 {
+            let norm = (NORMALIZE_TARGET_PEAK / peak).min(db_to_linear(NORMALIZE_MAX_GAIN_DB));
+            gain *= norm;
+        }
+    }
+
+    if (gain - 1.0).abs() < f32::EPSILON {
+        return;
+    }
+    tracing::info!("Input gain: {:+.1} dB (auto_gain={})", 20.0 * gain.log10(), auto_gain);
+    for s in samples.iter_mut() {
+        *s = (*s * gain).clamp(-1.0, 1.0);
+    }
+}
+
+/// Default silence threshold: -40 dBFS, an RMS of 0.01.
+pub const DEFAULT_SILENCE_THRESHOLD_DB: f32 = -40.0;
+/// Thresholds outside this range either trim quiet speech or keep room noise.
+pub const SILENCE_THRESHOLD_DB_RANGE: std::ops::RangeInclusive<f32> = -70.0..=-20.0;
+
+/// RMS level below which audio is treated as silence, both when trimming and
+/// when endpointing a live recording.  Mirrors `Settings::silence_threshold_db`.
+static SILENCE_THRESHOLD: RwLock<f32> = RwLock::new(0.01);
+
+/// Pull `db` into [`SILENCE_THRESHOLD_DB_RANGE`]; NaN and infinities fall
+/// back to the default.
+pub fn clamp_silence_threshold_db(db: f32) -> f32 {
+    if db.is_finite() {
+        db.clamp(*SILENCE_THRESHOLD_DB_RANGE.start(), *SILENCE_THRESHOLD_DB_RANGE.end())
+    } else {
+        DEFAULT_SILENCE_THRESHOLD_DB
+    }
+}
+
+/// Set the silence threshold in dBFS, clamped by [`clamp_silence_threshold_db`].
+/// Called once after `load_settings()` and whenever the user changes it.
+pub fn set_silence_threshold_db(db: f32) {
+    let db = clamp_silence_threshold_db(db);
+    if let Ok(mut guard) = SILENCE_THRESHOLD.write() {
+        *guard = db_to_rms(db);
+    }
+}
+
+pub(crate) fn silence_threshold() -> f32 {
+    SILENCE_THRESHOLD.read().map(|g| *g).unwrap_or(0.01)
+}
+
+/// Linear RMS level for `db` dBFS.
+fn db_to_rms(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// Speech that must be heard before silence endpointing arms, so a quiet
+/// start (user still gathering their thoughts) does not stop the recording.
+const ENDPOINT_MIN_SPEECH: std::time::Duration = std::time::Duration::from_millis(300);
+
+/// Auto-stop state machine for a live recording.
+///
+/// Starts disarmed; once `ENDPOINT_MIN_SPEECH` of audio above the silence
+/// threshold has been heard it arms, and from then on reports a stop when
+/// the trailing silence reaches `stop_after`.  Any speech resets the
+/// silence run.
+pub(crate) struct SilenceEndpointer {
+    threshold: f32,
+    stop_after: std::time::Duration,
+    speech: std::time::Duration,
+    silence: std::time::Duration,
+}
+
+impl SilenceEndpointer {
+    pub(crate) fn new(stop_after_ms: u32) -> Self {
+        Self {
+            threshold: silence_threshold(),
+            stop_after: std::time::Duration::from_millis(stop_after_ms as u64),
+            speech: std::time::Duration::ZERO,
+            silence: std::time::Duration::ZERO,
+        }
+    }
+
+    /// Feed the RMS of the latest `tick` of audio.  Returns true when the
+    /// recording should stop.
+    pub(crate) fn update(&mut self, level: f32, tick: std::time::Duration) -> bool {
+        if level > self.threshold {
+            self.speech += tick;
+            self.silence = std::time::Duration::ZERO;
+            return false;
+        }
+        if self.speech < ENDPOINT_MIN_SPEECH {
+            return false;
+        }
+        self.silence += tick;
+        self.silence >= self.stop_after
+    }
+}
+
+/// Strip leading/trailing audio quieter than `threshold` RMS, and reject
+/// audio that is near-silent overall.  Returns the range of the original
+/// buffer that was kept.
+fn rms_trim_silence(samples_16k: &mut Vec<f32>, threshold: f32) -> Result<std::ops::Range<usize>, String> {
     const WINDOW: usize = 160;
     const LOOKBACK: usize = 1600;
 
     let speech_onset = samples_16k
         .windows(WINDOW)
-        .position(|w| rms(w) > SILENCE_RMS_THRESHOLD)
+        .position(|w| rms(w) > threshold)
         .unwrap_or(0);
 
     let trim_start = speech_onset.saturating_sub(LOOKBACK);
@@ -643,11 +1171,12 @@ fn rms_trim_silence(samples_16k: &mut Vec<f32>) -> Result<(), String> {
         samples_16k.drain(0..trim_start);
     }
 
+    let mut kept = trim_start..trim_start + samples_16k.len();
     if samples_16k.len() > WINDOW {
         let total = samples_16k.len();
         let last_speech = samples_16k
             .windows(WINDOW)
-            .rposition(|w| rms(w) > SILENCE_RMS_THRESHOLD)
+            .rposition(|w| rms(w) > threshold)
             .map(|pos| pos + WINDOW)
             .unwrap_or(total);
 
@@ -658,6 +1187,7 @@ fn rms_trim_silence(samples_16k: &mut Vec<f32>) -> Result<(), String> {
                 (total - trim_end) as f64 / 16.0
             );
             samples_16k.truncate(trim_end);
+            kept.end = trim_start + trim_end;
         }
     }
 
@@ -669,7 +1199,7 @@ fn rms_trim_silence(samples_16k: &mut Vec<f32>) -> Result<(), String> {
     }
     tracing::info!("Audio RMS: {:.5}", overall_rms);
 
-    Ok(())
+    Ok(kept)
 }
 
 /// Simple linear interpolation resampler.
@@ -693,3 +1223,174 @@ pub fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
     }
     output
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── PreRoll ──
+
+    #[test]
+    fn pre_roll_keeps_only_most_recent_samples() {
+        let mut ring = PreRoll::new(3);
+        ring.extend_frames(&[1.0f32, 2.0, 3.0, 4.0, 5.0], 1, &ChannelMode::Average, |s| s);
+        let mut buf = vec![9.0];
+        ring.drain_into(&mut buf);
+        assert_eq!(buf, vec![9.0, 3.0, 4.0, 5.0]);
+        // Draining empties the ring.
+        let mut again = Vec::new();
+        ring.drain_into(&mut again);
+        assert!(again.is_empty());
+    }
+
+    #[test]
+    fn pre_roll_downmixes_frames() {
+        let mut ring = PreRoll::new(4);
+        ring.extend_frames(&[0.2f32, 0.4, 0.6, 0.8], 2, &ChannelMode::Average, |s| s);
+        let mut buf = Vec::new();
+        ring.drain_into(&mut buf);
+        assert_eq!(buf.len(), 2);
+        assert!((buf[0] - 0.3).abs() < 1e-6 && (buf[1] - 0.7).abs() < 1e-6);
+    }
+
+    #[test]
+    fn pre_roll_recent_rms_uses_newest_window() {
+        let mut ring = PreRoll::new(8);
+        assert_eq!(ring.recent_rms(4), 0.0);
+        ring.extend_frames(&[1.0f32, 1.0, 1.0, 1.0, 0.5, -0.5, 0.5, -0.5], 1, &ChannelMode::Average, |s| s);
+        assert!((ring.recent_rms(4) - 0.5).abs() < 1e-6);
+        // A window larger than the ring covers everything it holds.
+        assert!((ring.recent_rms(100) - 0.625f32.sqrt()).abs() < 1e-6);
+    }
+
+    // ── rms_trim_silence ──
+
+    #[test]
+    fn capture_length_flags_rate_mismatch() {
+        // 3 s at 48 kHz, interpreted correctly.
+        assert_eq!(implausible_capture(144_000, 48_000, 3.0), None);
+        // Device switched to 16 kHz but we still think it is 48 kHz.
+        let ratio = implausible_capture(48_000, 48_000, 3.0).unwrap();
+        assert!((ratio - 1.0 / 3.0).abs() < 1e-9);
+        // Too short to judge.
+        assert_eq!(implausible_capture(100, 48_000, 0.5), None);
+    }
+
+    #[test]
+    fn rms_trim_reports_kept_range() {
+        let mut samples = vec![0.0f32; 16000];
+        samples.resize(32000, 0.3);
+        samples.resize(48000, 0.0);
+        let original = samples.clone();
+
+        let kept = rms_trim_silence(&mut samples, db_to_rms(DEFAULT_SILENCE_THRESHOLD_DB)).unwrap();
+        assert!(kept.start > 0 && kept.end < original.len());
+        assert!(kept.start <= 16000 && kept.end >= 32000, "speech must be kept: {:?}", kept);
+        assert_eq!(samples, original[kept].to_vec());
+    }
+
+    #[test]
+    fn rms_trim_threshold_decides_what_counts_as_noise() {
+        assert!((db_to_rms(DEFAULT_SILENCE_THRESHOLD_DB) - 0.01).abs() < 1e-6);
+        assert_eq!(clamp_silence_threshold_db(-100.0), -70.0);
+        assert_eq!(clamp_silence_threshold_db(f32::NAN), DEFAULT_SILENCE_THRESHOLD_DB);
+        // A second of keyboard-level noise (RMS 0.02) before the speech.
+        let mut noisy = vec![0.02f32; 16000];
+        noisy.resize(32000, 0.3);
+
+        let kept = rms_trim_silence(&mut noisy.clone(), db_to_rms(-40.0)).unwrap();
+        assert_eq!(kept.start, 0, "at -40 dB the noise is kept as speech");
+        let kept = rms_trim_silence(&mut noisy, db_to_rms(-30.0)).unwrap();
+        assert!((14000..16000).contains(&kept.start), "at -30 dB the noise is trimmed: {:?}", kept);
+    }
+
+    // ── SilenceEndpointer ──
+
+    #[test]
+    fn endpointer_waits_for_speech_before_arming() {
+        let tick = std::time::Duration::from_millis(50);
+        let mut ep = SilenceEndpointer::new(500);
+        // A long quiet start never triggers.
+        for _ in 0..100 {
+            assert!(!ep.update(0.001, tick));
+        }
+        // Too little speech to arm.
+        ep.update(0.2, tick);
+        for _ in 0..20 {
+            assert!(!ep.update(0.001, tick));
+        }
+    }
+
+    #[test]
+    fn endpointer_stops_after_trailing_silence() {
+        let tick = std::time::Duration::from_millis(50);
+        let mut ep = SilenceEndpointer::new(500);
+        for _ in 0..10 {
+            assert!(!ep.update(0.2, tick));
+        }
+        for _ in 0..9 {
+            assert!(!ep.update(0.001, tick));
+        }
+        // Speech in the middle resets the silence run.
+        assert!(!ep.update(0.2, tick));
+        for _ in 0..9 {
+            assert!(!ep.update(0.001, tick));
+        }
+        assert!(ep.update(0.001, tick));
+    }
+
+    // ── mix_frame: channel selection over interleaved stereo ──
+
+    fn downmix(interleaved: &[f32], channels: usize, mode: &ChannelMode) -> Vec<f32> {
+        interleaved.chunks(channels).map(|c| mix_frame(c, mode, |s| s)).collect()
+    }
+
+    #[test]
+    fn channel_modes_pick_expected_samples() {
+        // Left carries signal, right is a dead channel.
+        let stereo = [0.8, 0.0, -0.4, 0.0, 0.2, 0.0];
+        assert_eq!(downmix(&stereo, 2, &ChannelMode::Average), vec![0.4, -0.2, 0.1]);
+        assert_eq!(downmix(&stereo, 2, &ChannelMode::Left), vec![0.8, -0.4, 0.2]);
+        assert_eq!(downmix(&stereo, 2, &ChannelMode::Right), vec![0.0, 0.0, 0.0]);
+        assert_eq!(downmix(&stereo, 2, &ChannelMode::Channel(0)), vec![0.8, -0.4, 0.2]);
+    }
+
+    #[test]
+    fn out_of_range_channel_falls_back_to_average() {
+        let four_ch = [0.4, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.8];
+        assert_eq!(downmix(&four_ch, 4, &ChannelMode::Channel(3)), vec![0.0, 0.8]);
+        assert_eq!(downmix(&four_ch, 4, &ChannelMode::Channel(7)), vec![0.1, 0.2]);
+    }
+
+    // ── apply_input_gain ──
+
+    fn sine(amplitude: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 16000.0).sin())
+            .collect()
+    }
+
+    #[test]
+    fn auto_gain_boosts_quiet_sine_without_clipping() {
+        let mut samples = sine(db_to_linear(-30.0), 16000);
+        apply_input_gain(&mut samples, 0.0, true);
+        let peak = samples.iter().fold(0.0f32, |m, &s| m.max(s.abs()));
+        assert!((peak - NORMALIZE_TARGET_PEAK).abs() < 0.01, "peak was {}", peak);
+        assert!(samples.iter().all(|s| s.abs() <= 1.0));
+    }
+
+    #[test]
+    fn fixed_gain_clamps_instead_of_clipping() {
+        let mut samples = sine(0.5, 1600);
+        apply_input_gain(&mut samples, 12.0, false);
+        let peak = samples.iter().fold(0.0f32, |m, &s| m.max(s.abs()));
+        assert_eq!(peak, 1.0);
+    }
+
+    #[test]
+    fn i16_frames_are_normalised() {
+        let frame = [i16::MAX, 0];
+        let v = mix_frame(&frame, &ChannelMode::Left, |s| s as f32 / i16::MAX as f32);
+        assert!((v - 1.0).abs() < f32::EPSILON);
+    }
+}
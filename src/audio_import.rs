@@ -339,9 +339,11 @@ pub fn run_import(app: &AppHandle, file_path: &str) -> Result<String, String> {
         return Err("import_already_active".to_string());
     }
     state.import_cancelled.store(false, Ordering::SeqCst);
+    state.touch_model_use();
 
     let result = run_import_inner(app, file_path);
 
+    state.touch_model_use();
     state.import_active.store(false, Ordering::SeqCst);
     result
 }
@@ -379,9 +381,9 @@ fn run_import_inner(app: &AppHandle, file_path: &str) -> Result<String, String>
     }
 
     // ── Step 3: Determine STT engine ──
-    let stt_config = {
+    let (stt_config, dictionary_terms) = {
         let s = state.settings.lock().map_err(|e| e.to_string())?;
-        s.stt.clone()
+        (s.stt.clone(), s.polish.dictionary_terms())
     };
     let language = stt_config.language.clone();
 
@@ -508,15 +510,16 @@ fn run_import_inner(app: &AppHandle, file_path: &str) -> Result<String, String>
             if !key.is_empty() {
                 cloud.api_key = key;
             }
+            let bias = crate::stt::cloud_bias_prompt(&cloud, &dictionary_terms);
             let app_c = app.clone();
             Box::new(move |samples: &[f32], start_secs, prev_text| {
                 let st = app_c.state::<crate::AppState>();
-                let prompt = if prev_text.is_empty() { None } else { Some(prev_text) };
+                let prompt = crate::stt::chunk_prompt(bias.as_deref(), prev_text);
                 let text = crate::stt::run_cloud_stt(
                     &cloud,
                     samples,
-                    &st.http_client,
-                    prompt,
+                    &st.http_client(),
+                    prompt.as_deref(),
                 )
                 .unwrap_or_else(|e| {
                     tracing::warn!("[import] cloud STT failed: {e}");
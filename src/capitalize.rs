@@ -0,0 +1,147 @@
+//! Sentence-start capitalization for unpolished transcripts.
+//!
+//! Whisper often leaves sentences in lowercase when AI polishing is off.
+//! This pass uppercases the first letter of each sentence but leaves alone
+//! anything a developer would not want touched:
+//!
+//! - Code-looking tokens: camelCase, snake_case, paths (`/`, `\`), dotted
+//!   names (`main.rs`), calls, flags and anything inside backticks.
+//! - Protected terms (the enabled dictionary), matched with their exact
+//!   spelling, so "npm" or "iOS" at the start of a sentence stay as written.
+
+/// Capitalize sentence starts in `text`, skipping code-like tokens and any
+/// of `protected_terms`.  Sentences begin at the start of the text, on a new
+/// line, and after `.` / `!` / `?`.
+pub fn smart_capitalize(text: &str, protected_terms: &[String]) -> String {
+    let protected: Vec<&str> = protected_terms.iter().map(|t| t.trim()).filter(|t| !t.is_empty()).collect();
+    let mut out = String::with_capacity(text.len());
+    let mut in_backticks = false;
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let mut sentence_start = true;
+        let mut pos = 0;
+        for piece in line.split_inclusive(char::is_whitespace) {
+            let token = piece.trim_end();
+            let trailing = &piece[token.len()..];
+            let quoted = in_backticks || token.contains('`');
+            if token.matches('`').count() % 2 == 1 {
+                in_backticks = !in_backticks;
+            }
+
+            let core_start = token.find(|c: char| c.is_alphanumeric()).unwrap_or(token.len());
+            let core = core(token);
+            if sentence_start && !core.is_empty() {
+                let rest = &line[pos + core_start..];
+                if !quoted && !looks_like_code(core) && !starts_with_term(rest, &protected) {
+                    out.push_str(&token[..core_start]);
+                    out.push_str(&capitalize_first(&token[core_start..]));
+                } else {
+                    out.push_str(token);
+                }
+                sentence_start = false;
+            } else {
+                out.push_str(token);
+            }
+            out.push_str(trailing);
+
+            if !in_backticks && ends_sentence(token) {
+                sentence_start = true;
+            }
+            pos += piece.len();
+        }
+    }
+    out
+}
+
+const OPENING: &[char] = &['"', '\'', '(', '[', '“', '‘', '「', '『'];
+const CLOSING: &[char] = &['"', '\'', ')', ']', '”', '’', '」', '』'];
+
+/// `token` without surrounding quotes, brackets and trailing punctuation.
+fn core(token: &str) -> &str {
+    token
+        .trim_start_matches(OPENING)
+        .trim_end_matches(|c: char| CLOSING.contains(&c) || matches!(c, '.' | ',' | ';' | ':' | '!' | '?'))
+}
+
+fn ends_sentence(token: &str) -> bool {
+    token
+        .trim_end_matches(CLOSING)
+        .ends_with(['.', '!', '?', '。', '！', '？'])
+}
+
+/// camelCase, snake_case, paths, dotted names, calls, flags, handles.
+fn looks_like_code(word: &str) -> bool {
+    if word.contains(['_', '/', '\\', '`', '(', ')', '=', '<', '>', '{', '}', '[', ']', '$', '@', '#', ':'])
+        || word.starts_with('-')
+    {
+        return true;
+    }
+    let chars: Vec<char> = word.chars().collect();
+    chars.windows(2).any(|w| {
+        (w[0].is_lowercase() && w[1].is_uppercase()) || (w[0] == '.' && w[1].is_alphanumeric())
+    })
+}
+
+/// Whether `rest` begins with one of `terms`, spelled exactly, at a word boundary.
+fn starts_with_term(rest: &str, terms: &[&str]) -> bool {
+    terms.iter().any(|term| {
+        rest.starts_with(term)
+            && !rest[term.len()..].chars().next().is_some_and(|c| c.is_alphanumeric())
+    })
+}
+
+fn capitalize_first(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) if first.is_lowercase() => first.to_uppercase().chain(chars).collect(),
+        _ => word.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cap(text: &str) -> String {
+        smart_capitalize(text, &[])
+    }
+
+    #[test]
+    fn capitalizes_sentence_starts() {
+        assert_eq!(cap("hello world"), "Hello world");
+        assert_eq!(cap("it works. ship it! really? yes"), "It works. Ship it! Really? Yes");
+        assert_eq!(cap("first line\nsecond line"), "First line\nSecond line");
+        assert_eq!(cap("\"quoted start\" he said"), "\"Quoted start\" he said");
+    }
+
+    #[test]
+    fn leaves_code_tokens_intact() {
+        assert_eq!(cap("use the getUserData function"), "Use the getUserData function");
+        assert_eq!(cap("getUserData returns a promise"), "getUserData returns a promise");
+        assert_eq!(cap("user_id is required. src/main.rs has it"), "user_id is required. src/main.rs has it");
+        assert_eq!(cap("main.rs is the entry point"), "main.rs is the entry point");
+        assert_eq!(cap("--verbose prints more"), "--verbose prints more");
+    }
+
+    #[test]
+    fn backtick_spans_are_untouched() {
+        assert_eq!(cap("`ls` lists files. `git status. shows` more"), "`ls` lists files. `git status. shows` more");
+        assert_eq!(cap("run `make test`. then deploy"), "Run `make test`. Then deploy");
+    }
+
+    #[test]
+    fn protected_terms_keep_their_spelling() {
+        let terms = vec!["npm".to_string(), "iOS".to_string(), "gRPC".to_string()];
+        assert_eq!(smart_capitalize("npm install it. npmx is not npm", &terms), "npm install it. Npmx is not npm");
+        assert_eq!(smart_capitalize("iOS builds pass", &terms), "iOS builds pass");
+        assert_eq!(smart_capitalize("grpc is down", &terms), "Grpc is down");
+    }
+
+    #[test]
+    fn uncased_scripts_pass_through() {
+        assert_eq!(cap("今天天氣很好。明天見"), "今天天氣很好。明天見");
+        assert_eq!(cap(""), "");
+    }
+}
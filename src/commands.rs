@@ -1,19 +1,20 @@
 use crate::audio;
 use crate::credentials;
+use crate::error::SumiError;
 use crate::hotkey::{hotkey_display_label, parse_hotkey_string};
 use crate::platform;
 use crate::polisher::{self, PolishModelInfo};
 use crate::qwen3_asr as qwen3;
 use crate::settings::{self, Settings};
-use crate::stt::{Qwen3AsrModel, Qwen3AsrModelInfo, SttMode};
+use crate::stt::{self, LocalSttEngine, Qwen3AsrModel, Qwen3AsrModelInfo, SttMode, SttProvider};
 use crate::system_info::{self as sysinfo, SystemInfo};
 use crate::whisper_models::{self, WhisperModel, WhisperModelInfo};
 use crate::{history, meeting_notes, AppState};
 use serde::Serialize;
 use std::collections::HashMap;
-use std::sync::atomic::Ordering;
-use std::sync::Mutex;
-use std::time::Instant;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, Manager, State};
 #[cfg(unix)]
 extern crate libc;
@@ -39,6 +40,17 @@ pub fn get_cached_api_key(cache: &Mutex<HashMap<String, String>>, provider: &str
     }
 }
 
+/// Switch the model loaders between GPU and CPU, dropping the cached Whisper
+/// and LLM contexts so the next use reloads them on the new device.
+fn apply_force_cpu(state: &AppState, enabled: bool) {
+    settings::set_force_cpu(enabled);
+    if let Ok(mut cache) = state.whisper_ctx.lock() {
+        *cache = None;
+    }
+    polisher::invalidate_cache(&state.llm_model);
+    tracing::info!("Force CPU {}: model caches invalidated", if enabled { "enabled" } else { "disabled" });
+}
+
 #[tauri::command]
 pub fn get_settings(state: State<'_, AppState>) -> Settings {
     state.settings.lock()
@@ -48,85 +60,185 @@ pub fn get_settings(state: State<'_, AppState>) -> Settings {
 
 #[tauri::command]
 pub fn save_settings(
+    app: AppHandle,
     state: State<'_, AppState>,
     new_settings: Settings,
 ) -> Result<(), String> {
+    let http_proxy = match new_settings.http_proxy.as_deref() {
+        Some(raw) => crate::proxy::validate_proxy_url(raw)?,
+        None => None,
+    };
     let mut current = state.settings.lock().map_err(|e| e.to_string())?;
     current.auto_paste = new_settings.auto_paste;
     current.polish = new_settings.polish;
     current.history_retention_days = new_settings.history_retention_days;
+    current.history_max_entries = new_settings.history_max_entries;
+    current.history_max_audio_bytes = new_settings.history_max_audio_bytes;
+    current.history_trash_retention_days = new_settings.history_trash_retention_days;
     current.language = new_settings.language;
     current.stt = new_settings.stt;
     // Keep cloud.language in sync with top-level language
     current.stt.cloud.language = current.stt.language.clone();
     current.edit_hotkey = new_settings.edit_hotkey;
     current.meeting_hotkey = new_settings.meeting_hotkey;
+    current.repeat_paste_hotkey = new_settings.repeat_paste_hotkey;
     current.onboarding_completed = new_settings.onboarding_completed;
     current.idle_mic_timeout_secs = new_settings.idle_mic_timeout_secs;
+    current.idle_unload_minutes = new_settings.idle_unload_minutes;
+    current.auto_stop_silence_ms = new_settings.auto_stop_silence_ms;
+    current.session_mode = new_settings.session_mode;
+    current.queue_dictations = new_settings.queue_dictations;
+    current.history_audio_format = new_settings.history_audio_format;
+    current.history_audio_bit_depth = new_settings.history_audio_bit_depth;
+    current.overlay_auto_hide_ms = new_settings.overlay_auto_hide_ms;
+    current.overlay_style = new_settings.overlay_style;
+    current.overlay_show_reasoning = new_settings.overlay_show_reasoning;
+    current.visualizer_gain = settings::clamp_visualizer_gain(new_settings.visualizer_gain);
+    current.remove_fillers = new_settings.remove_fillers;
+    current.filler_words = new_settings.filler_words;
+    current.normalize_numbers = new_settings.normalize_numbers;
+    current.smart_capitalize = new_settings.smart_capitalize;
+    let force_cpu_changed = current.force_cpu != new_settings.force_cpu;
+    current.force_cpu = new_settings.force_cpu;
+    current.whisper_threads = new_settings.whisper_threads;
+    settings::set_whisper_threads(current.whisper_threads);
+    current.whisper_coreml = cfg!(all(target_os = "macos", feature = "coreml")) && new_settings.whisper_coreml;
+    current.http_proxy = http_proxy;
+    crate::proxy::set_http_proxy(current.http_proxy.as_deref());
+    state.rebuild_http_client();
+    current.cloud_max_retries = settings::clamp_cloud_max_retries(new_settings.cloud_max_retries);
+    crate::retry::set_max_retries(current.cloud_max_retries);
+    current.verify_target_on_paste = new_settings.verify_target_on_paste;
+    current.edit_scope = new_settings.edit_scope;
+    current.input_gain_db = settings::clamp_input_gain_db(new_settings.input_gain_db);
+    current.silence_threshold_db = audio::clamp_silence_threshold_db(new_settings.silence_threshold_db);
+    crate::audio::set_silence_threshold_db(current.silence_threshold_db);
+    current.auto_gain = new_settings.auto_gain;
+    current.max_processing_secs = new_settings.max_processing_secs;
+    current.max_recording_secs = settings::clamp_max_recording_secs(new_settings.max_recording_secs);
+    current.trailing_behavior = new_settings.trailing_behavior;
+    current.hotkey_debounce_ms = new_settings.hotkey_debounce_ms;
+    current.hotkey_mode = new_settings.hotkey_mode;
+    current.plain_text_only = new_settings.plain_text_only;
+    current.insertion_method = new_settings.insertion_method;
+    current.keystroke_delay_ms = settings::clamp_keystroke_delay_ms(new_settings.keystroke_delay_ms);
+    current.paste_grace_ms = new_settings.paste_grace_ms;
+    let log_level_changed = current.log_level != new_settings.log_level;
+    current.log_level = new_settings.log_level;
+    current.local_api = new_settings.local_api;
     settings::save_settings_to_disk(&current);
+    let force_cpu = current.force_cpu;
+    let log_level = current.log_level;
+    let local_api = current.local_api.clone();
+    drop(current);
+    // Outside the settings lock: a running transcription holds the Whisper lock.
+    if force_cpu_changed {
+        apply_force_cpu(&state, force_cpu);
+    }
+    if log_level_changed {
+        crate::apply_log_level(log_level);
+    }
+    crate::local_api::apply(&app, &local_api);
     Ok(())
 }
 
-#[tauri::command]
-pub fn update_hotkey(
-    app: AppHandle,
-    state: State<'_, AppState>,
-    hotkey: String,
-) -> Result<(), String> {
-    use tauri_plugin_global_shortcut::GlobalShortcutExt;
+// ── Focused engine switches ─────────────────────────────────────────────────
+//
+// These change one field and persist it, so the UI need not round-trip the
+// whole `Settings` (and its partially-edited state) just to flip an engine.
 
-    let shortcut =
-        parse_hotkey_string(&hotkey).ok_or_else(|| "Invalid hotkey string".to_string())?;
+/// Refuse an engine change while audio is being captured or transcribed.
+fn guard_engine_change(state: &AppState) -> Result<(), String> {
+    if state.is_recording.load(Ordering::SeqCst)
+        || state.is_processing.load(Ordering::SeqCst)
+        || state.meeting_active.load(Ordering::SeqCst)
+    {
+        return Err("Cannot change engine while recording or processing".to_string());
+    }
+    Ok(())
+}
 
-    // Check for conflicts with existing edit/meeting hotkeys before touching shortcuts.
+/// Switch transcription between local and cloud.  Switching to cloud frees
+/// the local STT models; switching back loads them lazily on next use.
+#[tauri::command]
+pub fn set_stt_mode(state: State<'_, AppState>, mode: SttMode) -> Result<(), String> {
+    guard_engine_change(&state)?;
     {
-        let settings = state.settings.lock().map_err(|e| e.to_string())?;
-        if settings.edit_hotkey.as_deref() == Some(hotkey.as_str()) {
-            return Err("Primary hotkey must differ from edit hotkey".to_string());
+        let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+        if settings.stt.mode == mode {
+            return Ok(());
         }
-        if settings.meeting_hotkey.as_deref() == Some(hotkey.as_str()) {
-            return Err("Primary hotkey must differ from meeting hotkey".to_string());
+        settings.stt.mode = mode.clone();
+        settings::save_settings_to_disk(&settings);
+    }
+    if mode == SttMode::Cloud {
+        if let Ok(mut ctx) = state.whisper_ctx.lock() {
+            *ctx = None;
         }
+        *state.qwen3_ready_mu.lock().unwrap_or_else(|e| e.into_inner()) = false;
+        qwen3::invalidate_qwen3_asr_cache(&state.qwen3_asr_ctx);
+        state.qwen3_ready_cv.notify_all();
     }
+    tracing::info!("STT mode set to {:?}", mode);
+    Ok(())
+}
 
-    app.global_shortcut()
-        .unregister_all()
-        .map_err(|e| format!("Failed to unregister shortcuts: {}", e))?;
-
-    app.global_shortcut()
-        .register(shortcut)
-        .map_err(|e| format!("Failed to register shortcut: {}", e))?;
-
+/// Switch the cloud STT provider, resetting the endpoint (or Azure region)
+/// and model to the new provider's defaults.
+#[tauri::command]
+pub fn set_stt_provider(state: State<'_, AppState>, provider: SttProvider) -> Result<(), String> {
+    guard_engine_change(&state)?;
     let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
-    settings.hotkey = hotkey.clone();
+    if settings.stt.cloud.provider == provider {
+        return Ok(());
+    }
+    settings.stt.cloud.switch_provider(provider);
     settings::save_settings_to_disk(&settings);
+    tracing::info!("STT provider set to {:?}", settings.stt.cloud.provider);
+    Ok(())
+}
 
-    if let Some(ref edit_hk) = settings.edit_hotkey {
-        if let Some(edit_shortcut) = parse_hotkey_string(edit_hk) {
-            if let Err(e) = app.global_shortcut().register(edit_shortcut) {
-                tracing::warn!("Failed to re-register edit hotkey: {}", e);
-            }
+/// Switch polishing between the local LLM and a cloud provider.  Switching
+/// to cloud unloads the local LLM.
+#[tauri::command]
+pub fn set_polish_mode(state: State<'_, AppState>, mode: polisher::PolishMode) -> Result<(), String> {
+    guard_engine_change(&state)?;
+    {
+        let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+        if settings.polish.mode == mode {
+            return Ok(());
         }
+        settings.polish.mode = mode.clone();
+        settings::save_settings_to_disk(&settings);
     }
-
-    if let Some(ref meeting_hk) = settings.meeting_hotkey {
-        if let Some(meeting_shortcut) = parse_hotkey_string(meeting_hk) {
-            if let Err(e) = app.global_shortcut().register(meeting_shortcut) {
-                tracing::warn!("Failed to re-register meeting hotkey: {}", e);
-            }
-        }
+    if mode == polisher::PolishMode::Cloud {
+        polisher::invalidate_cache(&state.llm_model);
     }
+    tracing::info!("Polish mode set to {:?}", mode);
+    Ok(())
+}
 
-    let label = hotkey_display_label(&hotkey);
-    if let Some(tray) = app.tray_by_id("main-tray") {
-        let tooltip = if settings::is_debug() {
-            format!("Sumi [Dev] – {} to record", label)
-        } else {
-            format!("Sumi – {} to record", label)
-        };
-        let _ = tray.set_tooltip(Some(&tooltip));
+#[tauri::command]
+pub fn update_hotkey(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    hotkey: String,
+) -> Result<(), String> {
+    parse_hotkey_string(&hotkey).ok_or_else(|| "Invalid hotkey string".to_string())?;
+
+    // Check for conflicts with the other hotkeys before touching shortcuts.
+    let mut updated = state.settings.lock().map_err(|e| e.to_string())?.clone();
+    check_hotkey_conflict(&updated, "primary", &hotkey)?;
+    updated.hotkey = hotkey.clone();
+    register_or_revert(&app, &state, "primary", &updated)?;
+
+    {
+        let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+        settings.hotkey = hotkey.clone();
+        settings::save_settings_to_disk(&settings);
     }
 
+    let label = hotkey_display_label(&hotkey);
     tracing::info!("Hotkey updated to: {} ({})", hotkey, label);
     Ok(())
 }
@@ -137,58 +249,20 @@ pub fn update_edit_hotkey(
     state: State<'_, AppState>,
     hotkey: Option<String>,
 ) -> Result<(), String> {
-    use tauri_plugin_global_shortcut::GlobalShortcutExt;
-
-    // Validate BEFORE unregistering, so a failed validation cannot leave
-    // all shortcuts permanently unregistered (mirrors update_meeting_hotkey).
-    let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
-
+    // Validate BEFORE re-registering, so a rejected hotkey leaves the current
+    // shortcuts untouched (mirrors update_meeting_hotkey).
+    let hotkey = hotkey.filter(|s| !s.is_empty());
+    let mut updated = state.settings.lock().map_err(|e| e.to_string())?.clone();
     if let Some(ref hk) = hotkey {
-        if !hk.is_empty() {
-            let _ = parse_hotkey_string(hk)
-                .ok_or_else(|| "Invalid edit hotkey string".to_string())?;
-            // Symmetric conflict check: edit hotkey must not match primary or meeting hotkey.
-            if *hk == settings.hotkey {
-                return Err("Edit hotkey must differ from primary hotkey".to_string());
-            }
-            if settings.meeting_hotkey.as_deref() == Some(hk.as_str()) {
-                return Err("Edit hotkey must differ from meeting hotkey".to_string());
-            }
-        }
-    }
-    settings.edit_hotkey = hotkey.filter(|s| !s.is_empty());
-
-    // Unregister only after validation succeeds.
-    app.global_shortcut()
-        .unregister_all()
-        .map_err(|e| format!("Failed to unregister shortcuts: {}", e))?;
-
-    let primary = parse_hotkey_string(&settings.hotkey)
-        .ok_or_else(|| "Invalid primary hotkey".to_string())?;
-    app.global_shortcut()
-        .register(primary)
-        .map_err(|e| format!("Failed to register primary shortcut: {}", e))?;
-
-    if let Some(ref edit_hk) = settings.edit_hotkey {
-        if let Some(shortcut) = parse_hotkey_string(edit_hk) {
-            app.global_shortcut()
-                .register(shortcut)
-                .map_err(|e| format!("Failed to register edit shortcut: {}", e))?;
-            tracing::info!("Edit hotkey registered: {}", edit_hk);
-        }
+        let _ = parse_hotkey_string(hk)
+            .ok_or_else(|| "Invalid edit hotkey string".to_string())?;
+        check_hotkey_conflict(&updated, "edit", hk)?;
     }
+    updated.edit_hotkey = hotkey;
+    register_or_revert(&app, &state, "edit", &updated)?;
 
-    if let Some(ref meeting_hk) = settings.meeting_hotkey {
-        if let Some(shortcut) = parse_hotkey_string(meeting_hk) {
-            if let Err(e) = app.global_shortcut().register(shortcut) {
-                tracing::warn!("Failed to re-register meeting hotkey: {}", e);
-            }
-        }
-    }
-
-    *state.registered_edit_shortcut.lock().map_err(|e| e.to_string())? =
-        settings.edit_hotkey.as_deref().and_then(parse_hotkey_string);
-
+    let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+    settings.edit_hotkey = updated.edit_hotkey;
     settings::save_settings_to_disk(&settings);
     tracing::info!("Edit hotkey updated to: {:?}", settings.edit_hotkey);
     Ok(())
@@ -200,8 +274,6 @@ pub fn update_meeting_hotkey(
     state: State<'_, AppState>,
     hotkey: Option<String>,
 ) -> Result<(), String> {
-    use tauri_plugin_global_shortcut::GlobalShortcutExt;
-
     // Refuse if a meeting is in progress — unregistering the active hotkey
     // would make it impossible to stop the meeting via keyboard.
     if state.meeting_active.load(Ordering::SeqCst) {
@@ -209,67 +281,152 @@ pub fn update_meeting_hotkey(
     }
 
     // Validate before making any changes so a bad hotkey string does not leave
-    // the app with no shortcuts registered (unregister_all already called).
+    // the app with no shortcuts registered.
+    let hotkey = hotkey.filter(|s| !s.is_empty());
+    let mut updated = state.settings.lock().map_err(|e| e.to_string())?.clone();
+    if let Some(ref hk) = hotkey {
+        let _ = parse_hotkey_string(hk)
+            .ok_or_else(|| "Invalid meeting hotkey string".to_string())?;
+        // Must include at least one modifier to avoid swallowing bare keypresses.
+        let has_modifier = ["Alt+", "Control+", "Shift+", "Super+"]
+            .iter()
+            .any(|m| hk.contains(m));
+        if !has_modifier {
+            return Err("Meeting hotkey must include at least one modifier key".to_string());
+        }
+        check_hotkey_conflict(&updated, "meeting", hk)?;
+    }
+    updated.meeting_hotkey = hotkey;
+    register_or_revert(&app, &state, "meeting", &updated)?;
+
     let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+    settings.meeting_hotkey = updated.meeting_hotkey;
+    settings::save_settings_to_disk(&settings);
+    tracing::info!("Meeting hotkey updated to: {:?}", settings.meeting_hotkey);
+    Ok(())
+}
 
+/// Set or clear (None / "") the hotkey that pastes the last transcript again.
+#[tauri::command]
+pub fn update_repeat_hotkey(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    hotkey: Option<String>,
+) -> Result<(), String> {
+    let hotkey = hotkey.filter(|s| !s.is_empty());
+    let mut updated = state.settings.lock().map_err(|e| e.to_string())?.clone();
     if let Some(ref hk) = hotkey {
-        if !hk.is_empty() {
-            let _ = parse_hotkey_string(hk)
-                .ok_or_else(|| "Invalid meeting hotkey string".to_string())?;
-            // Must include at least one modifier to avoid swallowing bare keypresses.
-            let has_modifier = ["Alt+", "Control+", "Shift+", "Super+"]
-                .iter()
-                .any(|m| hk.contains(m));
-            if !has_modifier {
-                return Err("Meeting hotkey must include at least one modifier key".to_string());
-            }
-            // Must not conflict with primary or edit hotkeys.
-            if *hk == settings.hotkey {
-                return Err("Meeting hotkey must differ from primary hotkey".to_string());
-            }
-            if settings.edit_hotkey.as_deref() == Some(hk.as_str()) {
-                return Err("Meeting hotkey must differ from edit hotkey".to_string());
-            }
-        }
+        let _ = parse_hotkey_string(hk)
+            .ok_or_else(|| "Invalid repeat-paste hotkey string".to_string())?;
+        check_hotkey_conflict(&updated, "repeat-paste", hk)?;
     }
-    settings.meeting_hotkey = hotkey.filter(|s| !s.is_empty());
+    updated.repeat_paste_hotkey = hotkey;
+    register_or_revert(&app, &state, "repeat-paste", &updated)?;
 
-    // Unregister only after validation succeeds.
-    app.global_shortcut()
-        .unregister_all()
-        .map_err(|e| format!("Failed to unregister shortcuts: {}", e))?;
+    let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+    settings.repeat_paste_hotkey = updated.repeat_paste_hotkey;
+    settings::save_settings_to_disk(&settings);
+    tracing::info!("Repeat-paste hotkey updated to: {:?}", settings.repeat_paste_hotkey);
+    Ok(())
+}
 
-    // Re-register primary hotkey.
-    let primary = parse_hotkey_string(&settings.hotkey)
-        .ok_or_else(|| "Invalid primary hotkey".to_string())?;
-    app.global_shortcut()
-        .register(primary)
-        .map_err(|e| format!("Failed to register primary shortcut: {}", e))?;
+/// Every hotkey combination `settings` claims, by role.  Includes the
+/// Shift + primary paste-raw variant when that shortcut is enabled.
+fn claimed_hotkeys(settings: &Settings) -> Vec<(&'static str, String)> {
+    let mut claimed = vec![("primary", settings.hotkey.clone())];
+    let optional = [
+        ("edit", &settings.edit_hotkey),
+        ("meeting", &settings.meeting_hotkey),
+        ("repeat-paste", &settings.repeat_paste_hotkey),
+    ];
+    for (role, hk) in optional {
+        if let Some(hk) = hk {
+            claimed.push((role, hk.clone()));
+        }
+    }
+    if settings.paste_raw_on_modifier {
+        if let Some(variant) = crate::hotkey::shift_variant(&settings.hotkey) {
+            claimed.push(("paste-raw", variant));
+        }
+    }
+    claimed
+}
 
-    // Re-register edit hotkey if set.
-    if let Some(ref edit_hk) = settings.edit_hotkey {
-        if let Some(shortcut) = parse_hotkey_string(edit_hk) {
-            if let Err(e) = app.global_shortcut().register(shortcut) {
-                tracing::warn!("Failed to re-register edit hotkey: {}", e);
-            }
+/// Reject `hotkey` for `role` when another of the app's hotkeys already uses
+/// the same combination.
+fn check_hotkey_conflict(settings: &Settings, role: &str, hotkey: &str) -> Result<(), String> {
+    for (other, claimed) in claimed_hotkeys(settings) {
+        // The paste-raw variant follows the primary hotkey, so it moves with it.
+        if other == role || (role == "primary" && other == "paste-raw") {
+            continue;
+        }
+        if crate::hotkey::same_hotkey(hotkey, &claimed) {
+            return Err(format!(
+                "{} is already the {} hotkey",
+                hotkey_display_label(hotkey),
+                other
+            ));
         }
     }
+    Ok(())
+}
+
+/// The error shown when the OS refuses to register `hotkey`, which in
+/// practice means another app (or the system) has claimed the combination.
+fn hotkey_in_use(hotkey: &str, err: impl std::fmt::Display) -> String {
+    tracing::warn!("Failed to register shortcut {}: {}", hotkey, err);
+    format!(
+        "{} is already in use by another app or the system",
+        hotkey_display_label(hotkey)
+    )
+}
 
-    // Register new meeting hotkey if set.
-    if let Some(ref meeting_hk) = settings.meeting_hotkey {
-        if let Some(shortcut) = parse_hotkey_string(meeting_hk) {
-            app.global_shortcut()
-                .register(shortcut)
-                .map_err(|e| format!("Failed to register meeting shortcut: {}", e))?;
-            tracing::info!("Meeting hotkey registered: {}", meeting_hk);
+/// Register `updated`'s shortcuts, requiring the `role` hotkey to succeed.
+/// On failure the shortcuts from the current settings are restored, so a
+/// bad assignment never leaves the app without a working hotkey.
+fn register_or_revert(
+    app: &AppHandle,
+    state: &AppState,
+    role: &str,
+    updated: &Settings,
+) -> Result<(), String> {
+    let result = register_configured_hotkeys(app, state, updated).and_then(|failed| {
+        match failed.iter().find(|(r, _)| *r == role) {
+            Some((_, hotkey)) => Err(hotkey_in_use(hotkey, "registration failed")),
+            None => Ok(()),
+        }
+    });
+    if let Err(e) = result {
+        let previous = state.settings.lock().map_err(|e| e.to_string())?.clone();
+        if let Err(revert_err) = register_configured_hotkeys(app, state, &previous) {
+            tracing::error!("Failed to restore previous shortcuts: {}", revert_err);
         }
+        return Err(e);
     }
+    Ok(())
+}
 
-    *state.registered_meeting_shortcut.lock().map_err(|e| e.to_string())? =
-        settings.meeting_hotkey.as_deref().and_then(parse_hotkey_string);
+/// Enable or disable the Shift + primary hotkey "paste raw" shortcut.
+#[tauri::command]
+pub fn set_paste_raw_on_modifier(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), String> {
+    use tauri_plugin_global_shortcut::GlobalShortcutExt;
+
+    let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+    settings.paste_raw_on_modifier = enabled;
+
+    let previous = state.registered_raw_shortcut.lock().map_err(|e| e.to_string())?.take();
+    if let Some(shortcut) = previous {
+        if let Err(e) = app.global_shortcut().unregister(shortcut) {
+            tracing::warn!("Failed to unregister paste-raw shortcut: {}", e);
+        }
+    }
+    crate::register_raw_paste_shortcut(&app, &state, &settings);
 
     settings::save_settings_to_disk(&settings);
-    tracing::info!("Meeting hotkey updated to: {:?}", settings.meeting_hotkey);
     Ok(())
 }
 
@@ -282,32 +439,92 @@ pub fn trigger_undo(app: AppHandle) -> Result<(), String> {
         let app_for_hide = app_handle.clone();
         let _ = app_handle.run_on_main_thread(move || {
             if let Some(overlay) = app_for_hide.get_webview_window("overlay") {
-                platform::hide_overlay(&overlay);
+                crate::hide_overlay_idle(&overlay);
             }
         });
     });
     Ok(())
 }
 
+/// Hide the overlay on request (close affordance when it stays until dismissed).
 #[tauri::command]
-pub fn reset_settings(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
-    use tauri_plugin_global_shortcut::GlobalShortcutExt;
+pub fn dismiss_overlay(app: AppHandle) -> Result<(), String> {
+    let app_for_hide = app.clone();
+    app.run_on_main_thread(move || {
+        if let Some(overlay) = app_for_hide.get_webview_window("overlay") {
+            crate::hide_overlay_idle(&overlay);
+        }
+    })
+    .map_err(|e| e.to_string())
+}
 
+#[tauri::command]
+pub fn reset_settings(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
     // Save bare defaults to disk, then re-load and apply locale defaults.
+    let was_force_cpu = settings::force_cpu();
     settings::save_settings_to_disk(&Settings::default());
     let mut fresh = settings::load_settings();
+    // load_settings cleared the proxy; the shared client still routes through it.
+    state.rebuild_http_client();
+    crate::apply_log_level(fresh.log_level);
+    if was_force_cpu {
+        apply_force_cpu(&state, false);
+    }
     settings::apply_locale_defaults(&mut fresh);
     let default_hotkey = fresh.hotkey.clone();
-    let default_edit_hotkey = fresh.edit_hotkey.clone();
-    let default_meeting_hotkey = fresh.meeting_hotkey.clone();
+    let local_api = fresh.local_api.clone();
 
-    {
+    let stream_changed = {
         let mut current = state.settings.lock().map_err(|e| e.to_string())?;
+        let stream_changed =
+            current.channel_mode != fresh.channel_mode || current.capture_mode != fresh.capture_mode;
         *current = fresh;
+        register_configured_hotkeys(&app, &state, &current)?;
+        stream_changed
+    };
+    // load_settings already applied the default modes; the open stream was
+    // built with the old ones.
+    if stream_changed {
+        audio::close_audio_stream(&state.audio_thread, &state.mic_available);
     }
+    crate::local_api::apply(&app, &local_api);
 
-    let shortcut = parse_hotkey_string(&default_hotkey)
-        .ok_or_else(|| "Invalid default hotkey string".to_string())?;
+    let label = hotkey_display_label(&default_hotkey);
+    tracing::info!("Settings reset to defaults (hotkey: {})", label);
+    Ok(())
+}
+
+/// Replay the setup wizard: clear `onboarding_completed`, persist, and bring
+/// up the main window with the setup overlay.  Everything else (dictionary,
+/// prompt rules, hotkeys, models) is left untouched.
+#[tauri::command]
+pub fn restart_onboarding(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    {
+        let mut current = state.settings.lock().map_err(|e| e.to_string())?;
+        current.onboarding_completed = false;
+        settings::save_settings_to_disk(&current);
+    }
+    crate::show_settings_window(&app);
+    if let Some(main_win) = app.get_webview_window("main") {
+        let _ = main_win.emit("show-setup", ());
+    }
+    tracing::info!("Onboarding restarted");
+    Ok(())
+}
+
+/// Unregister every global shortcut and register the ones configured in
+/// `settings` (primary, edit, meeting, paste-raw, repeat-paste), refreshing the shortcut
+/// identity caches and the tray tooltip.  Only a primary failure is an
+/// error; the optional hotkeys the OS refused are returned by role.
+fn register_configured_hotkeys(
+    app: &AppHandle,
+    state: &AppState,
+    settings: &Settings,
+) -> Result<Vec<(&'static str, String)>, String> {
+    use tauri_plugin_global_shortcut::GlobalShortcutExt;
+
+    let shortcut = parse_hotkey_string(&settings.hotkey)
+        .ok_or_else(|| "Invalid primary hotkey string".to_string())?;
 
     app.global_shortcut()
         .unregister_all()
@@ -315,33 +532,36 @@ pub fn reset_settings(app: AppHandle, state: State<'_, AppState>) -> Result<(),
 
     app.global_shortcut()
         .register(shortcut)
-        .map_err(|e| format!("Failed to register shortcut: {}", e))?;
-
-    // Re-register edit hotkey if the default settings include one.
-    if let Some(ref edit_hk) = default_edit_hotkey {
-        if let Some(s) = parse_hotkey_string(edit_hk) {
-            if let Err(e) = app.global_shortcut().register(s) {
-                tracing::warn!("Failed to re-register edit hotkey after reset: {}", e);
-            }
-        }
-    }
-
-    // Re-register meeting hotkey if the default settings include one.
-    if let Some(ref meeting_hk) = default_meeting_hotkey {
-        if let Some(s) = parse_hotkey_string(meeting_hk) {
-            if let Err(e) = app.global_shortcut().register(s) {
-                tracing::warn!("Failed to re-register meeting hotkey after reset: {}", e);
+        .map_err(|e| hotkey_in_use(&settings.hotkey, e))?;
+
+    let mut failed = Vec::new();
+    let optional = [("edit", &settings.edit_hotkey), ("meeting", &settings.meeting_hotkey)];
+    for (role, hk) in optional {
+        if let Some(hk) = hk {
+            if let Some(s) = parse_hotkey_string(hk) {
+                if let Err(e) = app.global_shortcut().register(s) {
+                    tracing::warn!("Failed to re-register {} hotkey: {}", role, e);
+                    failed.push((role, hk.clone()));
+                }
             }
         }
     }
 
     // Update shortcut identity caches to match the newly registered shortcuts.
     *state.registered_edit_shortcut.lock().map_err(|e| e.to_string())? =
-        default_edit_hotkey.as_deref().and_then(parse_hotkey_string);
+        settings.edit_hotkey.as_deref().and_then(parse_hotkey_string);
     *state.registered_meeting_shortcut.lock().map_err(|e| e.to_string())? =
-        default_meeting_hotkey.as_deref().and_then(parse_hotkey_string);
+        settings.meeting_hotkey.as_deref().and_then(parse_hotkey_string);
+    crate::register_raw_paste_shortcut(app, state, settings);
+    crate::register_repeat_paste_shortcut(app, state, settings);
+    crate::restore_cancel_shortcut(app, settings);
+    if let Some(ref hk) = settings.repeat_paste_hotkey {
+        if state.registered_repeat_shortcut.lock().map_err(|e| e.to_string())?.is_none() {
+            failed.push(("repeat-paste", hk.clone()));
+        }
+    }
 
-    let label = hotkey_display_label(&default_hotkey);
+    let label = hotkey_display_label(&settings.hotkey);
     if let Some(tray) = app.tray_by_id("main-tray") {
         let tooltip = if settings::is_debug() {
             format!("Sumi [Dev] – {} to record", label)
@@ -350,11 +570,166 @@ pub fn reset_settings(app: AppHandle, state: State<'_, AppState>) -> Result<(),
         };
         let _ = tray.set_tooltip(Some(&tooltip));
     }
+    Ok(failed)
+}
 
-    tracing::info!("Settings reset to defaults (hotkey: {})", label);
+/// Re-register every configured shortcut from the current settings.
+///
+/// macOS can silently drop global shortcuts across sleep/wake, leaving the
+/// hotkeys dead until relaunch; called from the wake observer.
+pub(crate) fn reregister_hotkeys(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let settings = state.settings.lock().map_err(|e| e.to_string())?.clone();
+    register_configured_hotkeys(app, &state, &settings).map(|_| ())
+}
+
+/// Replace `current` with `updated` once `register` has accepted its
+/// hotkeys, returning the settings it replaced.  On failure the hotkeys of
+/// the untouched `current` are registered again (registration starts by
+/// dropping every shortcut), so a rejected import leaves nothing half-applied.
+fn swap_if_registered(
+    current: &mut Settings,
+    updated: Settings,
+    register: impl Fn(&Settings) -> Result<(), String>,
+) -> Result<Settings, String> {
+    if let Err(e) = register(&updated) {
+        if let Err(revert_err) = register(current) {
+            tracing::error!("Failed to restore previous shortcuts: {}", revert_err);
+        }
+        return Err(e);
+    }
+    Ok(std::mem::replace(current, updated))
+}
+
+/// Check that imported hotkeys parse and don't collide with each other.
+fn validate_hotkeys(settings: &Settings) -> Result<(), String> {
+    parse_hotkey_string(&settings.hotkey)
+        .ok_or_else(|| format!("Invalid primary hotkey: {}", settings.hotkey))?;
+    let optional = [
+        ("edit", &settings.edit_hotkey),
+        ("meeting", &settings.meeting_hotkey),
+        ("repeat-paste", &settings.repeat_paste_hotkey),
+    ];
+    for (i, (name, hk)) in optional.iter().enumerate() {
+        if let Some(hk) = hk {
+            parse_hotkey_string(hk).ok_or_else(|| format!("Invalid {} hotkey: {}", name, hk))?;
+            if crate::hotkey::same_hotkey(hk, &settings.hotkey) {
+                return Err(format!("The {} hotkey must differ from the primary hotkey", name));
+            }
+            if let Some((other, _)) = optional[..i]
+                .iter()
+                .find(|(_, o)| o.as_deref().is_some_and(|o| crate::hotkey::same_hotkey(o, hk)))
+            {
+                return Err(format!("The {} and {} hotkeys must differ", other, name));
+            }
+        }
+    }
     Ok(())
 }
 
+/// Serialize the whole configuration as pretty JSON for backup or moving to
+/// another machine.  API keys live in the keychain and are never part of
+/// `Settings`; the machine-local `data_root` and the local API token are
+/// left out as well.
+#[tauri::command]
+pub fn export_settings(state: State<'_, AppState>) -> Result<String, String> {
+    let settings = state.settings.lock().map_err(|e| e.to_string())?;
+    let mut value = serde_json::to_value(&*settings).map_err(|e| e.to_string())?;
+    if let Some(obj) = value.as_object_mut() {
+        obj.remove("data_root");
+        if let Some(local_api) = obj.get_mut("local_api").and_then(|v| v.as_object_mut()) {
+            local_api.remove("token");
+        }
+    }
+    serde_json::to_string_pretty(&value).map_err(|e| e.to_string())
+}
+
+/// Replace the whole configuration with JSON produced by [`export_settings`]
+/// (or a raw `settings.json`).  The input goes through the same migration as
+/// a file on disk and is validated before anything is applied; the local
+/// `data_root` is kept, as is the local API token when the input has none.
+/// Returns the top-level setting keys that changed.
+#[tauri::command]
+pub fn import_settings(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    json: String,
+) -> Result<Vec<String>, String> {
+    if state.is_recording.load(Ordering::SeqCst) {
+        return Err("Cannot import settings while recording".to_string());
+    }
+    if state.meeting_active.load(Ordering::SeqCst) {
+        return Err("Cannot import settings while a meeting is in progress".to_string());
+    }
+
+    let mut value: serde_json::Value =
+        serde_json::from_str(&json).map_err(|e| format!("Invalid settings JSON: {}", e))?;
+    if let Some(obj) = value.as_object_mut() {
+        obj.remove("data_root");
+    }
+    let mut imported = settings::migrate_settings(value)?;
+    imported.stt.migrate_language();
+    if imported.polish.model == polisher::PolishModel::Unknown {
+        imported.polish.model = polisher::recommend_polish_model(imported.language.as_deref());
+    }
+    imported.stt.cloud.language = imported.stt.language.clone();
+    imported.edit_hotkey = imported.edit_hotkey.filter(|s| !s.is_empty());
+    imported.meeting_hotkey = imported.meeting_hotkey.filter(|s| !s.is_empty());
+    imported.repeat_paste_hotkey = imported.repeat_paste_hotkey.filter(|s| !s.is_empty());
+    imported.max_recording_secs = settings::clamp_max_recording_secs(imported.max_recording_secs);
+    imported.input_gain_db = settings::clamp_input_gain_db(imported.input_gain_db);
+    imported.keystroke_delay_ms = settings::clamp_keystroke_delay_ms(imported.keystroke_delay_ms);
+    imported.cloud_max_retries = settings::clamp_cloud_max_retries(imported.cloud_max_retries);
+    imported.silence_threshold_db = audio::clamp_silence_threshold_db(imported.silence_threshold_db);
+    imported.whisper_coreml = cfg!(all(target_os = "macos", feature = "coreml")) && imported.whisper_coreml;
+    imported.http_proxy = match imported.http_proxy.as_deref() {
+        Some(raw) => crate::proxy::validate_proxy_url(raw)?,
+        None => None,
+    };
+    validate_hotkeys(&imported)?;
+
+    let (changed, previous, applied) = {
+        let mut current = state.settings.lock().map_err(|e| e.to_string())?;
+        imported.data_root = current.data_root.clone();
+        if imported.local_api.token.is_empty() {
+            imported.local_api.token = current.local_api.token.clone();
+        }
+        let changed = settings::changed_keys(&current, &imported);
+        let previous = swap_if_registered(&mut current, imported, |s| {
+            register_configured_hotkeys(&app, &state, s).map(|_| ())
+        })?;
+        settings::save_settings_to_disk(&current);
+        (changed, previous, current.clone())
+    };
+
+    // Runtime flags are applied outside the settings lock (see save_settings),
+    // and only once the new hotkeys are live and the file is written.
+    settings::set_whisper_threads(applied.whisper_threads);
+    crate::apply_log_level(applied.log_level);
+    crate::proxy::set_http_proxy(applied.http_proxy.as_deref());
+    state.rebuild_http_client();
+    crate::retry::set_max_retries(applied.cloud_max_retries);
+    audio::set_silence_threshold_db(applied.silence_threshold_db);
+    let force_cpu = applied.force_cpu;
+    let force_cpu_changed = previous.force_cpu != force_cpu;
+    let channel_mode = applied.channel_mode.clone();
+    let capture_mode = applied.capture_mode;
+    let stream_changed = previous.channel_mode != channel_mode || previous.capture_mode != capture_mode;
+    let local_api = applied.local_api;
+    if force_cpu_changed {
+        apply_force_cpu(&state, force_cpu);
+    }
+    if stream_changed {
+        audio::set_channel_mode(channel_mode);
+        audio::set_capture_mode(capture_mode);
+        audio::close_audio_stream(&state.audio_thread, &state.mic_available);
+    }
+    crate::local_api::apply(&app, &local_api);
+
+    tracing::info!("Imported settings ({} keys changed)", changed.len());
+    Ok(changed)
+}
+
 #[tauri::command]
 pub fn get_default_prompt() -> String {
     polisher::base_prompt_template()
@@ -365,6 +740,16 @@ pub fn get_default_prompt_rules(language: Option<String>) -> Vec<polisher::Promp
     polisher::default_prompt_rules_for_lang(language.as_deref())
 }
 
+#[tauri::command]
+pub fn get_default_filler_words(language: String) -> Vec<String> {
+    crate::fillers::default_filler_words(&language)
+}
+
+#[tauri::command]
+pub fn list_supported_languages(provider: crate::stt::SttProvider) -> Vec<crate::languages::LanguageOption> {
+    crate::languages::supported_languages(&provider)
+}
+
 #[tauri::command]
 pub fn save_api_key(state: State<'_, AppState>, provider: String, key: String) -> Result<(), String> {
     if key.is_empty() {
@@ -386,6 +771,54 @@ pub fn get_api_key(state: State<'_, AppState>, provider: String) -> Result<Strin
     Ok(get_cached_api_key(&state.api_key_cache, &provider))
 }
 
+/// Provider keys that currently have a non-empty stored credential.
+///
+/// Probes each known provider individually — the keychain cannot be
+/// wildcard-listed (see `credentials::known_providers`).
+#[tauri::command]
+pub async fn list_stored_credentials() -> Result<Vec<String>, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        credentials::known_providers()
+            .into_iter()
+            .filter(|provider| match credentials::load(provider) {
+                Ok(key) => !key.is_empty(),
+                Err(e) => {
+                    tracing::warn!("Credential probe for {} failed: {}", provider, e);
+                    false
+                }
+            })
+            .map(str::to_string)
+            .collect()
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Delete every stored provider credential and empty the in-memory key cache.
+#[tauri::command]
+pub async fn clear_all_credentials(app: AppHandle) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut failures = Vec::new();
+        for provider in credentials::known_providers() {
+            if let Err(e) = credentials::delete(provider) {
+                failures.push(format!("{}: {}", provider, e));
+            }
+        }
+        let state = app.state::<AppState>();
+        if let Ok(mut map) = state.api_key_cache.lock() {
+            map.clear();
+        }
+        tracing::info!("Cleared stored credentials ({} failures)", failures.len());
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Failed to delete some credentials: {}", failures.join("; ")))
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
 #[derive(Serialize)]
 pub struct HistoryPage {
     pub entries: Vec<history::HistoryEntry>,
@@ -393,60 +826,251 @@ pub struct HistoryPage {
 }
 
 #[tauri::command]
-pub async fn get_history_page(
-    before_timestamp: Option<i64>,
-    limit: Option<u32>,
-) -> Result<HistoryPage, String> {
+pub async fn get_history_page(
+    before_timestamp: Option<i64>,
+    limit: Option<u32>,
+) -> Result<HistoryPage, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let limit = limit.unwrap_or(10);
+        let (entries, has_more) =
+            history::load_history_page(&settings::history_dir(), before_timestamp, limit);
+        HistoryPage { entries, has_more }
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_entry_tags(id: String, tags: Vec<String>) -> Result<Vec<String>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        history::set_entry_tags(&settings::history_dir(), &id, tags)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_history_by_tag(
+    tag: String,
+    before_timestamp: Option<i64>,
+    limit: Option<u32>,
+) -> Result<HistoryPage, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let limit = limit.unwrap_or(10);
+        let (entries, has_more) =
+            history::load_history_by_tag(&settings::history_dir(), &tag, before_timestamp, limit);
+        HistoryPage { entries, has_more }
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_history_stats() -> Result<history::HistoryStats, String> {
+    tauri::async_runtime::spawn_blocking(move || history::get_stats(&settings::history_dir()))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Usage summary for entries in `[from_ms, to_ms)`; omit a bound to leave it open.
+#[tauri::command]
+pub async fn get_usage_stats(
+    from_ms: Option<i64>,
+    to_ms: Option<i64>,
+) -> Result<history::UsageStats, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        history::get_usage_stats(&settings::history_dir(), from_ms, to_ms)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_history() -> Result<Vec<history::HistoryEntry>, String> {
+    tauri::async_runtime::spawn_blocking(move || history::load_history(&settings::history_dir()))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_history_entry(id: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        history::delete_entry(&settings::history_dir(), &settings::audio_dir(), &id)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn export_history_audio(id: String) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let dest = history::export_audio(&settings::audio_dir(), &id)?;
+        Ok(dest.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Save a history entry as `.srt` subtitles, timed from its word timings
+/// when the provider returned any.
+#[tauri::command]
+pub async fn export_history_srt(id: String) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let dest = history::export_srt(&settings::history_dir(), &id)?;
+        Ok(dest.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Polish a history entry's raw transcript again with the current polish
+/// settings and the app context it was recorded in, replacing its text in
+/// place.  Returns the new text.
+#[tauri::command]
+pub async fn repolish_history_entry(app: AppHandle, id: String) -> Result<String, String> {
+    let (config, stt_language) = {
+        let state = app.state::<AppState>();
+        let settings = state.settings.lock().map_err(|e| e.to_string())?;
+        let mut config = settings.polish.clone();
+        if config.mode == polisher::PolishMode::Cloud {
+            let key = get_cached_api_key(&state.api_key_cache, config.cloud.provider.as_key());
+            if !key.is_empty() {
+                config.cloud.api_key = key;
+            }
+        }
+        (config, settings.stt.language.clone())
+    };
+    let model_dir = settings::models_dir();
+    if !polisher::is_polish_ready(&model_dir, &config) {
+        return Err("Polish is not configured (model missing or no API key)".to_string());
+    }
+
+    let app_clone = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app_clone.state::<AppState>();
+        let history_dir = settings::history_dir();
+        let entry = history::get_entry(&history_dir, &id).ok_or("History entry not found")?;
+        // Entries from before contexts were stored only know the app.
+        let context = entry.app_context.clone().unwrap_or_else(|| crate::context_detect::AppContext {
+            app_name: entry.app_name.clone(),
+            bundle_id: entry.bundle_id.clone(),
+            ..Default::default()
+        });
+
+        // Holds the pipeline like a dictation does, so the model is not
+        // switched, deleted or used by a dictation while it polishes.
+        if state
+            .is_processing
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err("Cannot re-polish while a dictation is processing".to_string());
+        }
+        if let Ok(mut since) = state.processing_since.lock() {
+            *since = Some(Instant::now());
+        }
+        state.processing_audio_ms.store(0, Ordering::SeqCst);
+        let start = Instant::now();
+        state.touch_model_use();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            polisher::polish_text(&state.llm_model, &model_dir, &config, &context, &entry.raw_text, &state.http_client())
+        }));
+        state.touch_model_use();
+        state.is_processing.store(false, Ordering::SeqCst);
+        if let Ok(mut since) = state.processing_since.lock() {
+            *since = None;
+        }
+        let result = result.map_err(|_| "Re-polish failed: polish panicked".to_string())?;
+        crate::emit_polish_model_corrupt(&app_clone);
+        let elapsed_ms = start.elapsed().as_millis() as u64;
+        if let Some(reason) = &result.fallback_reason {
+            return Err(format!("Re-polish failed: {}", reason));
+        }
+
+        let source_language = polisher::stt_language_override(&config, &context).unwrap_or(stt_language);
+        let target_language = config.translation_target();
+        let text = crate::maybe_convert_zh(&result.text, target_language.unwrap_or(&source_language));
+        history::set_entry_polish(
+            &history_dir,
+            &id,
+            &text,
+            result.reasoning.as_deref(),
+            &config.history_model_name(),
+            Some(elapsed_ms),
+            target_language.map(|target| (source_language.as_str(), target)),
+        )?;
+        tracing::info!("Re-polished history entry {} in {}ms", id, elapsed_ms);
+        Ok(text)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Copy the unpolished STT output of a history entry to the clipboard.
+#[tauri::command]
+pub async fn copy_entry_raw(id: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let entry = history::get_entry(&settings::history_dir(), &id)
+            .ok_or_else(|| "History entry not found".to_string())?;
+        let mut clipboard =
+            arboard::Clipboard::new().map_err(|e| format!("Clipboard error: {}", e))?;
+        clipboard
+            .set_text(entry.raw_text)
+            .map_err(|e| format!("Clipboard error: {}", e))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn clear_all_history() -> Result<(), String> {
     tauri::async_runtime::spawn_blocking(move || {
-        let limit = limit.unwrap_or(10);
-        let (entries, has_more) =
-            history::load_history_page(&settings::history_dir(), before_timestamp, limit);
-        HistoryPage { entries, has_more }
+        history::clear_all(&settings::history_dir(), &settings::audio_dir())
     })
     .await
-    .map_err(|e| e.to_string())
+    .map_err(|e| e.to_string())?
 }
 
+/// Soft-deleted history entries, most recently deleted first.
 #[tauri::command]
-pub async fn get_history_stats() -> Result<history::HistoryStats, String> {
-    tauri::async_runtime::spawn_blocking(move || history::get_stats(&settings::history_dir()))
+pub async fn get_history_trash() -> Result<Vec<history::TrashEntry>, String> {
+    tauri::async_runtime::spawn_blocking(move || history::load_trash(&settings::history_dir()))
         .await
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn get_history() -> Result<Vec<history::HistoryEntry>, String> {
-    tauri::async_runtime::spawn_blocking(move || history::load_history(&settings::history_dir()))
-        .await
-        .map_err(|e| e.to_string())
+pub async fn restore_history_entry(id: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        history::restore_entry(&settings::history_dir(), &settings::audio_dir(), &id)
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
-pub async fn delete_history_entry(id: String) -> Result<(), String> {
+pub async fn empty_history_trash() -> Result<(), String> {
     tauri::async_runtime::spawn_blocking(move || {
-        history::delete_entry(&settings::history_dir(), &settings::audio_dir(), &id);
+        history::empty_trash(&settings::history_dir(), &settings::audio_dir());
     })
     .await
     .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn export_history_audio(id: String) -> Result<String, String> {
-    tauri::async_runtime::spawn_blocking(move || {
-        let dest = history::export_audio(&settings::audio_dir(), &id)?;
-        Ok(dest.to_string_lossy().to_string())
-    })
-    .await
-    .map_err(|e| e.to_string())?
+pub async fn verify_history_db() -> Result<history::DbHealth, String> {
+    tauri::async_runtime::spawn_blocking(move || history::verify_db(&settings::history_dir()))
+        .await
+        .map_err(|e| e.to_string())
 }
 
+/// Salvage a damaged history database into a fresh file; the damaged copy is
+/// kept beside it.
 #[tauri::command]
-pub async fn clear_all_history() -> Result<(), String> {
-    tauri::async_runtime::spawn_blocking(move || {
-        history::clear_all(&settings::history_dir(), &settings::audio_dir());
-    })
-    .await
-    .map_err(|e| e.to_string())
+pub async fn repair_history_db() -> Result<history::DbRepair, String> {
+    tauri::async_runtime::spawn_blocking(move || history::repair_db(&settings::history_dir()))
+        .await
+        .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
@@ -598,6 +1222,104 @@ fn get_app_icon_macos(bundle_id: &str) -> Result<String, String> {
     }
 }
 
+/// Model ids for a cloud polish provider.  An empty `api_key` falls back to
+/// the stored key for that provider.
+#[tauri::command]
+pub async fn list_cloud_models(
+    app: AppHandle,
+    provider: polisher::CloudProvider,
+    api_key: String,
+    endpoint: String,
+) -> Result<Vec<String>, SumiError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let key = if api_key.trim().is_empty() {
+            get_cached_api_key(&state.api_key_cache, provider.as_key())
+        } else {
+            api_key.trim().to_string()
+        };
+        polisher::list_cloud_models(&provider, &key, endpoint.trim(), &state.http_client())
+    })
+    .await
+    .map_err(|e| SumiError::Other(format!("List models task failed: {}", e)))?
+}
+
+/// Placeholder transcript shown in [`preview_system_prompt`] output.
+const PREVIEW_TRANSCRIPT: &str = "(transcript)";
+
+/// A synthetic context for previewing a dictation into the given app.
+fn preview_context(app_name: &str, bundle_id: &str, url: &str) -> crate::context_detect::AppContext {
+    crate::context_detect::AppContext {
+        app_name: app_name.trim().to_string(),
+        bundle_id: bundle_id.trim().to_string(),
+        url: url.trim().to_string(),
+        terminal_host: String::new(),
+    }
+}
+
+/// The full polish prompt that would be sent for a dictation into the given
+/// app/bundle/URL with the current polish settings.
+#[tauri::command]
+pub fn preview_system_prompt(
+    state: State<'_, AppState>,
+    app_name: String,
+    bundle_id: String,
+    url: String,
+) -> Result<String, String> {
+    let config = state.settings.lock().map_err(|e| e.to_string())?.polish.clone();
+    let context = preview_context(&app_name, &bundle_id, &url);
+    Ok(polisher::preview_prompt(&config, &context, PREVIEW_TRANSCRIPT))
+}
+
+/// The prompt rule that would fire for a dictation into the given
+/// app/bundle/URL, and whether its primary or an alternate condition matched.
+#[tauri::command]
+pub fn match_prompt_rule(
+    state: State<'_, AppState>,
+    app_name: String,
+    bundle_id: String,
+    url: String,
+) -> Result<Option<polisher::MatchedRule>, String> {
+    let settings = state.settings.lock().map_err(|e| e.to_string())?;
+    let context = preview_context(&app_name, &bundle_id, &url);
+    Ok(polisher::match_prompt_rule(&settings.polish, &context))
+}
+
+/// What context detection currently sees for the frontmost app, so the rules
+/// UI can show the exact name, bundle ID and URL a rule would match.
+#[tauri::command]
+pub async fn get_current_context() -> Result<crate::context_detect::AppContext, String> {
+    tauri::async_runtime::spawn_blocking(crate::context_detect::detect_frontmost_app)
+        .await
+        .map_err(|e| format!("Context detection task failed: {}", e))
+}
+
+/// Polish the next dictation with `model` instead of the configured one:
+/// a model ID in cloud mode, a local model key in local mode.  The override
+/// is used once and never saved; `None` clears a pending one.  A local key
+/// must name a downloaded model.
+#[tauri::command]
+pub fn set_next_polish_model(state: State<'_, AppState>, model: Option<String>) -> Result<(), String> {
+    let model = model.map(|m| m.trim().to_string());
+    if let Some(ref m) = model {
+        if m.is_empty() {
+            return Err("Polish model must not be empty".to_string());
+        }
+        let mut config = state.settings.lock().map_err(|e| e.to_string())?.polish.clone();
+        if !polisher::apply_model_override(&mut config, m) {
+            return Err(format!("Unknown local polish model: {}", m));
+        }
+        if config.mode == polisher::PolishMode::Local
+            && !settings::models_dir().join(config.model.filename()).exists()
+        {
+            return Err(format!("Polish model {} is not downloaded", m));
+        }
+    }
+    tracing::info!("Next polish model: {:?}", model);
+    *state.next_polish_model.lock().map_err(|e| e.to_string())? = model;
+    Ok(())
+}
+
 #[derive(Serialize)]
 pub struct TestPolishResult {
     current_result: String,
@@ -609,7 +1331,7 @@ pub async fn test_polish(
     app: AppHandle,
     test_text: String,
     custom_prompt: String,
-) -> Result<TestPolishResult, String> {
+) -> Result<TestPolishResult, SumiError> {
     let config = {
         let state = app.state::<AppState>();
         let mut config = state.settings.lock().map_err(|e| e.to_string())?.polish.clone();
@@ -629,7 +1351,8 @@ pub async fn test_polish(
     let app_clone = app.clone();
     tauri::async_runtime::spawn_blocking(move || {
         let state = app_clone.state::<AppState>();
-        let system_prompt = "You are a speech-to-text post-processor.";
+        let system_prompt = polisher::POLISH_SYSTEM_PROMPT;
+        state.touch_model_use();
 
         let default_user = format!("<speech>\n{}\n</speech>\n\n{}", test_text, default_instructions);
         let default_result = polisher::polish_with_prompt(
@@ -638,7 +1361,7 @@ pub async fn test_polish(
             &config,
             system_prompt,
             &default_user,
-            &state.http_client,
+            &state.http_client(),
             None,
         )?;
 
@@ -649,9 +1372,68 @@ pub async fn test_polish(
             &config,
             system_prompt,
             &custom_user,
-            &state.http_client,
+            &state.http_client(),
             None,
         )?;
+        state.touch_model_use();
+
+        Ok(TestPolishResult {
+            current_result: default_result,
+            edited_result: custom_result,
+        })
+    })
+    .await
+    .map_err(|e| SumiError::Other(format!("Test polish task failed: {}", e)))?
+}
+
+/// Run an edit instruction against `selected_text` with both the built-in
+/// edit prompt and `edit_prompt`, so the two can be compared side by side.
+#[tauri::command]
+pub async fn test_edit(
+    app: AppHandle,
+    selected_text: String,
+    instruction: String,
+    edit_prompt: String,
+) -> Result<TestPolishResult, SumiError> {
+    let config = {
+        let state = app.state::<AppState>();
+        let mut config = state.settings.lock().map_err(|e| e.to_string())?.polish.clone();
+        if config.mode == polisher::PolishMode::Cloud {
+            let key = get_cached_api_key(&state.api_key_cache, config.cloud.provider.as_key());
+            if !key.is_empty() {
+                config.cloud.api_key = key;
+            }
+        }
+        config
+    };
+
+    let model_dir = settings::models_dir();
+
+    let app_clone = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app_clone.state::<AppState>();
+
+        let mut default_config = config.clone();
+        default_config.edit_system_prompt = None;
+        let default_result = polisher::edit_text_by_instruction(
+            &state.llm_model,
+            &model_dir,
+            &default_config,
+            &selected_text,
+            &instruction,
+            &state.http_client(),
+        )?;
+
+        let mut custom_config = config;
+        custom_config.edit_system_prompt = Some(edit_prompt);
+        let custom_result = polisher::edit_text_by_instruction(
+            &state.llm_model,
+            &model_dir,
+            &custom_config,
+            &selected_text,
+            &instruction,
+            &state.http_client(),
+        )?;
 
         Ok(TestPolishResult {
             current_result: default_result,
@@ -659,7 +1441,7 @@ pub async fn test_polish(
         })
     })
     .await
-    .map_err(|e| format!("Test polish task failed: {}", e))?
+    .map_err(|e| SumiError::Other(format!("Test edit task failed: {}", e)))?
 }
 
 // ── Voice Add Rule ────────────────────────────────────────────────────────
@@ -694,30 +1476,31 @@ fn parse_generated_rule(raw: &str) -> Result<GeneratedRule, String> {
     let val: serde_json::Value =
         serde_json::from_str(json_str).map_err(|e| format!("JSON parse error: {e}"))?;
 
-    let name = val
-        .get("name")
-        .and_then(|v| v.as_str())
-        .unwrap_or("")
-        .to_string();
-    let match_type = val
-        .get("match_type")
-        .and_then(|v| v.as_str())
-        .unwrap_or("app_name")
-        .to_string();
-    let match_value = val
-        .get("match_value")
-        .and_then(|v| v.as_str())
-        .unwrap_or("")
-        .to_string();
-    let prompt = val
-        .get("prompt")
-        .and_then(|v| v.as_str())
-        .unwrap_or("")
-        .to_string();
+    let field = |key: &str| {
+        val.get(key)
+            .and_then(|v| v.as_str())
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default()
+    };
 
-    let match_type = match match_type.as_str() {
-        "app_name" | "bundle_id" | "url" => match_type,
-        _ => "app_name".to_string(),
+    let match_type = field("match_type");
+    if !matches!(match_type.as_str(), "app_name" | "bundle_id" | "url") {
+        return Err(format!(
+            "Invalid match_type {:?}: expected \"app_name\", \"bundle_id\" or \"url\"",
+            match_type
+        ));
+    }
+    let match_value = field("match_value");
+    if match_value.is_empty() {
+        return Err("Generated rule has no match_value: name the app or website the rule applies to".to_string());
+    }
+    let prompt = field("prompt");
+    if prompt.is_empty() {
+        return Err("Generated rule has an empty prompt".to_string());
+    }
+    let name = match field("name") {
+        n if n.is_empty() => match_value.clone(),
+        n => n,
     };
 
     Ok(GeneratedRule {
@@ -732,7 +1515,7 @@ fn parse_generated_rule(raw: &str) -> Result<GeneratedRule, String> {
 pub async fn generate_rule_from_description(
     app: AppHandle,
     description: String,
-) -> Result<GeneratedRule, String> {
+) -> Result<GeneratedRule, SumiError> {
     let (config, model_dir) = {
         let state = app.state::<AppState>();
         let mut config = state
@@ -751,7 +1534,10 @@ pub async fn generate_rule_from_description(
 
         let model_dir = settings::models_dir();
         if !polisher::is_polish_ready(&model_dir, &config) {
-            return Err("LLM not configured".to_string());
+            return Err(match config.mode {
+                polisher::PolishMode::Cloud => SumiError::MissingApiKey("LLM not configured".to_string()),
+                polisher::PolishMode::Local => SumiError::ModelNotFound("LLM not configured".to_string()),
+            });
         }
         (config, model_dir)
     };
@@ -773,26 +1559,24 @@ Convert the description above into a JSON object with these fields:
 
 If a specific app is mentioned, use "app_name" as match_type.
 If a website or URL is mentioned, use "url" as match_type.
-If the match target is unclear, use "app_name" with empty match_value.
 
 Write "name" and "prompt" in {lang_hint}.
 Return ONLY the JSON object."#
         );
 
-        let result = polisher::polish_with_prompt(
+        let result = polisher::generate_json_with_prompt(
             &state.llm_model,
             &model_dir,
             &config,
             system_prompt,
             &user_text,
-            &state.http_client,
-            None,
+            &state.http_client(),
         )?;
 
-        parse_generated_rule(&result)
+        parse_generated_rule(&result).map_err(SumiError::Provider)
     })
     .await
-    .map_err(|e| format!("Generate rule task failed: {}", e))?
+    .map_err(|e| SumiError::Other(format!("Generate rule task failed: {}", e)))?
 }
 
 #[tauri::command]
@@ -814,11 +1598,11 @@ pub fn start_recording(state: State<'_, AppState>) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn stop_recording(state: State<'_, AppState>) -> Result<String, String> {
+pub fn stop_recording(state: State<'_, AppState>) -> Result<String, SumiError> {
     // Refuse to stop if meeting mode is active — the meeting hotkey must be
     // used to end a meeting session so the transcript is handled correctly.
     if state.meeting_active.load(std::sync::atomic::Ordering::SeqCst) {
-        return Err("meeting_mode_active".to_string());
+        return Err("meeting_mode_active".into());
     }
 
     let mut stt_config = state.settings.lock().map_err(|e| e.to_string())?.stt.clone();
@@ -832,23 +1616,16 @@ pub fn stop_recording(state: State<'_, AppState>) -> Result<String, String> {
     let dictionary_terms: Vec<String> = state
         .settings
         .lock()
-        .map(|s| {
-            s.polish
-                .dictionary
-                .entries
-                .iter()
-                .filter(|e| e.enabled && !e.term.is_empty())
-                .map(|e| e.term.clone())
-                .collect()
-        })
+        .map(|s| s.polish.dictionary_terms())
         .unwrap_or_default();
     audio::do_stop_recording(
         &state,
         &stt_config,
         &stt_language,
         &dictionary_terms,
+        None,
     )
-    .map(|(text, _samples)| text)
+    .map(|(text, _samples, _timings)| text)
 }
 
 #[tauri::command]
@@ -894,7 +1671,33 @@ pub fn cancel_recording(app: AppHandle, state: State<'_, AppState>) {
         state.meeting_cancelled.store(true, Ordering::SeqCst);
         state.meeting_active.store(false, Ordering::SeqCst);
     }
+    // In the paste grace window nothing is recording: cancelling only skips
+    // the paste, and the text stays on the clipboard.
+    if !state.is_recording.load(Ordering::SeqCst) {
+        if let Some(token) = state.paste_grace.lock().ok().and_then(|mut slot| slot.take()) {
+            token.store(true, Ordering::SeqCst);
+            tracing::info!("Auto-paste cancelled during grace period");
+            return;
+        }
+    }
     state.is_recording.store(false, Ordering::SeqCst);
+    state.recording_queued.store(false, Ordering::SeqCst);
+    crate::clear_dictation_queue(&state);
+    // Chunks already cut from a continuous session are discarded, including
+    // the one being transcribed; the next session waits for the worker.
+    if let Ok(mut sessions) = state.continuous_session.lock() {
+        sessions.cancel();
+    }
+    // An edit-by-voice recording copied the selection over the user's
+    // clipboard when it started; put that back and drop the captured text.
+    let was_editing = state.edit_mode.load(Ordering::SeqCst);
+    if let Some(original) = reset_edit_state(&state.edit_mode, &state.edit_selected_text, &state.saved_clipboard) {
+        // Off the main thread: the restore waits out in-flight keystrokes.
+        std::thread::spawn(move || crate::restore_clipboard_text(&original));
+    }
+    if was_editing {
+        tracing::info!("Edit-by-voice recording cancelled; clipboard restored");
+    }
     // Intentionally not updating last_recording_end: a cancelled recording
     // does not count as "real use" for the idle mic timeout.
     // Wake any sleeping feeder immediately.
@@ -904,8 +1707,52 @@ pub fn cancel_recording(app: AppHandle, state: State<'_, AppState>) {
         crate::platform::resume_now_playing();
     }
     if let Some(overlay) = app.get_webview_window("overlay") {
-        platform::hide_overlay(&overlay);
+        crate::hide_overlay_idle(&overlay);
+    }
+}
+
+/// Leave edit-by-voice: clear the mode and the captured selection, and take
+/// the clipboard content saved when the edit started so the caller can
+/// restore it.
+fn reset_edit_state(
+    edit_mode: &AtomicBool,
+    selected_text: &Mutex<Option<String>>,
+    saved_clipboard: &Mutex<Option<String>>,
+) -> Option<String> {
+    edit_mode.store(false, Ordering::SeqCst);
+    if let Ok(mut selected) = selected_text.lock() {
+        *selected = None;
+    }
+    saved_clipboard.lock().ok().and_then(|mut saved| saved.take())
+}
+
+/// Abort the transcribe/polish pipeline that is currently running (after the
+/// recording has already stopped).  The in-flight local generation or cloud
+/// request is abandoned, the clipboard is restored for edit-by-voice, and the
+/// overlay is hidden.  The pipeline thread clears `is_processing` itself once
+/// it has stopped, so a new dictation cannot overlap its last steps.
+#[tauri::command]
+pub fn cancel_processing(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    if !state.is_processing.load(Ordering::SeqCst) {
+        return Err("Nothing is being processed".to_string());
+    }
+    let token = state
+        .processing_cancel
+        .lock()
+        .map_err(|e| e.to_string())?
+        .take()
+        .ok_or_else(|| "Nothing is being processed".to_string())?;
+    token.store(true, Ordering::SeqCst);
+    tracing::info!("Processing cancelled by user");
+    crate::clear_dictation_queue(&state);
+
+    state.voice_rule_mode.store(false, Ordering::SeqCst);
+    if let Some(original) = reset_edit_state(&state.edit_mode, &state.edit_selected_text, &state.saved_clipboard) {
+        // Off the main thread: the restore waits out in-flight keystrokes.
+        std::thread::spawn(move || crate::restore_clipboard_text(&original));
     }
+    crate::reset_and_hide_overlay(&app);
+    Ok(())
 }
 
 #[derive(Serialize)]
@@ -917,6 +1764,10 @@ pub struct MicStatus {
 
 #[tauri::command]
 pub fn get_mic_status(_state: State<'_, AppState>) -> MicStatus {
+    mic_status()
+}
+
+fn mic_status() -> MicStatus {
     use cpal::traits::{DeviceTrait, HostTrait};
     let host = cpal::default_host();
     let default_device = host.default_input_device().and_then(|d| d.name().ok());
@@ -948,46 +1799,319 @@ pub fn get_mic_status(_state: State<'_, AppState>) -> MicStatus {
 }
 
 #[tauri::command]
-pub fn set_mic_device(device_name: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+pub async fn set_mic_device(app: AppHandle, device_name: Option<String>) -> Result<(), String> {
+    // Closing and reopening the stream joins the audio thread and opens the
+    // device, so it runs off the async runtime.
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        if state.is_recording.load(Ordering::SeqCst) {
+            return Err("Cannot change input device while recording".to_string());
+        }
+
+        // Save to settings
+        {
+            let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+            settings.mic_device = device_name.clone();
+            settings::save_settings_to_disk(&settings);
+        }
+
+        // Close the old stream (if any) and clear the buffer.  A stream that
+        // was open is reopened on the new device right away, which also
+        // refreshes `sample_rate`; a closed one stays closed (on-demand
+        // model) and picks up the device on the next recording start.
+        let was_open = state.mic_available.load(Ordering::SeqCst);
+        audio::close_audio_stream(&state.audio_thread, &state.mic_available);
+        if let Ok(mut buf) = state.buffer.lock() {
+            buf.clear();
+        }
+        if was_open && !state.reconnecting.swap(true, Ordering::SeqCst) {
+            let result = audio::try_reconnect_audio(
+                &state.mic_available,
+                &state.sample_rate,
+                &state.buffer,
+                &state.is_recording,
+                &state.audio_thread,
+                device_name,
+            );
+            state.reconnecting.store(false, Ordering::SeqCst);
+            result?;
+            crate::report_mic_fallback(&app, &state);
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn set_channel_mode(mode: audio::ChannelMode, state: State<'_, AppState>) -> Result<(), String> {
+    if state.is_recording.load(Ordering::SeqCst) {
+        return Err("Cannot change channel mode while recording".to_string());
+    }
+
+    {
+        let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+        settings.channel_mode = mode.clone();
+        settings::save_settings_to_disk(&settings);
+    }
+    audio::set_channel_mode(mode);
+
+    // The mode is captured when the stream is opened; close it so the next
+    // recording start reopens with the new layout.
+    audio::close_audio_stream(&state.audio_thread, &state.mic_available);
+    if let Ok(mut buf) = state.buffer.lock() {
+        buf.clear();
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_capture_mode(mode: audio::CaptureMode, state: State<'_, AppState>) -> Result<(), String> {
     if state.is_recording.load(Ordering::SeqCst) {
-        return Err("Cannot change input device while recording".to_string());
+        return Err("Cannot change capture source while recording".to_string());
     }
 
-    // Save to settings
     {
         let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
-        settings.mic_device = device_name.clone();
+        settings.capture_mode = mode;
         settings::save_settings_to_disk(&settings);
     }
+    audio::set_capture_mode(mode);
+
+    // Switching between the microphone and loopback needs a new stream.
+    audio::close_audio_stream(&state.audio_thread, &state.mic_available);
+    if let Ok(mut buf) = state.buffer.lock() {
+        buf.clear();
+    }
+    Ok(())
+}
+
+/// Interval between `mic-level` events (~20 Hz).
+const MIC_MONITOR_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Open the mic (without recording) and emit `mic-level` events with the
+/// current input RMS (0–1, after input gain) until [`stop_mic_monitor`].
+#[tauri::command]
+pub fn start_mic_monitor(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    if state.mic_monitor_active.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+    if let Err(e) = open_stream_for_monitor(&state) {
+        state.mic_monitor_active.store(false, Ordering::SeqCst);
+        return Err(e);
+    }
+
+    std::thread::spawn(move || {
+        let state = app.state::<AppState>();
+        while state.mic_monitor_active.load(Ordering::SeqCst) {
+            std::thread::sleep(MIC_MONITOR_INTERVAL);
+            // While recording the callback feeds the recording buffer instead
+            // of the pre-roll; the overlay visualizer covers that case.
+            if state.is_recording.load(Ordering::SeqCst) || state.meeting_active.load(Ordering::SeqCst) {
+                continue;
+            }
+            // A device or channel-mode change closes the stream; reopen it so
+            // the meter follows the new input.
+            if !state.mic_available.load(Ordering::SeqCst) {
+                if let Err(e) = open_stream_for_monitor(&state) {
+                    tracing::warn!("Mic monitor could not reopen stream: {}", e);
+                    let _ = app.emit("mic-level", 0.0f32);
+                    std::thread::sleep(Duration::from_secs(1));
+                    continue;
+                }
+            }
+            let _ = app.emit("mic-level", current_input_level(&state));
+        }
+        tracing::info!("Mic monitor stopped");
+    });
+    tracing::info!("Mic monitor started");
+    Ok(())
+}
+
+/// Input RMS over the last monitor interval (0–1, after input gain), read
+/// from the idle stream.  0 when the stream is closed or recording, so the
+/// UI should keep the mic monitor running while it polls this.
+#[tauri::command]
+pub fn get_current_input_level(state: State<'_, AppState>) -> f32 {
+    current_input_level(&state)
+}
+
+fn current_input_level(state: &AppState) -> f32 {
+    let window = state
+        .sample_rate
+        .lock()
+        .ok()
+        .and_then(|sr| *sr)
+        .map(|sr| (sr as u64 * MIC_MONITOR_INTERVAL.as_millis() as u64 / 1000) as usize)
+        .unwrap_or(0);
+    let rms = state
+        .audio_thread
+        .lock()
+        .ok()
+        .and_then(|at| at.as_ref().map(|c| c.input_level(window)))
+        .unwrap_or(0.0);
+    let gain_db = state.settings.lock().map(|s| s.input_gain_db).unwrap_or(0.0);
+    (rms * 10f32.powf(gain_db / 20.0)).clamp(0.0, 1.0)
+}
+
+#[tauri::command]
+pub fn stop_mic_monitor(state: State<'_, AppState>) {
+    if state.mic_monitor_active.swap(false, Ordering::SeqCst) {
+        // Start the idle clock now so the watcher can close the stream later.
+        if let Ok(mut t) = state.last_recording_end.lock() {
+            *t = Some(Instant::now());
+        }
+    }
+}
+
+fn open_stream_for_monitor(state: &AppState) -> Result<(), String> {
+    let device_name = state.settings.lock().ok().and_then(|s| s.mic_device.clone());
+    audio::ensure_stream_open(
+        &state.mic_available,
+        &state.reconnecting,
+        &state.sample_rate,
+        &state.buffer,
+        &state.is_recording,
+        &state.audio_thread,
+        device_name,
+    )
+}
+
+// ── Model download ──────────────────────────────────────────────────────────
+
+#[derive(Serialize)]
+pub struct ModelStatus {
+    engine: String,
+    model_exists: bool,
+}
+
+/// Whether the configured STT engine can transcribe: its local model is
+/// downloaded, or its cloud provider has credentials.
+#[tauri::command]
+pub fn check_model_status(state: State<'_, AppState>) -> ModelStatus {
+    let mut stt_config = state.settings.lock().map(|s| s.stt.clone()).unwrap_or_default();
+    if stt_config.mode == SttMode::Cloud {
+        stt_config.cloud.api_key = get_cached_api_key(&state.api_key_cache, stt_config.cloud.provider.as_key());
+    }
+    let engine = match (&stt_config.mode, &stt_config.local_engine) {
+        (SttMode::Cloud, _) => "cloud",
+        (SttMode::Local, LocalSttEngine::Whisper) => "whisper",
+        (SttMode::Local, LocalSttEngine::Qwen3Asr) => "qwen3_asr",
+    };
+    ModelStatus {
+        engine: engine.to_string(),
+        model_exists: stt::is_stt_ready(&stt_config),
+    }
+}
+
+// ── Download bookkeeping ─────────────────────────────────────────────────
+
+/// Progress of one in-flight download, tracked in `AppState::downloads_in_flight`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DownloadProgress {
+    /// Prefix of the download's `<kind>-download-progress` event, e.g. `"llm-model"`.
+    pub kind: &'static str,
+    pub downloaded: u64,
+    pub total: u64,
+    /// Set by `cancel_download`; the read loop checks it between chunks.
+    #[serde(skip)]
+    pub cancel: Arc<AtomicBool>,
+}
+
+/// Key a download by the file (or model directory) name it writes.
+fn download_key_for(path: &std::path::Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
+/// Claim `key` for a new download.  Different models may download side by
+/// side, but a second request for the same file would truncate the first
+/// one's `.part`, so it is refused.  Returns the flag `cancel_download` sets.
+fn begin_download(app: &AppHandle, kind: &'static str, key: &str) -> Result<Arc<AtomicBool>, String> {
+    let state = app.state::<AppState>();
+    let mut in_flight = state.downloads_in_flight.lock().map_err(|e| e.to_string())?;
+    if in_flight.contains_key(key) {
+        return Err("download already in progress".to_string());
+    }
+    let cancel = Arc::new(AtomicBool::new(false));
+    in_flight.insert(key.to_string(), DownloadProgress {
+        kind,
+        cancel: cancel.clone(),
+        ..Default::default()
+    });
+    state.downloading.store(true, Ordering::SeqCst);
+    emit_download_queue(app, &in_flight);
+    Ok(cancel)
+}
 
-    // Close the old stream (if any) and clear the buffer.
-    // On-demand model: the new device will be used on the next recording start.
-    audio::close_audio_stream(&state.audio_thread, &state.mic_available);
-    if let Ok(mut buf) = state.buffer.lock() {
-        buf.clear();
+fn report_download_progress(app: &AppHandle, key: &str, downloaded: u64, total: u64) {
+    let Some(state) = app.try_state::<AppState>() else { return };
+    let Ok(mut in_flight) = state.downloads_in_flight.lock() else { return };
+    if let Some(progress) = in_flight.get_mut(key) {
+        progress.downloaded = downloaded;
+        progress.total = total;
     }
-    Ok(())
+    emit_download_queue(app, &in_flight);
 }
 
-// ── Model download ──────────────────────────────────────────────────────────
+/// Release `key`; `downloading` stays set while any other download runs.
+fn end_download(app: &AppHandle, key: &str) {
+    let Some(state) = app.try_state::<AppState>() else { return };
+    let Ok(mut in_flight) = state.downloads_in_flight.lock() else { return };
+    in_flight.remove(key);
+    state.downloading.store(!in_flight.is_empty(), Ordering::SeqCst);
+    emit_download_queue(app, &in_flight);
+}
 
-#[derive(Serialize)]
-pub struct ModelStatus {
-    engine: String,
-    model_exists: bool,
+/// Clean up after a download stopped by `cancel_download`: drop the `.part`
+/// file so a retry starts fresh, then report on the kind's progress event.
+fn cancelled_download(app: &AppHandle, kind: &str, tmp_path: &std::path::Path) {
+    let _ = std::fs::remove_file(tmp_path);
+    let _ = app.emit(
+        &format!("{}-download-progress", kind),
+        serde_json::json!({ "status": "cancelled" }),
+    );
+    tracing::info!("{} download cancelled", kind);
 }
 
+/// Stop every in-flight download of `kind` (see `DownloadProgress::kind`).
+/// The download thread notices on its next chunk, deletes its `.part` file
+/// and emits `status: "cancelled"`.
 #[tauri::command]
-pub fn check_model_status() -> ModelStatus {
-    let model_exists = settings::models_dir()
-        .join(whisper_models::WhisperModel::LargeV3TurboZhTw.filename())
-        .exists();
-    ModelStatus {
-        engine: "whisper".to_string(),
-        model_exists,
+pub fn cancel_download(kind: String, state: State<'_, AppState>) -> Result<(), String> {
+    let in_flight = state.downloads_in_flight.lock().map_err(|e| e.to_string())?;
+    let mut found = false;
+    for progress in in_flight.values().filter(|p| p.kind == kind) {
+        progress.cancel.store(true, Ordering::SeqCst);
+        found = true;
+    }
+    if found {
+        Ok(())
+    } else {
+        Err(format!("No {} download in progress", kind))
     }
 }
 
+/// Emit `download-queue-progress` with every in-flight download and the
+/// combined totals, so the UI can show overall queue status.
+fn emit_download_queue(app: &AppHandle, in_flight: &HashMap<String, DownloadProgress>) {
+    let downloaded: u64 = in_flight.values().map(|p| p.downloaded).sum();
+    let total: u64 = in_flight.values().map(|p| p.total).sum();
+    let percent = if total > 0 {
+        (downloaded as f64 / total as f64) * 100.0
+    } else {
+        0.0
+    };
+    let _ = app.emit("download-queue-progress", serde_json::json!({
+        "downloads": in_flight,
+        "count": in_flight.len(),
+        "downloaded": downloaded,
+        "total": total,
+        "percent": percent
+    }));
+}
+
 #[tauri::command]
 pub fn download_model(app: AppHandle) -> Result<(), String> {
     use std::io::Read as _;
@@ -1006,12 +2130,8 @@ pub fn download_model(app: AppHandle) -> Result<(), String> {
         return Ok(());
     }
 
-    {
-        let state = app.state::<AppState>();
-        if state.downloading.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err() {
-            return Err("A model download is already in progress".to_string());
-        }
-    }
+    let download_key = download_key_for(&model_path);
+    let cancel = begin_download(&app, "model", &download_key)?;
 
     let tmp_path = model_path.with_extension("bin.part");
     let _ = std::fs::remove_file(&tmp_path);
@@ -1019,7 +2139,7 @@ pub fn download_model(app: AppHandle) -> Result<(), String> {
     std::thread::spawn(move || {
         (|| {
         let url = "https://huggingface.co/Alkd/whisper-large-v3-turbo-zh-TW/resolve/main/ggml-model.bin";
-        let client = match reqwest::blocking::Client::builder()
+        let client = match crate::proxy::client_builder()
             .timeout(std::time::Duration::from_secs(600))
             .build()
         {
@@ -1071,6 +2191,11 @@ pub fn download_model(app: AppHandle) -> Result<(), String> {
         let mut reader = resp;
 
         loop {
+            if cancel.load(Ordering::SeqCst) {
+                drop(file);
+                cancelled_download(&app, "model", &tmp_path);
+                return;
+            }
             let n = match reader.read(&mut buf) {
                 Ok(0) => break,
                 Ok(n) => n,
@@ -1105,6 +2230,7 @@ pub fn download_model(app: AppHandle) -> Result<(), String> {
                     "total": total,
                     "percent": percent
                 }));
+                report_download_progress(&app, &download_key, downloaded, total);
                 last_emit = Instant::now();
             }
         }
@@ -1133,9 +2259,7 @@ pub fn download_model(app: AppHandle) -> Result<(), String> {
         }));
         tracing::info!("Whisper model downloaded: {:?}", model_path);
         })();
-        if let Some(state) = app.try_state::<AppState>() {
-            state.downloading.store(false, Ordering::SeqCst);
-        }
+        end_download(&app, &download_key);
     });
 
     Ok(())
@@ -1186,9 +2310,8 @@ pub fn download_llm_model(app: AppHandle, state: State<'_, AppState>) -> Result<
         return Ok(());
     }
 
-    if state.downloading.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err() {
-        return Err("A model download is already in progress".to_string());
-    }
+    let download_key = download_key_for(&model_path);
+    let cancel = begin_download(&app, "llm-model", &download_key)?;
 
     let tmp_path = model_path.with_extension("gguf.part");
     let _ = std::fs::remove_file(&tmp_path);
@@ -1198,7 +2321,7 @@ pub fn download_llm_model(app: AppHandle, state: State<'_, AppState>) -> Result<
 
     std::thread::spawn(move || {
         (|| {
-        let client = match reqwest::blocking::Client::builder()
+        let client = match crate::proxy::client_builder()
             .timeout(std::time::Duration::from_secs(1800))
             .build()
         {
@@ -1250,6 +2373,11 @@ pub fn download_llm_model(app: AppHandle, state: State<'_, AppState>) -> Result<
         let mut reader = resp;
 
         loop {
+            if cancel.load(Ordering::SeqCst) {
+                drop(file);
+                cancelled_download(&app, "llm-model", &tmp_path);
+                return;
+            }
             let n = match reader.read(&mut buf) {
                 Ok(0) => break,
                 Ok(n) => n,
@@ -1284,6 +2412,7 @@ pub fn download_llm_model(app: AppHandle, state: State<'_, AppState>) -> Result<
                     "total": total,
                     "percent": percent
                 }));
+                report_download_progress(&app, &download_key, downloaded, total);
                 last_emit = Instant::now();
             }
         }
@@ -1296,6 +2425,7 @@ pub fn download_llm_model(app: AppHandle, state: State<'_, AppState>) -> Result<
             }));
             return;
         }
+        polisher::clear_load_failures(&model_path);
 
         if let Some(app_state) = app.try_state::<AppState>() {
             if let Err(e) = polisher::warm_llm_cache(&app_state.llm_model, &settings::models_dir(), &downloaded_model) {
@@ -1312,9 +2442,7 @@ pub fn download_llm_model(app: AppHandle, state: State<'_, AppState>) -> Result<
         }));
         tracing::info!("LLM model downloaded: {:?}", model_path);
         })();
-        if let Some(state) = app.try_state::<AppState>() {
-            state.downloading.store(false, Ordering::SeqCst);
-        }
+        end_download(&app, &download_key);
     });
 
     Ok(())
@@ -1398,12 +2526,8 @@ pub fn download_polish_model(app: AppHandle, model: polisher::PolishModel) -> Re
         return Ok(());
     }
 
-    {
-        let state = app.state::<AppState>();
-        if state.downloading.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err() {
-            return Err("A model download is already in progress".to_string());
-        }
-    }
+    let download_key = download_key_for(&model_path);
+    let cancel = begin_download(&app, "polish-model", &download_key)?;
 
     let tmp_path = model_path.with_extension("gguf.part");
     let _ = std::fs::remove_file(&tmp_path);
@@ -1412,7 +2536,7 @@ pub fn download_polish_model(app: AppHandle, model: polisher::PolishModel) -> Re
 
     std::thread::spawn(move || {
         (|| {
-        let client = match reqwest::blocking::Client::builder()
+        let client = match crate::proxy::client_builder()
             .timeout(std::time::Duration::from_secs(1800))
             .build()
         {
@@ -1464,6 +2588,11 @@ pub fn download_polish_model(app: AppHandle, model: polisher::PolishModel) -> Re
         let mut reader = resp;
 
         loop {
+            if cancel.load(Ordering::SeqCst) {
+                drop(file);
+                cancelled_download(&app, "polish-model", &tmp_path);
+                return;
+            }
             let n = match reader.read(&mut buf) {
                 Ok(0) => break,
                 Ok(n) => n,
@@ -1498,6 +2627,7 @@ pub fn download_polish_model(app: AppHandle, model: polisher::PolishModel) -> Re
                     "total": total,
                     "percent": percent
                 }));
+                report_download_progress(&app, &download_key, downloaded, total);
                 last_emit = Instant::now();
             }
         }
@@ -1510,6 +2640,7 @@ pub fn download_polish_model(app: AppHandle, model: polisher::PolishModel) -> Re
             }));
             return;
         }
+        polisher::clear_load_failures(&model_path);
 
         // Download external tokenizer JSON if required (e.g. Phi-4-mini).
         if let (Some(tok_url), Some(tok_path)) = (model.tokenizer_url(), tokenizer_path.as_ref()) {
@@ -1565,9 +2696,7 @@ pub fn download_polish_model(app: AppHandle, model: polisher::PolishModel) -> Re
         }));
         tracing::info!("Polish model downloaded: {:?}", model_path);
         })();
-        if let Some(state) = app.try_state::<AppState>() {
-            state.downloading.store(false, Ordering::SeqCst);
-        }
+        end_download(&app, &download_key);
     });
 
     Ok(())
@@ -1593,6 +2722,194 @@ pub fn get_system_info() -> SystemInfo {
     sysinfo::detect_system_info()
 }
 
+// ── Diagnostics ─────────────────────────────────────────────────────────────
+
+#[derive(Serialize)]
+pub struct ModelFileStatus {
+    kind: &'static str,
+    name: String,
+    downloaded: bool,
+    size_bytes: u64,
+}
+
+#[derive(Serialize)]
+pub struct Diagnostics {
+    app_version: String,
+    os: String,
+    os_version: String,
+    arch: String,
+    mic: MicStatus,
+    permissions: crate::permissions::PermissionStatus,
+    stt_mode: SttMode,
+    stt_model: String,
+    polish_enabled: bool,
+    polish_mode: polisher::PolishMode,
+    polish_model: String,
+    models: Vec<ModelFileStatus>,
+    keychain_ok: bool,
+    keychain_error: Option<String>,
+    log_path: String,
+    log_tail: Vec<String>,
+}
+
+/// Log lines included in `get_diagnostics`; the exported report carries more.
+const DIAGNOSTICS_LOG_LINES: usize = 50;
+
+fn file_size(path: &std::path::Path) -> u64 {
+    std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+/// Everything support needs from a bug report, in one call: the same data
+/// `get_mic_status`, `check_permissions` and the model status commands
+/// return, plus the active modes, keychain health and versions.
+#[tauri::command]
+pub fn get_diagnostics(app: AppHandle, state: State<'_, AppState>) -> Diagnostics {
+    let (stt, polish) = {
+        let s = state.settings.lock().unwrap_or_else(|e| e.into_inner());
+        (s.stt.clone(), s.polish.clone())
+    };
+    let dir = settings::models_dir();
+
+    let mut models = Vec::new();
+    for m in WhisperModel::all() {
+        let size = file_size(&dir.join(m.filename()));
+        models.push(ModelFileStatus {
+            kind: "whisper",
+            name: m.display_name().to_string(),
+            downloaded: size > 0,
+            size_bytes: size,
+        });
+    }
+    // A custom model is not in the catalog; report the one in use.
+    if let WhisperModel::Custom { path } = &stt.whisper_model {
+        let size = file_size(path);
+        models.push(ModelFileStatus {
+            kind: "whisper",
+            name: stt.whisper_model.describe(),
+            downloaded: size > 0,
+            size_bytes: size,
+        });
+    }
+    for m in Qwen3AsrModel::all() {
+        let model_dir = crate::stt::qwen3_asr_model_dir(m);
+        let size = m.required_files().iter().map(|f| file_size(&model_dir.join(f))).sum();
+        models.push(ModelFileStatus {
+            kind: "qwen3_asr",
+            name: m.display_name().to_string(),
+            downloaded: crate::stt::is_qwen3_asr_downloaded(m),
+            size_bytes: size,
+        });
+    }
+    for m in polisher::PolishModel::all() {
+        let (downloaded, size) = polisher::model_file_status(&dir, m);
+        models.push(ModelFileStatus {
+            kind: "polish",
+            name: m.display_name().to_string(),
+            downloaded,
+            size_bytes: size,
+        });
+    }
+    let vad_size = file_size(&settings::vad_model_path());
+    models.push(ModelFileStatus {
+        kind: "vad",
+        name: "Silero VAD".to_string(),
+        downloaded: vad_size > 0,
+        size_bytes: vad_size,
+    });
+
+    // A lookup of a key that never exists exercises the credential store
+    // without touching (or prompting for) real entries.
+    let keychain_error = credentials::load("diagnostics_probe").err();
+
+    let stt_model = match stt.mode {
+        SttMode::Cloud => format!("{} ({})", stt.cloud.model_id, stt.cloud.provider.as_key()),
+        SttMode::Local => match stt.local_engine {
+            crate::stt::LocalSttEngine::Whisper => stt.whisper_model.describe(),
+            crate::stt::LocalSttEngine::Qwen3Asr => stt.qwen3_asr_model.display_name().to_string(),
+        },
+    };
+    let polish_model = match polish.mode {
+        polisher::PolishMode::Cloud => format!("{} ({})", polish.cloud.model_id, polish.cloud.provider.as_key()),
+        polisher::PolishMode::Local => polish.model.display_name().to_string(),
+    };
+
+    Diagnostics {
+        app_version: app.package_info().version.to_string(),
+        os: std::env::consts::OS.to_string(),
+        os_version: tauri_plugin_os::version().to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        mic: mic_status(),
+        permissions: crate::permissions::check_permissions(),
+        stt_mode: stt.mode,
+        stt_model,
+        polish_enabled: polish.enabled,
+        polish_mode: polish.mode,
+        polish_model,
+        models,
+        keychain_ok: keychain_error.is_none(),
+        keychain_error,
+        log_path: get_log_path(),
+        log_tail: log_tail(DIAGNOSTICS_LOG_LINES),
+    }
+}
+
+#[derive(Serialize)]
+pub struct WhisperThreadBenchmark {
+    /// Fastest thread count in this run.
+    recommended: usize,
+    /// Thread count used when `whisper_threads` is unset.
+    auto_threads: usize,
+    /// `(threads, milliseconds)` per candidate, fastest first.
+    runs: Vec<(usize, u64)>,
+}
+
+/// Time Whisper at a few thread counts on the current model.  Most useful on
+/// CPU-only machines (or with `force_cpu`), where the thread count dominates
+/// latency; the frontend can save `recommended` as `whisper_threads`.
+#[tauri::command]
+pub async fn benchmark_whisper_threads(app: AppHandle) -> Result<WhisperThreadBenchmark, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        if state.is_recording.load(Ordering::SeqCst) {
+            return Err("Cannot benchmark while recording or processing".to_string());
+        }
+        // Hold the pipeline like a dictation would, so a hotkey press while
+        // the benchmark owns the Whisper context is refused instead of
+        // blocking behind it.
+        if state
+            .is_processing
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err("Cannot benchmark while recording or processing".to_string());
+        }
+        if let Ok(mut since) = state.processing_since.lock() {
+            *since = Some(Instant::now());
+        }
+        state.processing_audio_ms.store(0, Ordering::SeqCst);
+        let model = state.settings.lock().map(|s| s.stt.whisper_model.clone());
+        let runs = model.map_err(|e| e.to_string()).and_then(|model| {
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                crate::transcribe::benchmark_whisper_threads(&state.whisper_ctx, &model)
+            }))
+            .unwrap_or_else(|_| Err("Benchmark failed: Whisper panicked".to_string()))
+        });
+        state.is_processing.store(false, Ordering::SeqCst);
+        if let Ok(mut since) = state.processing_since.lock() {
+            *since = None;
+        }
+        let runs = runs?;
+        let recommended = runs.first().map(|&(n, _)| n).ok_or("No benchmark runs")?;
+        Ok(WhisperThreadBenchmark {
+            recommended,
+            auto_threads: crate::transcribe::auto_whisper_threads(),
+            runs: runs.into_iter().map(|(n, d)| (n, d.as_millis() as u64)).collect(),
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
 #[tauri::command]
 pub fn get_whisper_model_recommendation(state: State<'_, AppState>) -> WhisperModel {
     let system = sysinfo::detect_system_info();
@@ -1626,6 +2943,15 @@ pub async fn switch_whisper_model(app: AppHandle, model: WhisperModel) -> Result
             return Err("Cannot switch model while recording or processing".to_string());
         }
 
+        // A custom file must at least look like a GGML model before it
+        // replaces a working one.
+        if let WhisperModel::Custom { path } = &model {
+            if let Err(e) = whisper_models::validate_ggml_file(path) {
+                state.model_switching.store(false, Ordering::SeqCst);
+                return Err(e);
+            }
+        }
+
         match state.settings.lock() {
             Ok(mut s) => {
                 s.stt.whisper_model = model.clone();
@@ -1693,6 +3019,13 @@ pub fn download_whisper_model(app: AppHandle, model: WhisperModel) -> Result<(),
     std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
 
     let model_path = dir.join(model.filename());
+    let want_coreml = cfg!(all(target_os = "macos", feature = "coreml"))
+        && app
+            .state::<AppState>()
+            .settings
+            .lock()
+            .map(|s| s.whisper_coreml)
+            .unwrap_or(false);
     if model_path.exists() {
         let _ = app.emit(
             "whisper-model-download-progress",
@@ -1703,15 +3036,15 @@ pub fn download_whisper_model(app: AppHandle, model: WhisperModel) -> Result<(),
                 "percent": 100.0
             }),
         );
+        if want_coreml {
+            let app = app.clone();
+            std::thread::spawn(move || fetch_coreml_encoder(&app, &model));
+        }
         return Ok(());
     }
 
-    {
-        let state = app.state::<AppState>();
-        if state.downloading.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err() {
-            return Err("A model download is already in progress".to_string());
-        }
-    }
+    let download_key = download_key_for(&model_path);
+    let cancel = begin_download(&app, "whisper-model", &download_key)?;
 
     let tmp_path = model_path.with_extension("bin.part");
     let _ = std::fs::remove_file(&tmp_path);
@@ -1721,7 +3054,7 @@ pub fn download_whisper_model(app: AppHandle, model: WhisperModel) -> Result<(),
 
     std::thread::spawn(move || {
         (|| {
-        let client = match reqwest::blocking::Client::builder()
+        let client = match crate::proxy::client_builder()
             .timeout(std::time::Duration::from_secs(1800))
             .build()
         {
@@ -1785,6 +3118,11 @@ pub fn download_whisper_model(app: AppHandle, model: WhisperModel) -> Result<(),
         let mut reader = resp;
 
         loop {
+            if cancel.load(Ordering::SeqCst) {
+                drop(file);
+                cancelled_download(&app, "whisper-model", &tmp_path);
+                return;
+            }
             let n = match reader.read(&mut buf) {
                 Ok(0) => break,
                 Ok(n) => n,
@@ -1828,6 +3166,7 @@ pub fn download_whisper_model(app: AppHandle, model: WhisperModel) -> Result<(),
                         "percent": percent
                     }),
                 );
+                report_download_progress(&app, &download_key, downloaded, total);
                 last_emit = Instant::now();
             }
         }
@@ -1862,15 +3201,107 @@ pub fn download_whisper_model(app: AppHandle, model: WhisperModel) -> Result<(),
             }),
         );
         tracing::info!("Whisper model downloaded: {:?}", model_path);
-        })();
-        if let Some(state) = app.try_state::<AppState>() {
-            state.downloading.store(false, Ordering::SeqCst);
+        if want_coreml {
+            fetch_coreml_encoder(&app, &model);
         }
+        })();
+        end_download(&app, &download_key);
     });
 
     Ok(())
 }
 
+/// Fetch the Core ML encoder for `model` (if it has one and it is missing)
+/// and report the outcome as `whisper-coreml-status`.  Runs on the caller's
+/// thread; whisper.cpp picks the encoder up on the next model load.
+fn fetch_coreml_encoder(app: &AppHandle, model: &WhisperModel) {
+    let emit = |status: &str, message: String| {
+        let _ = app.emit(
+            "whisper-coreml-status",
+            serde_json::json!({ "status": status, "message": message }),
+        );
+    };
+    emit("downloading", String::new());
+    match download_coreml_encoder(model) {
+        Ok(true) => {
+            // Drop the cached context so the next load picks up the encoder.
+            if let Some(app_state) = app.try_state::<AppState>() {
+                if let Ok(mut ctx) = app_state.whisper_ctx.lock() {
+                    *ctx = None;
+                }
+            }
+            emit("complete", String::new());
+        }
+        Ok(false) => emit("unavailable", format!("No Core ML encoder is published for {}", model.display_name())),
+        Err(e) => {
+            tracing::warn!("Core ML encoder download failed: {}", e);
+            emit("error", e);
+        }
+    }
+}
+
+/// Download and unpack `<model>-encoder.mlmodelc` into the models directory.
+/// Returns `Ok(false)` when the model has no published encoder.  The archive
+/// is unpacked into a scratch directory and the encoder renamed into place,
+/// so an interrupted unpack never looks like a downloaded encoder.
+fn download_coreml_encoder(model: &WhisperModel) -> Result<bool, String> {
+    let (Some(dir_name), Some(url)) = (model.coreml_encoder_dir(), model.coreml_download_url()) else {
+        return Ok(false);
+    };
+    let dir = settings::models_dir();
+    if dir.join(dir_name).is_dir() {
+        return Ok(true);
+    }
+
+    let client = crate::proxy::client_builder()
+        .timeout(std::time::Duration::from_secs(1800))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let mut resp = client
+        .get(&url)
+        .send()
+        .map_err(|e| format!("Core ML encoder request failed: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("Core ML encoder download returned HTTP {}", resp.status()));
+    }
+
+    let zip_path = dir.join(format!("{}.zip.part", dir_name));
+    let mut file = std::fs::File::create(&zip_path)
+        .map_err(|e| format!("Failed to create temp file: {}", e))?;
+    let copied = resp.copy_to(&mut file);
+    drop(file);
+    if let Err(e) = copied {
+        let _ = std::fs::remove_file(&zip_path);
+        return Err(format!("Core ML encoder download failed: {}", e));
+    }
+
+    // The encoder is only usable on macOS, where `ditto` is always present.
+    let unpack_dir = dir.join(format!("{}.unpack", dir_name));
+    let _ = std::fs::remove_dir_all(&unpack_dir);
+    let unpacked = std::process::Command::new("ditto")
+        .args(["-x", "-k"])
+        .arg(&zip_path)
+        .arg(&unpack_dir)
+        .status();
+    let _ = std::fs::remove_file(&zip_path);
+    let placed = match unpacked {
+        Ok(status) if status.success() => {
+            if unpack_dir.join(dir_name).is_dir() {
+                std::fs::rename(unpack_dir.join(dir_name), dir.join(dir_name))
+                    .map_err(|e| format!("Failed to move Core ML encoder into place: {}", e))
+            } else {
+                Err(format!("Core ML archive did not contain {}", dir_name))
+            }
+        }
+        Ok(status) => Err(format!("Failed to unpack Core ML encoder (ditto: {})", status)),
+        Err(e) => Err(format!("Failed to run ditto: {}", e)),
+    };
+    let _ = std::fs::remove_dir_all(&unpack_dir);
+    placed?;
+    tracing::info!("Core ML encoder ready: {}", dir_name);
+    Ok(true)
+}
+
 // ── VAD model commands ──────────────────────────────────────────────────────
 
 #[tauri::command]
@@ -1896,11 +3327,15 @@ pub fn download_vad_model(app: AppHandle) -> Result<(), String> {
         return Ok(());
     }
 
+    let download_key = download_key_for(&model_path);
+    let cancel = begin_download(&app, "vad-model", &download_key)?;
+
     let tmp_path = model_path.with_extension("bin.part");
     let _ = std::fs::remove_file(&tmp_path);
 
     std::thread::spawn(move || {
-        let client = match reqwest::blocking::Client::builder()
+        (|| {
+        let client = match crate::proxy::client_builder()
             .timeout(std::time::Duration::from_secs(120))
             .build()
         {
@@ -1963,6 +3398,11 @@ pub fn download_vad_model(app: AppHandle) -> Result<(), String> {
         let mut reader = resp;
 
         loop {
+            if cancel.load(Ordering::SeqCst) {
+                drop(file);
+                cancelled_download(&app, "vad-model", &tmp_path);
+                return;
+            }
             let n = match reader.read(&mut buf) {
                 Ok(0) => break,
                 Ok(n) => n,
@@ -2021,6 +3461,8 @@ pub fn download_vad_model(app: AppHandle) -> Result<(), String> {
             }),
         );
         tracing::info!("VAD model downloaded: {:?}", model_path);
+        })();
+        end_download(&app, &download_key);
     });
 
     Ok(())
@@ -2052,6 +3494,57 @@ pub fn is_dev_mode() -> bool {
 
 // ── Diagnostic log export ────────────────────────────────────────────────────
 
+/// The newest `n` `sumi.log*` files (daily rotation), oldest first.
+fn recent_log_files(n: usize) -> Vec<std::path::PathBuf> {
+    let mut log_files: Vec<(std::time::SystemTime, std::path::PathBuf)> =
+        std::fs::read_dir(settings::logs_dir())
+            .ok()
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|e| {
+                e.path()
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .map(|n| n.starts_with("sumi.log"))
+                    .unwrap_or(false)
+            })
+            .filter_map(|e| {
+                let path = e.path();
+                let mtime = std::fs::metadata(&path).ok()?.modified().ok()?;
+                Some((mtime, path))
+            })
+            .collect();
+
+    // Sort ascending then take the last n (most recent), reversing back to chronological order.
+    log_files.sort_by_key(|(m, _)| *m);
+    log_files.into_iter().rev().take(n).rev().map(|(_, p)| p).collect()
+}
+
+/// Last `n` log lines, read from the two most recent files so context is
+/// preserved across the midnight rollover.
+fn log_tail(n: usize) -> Vec<String> {
+    let mut all_lines: Vec<String> = Vec::new();
+    for path in recent_log_files(2) {
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            all_lines.extend(content.lines().map(|l| l.to_string()));
+        }
+    }
+    let start = all_lines.len().saturating_sub(n);
+    all_lines.split_off(start)
+}
+
+/// Path of the log file currently being written, or the logs directory when
+/// none exists yet (dev builds log to stderr only).
+#[tauri::command]
+pub fn get_log_path() -> String {
+    recent_log_files(1)
+        .pop()
+        .unwrap_or_else(settings::logs_dir)
+        .to_string_lossy()
+        .to_string()
+}
+
 #[tauri::command]
 pub fn export_diagnostic_log(state: State<'_, AppState>) -> Result<String, String> {
     use std::fmt::Write as _;
@@ -2081,7 +3574,7 @@ pub fn export_diagnostic_log(state: State<'_, AppState>) -> Result<String, Strin
     let s = state.settings.lock().unwrap_or_else(|e| e.into_inner());
     writeln!(report, "--- Settings ---").ok();
     writeln!(report, "STT Mode: {:?}", s.stt.mode).ok();
-    writeln!(report, "Whisper Model: {}", s.stt.whisper_model.display_name()).ok();
+    writeln!(report, "Whisper Model: {}", s.stt.whisper_model.describe()).ok();
     writeln!(report, "Language: {}", s.stt.language).ok();
     writeln!(report, "VAD: always enabled (model downloaded: {})", crate::settings::vad_model_path().exists()).ok();
     writeln!(report, "Polish: {}", s.polish.enabled).ok();
@@ -2090,46 +3583,12 @@ pub fn export_diagnostic_log(state: State<'_, AppState>) -> Result<String, Strin
     drop(s);
     writeln!(report).ok();
 
-    // Collect sumi.log.* files (daily rotation), sort by mtime, take 2 most recent
-    // in chronological order so context is preserved across the midnight rollover.
-    let log_dir = settings::logs_dir();
-    let mut log_files: Vec<(std::time::SystemTime, std::path::PathBuf)> =
-        std::fs::read_dir(&log_dir)
-            .ok()
-            .into_iter()
-            .flatten()
-            .flatten()
-            .filter(|e| {
-                e.path()
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .map(|n| n.starts_with("sumi.log"))
-                    .unwrap_or(false)
-            })
-            .filter_map(|e| {
-                let path = e.path();
-                let mtime = std::fs::metadata(&path).ok()?.modified().ok()?;
-                Some((mtime, path))
-            })
-            .collect();
-
-    // Sort ascending then take last 2 (most recent), reversing back to chronological order.
-    log_files.sort_by_key(|(m, _)| *m);
-    let recent: Vec<_> = log_files.into_iter().rev().take(2).rev().collect();
-
-    let mut all_lines: Vec<String> = Vec::new();
-    for (_, path) in &recent {
-        if let Ok(content) = std::fs::read_to_string(path) {
-            all_lines.extend(content.lines().map(|l| l.to_string()));
-        }
-    }
-
-    if all_lines.is_empty() {
+    let tail = log_tail(200);
+    if tail.is_empty() {
         writeln!(report, "--- App Log ---\n(no log file yet)").ok();
     } else {
-        let start = all_lines.len().saturating_sub(200);
-        writeln!(report, "--- App Log (last {} lines) ---", all_lines.len() - start).ok();
-        for line in &all_lines[start..] {
+        writeln!(report, "--- App Log (last {} lines) ---", tail.len()).ok();
+        for line in &tail {
             writeln!(report, "{}", line).ok();
         }
     }
@@ -2244,15 +3703,12 @@ pub async fn switch_qwen3_asr_model(
 #[tauri::command]
 pub fn download_qwen3_asr_model(
     app: AppHandle,
-    state: State<'_, AppState>,
+    _state: State<'_, AppState>,
     model: Qwen3AsrModel,
 ) -> Result<(), String> {
-    // Only one download at a time.
-    if state.downloading.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err() {
-        return Err("A model download is already in progress".to_string());
-    }
-
     let model_dir = crate::stt::qwen3_asr_model_dir(&model);
+    let download_key = download_key_for(&model_dir);
+    let cancel = begin_download(&app, "qwen3-asr", &download_key)?;
     let _ = std::fs::create_dir_all(&model_dir);
 
     let files: Vec<(&'static str, &'static str)> = model.download_files();
@@ -2261,7 +3717,7 @@ pub fn download_qwen3_asr_model(
     std::thread::spawn(move || {
         let state = app.state::<AppState>();
 
-        let client = match reqwest::blocking::Client::builder()
+        let client = match crate::proxy::client_builder()
             .timeout(std::time::Duration::from_secs(3600))
             .build()
         {
@@ -2270,7 +3726,7 @@ pub fn download_qwen3_asr_model(
                 let _ = app.emit("qwen3-asr-download-progress", serde_json::json!({
                     "status": "error", "message": e.to_string()
                 }));
-                state.downloading.store(false, Ordering::SeqCst);
+                end_download(&app, &download_key);
                 return;
             }
         };
@@ -2305,7 +3761,7 @@ pub fn download_qwen3_asr_model(
                     let _ = app.emit("qwen3-asr-download-progress", serde_json::json!({
                         "status": "error", "message": format!("Download failed for {}: {}", filename, e)
                     }));
-                    state.downloading.store(false, Ordering::SeqCst);
+                    end_download(&app, &download_key);
                     return;
                 }
             };
@@ -2315,7 +3771,7 @@ pub fn download_qwen3_asr_model(
                     "status": "error",
                     "message": format!("HTTP {} for {}", resp.status(), filename)
                 }));
-                state.downloading.store(false, Ordering::SeqCst);
+                end_download(&app, &download_key);
                 return;
             }
 
@@ -2325,7 +3781,7 @@ pub fn download_qwen3_asr_model(
                     let _ = app.emit("qwen3-asr-download-progress", serde_json::json!({
                         "status": "error", "message": format!("Cannot create {}: {}", filename, e)
                     }));
-                    state.downloading.store(false, Ordering::SeqCst);
+                    end_download(&app, &download_key);
                     return;
                 }
             };
@@ -2333,7 +3789,14 @@ pub fn download_qwen3_asr_model(
             let mut stream = resp;
             let mut file_downloaded: u64 = 0;
             let mut buf = vec![0u8; 65536];
+            let mut last_emit = Instant::now();
             loop {
+                if cancel.load(Ordering::SeqCst) {
+                    drop(file);
+                    cancelled_download(&app, "qwen3-asr", &tmp);
+                    end_download(&app, &download_key);
+                    return;
+                }
                 use std::io::{Read, Write};
                 match stream.read(&mut buf) {
                     Ok(0) => break,
@@ -2342,26 +3805,30 @@ pub fn download_qwen3_asr_model(
                             let _ = app.emit("qwen3-asr-download-progress", serde_json::json!({
                                 "status": "error", "message": format!("Write error for {}: {}", filename, e)
                             }));
-                            state.downloading.store(false, Ordering::SeqCst);
+                            end_download(&app, &download_key);
                             return;
                         }
                         file_downloaded += n as u64;
                         downloaded_total += n as u64;
-                        let _ = app.emit("qwen3-asr-download-progress", serde_json::json!({
-                            "downloaded": downloaded_total,
-                            "total": total_size,
-                            "percent": (downloaded_total as f64 / total_size as f64 * 100.0) as u64,
-                            "current_file": filename,
-                            "file_index": file_idx,
-                            "file_count": num_files,
-                            "file_downloaded": file_downloaded,
-                        }));
+                        if last_emit.elapsed() >= std::time::Duration::from_millis(100) {
+                            report_download_progress(&app, &download_key, downloaded_total, total_size);
+                            let _ = app.emit("qwen3-asr-download-progress", serde_json::json!({
+                                "downloaded": downloaded_total,
+                                "total": total_size,
+                                "percent": (downloaded_total as f64 / total_size as f64 * 100.0) as u64,
+                                "current_file": filename,
+                                "file_index": file_idx,
+                                "file_count": num_files,
+                                "file_downloaded": file_downloaded,
+                            }));
+                            last_emit = Instant::now();
+                        }
                     }
                     Err(e) => {
                         let _ = app.emit("qwen3-asr-download-progress", serde_json::json!({
                             "status": "error", "message": format!("Read error for {}: {}", filename, e)
                         }));
-                        state.downloading.store(false, Ordering::SeqCst);
+                        end_download(&app, &download_key);
                         return;
                     }
                 }
@@ -2372,7 +3839,7 @@ pub fn download_qwen3_asr_model(
                 let _ = app.emit("qwen3-asr-download-progress", serde_json::json!({
                     "status": "error", "message": format!("Rename failed for {}: {}", filename, e)
                 }));
-                state.downloading.store(false, Ordering::SeqCst);
+                end_download(&app, &download_key);
                 return;
             }
         }
@@ -2398,7 +3865,7 @@ pub fn download_qwen3_asr_model(
             "percent": 100u64,
         }));
 
-        state.downloading.store(false, Ordering::SeqCst);
+        end_download(&app, &download_key);
     });
 
     Ok(())
@@ -2433,7 +3900,10 @@ pub fn delete_whisper_model(
 ) -> Result<u64, String> {
     guard_model_op(&state)?;
 
-    let path = settings::models_dir().join(model.filename());
+    if matches!(model, WhisperModel::Custom { .. }) {
+        return Err("Custom models are not managed by Sumi".to_string());
+    }
+    let path = model.local_path();
     if !path.exists() {
         return Err("Model file not found".to_string());
     }
@@ -2583,7 +4053,7 @@ pub fn download_diarization_model(_app: AppHandle, _state: State<'_, AppState>)
 
 #[cfg(feature = "diarization")]
 #[tauri::command]
-pub fn download_diarization_model(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+pub fn download_diarization_model(app: AppHandle, _state: State<'_, AppState>) -> Result<(), String> {
     use std::io::Read as _;
 
     let url = crate::diarization::WESPEAKER_URL;
@@ -2596,13 +4066,12 @@ pub fn download_diarization_model(app: AppHandle, state: State<'_, AppState>) ->
         return Ok(());
     }
 
-    if state.downloading.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err() {
-        return Err("A model download is already in progress".to_string());
-    }
+    let download_key = download_key_for(&model_path);
+    let cancel = begin_download(&app, "diarization-model", &download_key)?;
 
     if let Some(dir) = model_path.parent() {
         if let Err(e) = std::fs::create_dir_all(dir) {
-            state.downloading.store(false, Ordering::SeqCst);
+            end_download(&app, &download_key);
             return Err(e.to_string());
         }
     }
@@ -2618,7 +4087,7 @@ pub fn download_diarization_model(app: AppHandle, state: State<'_, AppState>) ->
             );
         };
         (|| -> Result<(), String> {
-            let client = reqwest::blocking::Client::builder()
+            let client = crate::proxy::client_builder()
                 .timeout(std::time::Duration::from_secs(300))
                 .build()
                 .map_err(|e| e.to_string())?;
@@ -2629,8 +4098,14 @@ pub fn download_diarization_model(app: AppHandle, state: State<'_, AppState>) ->
 
             let mut downloaded: u64 = 0;
             let mut buf = [0u8; 65536];
+            let mut last_emit = Instant::now();
             let mut reader = resp;
             loop {
+                if cancel.load(Ordering::SeqCst) {
+                    drop(file);
+                    cancelled_download(&app, "diarization-model", &tmp_path);
+                    return Ok(());
+                }
                 let n = match reader.read(&mut buf) {
                     Ok(0) => break,
                     Ok(n) => n,
@@ -2638,10 +4113,14 @@ pub fn download_diarization_model(app: AppHandle, state: State<'_, AppState>) ->
                 };
                 std::io::Write::write_all(&mut file, &buf[..n]).map_err(|e| e.to_string())?;
                 downloaded += n as u64;
-                let _ = app.emit(
-                    "diarization-model-download-progress",
-                    serde_json::json!({ "downloaded": downloaded, "total": total }),
-                );
+                if last_emit.elapsed() >= std::time::Duration::from_millis(100) {
+                    report_download_progress(&app, &download_key, downloaded, total);
+                    let _ = app.emit(
+                        "diarization-model-download-progress",
+                        serde_json::json!({ "downloaded": downloaded, "total": total }),
+                    );
+                    last_emit = Instant::now();
+                }
             }
             drop(file);
             std::fs::rename(&tmp_path, &model_path).map_err(|e| e.to_string())?;
@@ -2656,7 +4135,7 @@ pub fn download_diarization_model(app: AppHandle, state: State<'_, AppState>) ->
             let _ = std::fs::remove_file(&tmp_path);
             emit_err(e);
         });
-        app.state::<AppState>().downloading.store(false, Ordering::SeqCst);
+        end_download(&app, &download_key);
     });
 
     Ok(())
@@ -2716,7 +4195,7 @@ pub fn download_segmentation_model(_app: AppHandle, _state: State<'_, AppState>)
 
 #[cfg(feature = "diarization")]
 #[tauri::command]
-pub fn download_segmentation_model(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+pub fn download_segmentation_model(app: AppHandle, _state: State<'_, AppState>) -> Result<(), String> {
     use std::io::Read as _;
 
     let url = crate::diarization::SEGMENTATION_URL;
@@ -2729,13 +4208,12 @@ pub fn download_segmentation_model(app: AppHandle, state: State<'_, AppState>) -
         return Ok(());
     }
 
-    if state.downloading.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err() {
-        return Err("A model download is already in progress".to_string());
-    }
+    let download_key = download_key_for(&model_path);
+    let cancel = begin_download(&app, "segmentation-model", &download_key)?;
 
     if let Some(dir) = model_path.parent() {
         if let Err(e) = std::fs::create_dir_all(dir) {
-            state.downloading.store(false, Ordering::SeqCst);
+            end_download(&app, &download_key);
             return Err(e.to_string());
         }
     }
@@ -2751,7 +4229,7 @@ pub fn download_segmentation_model(app: AppHandle, state: State<'_, AppState>) -
             );
         };
         (|| -> Result<(), String> {
-            let client = reqwest::blocking::Client::builder()
+            let client = crate::proxy::client_builder()
                 .timeout(std::time::Duration::from_secs(300))
                 .build()
                 .map_err(|e| e.to_string())?;
@@ -2762,8 +4240,14 @@ pub fn download_segmentation_model(app: AppHandle, state: State<'_, AppState>) -
 
             let mut downloaded: u64 = 0;
             let mut buf = [0u8; 65536];
+            let mut last_emit = Instant::now();
             let mut reader = resp;
             loop {
+                if cancel.load(Ordering::SeqCst) {
+                    drop(file);
+                    cancelled_download(&app, "segmentation-model", &tmp_path);
+                    return Ok(());
+                }
                 let n = match reader.read(&mut buf) {
                     Ok(0) => break,
                     Ok(n) => n,
@@ -2771,10 +4255,14 @@ pub fn download_segmentation_model(app: AppHandle, state: State<'_, AppState>) -
                 };
                 std::io::Write::write_all(&mut file, &buf[..n]).map_err(|e| e.to_string())?;
                 downloaded += n as u64;
-                let _ = app.emit(
-                    "segmentation-model-download-progress",
-                    serde_json::json!({ "downloaded": downloaded, "total": total }),
-                );
+                if last_emit.elapsed() >= std::time::Duration::from_millis(100) {
+                    report_download_progress(&app, &download_key, downloaded, total);
+                    let _ = app.emit(
+                        "segmentation-model-download-progress",
+                        serde_json::json!({ "downloaded": downloaded, "total": total }),
+                    );
+                    last_emit = Instant::now();
+                }
             }
             drop(file);
             std::fs::rename(&tmp_path, &model_path).map_err(|e| e.to_string())?;
@@ -2789,7 +4277,7 @@ pub fn download_segmentation_model(app: AppHandle, state: State<'_, AppState>) -
             let _ = std::fs::remove_file(&tmp_path);
             emit_err(e);
         });
-        app.state::<AppState>().downloading.store(false, Ordering::SeqCst);
+        end_download(&app, &download_key);
     });
 
     Ok(())
@@ -2894,7 +4382,7 @@ fn download_infra_file(app: &AppHandle, url: &str, dest: &std::path::Path, model
 
     let tmp = dest.with_extension("ipart");
 
-    let client = match reqwest::blocking::Client::builder()
+    let client = match crate::proxy::client_builder()
         .timeout(std::time::Duration::from_secs(300))
         .build()
     {
@@ -3152,7 +4640,7 @@ Write in {lang_name}."#
             &config,
             system_prompt,
             &user_text,
-            &state.http_client,
+            &state.http_client(),
             Some(8192),
         )?;
 
@@ -3171,40 +4659,7 @@ Write in {lang_name}."#
 }
 
 fn language_display_name(bcp47: &str) -> &'static str {
-    match bcp47 {
-        "zh-TW" => "繁體中文 (Traditional Chinese)",
-        "zh-CN" | "zh" => "简体中文 (Simplified Chinese)",
-        "en" => "English",
-        "ja" => "日本語 (Japanese)",
-        "ko" => "한국어 (Korean)",
-        "es" => "Español (Spanish)",
-        "fr" => "Français (French)",
-        "de" => "Deutsch (German)",
-        "pt" => "Português (Portuguese)",
-        "it" => "Italiano (Italian)",
-        "ru" => "Русский (Russian)",
-        "ar" => "العربية (Arabic)",
-        "hi" => "हिन्दी (Hindi)",
-        "th" => "ไทย (Thai)",
-        "vi" => "Tiếng Việt (Vietnamese)",
-        "id" => "Bahasa Indonesia (Indonesian)",
-        "ms" => "Bahasa Melayu (Malay)",
-        "nl" => "Nederlands (Dutch)",
-        "pl" => "Polski (Polish)",
-        "tr" => "Türkçe (Turkish)",
-        "uk" => "Українська (Ukrainian)",
-        "sv" => "Svenska (Swedish)",
-        "da" => "Dansk (Danish)",
-        "fi" => "Suomi (Finnish)",
-        "no" => "Norsk (Norwegian)",
-        "cs" => "Čeština (Czech)",
-        "ro" => "Română (Romanian)",
-        "hu" => "Magyar (Hungarian)",
-        "el" => "Ελληνικά (Greek)",
-        "he" => "עברית (Hebrew)",
-        "auto" | "" => "the same language as the transcript",
-        _ => "the same language as the transcript",
-    }
+    polisher::language_name(bcp47).unwrap_or("the same language as the transcript")
 }
 
 fn parse_polish_json(raw: &str, fallback_title: &str) -> PolishedMeetingNote {
@@ -3269,6 +4724,23 @@ pub struct DataRootCheckResult {
     pub already_has_data: bool,
     pub free_bytes: u64,
     pub data_size_bytes: u64,
+    pub is_writable: bool,
+}
+
+/// Headroom required on the target volume on top of the data being moved.
+const DATA_ROOT_SPACE_MARGIN: u64 = 100 * 1024 * 1024;
+
+/// Verify that `dir` (or its nearest existing ancestor) accepts new files by
+/// creating and removing a probe file.  Creates no directories, so a
+/// missing `dir` leaves nothing behind.
+fn probe_writable(dir: &std::path::Path) -> bool {
+    let Some(existing) = dir.ancestors().find(|p| p.is_dir()) else {
+        return false;
+    };
+    let probe = existing.join(".sumi-write-probe");
+    let ok = std::fs::write(&probe, b"ok").is_ok();
+    let _ = std::fs::remove_file(&probe);
+    ok
 }
 
 /// Returns the total size (bytes) of all regular files under `dir`.
@@ -3348,10 +4820,11 @@ pub fn check_data_root_target(new_path: String) -> Result<DataRootCheckResult, S
         || new_root.join("history").exists()
         || new_root.join("audio").exists();
     Ok(DataRootCheckResult {
-        has_enough_space: available > data_size_bytes.saturating_add(100 * 1024 * 1024),
+        has_enough_space: available > data_size_bytes.saturating_add(DATA_ROOT_SPACE_MARGIN),
         already_has_data,
         free_bytes: available,
         data_size_bytes,
+        is_writable: probe_writable(&new_root),
     })
 }
 
@@ -3415,6 +4888,21 @@ pub async fn migrate_data_root(
         )
     };
 
+    // Re-validate the target here too: the frontend check may be stale, and
+    // a failure half-way through the copy is far more expensive.
+    if !probe_writable(&new_root) {
+        return Err("target_not_writable".to_string());
+    }
+    let needed = ["models", "history", "audio"]
+        .iter()
+        .map(|s| dir_size(&old_root.join(s)))
+        .sum::<u64>()
+        .saturating_add(DATA_ROOT_SPACE_MARGIN);
+    let available = free_space(if new_root.exists() { &new_root } else { new_root.parent().unwrap_or(&new_root) });
+    if available < needed {
+        return Err("insufficient_space".to_string());
+    }
+
     // Create target sub-directories
     for sub in &["models", "history", "audio"] {
         std::fs::create_dir_all(new_root.join(sub))
@@ -3474,6 +4962,45 @@ pub async fn migrate_data_root(
 mod tests {
     use super::*;
 
+    #[test]
+    fn cancelling_edit_recording_returns_saved_clipboard_and_clears_state() {
+        let edit_mode = AtomicBool::new(true);
+        let selected = Mutex::new(Some("selected text".to_string()));
+        let saved = Mutex::new(Some("original clipboard".to_string()));
+
+        let restored = reset_edit_state(&edit_mode, &selected, &saved);
+        assert_eq!(restored.as_deref(), Some("original clipboard"));
+        assert!(!edit_mode.load(Ordering::SeqCst));
+        assert!(selected.lock().unwrap().is_none());
+        assert!(saved.lock().unwrap().is_none());
+
+        // A second cancel (or a normal dictation) has nothing to restore.
+        assert_eq!(reset_edit_state(&edit_mode, &selected, &saved), None);
+    }
+
+    #[test]
+    fn parse_generated_rule_valid() {
+        let raw = "```json\n{\"name\": \"Slack\", \"match_type\": \"app_name\", \"match_value\": \"Slack\", \"prompt\": \"Be casual\"}\n```";
+        let rule = parse_generated_rule(raw).unwrap();
+        assert_eq!(rule.match_type, "app_name");
+        assert_eq!(rule.match_value, "Slack");
+        assert_eq!(rule.prompt, "Be casual");
+    }
+
+    #[test]
+    fn parse_generated_rule_rejects_unknown_match_type() {
+        let raw = r#"{"name": "x", "match_type": "window_title", "match_value": "Slack", "prompt": "p"}"#;
+        assert!(parse_generated_rule(raw).unwrap_err().contains("match_type"));
+    }
+
+    #[test]
+    fn parse_generated_rule_rejects_empty_fields() {
+        let raw = r#"{"name": "x", "match_type": "url", "match_value": "", "prompt": "p"}"#;
+        assert!(parse_generated_rule(raw).unwrap_err().contains("match_value"));
+        let raw = r#"{"name": "x", "match_type": "url", "match_value": "github.com", "prompt": " "}"#;
+        assert!(parse_generated_rule(raw).unwrap_err().contains("prompt"));
+    }
+
     #[test]
     fn parse_polish_json_valid() {
         let raw = r#"{"title": "Meeting Title", "summary": "Key Points"}"#;
@@ -3537,4 +5064,51 @@ mod tests {
         assert_eq!(result.title, "Trimmed");
         assert_eq!(result.summary, "Content");
     }
+
+    #[test]
+    fn hotkey_conflicts_match_combination_not_spelling() {
+        let settings = Settings {
+            hotkey: "Alt+KeyZ".to_string(),
+            edit_hotkey: Some("Control+Alt+KeyE".to_string()),
+            meeting_hotkey: None,
+            paste_raw_on_modifier: true,
+            ..Settings::default()
+        };
+        assert!(check_hotkey_conflict(&settings, "meeting", "Alt+Control+KeyE").is_err());
+        // Shift + primary is taken by paste-raw ...
+        assert!(check_hotkey_conflict(&settings, "edit", "Shift+Alt+KeyZ").is_err());
+        // ... but moves along with a new primary hotkey.
+        assert!(check_hotkey_conflict(&settings, "primary", "Shift+Alt+KeyZ").is_ok());
+        // A hotkey never conflicts with its own previous value.
+        assert!(check_hotkey_conflict(&settings, "edit", "Control+Alt+KeyE").is_ok());
+        assert!(check_hotkey_conflict(&settings, "repeat-paste", "Alt+KeyR").is_ok());
+    }
+
+    #[test]
+    fn failed_hotkey_registration_keeps_previous_settings() {
+        let mut current = Settings { hotkey: "Alt+KeyZ".to_string(), ..Settings::default() };
+        let updated = Settings {
+            hotkey: "Alt+KeyX".to_string(),
+            whisper_threads: Some(3),
+            ..Settings::default()
+        };
+        let attempts = std::cell::RefCell::new(Vec::new());
+        let result = swap_if_registered(&mut current, updated.clone(), |s| {
+            attempts.borrow_mut().push(s.hotkey.clone());
+            if s.hotkey == "Alt+KeyX" {
+                Err("Alt+X is already in use".to_string())
+            } else {
+                Ok(())
+            }
+        });
+        assert!(result.is_err());
+        assert_eq!(current.hotkey, "Alt+KeyZ");
+        assert_eq!(current.whisper_threads, None);
+        // The rejected hotkeys were tried, then the previous ones restored.
+        assert_eq!(*attempts.borrow(), ["Alt+KeyX", "Alt+KeyZ"]);
+
+        let previous = swap_if_registered(&mut current, updated, |_| Ok(())).unwrap();
+        assert_eq!(previous.hotkey, "Alt+KeyZ");
+        assert_eq!(current.hotkey, "Alt+KeyX");
+    }
 }
\ No newline at end of file
@@ -11,6 +11,13 @@ pub struct AppContext {
     pub terminal_host: String,
 }
 
+impl AppContext {
+    /// Nothing was detected: no permission, or an app that reports no name.
+    pub fn is_empty(&self) -> bool {
+        self.app_name.trim().is_empty() && self.bundle_id.trim().is_empty() && self.url.trim().is_empty()
+    }
+}
+
 // ── Terminal subprocess detection ────────────────────────────────────────────
 
 /// Known terminal emulator bundle IDs and their display names.
@@ -46,6 +53,77 @@ const CLI_TOOLS: &[CliTool] = &[
     CliTool { process_names: &["hx"], title_keywords: &["helix"], display_name: "Helix" },
 ];
 
+// ── Desktop web-app enrichment ───────────────────────────────────────────────
+
+/// A desktop wrapper (mostly Electron) around a web app.
+struct WebAppWrapper {
+    /// macOS bundle IDs.
+    bundle_ids: &'static [&'static str],
+    /// Windows executable names (lowercase, without `.exe`).
+    exe_names: &'static [&'static str],
+    /// Canonical name, matching the `AppName` preset rules.
+    app_name: &'static str,
+    /// Host of the web version, matching the `Url` preset rules.
+    pseudo_url: &'static str,
+}
+
+/// Desktop apps whose web counterpart is the target of a preset rule.
+///
+/// These report a bundle ID but no URL, so a rule keyed on the web host
+/// (`notion.so`, `app.slack.com`, ...) would never fire in the desktop app.
+/// Enrichment fills `url` with the web host and normalises `app_name`, so the
+/// desktop app matches either way.  When adding an entry, use the same host
+/// the preset rule (or its `alt_matches`) uses.
+const WEB_APP_WRAPPERS: &[WebAppWrapper] = &[
+    WebAppWrapper {
+        bundle_ids: &["com.tinyspeck.slackmacgap"],
+        exe_names: &["slack"],
+        app_name: "Slack",
+        pseudo_url: "app.slack.com",
+    },
+    WebAppWrapper {
+        bundle_ids: &["notion.id"],
+        exe_names: &["notion"],
+        app_name: "Notion",
+        pseudo_url: "notion.so",
+    },
+    WebAppWrapper {
+        bundle_ids: &["com.hnc.Discord"],
+        exe_names: &["discord"],
+        app_name: "Discord",
+        pseudo_url: "discord.com",
+    },
+    WebAppWrapper {
+        bundle_ids: &["net.whatsapp.WhatsApp", "desktop.WhatsApp"],
+        exe_names: &["whatsapp"],
+        app_name: "WhatsApp",
+        pseudo_url: "web.whatsapp.com",
+    },
+    WebAppWrapper {
+        bundle_ids: &["ru.keepcoder.Telegram", "com.tdesktop.Telegram"],
+        exe_names: &["telegram"],
+        app_name: "Telegram",
+        pseudo_url: "web.telegram.org",
+    },
+];
+
+/// Fill in `url` and a canonical `app_name` for known desktop web-app
+/// wrappers.  A URL detected from a real browser is never overwritten.
+pub fn enrich_web_app_wrapper(ctx: &mut AppContext) {
+    if !ctx.url.is_empty() {
+        return;
+    }
+    let app_lower = ctx.app_name.to_lowercase();
+    let Some(wrapper) = WEB_APP_WRAPPERS.iter().find(|w| {
+        w.bundle_ids.contains(&ctx.bundle_id.as_str())
+            || (ctx.bundle_id.is_empty() && w.exe_names.contains(&app_lower.as_str()))
+    }) else {
+        return;
+    };
+    ctx.app_name = wrapper.app_name.to_string();
+    ctx.url = wrapper.pseudo_url.to_string();
+}
+
 /// Detect the frontmost macOS application and, if it's a known browser, the current URL.
 /// If it's a terminal, attempt to detect a known CLI tool running inside it.
 #[cfg(target_os = "macos")]
@@ -67,6 +145,7 @@ pub fn detect_frontmost_app() -> AppContext {
             ctx.app_name = tool_name;
         }
     }
+    enrich_web_app_wrapper(&mut ctx);
 
     ctx
 }
@@ -74,12 +153,14 @@ pub fn detect_frontmost_app() -> AppContext {
 #[cfg(target_os = "windows")]
 pub fn detect_frontmost_app() -> AppContext {
     let app_name = get_foreground_app_name_windows();
-    AppContext {
+    let mut ctx = AppContext {
         app_name,
         bundle_id: String::new(),
         url: String::new(),
         terminal_host: String::new(),
-    }
+    };
+    enrich_web_app_wrapper(&mut ctx);
+    ctx
 }
 
 #[cfg(not(any(target_os = "macos", target_os = "windows")))]
@@ -87,6 +168,44 @@ pub fn detect_frontmost_app() -> AppContext {
     AppContext::default()
 }
 
+/// Identify the frontmost app without the URL and terminal probes of
+/// [`detect_frontmost_app`].  Cheap enough to run right before a paste.
+#[cfg(target_os = "macos")]
+pub fn detect_frontmost_identity() -> AppContext {
+    let (app_name, bundle_id) = get_frontmost_app_info();
+    AppContext { app_name, bundle_id, ..Default::default() }
+}
+
+#[cfg(target_os = "windows")]
+pub fn detect_frontmost_identity() -> AppContext {
+    detect_frontmost_app()
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn detect_frontmost_identity() -> AppContext {
+    AppContext::default()
+}
+
+/// Whether `current` is the same app that was frontmost in `captured`.
+///
+/// Compares bundle ids when available (stable across terminal and web-app
+/// enrichment), otherwise app names.  An empty capture always matches, since
+/// there is nothing to compare against.
+pub fn is_same_app(captured: &AppContext, current: &AppContext) -> bool {
+    if captured.bundle_id.is_empty() && captured.app_name.is_empty() {
+        return true;
+    }
+    if !captured.bundle_id.is_empty() || !current.bundle_id.is_empty() {
+        return captured.bundle_id == current.bundle_id;
+    }
+    let captured_name = if captured.terminal_host.is_empty() {
+        &captured.app_name
+    } else {
+        &captured.terminal_host
+    };
+    captured_name.eq_ignore_ascii_case(&current.app_name)
+}
+
 /// Get the foreground application's executable name on Windows.
 #[cfg(target_os = "windows")]
 fn get_foreground_app_name_windows() -> String {
@@ -328,3 +447,69 @@ fn get_browser_url(bundle_id: &str) -> String {
 fn get_browser_url(_bundle_id: &str) -> String {
     String::new()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(app_name: &str, bundle_id: &str, url: &str) -> AppContext {
+        AppContext {
+            app_name: app_name.to_string(),
+            bundle_id: bundle_id.to_string(),
+            url: url.to_string(),
+            terminal_host: String::new(),
+        }
+    }
+
+    #[test]
+    fn same_app_compares_bundle_ids_first() {
+        let mut captured = ctx("Claude Code", "com.apple.Terminal", "");
+        captured.terminal_host = "Terminal".to_string();
+        assert!(is_same_app(&captured, &ctx("Terminal", "com.apple.Terminal", "")));
+        assert!(!is_same_app(&captured, &ctx("Slack", "com.tinyspeck.slackmacgap", "")));
+    }
+
+    #[test]
+    fn same_app_falls_back_to_names() {
+        let captured = ctx("Slack", "", "app.slack.com");
+        assert!(is_same_app(&captured, &ctx("slack", "", "")));
+        assert!(!is_same_app(&captured, &ctx("Discord", "", "")));
+        assert!(is_same_app(&AppContext::default(), &ctx("Discord", "", "")));
+    }
+
+    #[test]
+    fn empty_context_has_no_identifying_field() {
+        assert!(AppContext::default().is_empty());
+        assert!(ctx(" ", "", "").is_empty());
+        assert!(!ctx("", "", "github.com").is_empty());
+        assert!(!ctx("", "com.apple.Terminal", "").is_empty());
+    }
+
+    #[test]
+    fn slack_desktop_gets_canonical_name_and_pseudo_url() {
+        let mut c = ctx("Slack Helper", "com.tinyspeck.slackmacgap", "");
+        enrich_web_app_wrapper(&mut c);
+        assert_eq!(c.app_name, "Slack");
+        assert_eq!(c.url, "app.slack.com");
+    }
+
+    #[test]
+    fn windows_exe_name_is_enriched() {
+        let mut c = ctx("Notion", "", "");
+        enrich_web_app_wrapper(&mut c);
+        assert_eq!(c.url, "notion.so");
+    }
+
+    #[test]
+    fn browser_url_and_unknown_apps_are_untouched() {
+        let mut c = ctx("Google Chrome", "com.google.Chrome", "https://github.com/");
+        enrich_web_app_wrapper(&mut c);
+        assert_eq!(c.app_name, "Google Chrome");
+        assert_eq!(c.url, "https://github.com/");
+
+        let mut c = ctx("Xcode", "com.apple.dt.Xcode", "");
+        enrich_web_app_wrapper(&mut c);
+        assert_eq!(c.app_name, "Xcode");
+        assert!(c.url.is_empty());
+    }
+}
@@ -0,0 +1,183 @@
+//! Worker bookkeeping for continuous dictation (`SessionMode::Continuous`).
+//!
+//! While a continuous recording runs, each pause cuts the audio so far into a
+//! chunk that is transcribed and pasted by a per-session worker, one chunk at
+//! a time in the order they were spoken.  Stopping the recording finishes the
+//! session (its queued chunks still paste before the final stretch);
+//! cancelling it sets the session's own cancel token, so the chunk in flight
+//! discards its result and queued ones are skipped.  A cancelled worker may
+//! still be unwinding a chunk, so the next session waits for it first.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// One session's chunk channel, worker and cancel token.
+struct Session {
+    chunks: Sender<(Vec<f32>, u32)>,
+    worker: JoinHandle<()>,
+    cancel: Arc<AtomicBool>,
+}
+
+/// The continuous session in progress, if any, and the worker of the last
+/// cancelled one until a later session has waited for it.
+#[derive(Default)]
+pub struct ContinuousSessions {
+    active: Option<Session>,
+    cancelled: Option<JoinHandle<()>>,
+}
+
+impl ContinuousSessions {
+    /// Queue a chunk recorded at `sample_rate` for the session worker.  The
+    /// first chunk of a session starts a worker that runs `process` on each
+    /// chunk with the session's cancel token; a chunk that panics is logged
+    /// and the worker moves on to the next.
+    pub fn commit<F>(&mut self, samples: Vec<f32>, sample_rate: u32, process: F)
+    where
+        F: FnMut(Vec<f32>, u32, &Arc<AtomicBool>) + Send + 'static,
+    {
+        let session = match self.active {
+            Some(ref session) => session,
+            None => {
+                if let Some(worker) = self.cancelled.take() {
+                    let _ = worker.join();
+                }
+                self.active.insert(start(process))
+            }
+        };
+        let _ = session.chunks.send((samples, sample_rate));
+    }
+
+    /// Close the session to new chunks.  Returns the workers to wait for,
+    /// outside any lock: the session's, which exits once its queued chunks
+    /// are done, and that of an earlier cancelled session.
+    pub fn finish(&mut self) -> Vec<JoinHandle<()>> {
+        let mut workers: Vec<_> = self.cancelled.take().into_iter().collect();
+        if let Some(Session { chunks, worker, .. }) = self.active.take() {
+            drop(chunks);
+            workers.push(worker);
+        }
+        workers
+    }
+
+    /// Cancel the session without waiting: the chunk in flight discards its
+    /// result and the queued ones are skipped.
+    pub fn cancel(&mut self) {
+        if let Some(Session { chunks, worker, cancel }) = self.active.take() {
+            cancel.store(true, Ordering::SeqCst);
+            drop(chunks);
+            if let Some(previous) = self.cancelled.replace(worker) {
+                let _ = previous.join();
+            }
+        }
+    }
+}
+
+fn start<F>(mut process: F) -> Session
+where
+    F: FnMut(Vec<f32>, u32, &Arc<AtomicBool>) + Send + 'static,
+{
+    let (chunks, rx) = mpsc::channel::<(Vec<f32>, u32)>();
+    let cancel = Arc::new(AtomicBool::new(false));
+    let worker_cancel = Arc::clone(&cancel);
+    let worker = std::thread::spawn(move || {
+        for (samples, sample_rate) in rx {
+            if worker_cancel.load(Ordering::SeqCst) {
+                break;
+            }
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                process(samples, sample_rate, &worker_cancel)
+            }));
+            if result.is_err() {
+                tracing::error!("Continuous dictation chunk panicked; continuing with the next one");
+            }
+        }
+    });
+    Session { chunks, worker, cancel }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn join_all(workers: Vec<JoinHandle<()>>) {
+        for worker in workers {
+            worker.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn finish_waits_for_every_committed_chunk_in_order() {
+        let mut sessions = ContinuousSessions::default();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        sessions.commit(vec![0.0; 1], 16000, move |samples, _, _| sink.lock().unwrap().push(samples.len()));
+        // Later chunks reuse the running worker; their closures are unused.
+        sessions.commit(vec![0.0; 2], 16000, |_, _, _| unreachable!());
+        sessions.commit(vec![0.0; 3], 16000, |_, _, _| unreachable!());
+        join_all(sessions.finish());
+        assert_eq!(*seen.lock().unwrap(), [1, 2, 3]);
+        assert!(sessions.finish().is_empty());
+    }
+
+    #[test]
+    fn cancel_flags_the_chunk_in_flight_and_skips_the_rest() {
+        let mut sessions = ContinuousSessions::default();
+        let (started_tx, started_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        sessions.commit(vec![0.0; 1], 16000, move |samples, _, cancel| {
+            started_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+            sink.lock().unwrap().push((samples.len(), cancel.load(Ordering::SeqCst)));
+        });
+        sessions.commit(vec![0.0; 2], 16000, |_, _, _| unreachable!());
+        started_rx.recv().unwrap();
+        sessions.cancel();
+        release_tx.send(()).unwrap();
+        join_all(sessions.finish());
+        // The first chunk saw the cancel; the queued one never ran.
+        assert_eq!(*seen.lock().unwrap(), [(1, true)]);
+    }
+
+    #[test]
+    fn new_session_waits_for_a_cancelled_worker() {
+        let mut sessions = ContinuousSessions::default();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let first = Arc::clone(&order);
+        let (started_tx, started_rx) = mpsc::channel();
+        sessions.commit(vec![0.0; 1], 16000, move |_, _, _| {
+            started_tx.send(()).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            first.lock().unwrap().push("cancelled");
+        });
+        started_rx.recv().unwrap();
+        sessions.cancel();
+        let second = Arc::clone(&order);
+        sessions.commit(vec![0.0; 1], 16000, move |_, _, cancel| {
+            assert!(!cancel.load(Ordering::SeqCst));
+            second.lock().unwrap().push("next");
+        });
+        join_all(sessions.finish());
+        assert_eq!(*order.lock().unwrap(), ["cancelled", "next"]);
+    }
+
+    #[test]
+    fn panicking_chunk_does_not_stop_the_worker() {
+        let mut sessions = ContinuousSessions::default();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        sessions.commit(vec![0.0; 1], 16000, move |samples, _, _| {
+            if samples.len() == 1 {
+                panic!("chunk failed");
+            }
+            sink.lock().unwrap().push(samples.len());
+        });
+        sessions.commit(vec![0.0; 2], 16000, |_, _, _| unreachable!());
+        join_all(sessions.finish());
+        assert_eq!(*seen.lock().unwrap(), [2]);
+    }
+}
@@ -18,6 +18,20 @@ fn keychain_service(provider: &str) -> String {
     format!("{}-api-key-{}", SERVICE, provider)
 }
 
+/// Credential keys for every provider this build knows about (polish
+/// providers plus `stt_`-prefixed STT providers).
+///
+/// Neither the macOS keychain CLI nor the `keyring` crate can list items by
+/// a wildcard service name, so callers that need "all stored keys" probe
+/// each known key with [`load`] instead.
+pub fn known_providers() -> Vec<&'static str> {
+    crate::polisher::CloudProvider::ALL
+        .iter()
+        .map(|p| p.as_key())
+        .chain(crate::stt::SttProvider::ALL.iter().map(|p| p.as_key()))
+        .collect()
+}
+
 // ── macOS ──────────────────────────────────────────────────────────
 
 #[cfg(target_os = "macos")]
@@ -0,0 +1,192 @@
+//! Dictations recorded while the pipeline was busy (`queue_dictations`).
+//!
+//! A press while a dictation is processing may record the next one; stopping
+//! it queues the audio here instead of running the pipeline.  A single worker
+//! drains the queue one job at a time, each once the pipeline is free, so the
+//! pastes land in the order the recordings were made.  The bookkeeping lives
+//! apart from the Tauri handles so these rules can be tested on their own.
+
+use std::collections::VecDeque;
+
+use crate::context_detect::AppContext;
+
+/// A dictation recorded while another was processing, waiting its turn.
+pub struct QueuedDictation {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+    pub context: AppContext,
+    pub paste_raw: bool,
+    pub polish_model: Option<String>,
+}
+
+/// Outcome of [`DictationQueue::push`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum Push {
+    /// The recording captured no audio and was dropped.
+    Dropped,
+    /// The job is queued; `spawn_worker` when no worker is draining yet.
+    Queued { spawn_worker: bool },
+}
+
+/// Dictations waiting for the pipeline, oldest first.
+#[derive(Default)]
+pub struct DictationQueue {
+    jobs: VecDeque<QueuedDictation>,
+    /// A worker is draining `jobs`.
+    worker_running: bool,
+}
+
+impl DictationQueue {
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    /// Whether a stopped recording has to wait: the pipeline is running or
+    /// earlier recordings are still queued.
+    pub fn has_work_ahead(&self, processing: bool) -> bool {
+        processing || !self.jobs.is_empty()
+    }
+
+    /// Queue `job` behind the ones already waiting.  The caller starts a
+    /// worker when asked to; until that worker finds the queue empty, later
+    /// jobs are left to it.
+    pub fn push(&mut self, job: QueuedDictation) -> Push {
+        if job.samples.is_empty() {
+            return Push::Dropped;
+        }
+        self.jobs.push_back(job);
+        let spawn_worker = !self.worker_running;
+        self.worker_running = true;
+        Push::Queued { spawn_worker }
+    }
+
+    /// Take the oldest job for the worker.  `None` means the worker should
+    /// exit; the next [`push`](Self::push) then starts a new one.
+    pub fn next_job(&mut self) -> Option<QueuedDictation> {
+        let job = self.jobs.pop_front();
+        self.worker_running = job.is_some();
+        job
+    }
+
+    /// Drop every waiting job (the user cancelled).  A running worker finds
+    /// the queue empty and exits.  Returns how many were dropped.
+    pub fn clear(&mut self) -> usize {
+        let dropped = self.jobs.len();
+        self.jobs.clear();
+        dropped
+    }
+}
+
+/// A hotkey press that arrives while a dictation is processing or queued.
+pub struct BusyPress {
+    /// A recording is in progress (the press would stop it).
+    pub recording: bool,
+    /// That recording was started while busy and will be queued.
+    pub recording_queued: bool,
+    /// The press is the plain primary hotkey (not edit, meeting or repeat).
+    pub primary: bool,
+    /// A stop has claimed the current recording but its pipeline has not
+    /// taken the audio yet.
+    pub stop_pending: bool,
+    /// `queue_dictations` is on (and the session mode allows it).
+    pub queue_enabled: bool,
+}
+
+impl BusyPress {
+    /// Whether the press goes ahead: it stops a queued recording, or it
+    /// starts one with the primary hotkey when queueing is enabled.  Nothing
+    /// may start before a pending stop has taken its audio out of the buffer.
+    pub fn accepted(&self) -> bool {
+        if self.recording {
+            self.recording_queued
+        } else {
+            self.primary && !self.stop_pending && self.queue_enabled
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(samples: usize, tag: &str) -> QueuedDictation {
+        QueuedDictation {
+            samples: vec![0.1; samples],
+            sample_rate: 16000,
+            context: AppContext::default(),
+            paste_raw: false,
+            polish_model: Some(tag.to_string()),
+        }
+    }
+
+    #[test]
+    fn jobs_run_in_recording_order() {
+        let mut queue = DictationQueue::default();
+        for tag in ["first", "second", "third"] {
+            queue.push(job(160, tag));
+        }
+        let order: Vec<_> = std::iter::from_fn(|| queue.next_job())
+            .map(|j| j.polish_model.unwrap())
+            .collect();
+        assert_eq!(order, ["first", "second", "third"]);
+    }
+
+    #[test]
+    fn empty_capture_is_dropped() {
+        let mut queue = DictationQueue::default();
+        assert_eq!(queue.push(job(0, "empty")), Push::Dropped);
+        assert!(queue.is_empty());
+        // Nothing queued, so no worker was claimed either.
+        assert_eq!(queue.push(job(160, "a")), Push::Queued { spawn_worker: true });
+    }
+
+    #[test]
+    fn worker_is_handed_off_once_the_queue_drains() {
+        let mut queue = DictationQueue::default();
+        assert_eq!(queue.push(job(160, "a")), Push::Queued { spawn_worker: true });
+        assert_eq!(queue.push(job(160, "b")), Push::Queued { spawn_worker: false });
+        assert!(queue.next_job().is_some());
+        // Still draining: a push now is picked up by the same worker.
+        assert_eq!(queue.push(job(160, "c")), Push::Queued { spawn_worker: false });
+        assert!(queue.next_job().is_some());
+        assert!(queue.next_job().is_some());
+        // The worker saw the queue empty and exits; the next push needs a new one.
+        assert!(queue.next_job().is_none());
+        assert_eq!(queue.push(job(160, "d")), Push::Queued { spawn_worker: true });
+    }
+
+    #[test]
+    fn clear_drops_waiting_jobs() {
+        let mut queue = DictationQueue::default();
+        queue.push(job(160, "a"));
+        queue.push(job(160, "b"));
+        assert_eq!(queue.clear(), 2);
+        assert!(!queue.has_work_ahead(false));
+        assert!(queue.next_job().is_none());
+    }
+
+    #[test]
+    fn busy_press_waits_for_a_pending_stop() {
+        let press = |recording, recording_queued, primary, stop_pending| BusyPress {
+            recording,
+            recording_queued,
+            primary,
+            stop_pending,
+            queue_enabled: true,
+        };
+        assert!(press(false, false, true, false).accepted());
+        // The previous stop has not taken its audio yet.
+        assert!(!press(false, false, true, true).accepted());
+        // Only the primary hotkey records a queued dictation.
+        assert!(!press(false, false, false, false).accepted());
+        // Stopping works only for the recording that will be queued.
+        assert!(press(true, true, true, true).accepted());
+        assert!(!press(true, false, true, false).accepted());
+        let disabled = BusyPress { queue_enabled: false, ..press(false, false, true, false) };
+        assert!(!disabled.accepted());
+    }
+}
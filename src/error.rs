@@ -0,0 +1,311 @@
+//! Typed errors for the cloud STT / polish paths.
+//!
+//! Serialized as `{ "kind": "...", "message": "..." }` so the frontend can
+//! tell "no API key" from "rate limited" from "network down" and show an
+//! actionable hint; rate limits add `retry_after_secs` when the provider says
+//! when to come back.  The transcription path keeps the type from the STT
+//! call up to the pipeline and the Tauri commands, which match on it; code
+//! that still reports plain strings crosses over through the `From` impls,
+//! and the `"no_speech"` sentinel maps to [`SumiError::NoSpeech`].
+
+use reqwest::header::HeaderMap;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SumiError {
+    /// No API key stored for the selected provider.
+    MissingApiKey(String),
+    /// Provider rejected the key (HTTP 401 / 403).
+    Auth(String),
+    /// Provider throttled the request (HTTP 429).  `retry_after_secs` comes
+    /// from the response's rate-limit headers, when it sent any.
+    RateLimited { message: String, retry_after_secs: Option<u64> },
+    /// Request never got a response: DNS, connect, TLS, timeout.
+    Network(String),
+    /// Unknown model or endpoint (HTTP 404), or a local model that is not downloaded.
+    ModelNotFound(String),
+    /// Endpoint, region or model ID is missing or invalid.
+    Config(String),
+    /// Audio contained no recognisable speech.
+    NoSpeech(String),
+    /// The user cancelled the request.
+    Cancelled(String),
+    /// Any other provider error (5xx, unexpected response shape, …).
+    Provider(String),
+    /// Errors from code that still reports plain strings.
+    Other(String),
+}
+
+impl SumiError {
+    pub fn no_speech() -> Self {
+        Self::NoSpeech("no_speech".to_string())
+    }
+
+    pub fn cancelled() -> Self {
+        Self::Cancelled("cancelled".to_string())
+    }
+
+    /// The `kind` tag sent to the frontend.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::MissingApiKey(_) => "missing_api_key",
+            Self::Auth(_) => "auth",
+            Self::RateLimited { .. } => "rate_limited",
+            Self::Network(_) => "network",
+            Self::ModelNotFound(_) => "model_not_found",
+            Self::Config(_) => "config",
+            Self::NoSpeech(_) => "no_speech",
+            Self::Cancelled(_) => "cancelled",
+            Self::Provider(_) => "provider",
+            Self::Other(_) => "other",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::RateLimited { message, .. } => message,
+            Self::MissingApiKey(m)
+            | Self::Auth(m)
+            | Self::Network(m)
+            | Self::ModelNotFound(m)
+            | Self::Config(m)
+            | Self::NoSpeech(m)
+            | Self::Cancelled(m)
+            | Self::Provider(m)
+            | Self::Other(m) => m,
+        }
+    }
+
+    /// Classify a non-success HTTP response.  `context` names the service
+    /// ("Cloud STT", "Cloud API") and `body` is a short preview of the reply.
+    pub fn from_status(context: &str, status: reqwest::StatusCode, headers: &HeaderMap, body: &str) -> Self {
+        let message = format!("{} returned HTTP {}: {}", context, status, body);
+        match status.as_u16() {
+            401 | 403 => Self::Auth(message),
+            404 => Self::ModelNotFound(message),
+            429 => {
+                let retry_after_secs = retry_after_secs(headers, SystemTime::now());
+                let message = match retry_after_secs {
+                    Some(secs) => format!("{} returned HTTP {} (retry in {}s): {}", context, status, secs, body),
+                    None => message,
+                };
+                Self::RateLimited { message, retry_after_secs }
+            }
+            _ => Self::Provider(message),
+        }
+    }
+
+    /// Wrap a transport-level failure (no HTTP status available).
+    pub fn from_request(context: &str, err: reqwest::Error) -> Self {
+        Self::Network(format!("{} request failed: {}", context, err))
+    }
+}
+
+impl Serialize for SumiError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let retry_after_secs = match self {
+            Self::RateLimited { retry_after_secs, .. } => *retry_after_secs,
+            _ => None,
+        };
+        let mut s = serializer.serialize_struct("SumiError", 2 + retry_after_secs.is_some() as usize)?;
+        s.serialize_field("kind", self.kind())?;
+        s.serialize_field("message", self.message())?;
+        if let Some(secs) = retry_after_secs {
+            s.serialize_field("retry_after_secs", &secs)?;
+        }
+        s.end()
+    }
+}
+
+/// Seconds until a throttled request may be retried, from `Retry-After`
+/// (delta-seconds or an HTTP date) or, failing that, the latest of the
+/// `x-ratelimit-reset*` headers.  Those come as delta-seconds, a Unix
+/// timestamp, or a Go-style duration such as `"6m0s"` / `"250ms"`
+/// depending on the provider.  Partial seconds round up.
+pub fn retry_after_secs(headers: &HeaderMap, now: SystemTime) -> Option<u64> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim);
+    let retry_after = header("retry-after-ms")
+        .and_then(|v| v.parse::<f64>().ok())
+        .map(|ms| Duration::from_secs_f64(ms.max(0.0) / 1000.0))
+        .or_else(|| {
+            header("retry-after").and_then(|v| {
+                v.parse::<u64>()
+                    .ok()
+                    .map(Duration::from_secs)
+                    .or_else(|| parse_http_date(v).map(|at| until(at, now)))
+            })
+        });
+    let wait = retry_after.or_else(|| {
+        ["x-ratelimit-reset", "x-ratelimit-reset-requests", "x-ratelimit-reset-tokens"]
+            .iter()
+            .filter_map(|name| header(name).and_then(|v| parse_reset(v, now)))
+            .max()
+    })?;
+    Some(wait.as_millis().div_ceil(1000) as u64)
+}
+
+/// An `x-ratelimit-reset*` value: a duration string, delta-seconds, or an
+/// absolute Unix timestamp (anything past 2001 is taken as one).
+fn parse_reset(value: &str, now: SystemTime) -> Option<Duration> {
+    if let Ok(n) = value.parse::<f64>() {
+        if !n.is_finite() || n < 0.0 {
+            return None;
+        }
+        if n >= 1e9 {
+            return Some(until(UNIX_EPOCH + Duration::from_secs_f64(n), now));
+        }
+        return Some(Duration::from_secs_f64(n));
+    }
+    parse_go_duration(value)
+}
+
+/// `"1m30.5s"`, `"250ms"`, `"2h"`: number-unit pairs, summed.
+fn parse_go_duration(value: &str) -> Option<Duration> {
+    let mut total = 0.0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let num_end = rest.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+        let n: f64 = rest[..num_end].parse().ok()?;
+        let unit_end = rest[num_end..]
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .map_or(rest.len(), |i| num_end + i);
+        total += n * match &rest[num_end..unit_end] {
+            "h" => 3600.0,
+            "m" => 60.0,
+            "s" => 1.0,
+            "ms" => 0.001,
+            _ => return None,
+        };
+        rest = &rest[unit_end..];
+    }
+    Some(Duration::from_secs_f64(total))
+}
+
+/// IMF-fixdate, the only HTTP date format servers may send:
+/// `"Sun, 06 Nov 1994 08:49:37 GMT"`.
+fn parse_http_date(value: &str) -> Option<SystemTime> {
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    let [_, day, month, year, time, "GMT"] = parts.as_slice() else {
+        return None;
+    };
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+    let month = MONTHS.iter().position(|m| m == month)? as i64 + 1;
+    let day: i64 = day.parse().ok()?;
+    let year: i64 = year.parse().ok()?;
+    let hms: Vec<i64> = time.split(':').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    let [h, m, s] = hms.as_slice() else {
+        return None;
+    };
+    // Days since 1970-01-01 for a proleptic Gregorian date.
+    let (y, mo) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * mo + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    let secs = days * 86_400 + h * 3600 + m * 60 + s;
+    Some(UNIX_EPOCH + Duration::from_secs(u64::try_from(secs).ok()?))
+}
+
+fn until(at: SystemTime, now: SystemTime) -> Duration {
+    at.duration_since(now).unwrap_or_default()
+}
+
+impl std::fmt::Display for SumiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for SumiError {}
+
+impl From<String> for SumiError {
+    fn from(message: String) -> Self {
+        match message.as_str() {
+            "no_speech" => Self::NoSpeech(message),
+            "cancelled" => Self::Cancelled(message),
+            _ => Self::Other(message),
+        }
+    }
+}
+
+impl From<&str> for SumiError {
+    fn from(message: &str) -> Self {
+        Self::from(message.to_string())
+    }
+}
+
+impl From<SumiError> for String {
+    fn from(err: SumiError) -> Self {
+        err.message().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    #[test]
+    fn serializes_as_kind_and_message() {
+        let limited = |retry_after_secs| SumiError::RateLimited { message: "slow down".into(), retry_after_secs };
+        let json = serde_json::to_value(limited(None)).unwrap();
+        assert_eq!(json, serde_json::json!({ "kind": "rate_limited", "message": "slow down" }));
+        let json = serde_json::to_value(limited(Some(12))).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "kind": "rate_limited", "message": "slow down", "retry_after_secs": 12 })
+        );
+        let json = serde_json::to_value(SumiError::MissingApiKey("no key".into())).unwrap();
+        assert_eq!(json, serde_json::json!({ "kind": "missing_api_key", "message": "no key" }));
+    }
+
+    #[test]
+    fn http_status_maps_to_kind() {
+        let kind = |code| SumiError::from_status("Cloud STT", StatusCode::from_u16(code).unwrap(), &HeaderMap::new(), "");
+        assert!(matches!(kind(401), SumiError::Auth(_)));
+        assert!(matches!(kind(403), SumiError::Auth(_)));
+        assert!(matches!(kind(404), SumiError::ModelNotFound(_)));
+        assert!(matches!(kind(429), SumiError::RateLimited { retry_after_secs: None, .. }));
+        assert!(matches!(kind(503), SumiError::Provider(_)));
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        pairs.iter().map(|(k, v)| (reqwest::header::HeaderName::from_static(k), v.parse().unwrap())).collect()
+    }
+
+    #[test]
+    fn retry_after_reads_seconds_dates_and_reset_headers() {
+        let now = UNIX_EPOCH + Duration::from_secs(784_111_770); // Sun, 06 Nov 1994 08:49:30 GMT
+        let secs = |pairs: &[(&'static str, &str)]| retry_after_secs(&headers(pairs), now);
+        assert_eq!(secs(&[("retry-after", "12")]), Some(12));
+        assert_eq!(secs(&[("retry-after", "Sun, 06 Nov 1994 08:49:37 GMT")]), Some(7));
+        assert_eq!(secs(&[("retry-after-ms", "1500"), ("retry-after", "9")]), Some(2));
+        assert_eq!(secs(&[("x-ratelimit-reset-requests", "1s"), ("x-ratelimit-reset-tokens", "1m30.5s")]), Some(91));
+        let later = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!(retry_after_secs(&headers(&[("x-ratelimit-reset", "1700000030")]), later), Some(30));
+        assert_eq!(secs(&[("x-ratelimit-reset-tokens", "250ms")]), Some(1));
+        assert_eq!(secs(&[("retry-after", "soon")]), None);
+        assert_eq!(secs(&[]), None);
+    }
+
+    #[test]
+    fn rate_limit_message_includes_retry_hint() {
+        let err = SumiError::from_status(
+            "Cloud STT",
+            StatusCode::TOO_MANY_REQUESTS,
+            &headers(&[("retry-after", "20")]),
+            "busy",
+        );
+        assert_eq!(err.message(), "Cloud STT returned HTTP 429 Too Many Requests (retry in 20s): busy");
+        assert!(matches!(err, SumiError::RateLimited { retry_after_secs: Some(20), .. }));
+    }
+
+    #[test]
+    fn no_speech_round_trips_through_string() {
+        let s: String = SumiError::no_speech().into();
+        assert_eq!(s, "no_speech");
+        assert_eq!(SumiError::from(s), SumiError::no_speech());
+    }
+}
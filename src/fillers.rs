@@ -0,0 +1,218 @@
+//! Deterministic filler-word removal for unpolished transcripts.
+//!
+//! When AI polishing is off, hesitations like "um" / "uh" / "嗯" / "呃" end up
+//! verbatim in the pasted text.  This module strips them without an LLM:
+//!
+//! - Fillers in space-separated scripts (Latin, Hangul, …) are matched as whole
+//!   tokens, ignoring surrounding punctuation, so "um" never matches "umbrella".
+//! - Fillers written in Han/Kana have no word boundaries to lean on and are
+//!   removed wherever they occur, together with the pause punctuation that
+//!   follows them.  Only list characters that never appear inside real words.
+
+/// Default filler list for an STT language code ("zh-TW", "en", "auto", …).
+pub fn default_filler_words(language: &str) -> Vec<String> {
+    let base = language.split(['-', '_']).next().unwrap_or("").to_ascii_lowercase();
+    let words: &[&str] = match base.as_str() {
+        "zh" => &["嗯", "呃", "um", "uh"],
+        "ja" => &["えーと", "えっと", "えー", "あのー"],
+        "ko" => &["음", "어", "um", "uh"],
+        "es" => &["eh", "em", "mmm"],
+        "fr" => &["euh", "heu", "hum"],
+        "de" => &["äh", "ähm", "öhm", "hm"],
+        "en" => &["um", "umm", "uh", "uhh", "uhm", "er", "erm", "hmm"],
+        // Auto-detect / other languages: English hesitations plus the
+        // unambiguous Mandarin ones, which are common in mixed dictation.
+        _ => &["um", "umm", "uh", "uhh", "uhm", "erm", "hmm", "嗯", "呃"],
+    };
+    words.iter().map(|w| w.to_string()).collect()
+}
+
+/// Remove standalone `fillers` from `text`, tidying the whitespace and
+/// punctuation left behind.  Matching is case-insensitive.
+pub fn remove_fillers(text: &str, fillers: &[String]) -> String {
+    let (unspaced, spaced): (Vec<&str>, Vec<&str>) = fillers
+        .iter()
+        .map(|f| f.trim())
+        .filter(|f| !f.is_empty())
+        .partition(|f| f.chars().all(is_unspaced_char));
+    if unspaced.is_empty() && spaced.is_empty() {
+        return text.to_string();
+    }
+
+    let text = if unspaced.is_empty() {
+        text.to_string()
+    } else {
+        remove_unspaced(text, &unspaced)
+    };
+    if spaced.is_empty() {
+        return text;
+    }
+    let spaced: Vec<String> = spaced.iter().map(|f| f.to_lowercase()).collect();
+    text.lines()
+        .map(|line| remove_spaced_in_line(line, &spaced))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Han, Hiragana and Katakana (incl. the prolonged sound mark "ー").
+fn is_unspaced_char(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FF66}'..='\u{FF9F}')
+}
+
+/// Punctuation that marks a pause and is dropped along with a filler.
+fn is_pause_punct(c: char) -> bool {
+    matches!(c, ',' | '，' | '、' | ';' | '；')
+}
+
+fn is_sentence_end(c: char) -> bool {
+    matches!(c, '.' | '!' | '?' | '。' | '！' | '？' | '…')
+}
+
+fn remove_unspaced(text: &str, fillers: &[&str]) -> String {
+    let mut fillers = fillers.to_vec();
+    // Longest first so "えーと" wins over "えー".
+    fillers.sort_by_key(|f| std::cmp::Reverse(f.len()));
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if let Some(filler) = fillers.iter().find(|f| rest.starts_with(**f)) {
+            rest = rest[filler.len()..].trim_start_matches(|c: char| is_pause_punct(c) || c == ' ');
+            // "好的，嗯。" → "好的。": a pause mark left dangling before the end
+            // of a sentence (or the text) goes too.
+            if rest.is_empty() || rest.starts_with(is_sentence_end) {
+                let trimmed = out.trim_end_matches(|c: char| is_pause_punct(c) || c == ' ').len();
+                out.truncate(trimmed);
+            }
+            continue;
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out.trim().to_string()
+}
+
+fn remove_spaced_in_line(line: &str, fillers: &[String]) -> String {
+    let mut kept: Vec<String> = Vec::new();
+    let mut removed_any = false;
+    let mut capitalize_next = false;
+
+    for token in line.split_whitespace() {
+        let core = token.trim_matches(|c: char| !c.is_alphanumeric());
+        if core.is_empty() || !fillers.iter().any(|f| *f == core.to_lowercase()) {
+            let mut token = token.to_string();
+            if capitalize_next {
+                token = capitalize_first(&token);
+                capitalize_next = false;
+            }
+            kept.push(token);
+            continue;
+        }
+
+        removed_any = true;
+        let at_sentence_start = kept
+            .last()
+            .is_none_or(|prev| prev.ends_with(is_sentence_end));
+        if at_sentence_start && core.starts_with(char::is_uppercase) {
+            capitalize_next = true;
+        }
+        // Keep sentence-ending punctuation attached to the filler ("go, um.").
+        let trailing = &token[token.trim_end_matches(|c: char| !c.is_alphanumeric()).len()..];
+        let end: String = trailing.chars().filter(|c| is_sentence_end(*c)).collect();
+        if let Some(prev) = kept.last_mut() {
+            if !end.is_empty() && !prev.ends_with(is_sentence_end) {
+                let trimmed = prev.trim_end_matches(is_pause_punct).len();
+                prev.truncate(trimmed);
+                prev.push_str(&end);
+            }
+        }
+    }
+
+    if removed_any {
+        kept.join(" ")
+    } else {
+        line.to_string()
+    }
+}
+
+fn capitalize_first(token: &str) -> String {
+    let mut chars = token.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strip(text: &str, language: &str) -> String {
+        remove_fillers(text, &default_filler_words(language))
+    }
+
+    // ── English ──
+
+    #[test]
+    fn removes_english_fillers_and_commas() {
+        assert_eq!(strip("So, uh, we should ship it.", "en"), "So, we should ship it.");
+        assert_eq!(strip("I think um we are done", "en"), "I think we are done");
+    }
+
+    #[test]
+    fn sentence_initial_filler_capitalizes_next_word() {
+        assert_eq!(strip("Um, the build is green.", "en"), "The build is green.");
+    }
+
+    #[test]
+    fn trailing_filler_keeps_sentence_end() {
+        assert_eq!(strip("We can go, um.", "en"), "We can go.");
+    }
+
+    #[test]
+    fn does_not_touch_words_containing_fillers() {
+        let text = "Bring an umbrella, the summer rain is heavy.";
+        assert_eq!(strip(text, "en"), text);
+    }
+
+    #[test]
+    fn is_case_insensitive() {
+        assert_eq!(strip("UH okay UMM fine", "en"), "Okay fine");
+    }
+
+    // ── Mandarin ──
+
+    #[test]
+    fn removes_mandarin_fillers() {
+        assert_eq!(strip("嗯，我觉得呃这个方案可以", "zh-TW"), "我觉得这个方案可以");
+        assert_eq!(strip("我觉得，嗯，这个不错", "zh-CN"), "我觉得，这个不错");
+    }
+
+    #[test]
+    fn mandarin_filler_before_sentence_end_drops_dangling_comma() {
+        assert_eq!(strip("好的，嗯。", "zh-TW"), "好的。");
+        assert_eq!(strip("嗯嗯，可以", "zh-TW"), "可以");
+    }
+
+    #[test]
+    fn mixed_language_dictation() {
+        assert_eq!(strip("嗯，uh 這個 API 呃要改", "auto"), "這個 API 要改");
+    }
+
+    // ── Japanese ──
+
+    #[test]
+    fn longest_japanese_filler_wins() {
+        assert_eq!(strip("えーと、明日行きます", "ja"), "明日行きます");
+    }
+
+    #[test]
+    fn empty_list_is_noop() {
+        assert_eq!(remove_fillers("um hello", &[]), "um hello");
+    }
+}
@@ -3,6 +3,9 @@ use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use unicode_segmentation::UnicodeSegmentation;
 
+pub use crate::context_detect::AppContext;
+pub use crate::stt::WordTiming;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryEntry {
     pub id: String,
@@ -27,6 +30,106 @@ pub struct HistoryEntry {
     pub chars_per_sec: f64,
     #[serde(default)]
     pub word_count: u64,
+    /// Overall STT confidence in `[0, 1]`; only set by cloud providers in verbose mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    /// Encoding of the stored audio file; entries written before this field
+    /// existed are WAV.
+    #[serde(default)]
+    pub audio_format: AudioFormat,
+    /// Spoken language, recorded when the output was translated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_language: Option<String>,
+    /// Language the output was translated into, if translate mode was on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_language: Option<String>,
+    /// User-assigned labels, normalized by [`normalize_tags`].
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// When each word of `raw_text` was spoken in the stored audio; only
+    /// recorded for STT providers that return word timestamps (Deepgram).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub word_timings: Option<Vec<WordTiming>>,
+    /// Full app context detected at recording time (URL and terminal host
+    /// included), so a re-polish matches the same prompt rule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_context: Option<AppContext>,
+    /// Why polish fell back to the raw text, if it did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback_reason: Option<String>,
+}
+
+/// How dictation audio is retained alongside history entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioFormat {
+    /// 16 kHz mono PCM, 16-bit by default (~1.9 MB per minute); see [`WavBitDepth`].
+    #[default]
+    Wav,
+    /// 32 kbps mono MP3 via LAME (~240 KB per minute).
+    Mp3,
+    /// 24 kbps Opus in an Ogg container (~180 KB per minute).
+    Opus,
+    /// Do not keep audio at all.
+    None,
+}
+
+/// Sample encoding for history audio kept as WAV.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum WavBitDepth {
+    /// 16-bit integer PCM.
+    #[default]
+    #[serde(rename = "16")]
+    Int16,
+    /// 24-bit integer PCM.
+    #[serde(rename = "24")]
+    Int24,
+    /// 32-bit IEEE float, the capture pipeline's own precision.
+    #[serde(rename = "32f")]
+    Float32,
+}
+
+impl WavBitDepth {
+    fn spec(self) -> hound::WavSpec {
+        let (bits_per_sample, sample_format) = match self {
+            WavBitDepth::Int16 => (16, hound::SampleFormat::Int),
+            WavBitDepth::Int24 => (24, hound::SampleFormat::Int),
+            WavBitDepth::Float32 => (32, hound::SampleFormat::Float),
+        };
+        hound::WavSpec { channels: 1, sample_rate: 16000, bits_per_sample, sample_format }
+    }
+}
+
+impl AudioFormat {
+    /// Formats that may exist on disk, in probe order.
+    const STORED: [AudioFormat; 3] = [AudioFormat::Wav, AudioFormat::Mp3, AudioFormat::Opus];
+
+    pub fn extension(&self) -> Option<&'static str> {
+        match self {
+            AudioFormat::Wav => Some("wav"),
+            AudioFormat::Mp3 => Some("mp3"),
+            AudioFormat::Opus => Some("opus"),
+            AudioFormat::None => None,
+        }
+    }
+
+    fn as_key(&self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "wav",
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Opus => "opus",
+            AudioFormat::None => "none",
+        }
+    }
+
+    fn from_key(key: &str) -> Self {
+        match key {
+            "mp3" => AudioFormat::Mp3,
+            "opus" => AudioFormat::Opus,
+            "none" => AudioFormat::None,
+            _ => AudioFormat::Wav,
+        }
+    }
 }
 
 /// Count "words" using UAX#29 word boundaries.
@@ -39,9 +142,27 @@ fn db_path(history_dir: &Path) -> PathBuf {
     history_dir.join("history.db")
 }
 
-fn audio_path(audio_dir: &Path, id: &str) -> PathBuf {
+fn audio_path(audio_dir: &Path, id: &str, ext: &str) -> PathBuf {
     // Caller must validate id before calling; this is a low-level helper.
-    audio_dir.join(format!("{}.wav", id))
+    audio_dir.join(format!("{}.{}", id, ext))
+}
+
+/// The stored audio file for `id`, whichever format it was written in.
+fn find_audio(audio_dir: &Path, id: &str) -> Option<PathBuf> {
+    AudioFormat::STORED
+        .iter()
+        .filter_map(|f| f.extension())
+        .map(|ext| audio_path(audio_dir, id, ext))
+        .find(|p| p.exists())
+}
+
+fn remove_audio(audio_dir: &Path, id: &str) {
+    for ext in AudioFormat::STORED.iter().filter_map(|f| f.extension()) {
+        let path = audio_path(audio_dir, id, ext);
+        if path.exists() {
+            let _ = std::fs::remove_file(&path);
+        }
+    }
 }
 
 /// Validate that a history ID contains only safe characters (digits and underscores).
@@ -123,6 +244,81 @@ fn open_db_and_migrate(history_dir: &Path) -> Result<Connection, rusqlite::Error
     if !has_wc {
         conn.execute_batch("ALTER TABLE history ADD COLUMN word_count INTEGER NOT NULL DEFAULT 0;")?;
     }
+    // Migrate: add confidence column if missing (non-destructive)
+    let has_conf: bool = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info('history') WHERE name = 'confidence'",
+        [],
+        |row| row.get::<_, i64>(0),
+    )? > 0;
+    if !has_conf {
+        conn.execute_batch("ALTER TABLE history ADD COLUMN confidence REAL;")?;
+    }
+    // Migrate: add audio_format column if missing (non-destructive)
+    let has_fmt: bool = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info('history') WHERE name = 'audio_format'",
+        [],
+        |row| row.get::<_, i64>(0),
+    )? > 0;
+    if !has_fmt {
+        conn.execute_batch("ALTER TABLE history ADD COLUMN audio_format TEXT NOT NULL DEFAULT 'wav';")?;
+    }
+    // Migrate: add translation language columns if missing (non-destructive)
+    let has_lang: bool = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info('history') WHERE name = 'target_language'",
+        [],
+        |row| row.get::<_, i64>(0),
+    )? > 0;
+    if !has_lang {
+        conn.execute_batch(
+            "ALTER TABLE history ADD COLUMN source_language TEXT;
+             ALTER TABLE history ADD COLUMN target_language TEXT;",
+        )?;
+    }
+    // Migrate: add tags column (JSON array) if missing (non-destructive)
+    let has_tags: bool = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info('history') WHERE name = 'tags'",
+        [],
+        |row| row.get::<_, i64>(0),
+    )? > 0;
+    if !has_tags {
+        conn.execute_batch("ALTER TABLE history ADD COLUMN tags TEXT NOT NULL DEFAULT '[]';")?;
+    }
+    // Migrate: add word_timings column (JSON array) if missing (non-destructive)
+    let has_timings: bool = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info('history') WHERE name = 'word_timings'",
+        [],
+        |row| row.get::<_, i64>(0),
+    )? > 0;
+    if !has_timings {
+        conn.execute_batch("ALTER TABLE history ADD COLUMN word_timings TEXT;")?;
+    }
+    // Migrate: add app_context column (JSON object) if missing (non-destructive)
+    let has_context: bool = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info('history') WHERE name = 'app_context'",
+        [],
+        |row| row.get::<_, i64>(0),
+    )? > 0;
+    if !has_context {
+        conn.execute_batch("ALTER TABLE history ADD COLUMN app_context TEXT;")?;
+    }
+    // Migrate: add fallback_reason column if missing (non-destructive)
+    let has_fallback: bool = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info('history') WHERE name = 'fallback_reason'",
+        [],
+        |row| row.get::<_, i64>(0),
+    )? > 0;
+    if !has_fallback {
+        conn.execute_batch("ALTER TABLE history ADD COLUMN fallback_reason TEXT;")?;
+    }
+    // Soft-deleted entries, kept as JSON so later `history` migrations don't
+    // need a matching one here.
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS trash (
+            id         TEXT PRIMARY KEY,
+            deleted_at INTEGER NOT NULL,
+            entry      TEXT NOT NULL
+        );",
+    )?;
     // Backfill word_count for existing rows that have 0
     {
         let mut stmt = conn.prepare("SELECT id, raw_text FROM history WHERE word_count = 0")?;
@@ -161,6 +357,157 @@ pub fn migrate_from_json(history_dir: &Path, audio_dir: &Path) {
     }
 }
 
+// ── Integrity check & repair ────────────────────────────────────────────────
+
+/// Result of `PRAGMA integrity_check` on `history.db`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DbHealth {
+    pub ok: bool,
+    /// Problems reported by SQLite (empty when healthy).
+    pub problems: Vec<String>,
+}
+
+/// Outcome of [`repair_db`].
+#[derive(Debug, Clone, Serialize)]
+pub struct DbRepair {
+    /// Rows copied into the fresh database, across all tables.
+    pub recovered_rows: u64,
+    /// Where the damaged database was moved to.
+    pub quarantined_path: String,
+}
+
+/// Run `PRAGMA integrity_check`.  A database that cannot even be opened or
+/// queried counts as unhealthy rather than an error.
+pub fn verify_db(history_dir: &Path) -> DbHealth {
+    if !db_path(history_dir).exists() {
+        return DbHealth { ok: true, problems: Vec::new() };
+    }
+    let problems: Result<Vec<String>, rusqlite::Error> = (|| {
+        let conn = open_db(history_dir)?;
+        let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        rows.collect()
+    })();
+    match problems {
+        Ok(lines) if lines.len() == 1 && lines[0] == "ok" => DbHealth { ok: true, problems: Vec::new() },
+        Ok(lines) => DbHealth { ok: false, problems: lines },
+        Err(e) => DbHealth { ok: false, problems: vec![e.to_string()] },
+    }
+}
+
+/// Salvage whatever rows are still readable into a fresh `history.db`.
+///
+/// Each table is scanned by rowid from both ends, so a damaged page in the
+/// middle only costs the rows on it.  The damaged file (and its WAL/SHM
+/// siblings) is kept next to the new one as `history.db.corrupt-<secs>`.
+pub fn repair_db(history_dir: &Path) -> Result<DbRepair, String> {
+    let path = db_path(history_dir);
+    if !path.exists() {
+        return Err("History database does not exist".to_string());
+    }
+    let fresh_path = history_dir.join("history.db.recovering");
+    let _ = std::fs::remove_file(&fresh_path);
+
+    let recovered_rows = {
+        let old = Connection::open(&path).map_err(|e| format!("Failed to open history DB: {}", e))?;
+        let schema: Vec<(String, String, String)> = {
+            let mut stmt = old
+                .prepare(
+                    "SELECT type, name, sql FROM sqlite_master
+                     WHERE sql IS NOT NULL AND name NOT LIKE 'sqlite_%'
+                     ORDER BY CASE type WHEN 'table' THEN 0 ELSE 1 END",
+                )
+                .map_err(|e| format!("Schema is unreadable: {}", e))?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+                .map_err(|e| format!("Schema is unreadable: {}", e))?;
+            rows.filter_map(|r| r.ok()).collect()
+        };
+
+        {
+            let fresh = Connection::open(&fresh_path)
+                .map_err(|e| format!("Failed to create recovery DB: {}", e))?;
+            for (_, name, sql) in &schema {
+                if let Err(e) = fresh.execute_batch(sql) {
+                    tracing::warn!("History repair: could not recreate {}: {}", name, e);
+                }
+            }
+        }
+        old.execute("ATTACH DATABASE ?1 AS fresh", params![fresh_path.to_string_lossy()])
+            .map_err(|e| format!("Failed to attach recovery DB: {}", e))?;
+        let mut recovered = 0u64;
+        let _ = old.execute_batch("BEGIN;");
+        for (_, name, _) in schema.iter().filter(|(kind, _, _)| kind == "table") {
+            recovered += salvage_table(&old, name);
+        }
+        old.execute_batch("COMMIT;").map_err(|e| format!("Failed to write recovered rows: {}", e))?;
+        let _ = old.execute_batch("DETACH DATABASE fresh;");
+        recovered
+    };
+
+    let ts = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let quarantined = history_dir.join(format!("history.db.corrupt-{}", ts));
+    std::fs::rename(&path, &quarantined).map_err(|e| format!("Failed to quarantine history DB: {}", e))?;
+    for suffix in ["-wal", "-shm"] {
+        let side = history_dir.join(format!("history.db{}", suffix));
+        if side.exists() {
+            let _ = std::fs::rename(&side, history_dir.join(format!("history.db.corrupt-{}{}", ts, suffix)));
+        }
+    }
+    std::fs::rename(&fresh_path, &path).map_err(|e| format!("Failed to install recovered DB: {}", e))?;
+    // Bring the recovered schema up to date (also recreates `history` if it was lost).
+    init_db(history_dir);
+
+    tracing::warn!(
+        "History DB repaired: {} rows recovered, damaged copy at {:?}",
+        recovered_rows,
+        quarantined
+    );
+    Ok(DbRepair {
+        recovered_rows,
+        quarantined_path: quarantined.to_string_lossy().to_string(),
+    })
+}
+
+/// Copy every readable row of `table` from `main` into `fresh`, one row at a
+/// time.  Returns the number of rows copied.
+fn salvage_table(conn: &Connection, table: &str) -> u64 {
+    let quoted = format!("\"{}\"", table.replace('"', "\"\""));
+    let mut rowids: Vec<i64> = Vec::new();
+    for order in ["ASC", "DESC"] {
+        let Ok(mut stmt) = conn.prepare(&format!("SELECT rowid FROM main.{} ORDER BY rowid {}", quoted, order)) else {
+            continue;
+        };
+        let Ok(mut rows) = stmt.query([]) else { continue };
+        // Stop at the first unreadable page; the other direction picks up the rest.
+        while let Ok(Some(row)) = rows.next() {
+            match row.get::<_, i64>(0) {
+                Ok(id) => rowids.push(id),
+                Err(_) => break,
+            }
+        }
+    }
+    rowids.sort_unstable();
+    rowids.dedup();
+
+    let sql = format!("INSERT OR IGNORE INTO fresh.{0} SELECT * FROM main.{0} WHERE rowid = ?1", quoted);
+    let Ok(mut insert) = conn.prepare(&sql) else {
+        tracing::warn!("History repair: cannot copy table {}", table);
+        return 0;
+    };
+    let copied = rowids
+        .iter()
+        .filter(|id| matches!(insert.execute(params![id]), Ok(n) if n > 0))
+        .count() as u64;
+    if copied < rowids.len() as u64 {
+        tracing::warn!("History repair: {} of {} rows in {} were unreadable", rowids.len() as u64 - copied, rowids.len(), table);
+    }
+    copied
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryStats {
     pub total_entries: u64,
@@ -211,6 +558,83 @@ pub fn get_stats(history_dir: &Path) -> HistoryStats {
     .unwrap_or(zero)
 }
 
+/// Entry count and audio time for one STT or polish model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelUsage {
+    pub model: String,
+    pub entries: u64,
+    pub duration_secs: f64,
+}
+
+/// Usage summary over a time range, for the "your stats" panel.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageStats {
+    pub total_entries: u64,
+    pub total_duration_secs: f64,
+    /// Characters of the delivered `text`, like the word counts.
+    pub total_chars: u64,
+    pub avg_total_elapsed_ms: f64,
+    /// Most-used first.
+    pub stt_models: Vec<ModelUsage>,
+    /// Most-used first; unpolished entries are listed as `"None"`.
+    pub polish_models: Vec<ModelUsage>,
+}
+
+/// Aggregate usage over entries with `from_ms <= timestamp < to_ms`; either
+/// bound may be omitted.
+pub fn get_usage_stats(history_dir: &Path, from_ms: Option<i64>, to_ms: Option<i64>) -> Result<UsageStats, String> {
+    let conn = open_db(history_dir).map_err(|e| e.to_string())?;
+    let from = from_ms.unwrap_or(i64::MIN);
+    let to = to_ms.unwrap_or(i64::MAX);
+
+    let (total_entries, total_duration_secs, total_chars, avg_total_elapsed_ms) = conn
+        .query_row(
+            "SELECT COUNT(*), COALESCE(SUM(duration_secs), 0), COALESCE(SUM(LENGTH(text)), 0),
+                    COALESCE(AVG(total_elapsed_ms), 0)
+             FROM history WHERE timestamp >= ?1 AND timestamp < ?2",
+            params![from, to],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)? as u64,
+                    row.get::<_, f64>(1)?,
+                    row.get::<_, i64>(2)? as u64,
+                    row.get::<_, f64>(3)?,
+                ))
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    let by_model = |column: &str| -> Result<Vec<ModelUsage>, String> {
+        // `column` is one of two fixed names, never user input.
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {column}, COUNT(*), COALESCE(SUM(duration_secs), 0)
+                 FROM history WHERE timestamp >= ?1 AND timestamp < ?2
+                 GROUP BY {column} ORDER BY COUNT(*) DESC, {column}"
+            ))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![from, to], |row| {
+                Ok(ModelUsage {
+                    model: row.get(0)?,
+                    entries: row.get::<_, i64>(1)? as u64,
+                    duration_secs: row.get(2)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+    };
+
+    Ok(UsageStats {
+        total_entries,
+        total_duration_secs,
+        total_chars,
+        avg_total_elapsed_ms,
+        stt_models: by_model("stt_model")?,
+        polish_models: by_model("polish_model")?,
+    })
+}
+
 /// Shared row mapper for HistoryEntry — used by all query functions.
 fn map_row(row: &rusqlite::Row) -> Result<HistoryEntry, rusqlite::Error> {
     Ok(HistoryEntry {
@@ -230,9 +654,33 @@ fn map_row(row: &rusqlite::Row) -> Result<HistoryEntry, rusqlite::Error> {
         bundle_id: row.get::<_, String>(13).unwrap_or_default(),
         chars_per_sec: row.get::<_, f64>(14).unwrap_or(0.0),
         word_count: row.get::<_, i64>(15).unwrap_or(0) as u64,
+        confidence: row.get::<_, Option<f64>>(16).ok().flatten(),
+        audio_format: AudioFormat::from_key(&row.get::<_, String>(17).unwrap_or_default()),
+        source_language: row.get::<_, Option<String>>(18).ok().flatten(),
+        target_language: row.get::<_, Option<String>>(19).ok().flatten(),
+        tags: row
+            .get::<_, String>(20)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        word_timings: row
+            .get::<_, Option<String>>(21)
+            .ok()
+            .flatten()
+            .and_then(|json| serde_json::from_str(&json).ok()),
+        app_context: row
+            .get::<_, Option<String>>(22)
+            .ok()
+            .flatten()
+            .and_then(|json| serde_json::from_str(&json).ok()),
+        fallback_reason: row.get::<_, Option<String>>(23).ok().flatten(),
     })
 }
 
+fn tags_json(tags: &[String]) -> String {
+    serde_json::to_string(tags).unwrap_or_else(|_| "[]".to_string())
+}
+
 pub fn load_history_page(
     history_dir: &Path,
     before_timestamp: Option<i64>,
@@ -250,7 +698,8 @@ pub fn load_history_page(
         let mut stmt = match conn.prepare(
             "SELECT id, timestamp, text, raw_text, reasoning, stt_model, polish_model,
                     duration_secs, has_audio, stt_elapsed_ms, polish_elapsed_ms, total_elapsed_ms,
-                    app_name, bundle_id, chars_per_sec, word_count
+                    app_name, bundle_id, chars_per_sec, word_count, confidence, audio_format,
+                    source_language, target_language, tags, word_timings, app_context, fallback_reason
              FROM history WHERE timestamp < ?1 ORDER BY timestamp DESC LIMIT ?2",
         ) {
             Ok(s) => s,
@@ -271,7 +720,8 @@ pub fn load_history_page(
         let mut stmt = match conn.prepare(
             "SELECT id, timestamp, text, raw_text, reasoning, stt_model, polish_model,
                     duration_secs, has_audio, stt_elapsed_ms, polish_elapsed_ms, total_elapsed_ms,
-                    app_name, bundle_id, chars_per_sec, word_count
+                    app_name, bundle_id, chars_per_sec, word_count, confidence, audio_format,
+                    source_language, target_language, tags, word_timings, app_context, fallback_reason
              FROM history ORDER BY timestamp DESC LIMIT ?1",
         ) {
             Ok(s) => s,
@@ -307,7 +757,8 @@ pub fn load_history(history_dir: &Path) -> Vec<HistoryEntry> {
     let mut stmt = match conn.prepare(
         "SELECT id, timestamp, text, raw_text, reasoning, stt_model, polish_model,
                 duration_secs, has_audio, stt_elapsed_ms, polish_elapsed_ms, total_elapsed_ms,
-                app_name, bundle_id, chars_per_sec, word_count
+                app_name, bundle_id, chars_per_sec, word_count, confidence, audio_format,
+                source_language, target_language, tags, word_timings, app_context, fallback_reason
          FROM history ORDER BY timestamp DESC LIMIT 200",
     ) {
         Ok(s) => s,
@@ -326,7 +777,200 @@ pub fn load_history(history_dir: &Path) -> Vec<HistoryEntry> {
     }
 }
 
-pub fn add_entry(history_dir: &Path, audio_dir: &Path, entry: HistoryEntry, retention_days: u32) {
+/// Text of the most recent history entry, if any.
+pub fn latest_text(history_dir: &Path) -> Option<String> {
+    load_history_page(history_dir, None, 1).0.into_iter().next().map(|e| e.text)
+}
+
+/// Texts of the newest `limit` entries recorded within `max_age`, newest first.
+pub fn recent_texts(history_dir: &Path, limit: u32, max_age: std::time::Duration) -> Vec<String> {
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64;
+    let cutoff = now_ms - max_age.as_millis() as i64;
+    load_history_page(history_dir, None, limit)
+        .0
+        .into_iter()
+        .filter(|e| e.timestamp >= cutoff)
+        .map(|e| e.text)
+        .collect()
+}
+
+pub fn get_entry(history_dir: &Path, id: &str) -> Option<HistoryEntry> {
+    validate_id(id).ok()?;
+    let conn = match open_db(history_dir) {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!("Failed to open history DB: {}", e);
+            return None;
+        }
+    };
+    conn.query_row(
+        "SELECT id, timestamp, text, raw_text, reasoning, stt_model, polish_model,
+                duration_secs, has_audio, stt_elapsed_ms, polish_elapsed_ms, total_elapsed_ms,
+                app_name, bundle_id, chars_per_sec, word_count, confidence, audio_format,
+                source_language, target_language, tags, word_timings, app_context, fallback_reason
+         FROM history WHERE id = ?1",
+        params![id],
+        map_row,
+    )
+    .ok()
+}
+
+/// Longest tag kept, in characters.
+const MAX_TAG_CHARS: usize = 32;
+
+/// Trim tags, drop empty ones, truncate overly long ones and remove
+/// case-insensitive duplicates (the first spelling wins).
+pub fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag: String = tag.trim().chars().take(MAX_TAG_CHARS).collect();
+        if tag.is_empty() || out.iter().any(|t| t.to_lowercase() == tag.to_lowercase()) {
+            continue;
+        }
+        out.push(tag);
+    }
+    out
+}
+
+/// Replace the tags of entry `id`.  Returns the normalized tags stored.
+pub fn set_entry_tags(history_dir: &Path, id: &str, tags: Vec<String>) -> Result<Vec<String>, String> {
+    validate_id(id)?;
+    let tags = normalize_tags(tags);
+    let conn = open_db(history_dir).map_err(|e| format!("Failed to open history DB: {}", e))?;
+    let updated = conn
+        .execute("UPDATE history SET tags = ?1 WHERE id = ?2", params![tags_json(&tags), id])
+        .map_err(|e| format!("Failed to update tags: {}", e))?;
+    if updated == 0 {
+        return Err("History entry not found".to_string());
+    }
+    Ok(tags)
+}
+
+/// Replace an entry's polished output after a re-polish.  `polish_elapsed_ms`
+/// is `None` when polish fell back to the raw text.  `translation` is the
+/// `(source, target)` language pair when the re-polish translated, and
+/// `None` clears the pair of an entry that was translated before.  A
+/// successful re-polish also clears any earlier `fallback_reason`.
+pub fn set_entry_polish(
+    history_dir: &Path,
+    id: &str,
+    text: &str,
+    reasoning: Option<&str>,
+    polish_model: &str,
+    polish_elapsed_ms: Option<u64>,
+    translation: Option<(&str, &str)>,
+) -> Result<(), String> {
+    validate_id(id)?;
+    let conn = open_db(history_dir).map_err(|e| format!("Failed to open history DB: {}", e))?;
+    let (source_language, target_language) = translation.unzip();
+    let updated = conn
+        .execute(
+            "UPDATE history SET text = ?1, reasoning = ?2, polish_model = ?3, polish_elapsed_ms = ?4,
+                 word_count = ?5, source_language = ?6, target_language = ?7, fallback_reason = NULL
+             WHERE id = ?8",
+            params![
+                text,
+                reasoning,
+                polish_model,
+                polish_elapsed_ms.map(|v| v as i64),
+                count_words(text) as i64,
+                source_language,
+                target_language,
+                id
+            ],
+        )
+        .map_err(|e| format!("Failed to update entry: {}", e))?;
+    if updated == 0 {
+        return Err("History entry not found".to_string());
+    }
+    Ok(())
+}
+
+/// Like [`load_history_page`], restricted to entries carrying `tag`
+/// (case-insensitive).
+pub fn load_history_by_tag(
+    history_dir: &Path,
+    tag: &str,
+    before_timestamp: Option<i64>,
+    limit: u32,
+) -> (Vec<HistoryEntry>, bool) {
+    let conn = match open_db(history_dir) {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!("Failed to open history DB: {}", e);
+            return (Vec::new(), false);
+        }
+    };
+    // SQLite's lower() only folds ASCII, so resolve the stored spellings
+    // that match case-insensitively here and filter on those exactly.
+    let wanted = tag.trim().to_lowercase();
+    let spellings: Vec<String> = match conn
+        .prepare("SELECT DISTINCT json_each.value FROM history, json_each(history.tags)")
+        .and_then(|mut s| {
+            let rows = s.query_map([], |row| row.get::<_, String>(0))?;
+            rows.collect::<Result<Vec<_>, _>>()
+        })
+    {
+        Ok(all) => all.into_iter().filter(|t| t.to_lowercase() == wanted).collect(),
+        Err(e) => {
+            tracing::error!("Failed to list history tags: {}", e);
+            return (Vec::new(), false);
+        }
+    };
+    if spellings.is_empty() {
+        return (Vec::new(), false);
+    }
+    let mut stmt = match conn.prepare(
+        "SELECT id, timestamp, text, raw_text, reasoning, stt_model, polish_model,
+                duration_secs, has_audio, stt_elapsed_ms, polish_elapsed_ms, total_elapsed_ms,
+                app_name, bundle_id, chars_per_sec, word_count, confidence, audio_format,
+                source_language, target_language, tags, word_timings, app_context, fallback_reason
+         FROM history
+         WHERE timestamp < ?1
+           AND EXISTS (SELECT 1 FROM json_each(history.tags)
+                       WHERE json_each.value IN (SELECT value FROM json_each(?2)))
+         ORDER BY timestamp DESC LIMIT ?3",
+    ) {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::error!("Failed to prepare tagged history query: {}", e);
+            return (Vec::new(), false);
+        }
+    };
+    let before = before_timestamp.unwrap_or(i64::MAX);
+    let mut entries: Vec<HistoryEntry> =
+        match stmt.query_map(params![before, tags_json(&spellings), limit as i64 + 1], map_row) {
+            Ok(iter) => iter.filter_map(|r| r.ok()).collect(),
+            Err(e) => {
+                tracing::error!("Failed to query tagged history: {}", e);
+                return (Vec::new(), false);
+            }
+        };
+    let has_more = entries.len() > limit as usize;
+    if has_more {
+        entries.truncate(limit as usize);
+    }
+    (entries, has_more)
+}
+
+/// Pruning applied after every insert.  Zero disables a limit.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HistoryLimits {
+    /// Delete entries (and their audio) older than this many days.
+    pub retention_days: u32,
+    /// Keep at most this many entries; the oldest are deleted with their audio.
+    pub max_entries: u32,
+    /// Keep at most this many bytes of stored audio.  The oldest recordings
+    /// are removed first; their text entries stay.
+    pub max_audio_bytes: u64,
+    /// Permanently delete trashed entries after this many days.
+    pub trash_retention_days: u32,
+}
+
+pub fn add_entry(history_dir: &Path, audio_dir: &Path, entry: HistoryEntry, limits: HistoryLimits) {
     let conn = match open_db(history_dir) {
         Ok(c) => c,
         Err(e) => {
@@ -334,14 +978,33 @@ pub fn add_entry(history_dir: &Path, audio_dir: &Path, entry: HistoryEntry, rete
             return;
         }
     };
+    if let Err(e) = insert_entry(&conn, &entry) {
+        tracing::error!("Failed to insert history entry: {}", e);
+    }
+    if limits.retention_days > 0 {
+        cleanup_expired(&conn, audio_dir, limits.retention_days);
+    }
+    if limits.max_entries > 0 {
+        evict_over_entry_cap(&conn, audio_dir, limits.max_entries);
+    }
+    if limits.max_audio_bytes > 0 {
+        evict_over_audio_cap(&conn, audio_dir, limits.max_audio_bytes);
+    }
+    if limits.trash_retention_days > 0 {
+        purge_expired_trash(&conn, audio_dir, limits.trash_retention_days);
+    }
+}
+
+fn insert_entry(conn: &Connection, entry: &HistoryEntry) -> Result<usize, rusqlite::Error> {
     let has_audio_int: i32 = if entry.has_audio { 1 } else { 0 };
     let polish_ms: Option<i64> = entry.polish_elapsed_ms.map(|v| v as i64);
-    if let Err(e) = conn.execute(
+    conn.execute(
         "INSERT OR REPLACE INTO history
             (id, timestamp, text, raw_text, reasoning, stt_model, polish_model,
              duration_secs, has_audio, stt_elapsed_ms, polish_elapsed_ms, total_elapsed_ms,
-             app_name, bundle_id, chars_per_sec, word_count)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+             app_name, bundle_id, chars_per_sec, word_count, confidence, audio_format,
+             source_language, target_language, tags, word_timings, app_context, fallback_reason)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24)",
         params![
             entry.id,
             entry.timestamp,
@@ -359,12 +1022,95 @@ pub fn add_entry(history_dir: &Path, audio_dir: &Path, entry: HistoryEntry, rete
             entry.bundle_id,
             entry.chars_per_sec,
             entry.word_count as i64,
+            entry.confidence,
+            entry.audio_format.as_key(),
+            entry.source_language,
+            entry.target_language,
+            tags_json(&entry.tags),
+            entry.word_timings.as_ref().and_then(|w| serde_json::to_string(w).ok()),
+            entry.app_context.as_ref().and_then(|c| serde_json::to_string(c).ok()),
+            entry.fallback_reason,
         ],
-    ) {
-        tracing::error!("Failed to insert history entry: {}", e);
+    )
+}
+
+/// Delete everything but the newest `max_entries` rows, audio included.
+fn evict_over_entry_cap(conn: &Connection, audio_dir: &Path, max_entries: u32) {
+    let ids: Vec<String> = {
+        let mut stmt = match conn.prepare(
+            "SELECT id FROM history ORDER BY timestamp DESC LIMIT -1 OFFSET ?1",
+        ) {
+            Ok(s) => s,
+            Err(_) => return,
+        };
+        stmt.query_map(params![max_entries], |row| row.get(0))
+            .ok()
+            .map(|iter| iter.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    };
+    if ids.is_empty() {
+        return;
     }
-    if retention_days > 0 {
-        cleanup_expired(&conn, audio_dir, retention_days);
+    for id in &ids {
+        remove_audio(audio_dir, id);
+        let _ = conn.execute("DELETE FROM history WHERE id = ?1", params![id]);
+    }
+    tracing::info!("History entry cap ({}): evicted {} oldest entries", max_entries, ids.len());
+}
+
+/// Walk recordings newest-first and drop the audio of every entry past the
+/// point where the running total exceeds `max_bytes`.  Trashed audio counts
+/// too, after all live recordings, so it goes first.
+fn evict_over_audio_cap(conn: &Connection, audio_dir: &Path, max_bytes: u64) {
+    let ids: Vec<String> = {
+        let mut stmt = match conn.prepare(
+            "SELECT id FROM history WHERE has_audio = 1 ORDER BY timestamp DESC",
+        ) {
+            Ok(s) => s,
+            Err(_) => return,
+        };
+        stmt.query_map([], |row| row.get(0))
+            .ok()
+            .map(|iter| iter.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    };
+    let mut total: u64 = 0;
+    let mut evicted = 0;
+    for id in &ids {
+        let size = find_audio(audio_dir, id)
+            .and_then(|p| std::fs::metadata(p).ok())
+            .map(|m| m.len())
+            .unwrap_or(0);
+        total = total.saturating_add(size);
+        if total > max_bytes {
+            remove_audio(audio_dir, id);
+            let _ = conn.execute("UPDATE history SET has_audio = 0 WHERE id = ?1", params![id]);
+            evicted += 1;
+        }
+    }
+    // A restored entry finds its audio gone and comes back without it.
+    let trashed: Vec<String> = match conn.prepare("SELECT id FROM trash ORDER BY deleted_at DESC") {
+        Ok(mut stmt) => stmt
+            .query_map([], |row| row.get(0))
+            .ok()
+            .map(|iter| iter.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
+    let trash_dir = trash_audio_dir(audio_dir);
+    for id in &trashed {
+        let size = find_audio(&trash_dir, id)
+            .and_then(|p| std::fs::metadata(p).ok())
+            .map(|m| m.len())
+            .unwrap_or(0);
+        total = total.saturating_add(size);
+        if size > 0 && total > max_bytes {
+            remove_audio(&trash_dir, id);
+            evicted += 1;
+        }
+    }
+    if evicted > 0 {
+        tracing::info!("History audio cap ({} bytes): removed audio of {} oldest entries", max_bytes, evicted);
     }
 }
 
@@ -375,7 +1121,7 @@ fn cleanup_expired(conn: &Connection, audio_dir: &Path, retention_days: u32) {
         .as_millis() as i64;
     let cutoff = now_millis - (retention_days as i64) * 86_400_000;
 
-    // Collect IDs of expired entries that have audio, so we can delete their WAV files.
+    // Collect IDs of expired entries that have audio, so we can delete their audio files.
     let ids: Vec<String> = {
         let mut stmt = match conn.prepare(
             "SELECT id FROM history WHERE timestamp < ?1 AND has_audio = 1",
@@ -389,76 +1135,382 @@ fn cleanup_expired(conn: &Connection, audio_dir: &Path, retention_days: u32) {
             .unwrap_or_default()
     };
     for id in &ids {
-        let wav = audio_path(audio_dir, id);
-        if wav.exists() {
-            let _ = std::fs::remove_file(&wav);
-        }
+        remove_audio(audio_dir, id);
     }
     let _ = conn.execute("DELETE FROM history WHERE timestamp < ?1", params![cutoff]);
 }
 
-pub fn delete_entry(history_dir: &Path, audio_dir: &Path, id: &str) {
-    if validate_id(id).is_err() { return; }
-    if let Ok(conn) = open_db(history_dir) {
-        let _ = conn.execute("DELETE FROM history WHERE id = ?1", params![id]);
+// ── Trash ───────────────────────────────────────────────────────────────────
+
+/// A soft-deleted entry, restorable until the trash is emptied or it expires.
+#[derive(Debug, Clone, Serialize)]
+pub struct TrashEntry {
+    pub deleted_at: i64,
+    #[serde(flatten)]
+    pub entry: HistoryEntry,
+}
+
+/// Trashed audio lives in a subdirectory so it never collides with live files.
+fn trash_audio_dir(audio_dir: &Path) -> PathBuf {
+    audio_dir.join("trash")
+}
+
+fn move_audio(from_dir: &Path, to_dir: &Path, id: &str) {
+    let Some(src) = find_audio(from_dir, id) else { return };
+    let Some(name) = src.file_name() else { return };
+    let _ = std::fs::create_dir_all(to_dir);
+    if let Err(e) = std::fs::rename(&src, to_dir.join(name)) {
+        tracing::warn!("Failed to move audio for {}: {}", id, e);
+    }
+}
+
+/// Move the rows in `ids` (and their audio) from `history` into `trash`.
+/// The audio follows only once the rows are committed.
+fn trash_ids(conn: &Connection, audio_dir: &Path, ids: &[String]) -> Result<usize, String> {
+    let now_millis = std::time::SystemTime::now()
+        .duration_since(std::time::SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64;
+    let mut moved = Vec::new();
+    conn.execute_batch("BEGIN;").map_err(|e| format!("Failed to start trash transaction: {}", e))?;
+    for id in ids {
+        let Ok(entry) = conn.query_row(
+            "SELECT id, timestamp, text, raw_text, reasoning, stt_model, polish_model,
+                    duration_secs, has_audio, stt_elapsed_ms, polish_elapsed_ms, total_elapsed_ms,
+                    app_name, bundle_id, chars_per_sec, word_count, confidence, audio_format,
+                    source_language, target_language, tags, word_timings, app_context, fallback_reason
+             FROM history WHERE id = ?1",
+            params![id],
+            map_row,
+        ) else {
+            continue;
+        };
+        let Ok(json) = serde_json::to_string(&entry) else { continue };
+        let inserted = conn.execute(
+            "INSERT OR REPLACE INTO trash (id, deleted_at, entry) VALUES (?1, ?2, ?3)",
+            params![id, now_millis, json],
+        );
+        if inserted.is_ok() && conn.execute("DELETE FROM history WHERE id = ?1", params![id]).is_ok() {
+            moved.push(id);
+        }
     }
-    let wav = audio_path(audio_dir, id);
-    if wav.exists() {
-        let _ = std::fs::remove_file(&wav);
+    if let Err(e) = conn.execute_batch("COMMIT;") {
+        let _ = conn.execute_batch("ROLLBACK;");
+        return Err(format!("Failed to move entries to the trash: {}", e));
     }
+    for id in &moved {
+        move_audio(audio_dir, &trash_audio_dir(audio_dir), id);
+    }
+    Ok(moved.len())
+}
+
+/// Move one entry to the trash.
+pub fn delete_entry(history_dir: &Path, audio_dir: &Path, id: &str) -> Result<(), String> {
+    validate_id(id)?;
+    let conn = open_db(history_dir).map_err(|e| format!("Failed to open history DB: {}", e))?;
+    trash_ids(&conn, audio_dir, &[id.to_string()]).map(|_| ())
+}
+
+/// Move every entry to the trash.
+pub fn clear_all(history_dir: &Path, audio_dir: &Path) -> Result<(), String> {
+    let conn = open_db(history_dir).map_err(|e| format!("Failed to open history DB: {}", e))?;
+    let ids: Vec<String> = {
+        let mut stmt = conn.prepare("SELECT id FROM history").map_err(|e| e.to_string())?;
+        stmt.query_map([], |row| row.get(0))
+            .ok()
+            .map(|iter| iter.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    };
+    let moved = trash_ids(&conn, audio_dir, &ids)?;
+    tracing::info!("History cleared: {} entries moved to trash", moved);
+    Ok(())
+}
+
+/// Trashed entries, most recently deleted first.
+pub fn load_trash(history_dir: &Path) -> Vec<TrashEntry> {
+    let Ok(conn) = open_db(history_dir) else { return Vec::new() };
+    let Ok(mut stmt) = conn.prepare("SELECT deleted_at, entry FROM trash ORDER BY deleted_at DESC") else {
+        return Vec::new();
+    };
+    stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+        .ok()
+        .map(|iter| {
+            iter.filter_map(|r| r.ok())
+                .filter_map(|(deleted_at, json)| {
+                    serde_json::from_str(&json).ok().map(|entry| TrashEntry { deleted_at, entry })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
-pub fn clear_all(history_dir: &Path, audio_dir: &Path) {
+/// Put a trashed entry (and its audio) back into history.
+pub fn restore_entry(history_dir: &Path, audio_dir: &Path, id: &str) -> Result<(), String> {
+    validate_id(id)?;
+    let conn = open_db(history_dir).map_err(|e| e.to_string())?;
+    let json: String = conn
+        .query_row("SELECT entry FROM trash WHERE id = ?1", params![id], |row| row.get(0))
+        .map_err(|_| "Entry is not in the trash".to_string())?;
+    let mut entry: HistoryEntry =
+        serde_json::from_str(&json).map_err(|e| format!("Trashed entry is unreadable: {}", e))?;
+    move_audio(&trash_audio_dir(audio_dir), audio_dir, id);
+    entry.has_audio = entry.has_audio && find_audio(audio_dir, id).is_some();
+    insert_entry(&conn, &entry).map_err(|e| format!("Failed to restore entry: {}", e))?;
+    conn.execute("DELETE FROM trash WHERE id = ?1", params![id]).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Permanently delete everything in the trash.
+pub fn empty_trash(history_dir: &Path, audio_dir: &Path) {
     if let Ok(conn) = open_db(history_dir) {
-        let _ = conn.execute("DELETE FROM history", []);
+        let _ = conn.execute("DELETE FROM trash", []);
     }
-    if audio_dir.exists() {
-        let _ = std::fs::remove_dir_all(audio_dir);
+    let dir = trash_audio_dir(audio_dir);
+    if dir.exists() {
+        let _ = std::fs::remove_dir_all(dir);
     }
 }
 
-pub fn save_audio_wav(audio_dir: &Path, id: &str, samples_16k: &[f32]) -> bool {
+fn purge_expired_trash(conn: &Connection, audio_dir: &Path, retention_days: u32) {
+    let now_millis = std::time::SystemTime::now()
+        .duration_since(std::time::SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64;
+    let cutoff = now_millis - (retention_days as i64) * 86_400_000;
+    let ids: Vec<String> = {
+        let Ok(mut stmt) = conn.prepare("SELECT id FROM trash WHERE deleted_at < ?1") else { return };
+        stmt.query_map(params![cutoff], |row| row.get(0))
+            .ok()
+            .map(|iter| iter.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    };
+    let trash_dir = trash_audio_dir(audio_dir);
+    for id in &ids {
+        remove_audio(&trash_dir, id);
+    }
+    let _ = conn.execute("DELETE FROM trash WHERE deleted_at < ?1", params![cutoff]);
+}
+
+/// Drop trashed entries older than `retention_days`; 0 keeps them until emptied.
+pub fn purge_trash(history_dir: &Path, audio_dir: &Path, retention_days: u32) {
+    if retention_days == 0 { return; }
+    if let Ok(conn) = open_db(history_dir) {
+        purge_expired_trash(&conn, audio_dir, retention_days);
+    }
+}
+
+/// Store the dictation audio for `id` in `format`, using `bit_depth` when the
+/// format is WAV.  Returns false when no audio was kept (`AudioFormat::None`)
+/// or writing failed.
+pub fn save_audio(
+    audio_dir: &Path,
+    id: &str,
+    samples_16k: &[f32],
+    format: AudioFormat,
+    bit_depth: WavBitDepth,
+) -> bool {
     if validate_id(id).is_err() { return false; }
+    let Some(ext) = format.extension() else { return false };
     if std::fs::create_dir_all(audio_dir).is_err() {
         return false;
     }
-    let path = audio_path(audio_dir, id);
-    let spec = hound::WavSpec {
-        channels: 1,
-        sample_rate: 16000,
-        bits_per_sample: 16,
-        sample_format: hound::SampleFormat::Int,
+    let path = audio_path(audio_dir, id, ext);
+    let result = match format {
+        AudioFormat::Wav => write_wav(&path, samples_16k, bit_depth),
+        AudioFormat::Mp3 => encode_mp3(samples_16k)
+            .and_then(|bytes| std::fs::write(&path, bytes).map_err(|e| e.to_string())),
+        AudioFormat::Opus => encode_ogg_opus(samples_16k)
+            .and_then(|bytes| std::fs::write(&path, bytes).map_err(|e| e.to_string())),
+        AudioFormat::None => return false,
     };
-    match hound::WavWriter::create(&path, spec) {
-        Ok(mut writer) => {
-            for &s in samples_16k {
-                let clamped = s.clamp(-1.0, 1.0);
-                let val = (clamped * 32767.0) as i16;
-                if writer.write_sample(val).is_err() {
-                    return false;
-                }
-            }
-            writer.finalize().is_ok()
+    match result {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::warn!("Failed to save {} audio for {}: {}", ext, id, e);
+            let _ = std::fs::remove_file(&path);
+            false
         }
-        Err(_) => false,
     }
 }
 
+fn to_i16(s: f32) -> i16 {
+    (s.clamp(-1.0, 1.0) * 32767.0) as i16
+}
+
+fn to_i24(s: f32) -> i32 {
+    (s.clamp(-1.0, 1.0) * 8_388_607.0) as i32
+}
+
+fn write_wav(path: &Path, samples_16k: &[f32], bit_depth: WavBitDepth) -> Result<(), String> {
+    let mut writer = hound::WavWriter::create(path, bit_depth.spec()).map_err(|e| e.to_string())?;
+    for &s in samples_16k {
+        let written = match bit_depth {
+            WavBitDepth::Int16 => writer.write_sample(to_i16(s)),
+            WavBitDepth::Int24 => writer.write_sample(to_i24(s)),
+            WavBitDepth::Float32 => writer.write_sample(s),
+        };
+        written.map_err(|e| e.to_string())?;
+    }
+    writer.finalize().map_err(|e| e.to_string())
+}
+
+fn encode_mp3(samples_16k: &[f32]) -> Result<Vec<u8>, String> {
+    use mp3lame_encoder::{Bitrate, Builder, FlushNoGap, MonoPcm, Quality};
+
+    let mut builder = Builder::new().ok_or("Failed to create LAME encoder")?;
+    builder.set_num_channels(1).map_err(|e| format!("LAME: {:?}", e))?;
+    builder.set_sample_rate(16_000).map_err(|e| format!("LAME: {:?}", e))?;
+    builder.set_brate(Bitrate::Kbps32).map_err(|e| format!("LAME: {:?}", e))?;
+    builder.set_quality(Quality::Good).map_err(|e| format!("LAME: {:?}", e))?;
+    let mut encoder = builder.build().map_err(|e| format!("LAME: {:?}", e))?;
+
+    let pcm: Vec<i16> = samples_16k.iter().map(|&s| to_i16(s)).collect();
+    let mut out = Vec::with_capacity(mp3lame_encoder::max_required_buffer_size(pcm.len()));
+    encoder
+        .encode_to_vec(MonoPcm(&pcm), &mut out)
+        .map_err(|e| format!("LAME encode: {:?}", e))?;
+    encoder
+        .flush_to_vec::<FlushNoGap>(&mut out)
+        .map_err(|e| format!("LAME flush: {:?}", e))?;
+    Ok(out)
+}
+
+/// Encode as Ogg Opus (RFC 7845).  Opus runs on 20 ms frames; the final
+/// partial frame is zero-padded and its end granule trims the padding.
+fn encode_ogg_opus(samples_16k: &[f32]) -> Result<Vec<u8>, String> {
+    use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+
+    const FRAME: usize = 320; // 20 ms at 16 kHz
+    const SERIAL: u32 = 0x5355_4d49;
+    // Ogg Opus granule positions are always counted at 48 kHz.
+    const GRANULE_PER_SAMPLE: u64 = 3;
+
+    let mut encoder = opus::Encoder::new(16_000, opus::Channels::Mono, opus::Application::Voip)
+        .map_err(|e| format!("Opus: {}", e))?;
+    encoder
+        .set_bitrate(opus::Bitrate::Bits(24_000))
+        .map_err(|e| format!("Opus: {}", e))?;
+    let pre_skip = encoder.get_lookahead().map_err(|e| format!("Opus: {}", e))? as u64 * GRANULE_PER_SAMPLE;
+
+    let mut out = Vec::new();
+    {
+        let mut writer = PacketWriter::new(&mut out);
+
+        let mut head = Vec::with_capacity(19);
+        head.extend_from_slice(b"OpusHead");
+        head.push(1); // version
+        head.push(1); // channels
+        head.extend_from_slice(&(pre_skip as u16).to_le_bytes());
+        head.extend_from_slice(&16_000u32.to_le_bytes());
+        head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+        head.push(0); // channel mapping family
+        writer
+            .write_packet(head, SERIAL, PacketWriteEndInfo::EndPage, 0)
+            .map_err(|e| e.to_string())?;
+
+        let vendor = b"sumi";
+        let mut tags = Vec::new();
+        tags.extend_from_slice(b"OpusTags");
+        tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+        tags.extend_from_slice(vendor);
+        tags.extend_from_slice(&0u32.to_le_bytes()); // no user comments
+        writer
+            .write_packet(tags, SERIAL, PacketWriteEndInfo::EndPage, 0)
+            .map_err(|e| e.to_string())?;
+
+        let frame_count = samples_16k.len().div_ceil(FRAME);
+        let mut packet = [0u8; 1500];
+        for (i, chunk) in samples_16k.chunks(FRAME).enumerate() {
+            let mut frame = [0.0f32; FRAME];
+            frame[..chunk.len()].copy_from_slice(chunk);
+            let len = encoder
+                .encode_float(&frame, &mut packet)
+                .map_err(|e| format!("Opus encode: {}", e))?;
+            let is_last = i + 1 == frame_count;
+            let samples_done = if is_last { samples_16k.len() } else { (i + 1) * FRAME };
+            let end = if is_last {
+                PacketWriteEndInfo::EndStream
+            } else {
+                PacketWriteEndInfo::NormalPacket
+            };
+            let granule = pre_skip + samples_done as u64 * GRANULE_PER_SAMPLE;
+            writer
+                .write_packet(packet[..len].to_vec(), SERIAL, end, granule)
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(out)
+}
+
 pub fn export_audio(audio_dir: &Path, id: &str) -> Result<PathBuf, String> {
     validate_id(id)?;
-    let src = audio_path(audio_dir, id);
-    if !src.exists() {
-        return Err("Audio file not found".to_string());
-    }
+    let src = find_audio(audio_dir, id).ok_or("Audio file not found")?;
+    let ext = src.extension().and_then(|e| e.to_str()).unwrap_or("wav").to_string();
+    let dest = downloads_dir().join(format!("{}.{}", id, ext));
+    std::fs::copy(&src, &dest).map_err(|e| format!("Failed to copy audio: {}", e))?;
+    Ok(dest)
+}
+
+/// Write an entry's transcript as SubRip subtitles to the Downloads folder.
+pub fn export_srt(history_dir: &Path, id: &str) -> Result<PathBuf, String> {
+    validate_id(id)?;
+    let entry = get_entry(history_dir, id).ok_or("History entry not found")?;
+    let dest = downloads_dir().join(format!("{}.srt", id));
+    std::fs::write(&dest, to_srt(&entry)).map_err(|e| format!("Failed to write subtitles: {}", e))?;
+    Ok(dest)
+}
+
+fn downloads_dir() -> PathBuf {
     let downloads = dirs::download_dir().unwrap_or_else(|| {
         dirs::home_dir()
             .unwrap_or_else(|| PathBuf::from("/tmp"))
             .join("Downloads")
     });
     let _ = std::fs::create_dir_all(&downloads);
-    let dest = downloads.join(format!("{}.wav", id));
-    std::fs::copy(&src, &dest).map_err(|e| format!("Failed to copy audio: {}", e))?;
-    Ok(dest)
+    downloads
+}
+
+/// A caption block closes after this many words…
+const SRT_MAX_WORDS: usize = 7;
+/// …or once it spans this many seconds.
+const SRT_MAX_SECS: f64 = 3.0;
+
+/// SubRip text for `entry`.  With word timings the words are grouped into
+/// short caption blocks; without them the whole text is one caption
+/// spanning the recording.
+fn to_srt(entry: &HistoryEntry) -> String {
+    let mut blocks: Vec<(f64, f64, String)> = Vec::new();
+    let mut words = 0;
+    for w in entry.word_timings.iter().flatten() {
+        match blocks.last_mut() {
+            Some((start, end, text)) if words < SRT_MAX_WORDS && w.end_secs - *start <= SRT_MAX_SECS => {
+                text.push(' ');
+                text.push_str(&w.word);
+                *end = w.end_secs;
+                words += 1;
+            }
+            _ => {
+                blocks.push((w.start_secs, w.end_secs, w.word.clone()));
+                words = 1;
+            }
+        }
+    }
+    if blocks.is_empty() {
+        blocks.push((0.0, entry.duration_secs, entry.text.trim().to_string()));
+    }
+    blocks
+        .iter()
+        .enumerate()
+        .map(|(i, (start, end, text))| {
+            format!("{}\n{} --> {}\n{}\n\n", i + 1, srt_timestamp(*start), srt_timestamp(*end), text)
+        })
+        .collect()
+}
+
+/// `HH:MM:SS,mmm`, as SubRip expects.
+fn srt_timestamp(secs: f64) -> String {
+    let ms = (secs.max(0.0) * 1000.0).round() as u64;
+    format!("{:02}:{:02}:{:02},{:03}", ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60, ms % 1000)
 }
 
 pub fn generate_id() -> String {
@@ -586,6 +1638,14 @@ mod tests {
             bundle_id: "".to_string(),
             chars_per_sec: 10.0,
             word_count: 1,
+            confidence: None,
+            audio_format: AudioFormat::Wav,
+            source_language: None,
+            target_language: None,
+            tags: Vec::new(),
+            word_timings: None,
+            app_context: None,
+            fallback_reason: None,
         }
     }
 
@@ -609,15 +1669,16 @@ mod tests {
         let fresh = now - 3_600_000; // 1 hour ago
 
         // Insert an old entry and a fresh entry.
-        add_entry(hp, ap, make_entry("111_111_111", two_days_ago), 0);
-        add_entry(hp, ap, make_entry("222_222_222", fresh), 0);
+        add_entry(hp, ap, make_entry("111_111_111", two_days_ago), HistoryLimits::default());
+        add_entry(hp, ap, make_entry("222_222_222", fresh), HistoryLimits::default());
 
         // Create fake audio files for both.
         std::fs::write(ap.join("111_111_111.wav"), b"old").unwrap();
         std::fs::write(ap.join("222_222_222.wav"), b"new").unwrap();
 
         // Now add a third entry with retention_days=1 — triggers cleanup.
-        add_entry(hp, ap, make_entry("333_333_333", now), 1);
+        let limits = HistoryLimits { retention_days: 1, ..Default::default() };
+        add_entry(hp, ap, make_entry("333_333_333", now), limits);
 
         let entries = load_history(hp);
         let ids: Vec<&str> = entries.iter().map(|e| e.id.as_str()).collect();
@@ -630,6 +1691,39 @@ mod tests {
         assert!(ap.join("222_222_222.wav").exists(), "fresh audio should remain");
     }
 
+    #[test]
+    fn usage_stats_aggregate_within_range() {
+        let hist_dir = tempfile::tempdir().unwrap();
+        let audio_dir = tempfile::tempdir().unwrap();
+        let hp = hist_dir.path();
+        let ap = audio_dir.path();
+        init_db(hp);
+
+        let now = now_ms();
+        let mut cloud = make_entry("111_111_111", now - 2000);
+        cloud.stt_model = "whisper (Cloud/groq)".to_string();
+        cloud.total_elapsed_ms = 300;
+        add_entry(hp, ap, cloud, HistoryLimits::default());
+        add_entry(hp, ap, make_entry("222_222_222", now - 1000), HistoryLimits::default());
+        add_entry(hp, ap, make_entry("333_333_333", now), HistoryLimits::default());
+
+        let all = get_usage_stats(hp, None, None).unwrap();
+        assert_eq!(all.total_entries, 3);
+        assert_eq!(all.total_chars, 24);
+        assert!((all.avg_total_elapsed_ms - 200.0).abs() < 1e-9);
+        assert_eq!(all.stt_models[0].model, "test");
+        assert_eq!(all.stt_models[0].entries, 2);
+        assert_eq!(all.polish_models.len(), 1);
+
+        let recent = get_usage_stats(hp, Some(now - 1000), None).unwrap();
+        assert_eq!(recent.total_entries, 2);
+        assert_eq!(recent.stt_models.len(), 1);
+
+        let none = get_usage_stats(hp, None, Some(now - 5000)).unwrap();
+        assert_eq!(none.total_entries, 0);
+        assert!(none.stt_models.is_empty());
+    }
+
     #[test]
     fn retention_zero_keeps_everything() {
         let hist_dir = tempfile::tempdir().unwrap();
@@ -639,12 +1733,281 @@ mod tests {
         init_db(hp);
 
         let old_ts = now_ms() - 365 * 86_400_000; // 1 year ago
-        add_entry(hp, ap, make_entry("111_111_111", old_ts), 0);
+        add_entry(hp, ap, make_entry("111_111_111", old_ts), HistoryLimits::default());
 
         let entries = load_history(hp);
         assert_eq!(entries.len(), 1, "retention_days=0 should keep all entries");
     }
 
+    // ── Size caps: oldest evicted first ──
+
+    #[test]
+    fn entry_cap_evicts_oldest_with_audio() {
+        let hist_dir = tempfile::tempdir().unwrap();
+        let audio_dir = tempfile::tempdir().unwrap();
+        let hp = hist_dir.path();
+        let ap = audio_dir.path();
+        init_db(hp);
+
+        let now = now_ms();
+        let limits = HistoryLimits { max_entries: 2, ..Default::default() };
+        for (i, id) in ["111_111_111", "222_222_222", "333_333_333"].iter().enumerate() {
+            std::fs::write(ap.join(format!("{}.wav", id)), b"audio").unwrap();
+            add_entry(hp, ap, make_entry(id, now - (3 - i as i64) * 1000), limits);
+        }
+
+        let ids: Vec<String> = load_history(hp).into_iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec!["333_333_333", "222_222_222"]);
+        assert!(!ap.join("111_111_111.wav").exists(), "evicted row's audio should be deleted");
+        assert!(ap.join("222_222_222.wav").exists());
+    }
+
+    #[test]
+    fn audio_cap_drops_oldest_audio_but_keeps_text() {
+        let hist_dir = tempfile::tempdir().unwrap();
+        let audio_dir = tempfile::tempdir().unwrap();
+        let hp = hist_dir.path();
+        let ap = audio_dir.path();
+        init_db(hp);
+
+        let now = now_ms();
+        let limits = HistoryLimits { max_audio_bytes: 250, ..Default::default() };
+        for (i, id) in ["111_111_111", "222_222_222", "333_333_333"].iter().enumerate() {
+            std::fs::write(ap.join(format!("{}.wav", id)), [0u8; 100]).unwrap();
+            add_entry(hp, ap, make_entry(id, now - (3 - i as i64) * 1000), limits);
+        }
+
+        assert!(!ap.join("111_111_111.wav").exists(), "oldest audio should be evicted");
+        assert!(ap.join("222_222_222.wav").exists());
+        assert!(ap.join("333_333_333.wav").exists());
+        let oldest = get_entry(hp, "111_111_111").expect("text entry should remain");
+        assert!(!oldest.has_audio);
+    }
+
+    #[test]
+    fn audio_cap_counts_trashed_audio_first() {
+        let hist_dir = tempfile::tempdir().unwrap();
+        let audio_dir = tempfile::tempdir().unwrap();
+        let hp = hist_dir.path();
+        let ap = audio_dir.path();
+        init_db(hp);
+
+        let now = now_ms();
+        let limits = HistoryLimits { max_audio_bytes: 250, ..Default::default() };
+        for (i, id) in ["111_111_111", "222_222_222"].iter().enumerate() {
+            std::fs::write(ap.join(format!("{}.wav", id)), [0u8; 100]).unwrap();
+            add_entry(hp, ap, make_entry(id, now - (3 - i as i64) * 1000), limits);
+        }
+        delete_entry(hp, ap, "222_222_222").unwrap();
+        std::fs::write(ap.join("333_333_333.wav"), [0u8; 100]).unwrap();
+        add_entry(hp, ap, make_entry("333_333_333", now), limits);
+
+        assert!(!trash_audio_dir(ap).join("222_222_222.wav").exists(), "trashed audio should go first");
+        assert!(ap.join("111_111_111.wav").exists());
+        assert!(ap.join("333_333_333.wav").exists());
+    }
+
+    // ── Tags ──
+
+    #[test]
+    fn tags_are_normalized_and_filterable() {
+        let hist_dir = tempfile::tempdir().unwrap();
+        let audio_dir = tempfile::tempdir().unwrap();
+        let hp = hist_dir.path();
+        let ap = audio_dir.path();
+        init_db(hp);
+
+        let now = now_ms();
+        for (i, id) in ["111_111_111", "222_222_222", "333_333_333"].iter().enumerate() {
+            add_entry(hp, ap, make_entry(id, now - (3 - i as i64) * 1000), HistoryLimits::default());
+        }
+        let stored = set_entry_tags(hp, "111_111_111", vec![" Work ".into(), "".into(), "work".into(), "idea".into()]).unwrap();
+        assert_eq!(stored, vec!["Work", "idea"]);
+        set_entry_tags(hp, "333_333_333", vec!["work".into()]).unwrap();
+        assert!(set_entry_tags(hp, "999_999_999", vec!["work".into()]).is_err());
+
+        assert_eq!(get_entry(hp, "111_111_111").unwrap().tags, vec!["Work", "idea"]);
+        assert!(get_entry(hp, "222_222_222").unwrap().tags.is_empty());
+
+        let (page, has_more) = load_history_by_tag(hp, "WORK", None, 1);
+        assert_eq!(page.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(), vec!["333_333_333"]);
+        assert!(has_more);
+        let (page, has_more) = load_history_by_tag(hp, "work", Some(page[0].timestamp), 10);
+        assert_eq!(page.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(), vec!["111_111_111"]);
+        assert!(!has_more);
+
+        // Case folding goes beyond ASCII.
+        set_entry_tags(hp, "222_222_222", vec!["Ärger".into()]).unwrap();
+        let (page, _) = load_history_by_tag(hp, "ärger", None, 10);
+        assert_eq!(page.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(), vec!["222_222_222"]);
+        assert!(load_history_by_tag(hp, "missing", None, 10).0.is_empty());
+    }
+
+    // ── Word timings ──
+
+    #[test]
+    fn word_timings_round_trip_through_db() {
+        let hist_dir = tempfile::tempdir().unwrap();
+        let audio_dir = tempfile::tempdir().unwrap();
+        let hp = hist_dir.path();
+        let ap = audio_dir.path();
+        init_db(hp);
+
+        let timings = vec![
+            WordTiming { word: "Hello,".into(), start_secs: 0.08, end_secs: 0.42 },
+            WordTiming { word: "world.".into(), start_secs: 0.5, end_secs: 0.91 },
+        ];
+        let mut entry = make_entry("111_111_111", now_ms());
+        entry.word_timings = Some(timings.clone());
+        add_entry(hp, ap, entry, HistoryLimits::default());
+        add_entry(hp, ap, make_entry("222_222_222", now_ms()), HistoryLimits::default());
+
+        assert_eq!(get_entry(hp, "111_111_111").unwrap().word_timings, Some(timings));
+        assert_eq!(get_entry(hp, "222_222_222").unwrap().word_timings, None);
+    }
+
+    #[test]
+    fn repolish_replaces_text_and_keeps_context() {
+        let hist_dir = tempfile::tempdir().unwrap();
+        let audio_dir = tempfile::tempdir().unwrap();
+        let hp = hist_dir.path();
+        init_db(hp);
+
+        let context = AppContext {
+            app_name: "Chrome".into(),
+            bundle_id: "com.google.Chrome".into(),
+            url: "https://github.com/pulls".into(),
+            terminal_host: String::new(),
+        };
+        let mut entry = make_entry("111_111_111", now_ms());
+        entry.polish_elapsed_ms = None;
+        entry.app_context = Some(context);
+        add_entry(hp, audio_dir.path(), entry, HistoryLimits::default());
+
+        set_entry_polish(hp, "111_111_111", "Polished again, twice.", None, "qwen (Cloud/groq)", Some(80), Some(("zh", "en")))
+            .unwrap();
+        let stored = get_entry(hp, "111_111_111").unwrap();
+        assert_eq!(stored.text, "Polished again, twice.");
+        assert_eq!(stored.raw_text, "raw");
+        assert_eq!(stored.polish_model, "qwen (Cloud/groq)");
+        assert_eq!(stored.polish_elapsed_ms, Some(80));
+        assert_eq!(stored.word_count, 3);
+        assert_eq!(stored.app_context.unwrap().url, "https://github.com/pulls");
+        assert_eq!(stored.source_language.as_deref(), Some("zh"));
+        assert_eq!(stored.target_language.as_deref(), Some("en"));
+
+        // Re-polishing without translate mode drops the old language pair.
+        set_entry_polish(hp, "111_111_111", "Polished.", None, "m", Some(10), None).unwrap();
+        let stored = get_entry(hp, "111_111_111").unwrap();
+        assert_eq!((stored.source_language, stored.target_language), (None, None));
+        assert!(set_entry_polish(hp, "999_999_999", "x", None, "m", None, None).is_err());
+    }
+
+    #[test]
+    fn srt_groups_words_into_short_captions() {
+        let word = |w: &str, start_secs: f64| WordTiming { word: w.into(), start_secs, end_secs: start_secs + 0.3 };
+        let mut entry = make_entry("111_111_111", now_ms());
+        let mut timings: Vec<WordTiming> =
+            "one two three four five six seven eight".split(' ').enumerate().map(|(i, w)| word(w, i as f64 * 0.4)).collect();
+        timings.push(word("later", 10.0));
+        timings.push(word("end", 13.5));
+        entry.word_timings = Some(timings);
+        assert_eq!(
+            to_srt(&entry),
+            "1\n00:00:00,000 --> 00:00:02,700\none two three four five six seven\n\n\
+             2\n00:00:02,800 --> 00:00:03,100\neight\n\n\
+             3\n00:00:10,000 --> 00:00:10,300\nlater\n\n\
+             4\n00:00:13,500 --> 00:00:13,800\nend\n\n"
+        );
+    }
+
+    #[test]
+    fn srt_without_timings_is_one_caption() {
+        let mut entry = make_entry("111_111_111", now_ms());
+        entry.duration_secs = 3725.5;
+        assert_eq!(to_srt(&entry), "1\n00:00:00,000 --> 01:02:05,500\npolished\n\n");
+    }
+
+    // ── Trash ──
+
+    #[test]
+    fn deleted_entry_can_be_restored_with_audio() {
+        let hist_dir = tempfile::tempdir().unwrap();
+        let audio_dir = tempfile::tempdir().unwrap();
+        let hp = hist_dir.path();
+        let ap = audio_dir.path();
+        init_db(hp);
+
+        add_entry(hp, ap, make_entry("111_111_111", now_ms()), HistoryLimits::default());
+        std::fs::write(ap.join("111_111_111.wav"), b"audio").unwrap();
+
+        delete_entry(hp, ap, "111_111_111").unwrap();
+        assert!(get_entry(hp, "111_111_111").is_none());
+        assert!(!ap.join("111_111_111.wav").exists());
+        let trash = load_trash(hp);
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].entry.id, "111_111_111");
+
+        restore_entry(hp, ap, "111_111_111").unwrap();
+        let restored = get_entry(hp, "111_111_111").expect("entry should be back");
+        assert!(restored.has_audio);
+        assert!(ap.join("111_111_111.wav").exists());
+        assert!(load_trash(hp).is_empty());
+    }
+
+    #[test]
+    fn clear_all_goes_to_trash_until_emptied() {
+        let hist_dir = tempfile::tempdir().unwrap();
+        let audio_dir = tempfile::tempdir().unwrap();
+        let hp = hist_dir.path();
+        let ap = audio_dir.path();
+        init_db(hp);
+
+        for id in ["111_111_111", "222_222_222"] {
+            add_entry(hp, ap, make_entry(id, now_ms()), HistoryLimits::default());
+            std::fs::write(ap.join(format!("{}.wav", id)), b"audio").unwrap();
+        }
+        clear_all(hp, ap).unwrap();
+        assert!(load_history(hp).is_empty());
+        assert_eq!(load_trash(hp).len(), 2);
+
+        empty_trash(hp, ap);
+        assert!(load_trash(hp).is_empty());
+        assert!(restore_entry(hp, ap, "111_111_111").is_err());
+        assert!(!trash_audio_dir(ap).exists());
+    }
+
+    // ── Integrity check & repair ──
+
+    #[test]
+    fn healthy_db_verifies_ok() {
+        let hist_dir = tempfile::tempdir().unwrap();
+        let audio_dir = tempfile::tempdir().unwrap();
+        init_db(hist_dir.path());
+        add_entry(hist_dir.path(), audio_dir.path(), make_entry("111_111_111", now_ms()), HistoryLimits::default());
+
+        let health = verify_db(hist_dir.path());
+        assert!(health.ok, "unexpected problems: {:?}", health.problems);
+    }
+
+    #[test]
+    fn repair_keeps_rows_and_quarantines_old_file() {
+        let hist_dir = tempfile::tempdir().unwrap();
+        let audio_dir = tempfile::tempdir().unwrap();
+        let hp = hist_dir.path();
+        init_db(hp);
+        let now = now_ms();
+        add_entry(hp, audio_dir.path(), make_entry("111_111_111", now - 1000), HistoryLimits::default());
+        add_entry(hp, audio_dir.path(), make_entry("222_222_222", now), HistoryLimits::default());
+
+        let report = repair_db(hp).unwrap();
+        assert_eq!(report.recovered_rows, 2);
+        assert!(Path::new(&report.quarantined_path).exists());
+        let ids: Vec<String> = load_history(hp).into_iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec!["222_222_222", "111_111_111"]);
+        assert!(verify_db(hp).ok);
+    }
+
     // ── History stats aggregation ──
 
     #[test]
@@ -662,12 +2025,56 @@ mod tests {
         let mut e2 = make_entry("222_222_222", now - 1000);
         e2.duration_secs = 5.0;
         e2.word_count = 10;
-        add_entry(hp, ap, e1, 0);
-        add_entry(hp, ap, e2, 0);
+        add_entry(hp, ap, e1, HistoryLimits::default());
+        add_entry(hp, ap, e2, HistoryLimits::default());
 
         let stats = get_stats(hp);
         assert_eq!(stats.total_entries, 2);
         assert!((stats.total_duration_secs - 15.5).abs() < 0.01);
         assert_eq!(stats.total_words, 30);
     }
+
+    #[test]
+    fn save_audio_none_keeps_nothing() {
+        let audio_dir = tempfile::tempdir().unwrap();
+        let ap = audio_dir.path();
+        assert!(!save_audio(ap, "111_111_111", &[0.1; 1600], AudioFormat::None, WavBitDepth::Int16));
+        assert!(find_audio(ap, "111_111_111").is_none());
+    }
+
+    #[test]
+    fn stored_audio_is_found_and_removed_by_any_format() {
+        let audio_dir = tempfile::tempdir().unwrap();
+        let ap = audio_dir.path();
+        std::fs::write(ap.join("111_111_111.opus"), b"ogg").unwrap();
+        assert!(save_audio(ap, "222_222_222", &[0.1; 1600], AudioFormat::Wav, WavBitDepth::Int16));
+        assert_eq!(find_audio(ap, "111_111_111").unwrap(), ap.join("111_111_111.opus"));
+        assert_eq!(find_audio(ap, "222_222_222").unwrap(), ap.join("222_222_222.wav"));
+        remove_audio(ap, "111_111_111");
+        assert!(find_audio(ap, "111_111_111").is_none());
+    }
+
+    #[test]
+    fn wav_bit_depths_round_trip_through_hound() {
+        let audio_dir = tempfile::tempdir().unwrap();
+        let ap = audio_dir.path();
+        let samples = [0.0, 0.25, -0.5, 1.0, -1.0];
+        for (i, depth) in [WavBitDepth::Int16, WavBitDepth::Int24, WavBitDepth::Float32].into_iter().enumerate() {
+            let id = format!("{0}{0}{0}_{0}{0}{0}_{0}{0}{0}", i + 1);
+            assert!(save_audio(ap, &id, &samples, AudioFormat::Wav, depth));
+            let mut reader = hound::WavReader::open(ap.join(format!("{}.wav", id))).unwrap();
+            let spec = reader.spec();
+            assert_eq!(spec, depth.spec());
+            assert_eq!(reader.len() as usize, samples.len());
+            let decoded: Vec<f32> = match depth {
+                WavBitDepth::Int16 => reader.samples::<i16>().map(|s| s.unwrap() as f32 / 32767.0).collect(),
+                WavBitDepth::Int24 => reader.samples::<i32>().map(|s| s.unwrap() as f32 / 8_388_607.0).collect(),
+                WavBitDepth::Float32 => reader.samples::<f32>().map(Result::unwrap).collect(),
+            };
+            let tolerance = if depth == WavBitDepth::Float32 { 0.0 } else { 1e-4 };
+            for (got, want) in decoded.iter().zip(samples) {
+                assert!((got - want).abs() <= tolerance, "{:?}: {} vs {}", depth, got, want);
+            }
+        }
+    }
 }
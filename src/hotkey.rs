@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut};
 
 pub fn parse_key_code(s: &str) -> Option<Code> {
@@ -148,10 +150,100 @@ pub fn hotkey_display_label(s: &str) -> String {
     labels.join(" ")
 }
 
+/// The Shift-modified form of a hotkey string (`"Alt+KeyV"` → `"Shift+Alt+KeyV"`).
+/// Returns `None` when the hotkey already uses Shift, since there is no
+/// distinct variant to register.
+pub fn shift_variant(s: &str) -> Option<String> {
+    if s.is_empty() || s.split('+').any(|p| p == "Shift") {
+        return None;
+    }
+    Some(format!("Shift+{}", s))
+}
+
+/// Whether two hotkey strings name the same key combination, regardless of
+/// modifier order (`"Shift+Alt+KeyV"` and `"Alt+Shift+KeyV"` are one hotkey).
+pub fn same_hotkey(a: &str, b: &str) -> bool {
+    match (parse_hotkey_string(a), parse_hotkey_string(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Presses closer together than this are OS auto-repeat even when no release
+/// was seen; typical repeat intervals are 30–90 ms.
+pub const KEY_REPEAT_WINDOW: Duration = Duration::from_millis(100);
+/// How long a key with no release event still counts as held.  Covers the
+/// initial auto-repeat delay (~0.5 s by default) and platforms that drop the
+/// release, so a lost release cannot lock out the hotkey for good.
+pub const HELD_KEY_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// What a global-shortcut press event means once repeat and bounce are ruled out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PressKind {
+    /// A deliberate press: act on it.
+    Accept,
+    /// Auto-repeat from a key that is still held down.
+    Repeat,
+    /// A second deliberate press inside the debounce window.
+    Bounce,
+}
+
+/// Classify a press from whether the key is still `held` (no release since
+/// the previous press), the time since the previous press event of any kind,
+/// and the time since the last accepted press.
+///
+/// Auto-repeat is recognised by the held state or the OS repeat rate, not by
+/// `debounce`, so a short debounce still never turns a held key into a
+/// stream of toggles, and a long one never swallows repeat-free presses
+/// beyond its own window.
+pub fn classify_press(
+    held: bool,
+    since_last_event: Duration,
+    since_last_accepted: Duration,
+    debounce: Duration,
+) -> PressKind {
+    if since_last_event < KEY_REPEAT_WINDOW || (held && since_last_event < HELD_KEY_TIMEOUT) {
+        PressKind::Repeat
+    } else if since_last_accepted < debounce {
+        PressKind::Bounce
+    } else {
+        PressKind::Accept
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn shift_variant_adds_shift_once() {
+        assert_eq!(shift_variant("Alt+KeyV").as_deref(), Some("Shift+Alt+KeyV"));
+        assert!(shift_variant("Shift+Alt+KeyV").is_none());
+        let s = parse_hotkey_string(&shift_variant("Alt+KeyV").unwrap()).unwrap();
+        assert_eq!(s.mods, Modifiers::SHIFT | Modifiers::ALT);
+    }
+
+    #[test]
+    fn classify_press_separates_repeat_from_double_press() {
+        let ms = Duration::from_millis;
+        // Held key: the first auto-repeat arrives after the initial delay.
+        assert_eq!(classify_press(true, ms(500), ms(500), ms(300)), PressKind::Repeat);
+        // Repeat stream without a tracked release.
+        assert_eq!(classify_press(false, ms(40), ms(2000), ms(50)), PressKind::Repeat);
+        // Released and pressed again quickly: a real double press.
+        assert_eq!(classify_press(false, ms(150), ms(150), ms(100)), PressKind::Accept);
+        assert_eq!(classify_press(false, ms(150), ms(150), ms(300)), PressKind::Bounce);
+        // A lost release does not lock the hotkey.
+        assert_eq!(classify_press(true, ms(1500), ms(1500), ms(300)), PressKind::Accept);
+    }
+
+    #[test]
+    fn same_hotkey_ignores_modifier_order() {
+        assert!(same_hotkey("Shift+Alt+KeyV", "Alt+Shift+KeyV"));
+        assert!(!same_hotkey("Alt+KeyV", "Shift+Alt+KeyV"));
+        assert!(!same_hotkey("Alt+KeyV", "Alt+KeyB"));
+    }
+
     #[test]
     fn parses_multi_modifier_hotkey() {
         let s = parse_hotkey_string("Control+Alt+KeyZ").unwrap();
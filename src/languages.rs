@@ -0,0 +1,386 @@
+//! Language code handling for speech-to-text.
+//!
+//! Settings, imported profiles and the local API all accept a free-form
+//! language string.  [`normalize_language_code`] turns whatever arrives
+//! ("EN_us", "zh-Hant", "Japanese") into the canonical BCP-47 tag the
+//! rest of the app uses, or rejects it.  [`supported_languages`] lists the
+//! languages each cloud provider accepts, for the settings dropdowns.
+
+use crate::stt::SttProvider;
+use serde::Serialize;
+
+/// One entry in a language picker.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LanguageOption {
+    pub code: String,
+    pub name: String,
+}
+
+/// Every language Whisper recognises, as `(code, "Native (English)")`.
+/// Kept in the same order as the frontend's `STT_LANGUAGES`.
+const WHISPER_LANGUAGES: &[(&str, &str)] = &[
+    ("zh-TW", "繁體中文 (Traditional Chinese)"),
+    ("zh-CN", "简体中文 (Simplified Chinese)"),
+    ("en", "English"),
+    ("ja", "日本語 (Japanese)"),
+    ("ko", "한국어 (Korean)"),
+    ("yue", "粵語 (Cantonese)"),
+    ("af", "Afrikaans"),
+    ("am", "Amharic"),
+    ("ar", "العربية (Arabic)"),
+    ("as", "অসমীয়া (Assamese)"),
+    ("az", "Azərbaycan (Azerbaijani)"),
+    ("ba", "Bashkir"),
+    ("be", "Беларуская (Belarusian)"),
+    ("bg", "Български (Bulgarian)"),
+    ("bn", "বাংলা (Bengali)"),
+    ("bo", "བོད་སྐད (Tibetan)"),
+    ("br", "Brezhoneg (Breton)"),
+    ("bs", "Bosanski (Bosnian)"),
+    ("ca", "Català (Catalan)"),
+    ("cs", "Čeština (Czech)"),
+    ("cy", "Cymraeg (Welsh)"),
+    ("da", "Dansk (Danish)"),
+    ("de", "Deutsch (German)"),
+    ("el", "Ελληνικά (Greek)"),
+    ("es", "Español (Spanish)"),
+    ("et", "Eesti (Estonian)"),
+    ("eu", "Euskara (Basque)"),
+    ("fa", "فارسی (Persian)"),
+    ("fi", "Suomi (Finnish)"),
+    ("fo", "Føroyskt (Faroese)"),
+    ("fr", "Français (French)"),
+    ("gl", "Galego (Galician)"),
+    ("gu", "ગુજરાતી (Gujarati)"),
+    ("ha", "Hausa"),
+    ("haw", "ʻŌlelo Hawaiʻi (Hawaiian)"),
+    ("he", "עברית (Hebrew)"),
+    ("hi", "हिन्दी (Hindi)"),
+    ("hr", "Hrvatski (Croatian)"),
+    ("ht", "Kreyòl Ayisyen (Haitian Creole)"),
+    ("hu", "Magyar (Hungarian)"),
+    ("hy", "Հայերեն (Armenian)"),
+    ("id", "Bahasa Indonesia (Indonesian)"),
+    ("is", "Íslenska (Icelandic)"),
+    ("it", "Italiano (Italian)"),
+    ("jw", "Basa Jawa (Javanese)"),
+    ("ka", "ქართული (Georgian)"),
+    ("kk", "Қазақ (Kazakh)"),
+    ("km", "ខ្មែរ (Khmer)"),
+    ("kn", "ಕನ್ನಡ (Kannada)"),
+    ("lb", "Lëtzebuergesch (Luxembourgish)"),
+    ("ln", "Lingála (Lingala)"),
+    ("lo", "ລາວ (Lao)"),
+    ("lt", "Lietuvių (Lithuanian)"),
+    ("lv", "Latviešu (Latvian)"),
+    ("mg", "Malagasy"),
+    ("mi", "Te Reo Māori (Maori)"),
+    ("mk", "Македонски (Macedonian)"),
+    ("ml", "മലയാളം (Malayalam)"),
+    ("mn", "Монгол (Mongolian)"),
+    ("mr", "मराठी (Marathi)"),
+    ("ms", "Bahasa Melayu (Malay)"),
+    ("mt", "Malti (Maltese)"),
+    ("my", "မြန်မာ (Myanmar)"),
+    ("ne", "नेपाली (Nepali)"),
+    ("nl", "Nederlands (Dutch)"),
+    ("nn", "Nynorsk (Norwegian Nynorsk)"),
+    ("no", "Norsk (Norwegian)"),
+    ("oc", "Occitan"),
+    ("pa", "ਪੰਜਾਬੀ (Punjabi)"),
+    ("pl", "Polski (Polish)"),
+    ("ps", "پښتو (Pashto)"),
+    ("pt", "Português (Portuguese)"),
+    ("ro", "Română (Romanian)"),
+    ("ru", "Русский (Russian)"),
+    ("sa", "संस्कृत (Sanskrit)"),
+    ("sd", "سنڌي (Sindhi)"),
+    ("si", "සිංහල (Sinhala)"),
+    ("sk", "Slovenčina (Slovak)"),
+    ("sl", "Slovenščina (Slovenian)"),
+    ("sn", "ChiShona (Shona)"),
+    ("so", "Soomaali (Somali)"),
+    ("sq", "Shqip (Albanian)"),
+    ("sr", "Српски (Serbian)"),
+    ("su", "Basa Sunda (Sundanese)"),
+    ("sv", "Svenska (Swedish)"),
+    ("sw", "Kiswahili (Swahili)"),
+    ("ta", "தமிழ் (Tamil)"),
+    ("te", "తెలుగు (Telugu)"),
+    ("tg", "Тоҷикӣ (Tajik)"),
+    ("th", "ไทย (Thai)"),
+    ("tk", "Türkmen (Turkmen)"),
+    ("tl", "Tagalog"),
+    ("tr", "Türkçe (Turkish)"),
+    ("tt", "Татар (Tatar)"),
+    ("uk", "Українська (Ukrainian)"),
+    ("ur", "اردو (Urdu)"),
+    ("uz", "Oʻzbek (Uzbek)"),
+    ("vi", "Tiếng Việt (Vietnamese)"),
+    ("yi", "ייִדיש (Yiddish)"),
+    ("yo", "Yorùbá (Yoruba)"),
+];
+
+/// Languages accepted by Deepgram's Nova models.
+const DEEPGRAM_LANGUAGES: &[&str] = &[
+    "zh-TW", "zh-CN", "en", "ja", "ko", "bg", "ca", "cs", "da", "de", "el",
+    "es", "et", "fi", "fr", "hi", "hu", "id", "it", "lt", "lv", "ms", "nl",
+    "no", "pl", "pt", "ro", "ru", "sk", "sv", "th", "tr", "uk", "vi",
+];
+
+/// Azure speech-to-text wants a full locale.  The first locale listed for
+/// a language is the one a bare code maps to.
+const AZURE_LOCALES: &[(&str, &str)] = &[
+    ("zh-TW", "繁體中文 (Taiwan)"),
+    ("zh-CN", "简体中文 (China)"),
+    ("en-US", "English (United States)"),
+    ("en-GB", "English (United Kingdom)"),
+    ("en-AU", "English (Australia)"),
+    ("en-IN", "English (India)"),
+    ("ja-JP", "日本語 (Japan)"),
+    ("ko-KR", "한국어 (Korea)"),
+    ("ar-SA", "العربية (Saudi Arabia)"),
+    ("ca-ES", "Català (Spain)"),
+    ("cs-CZ", "Čeština (Czechia)"),
+    ("da-DK", "Dansk (Denmark)"),
+    ("de-DE", "Deutsch (Germany)"),
+    ("el-GR", "Ελληνικά (Greece)"),
+    ("es-ES", "Español (Spain)"),
+    ("es-MX", "Español (Mexico)"),
+    ("fi-FI", "Suomi (Finland)"),
+    ("fr-FR", "Français (France)"),
+    ("fr-CA", "Français (Canada)"),
+    ("he-IL", "עברית (Israel)"),
+    ("hi-IN", "हिन्दी (India)"),
+    ("hu-HU", "Magyar (Hungary)"),
+    ("id-ID", "Bahasa Indonesia (Indonesia)"),
+    ("it-IT", "Italiano (Italy)"),
+    ("ms-MY", "Bahasa Melayu (Malaysia)"),
+    ("nl-NL", "Nederlands (Netherlands)"),
+    ("no-NO", "Norsk (Norway)"),
+    ("pl-PL", "Polski (Poland)"),
+    ("pt-BR", "Português (Brazil)"),
+    ("pt-PT", "Português (Portugal)"),
+    ("ro-RO", "Română (Romania)"),
+    ("ru-RU", "Русский (Russia)"),
+    ("sk-SK", "Slovenčina (Slovakia)"),
+    ("sv-SE", "Svenska (Sweden)"),
+    ("th-TH", "ไทย (Thailand)"),
+    ("tr-TR", "Türkçe (Türkiye)"),
+    ("uk-UA", "Українська (Ukraine)"),
+    ("vi-VN", "Tiếng Việt (Vietnam)"),
+];
+
+/// Codes and names that are not in [`WHISPER_LANGUAGES`] but mean one of
+/// its entries: legacy ISO 639 codes, ISO 639-2 forms and common names.
+const ALIASES: &[(&str, &str)] = &[
+    ("iw", "he"),
+    ("in", "id"),
+    ("ji", "yi"),
+    ("jv", "jw"),
+    ("nb", "no"),
+    ("fil", "tl"),
+    ("eng", "en"),
+    ("jpn", "ja"),
+    ("kor", "ko"),
+    ("zho", "zh-CN"),
+    ("chi", "zh-CN"),
+    ("chinese", "zh-CN"),
+    ("mandarin", "zh-CN"),
+    ("中文", "zh-CN"),
+    ("filipino", "tl"),
+];
+
+const AUTO: &str = "auto";
+
+/// Validate a language string and canonicalise it to BCP-47.
+///
+/// Accepts codes in any case with `-` or `_` separators, an optional
+/// encoding suffix (`"en_US.UTF-8"`), English or native language names and
+/// a few legacy aliases.  Chinese always resolves to `zh-TW` or `zh-CN` by
+/// script or region.  Empty input, `"auto"` and Deepgram's `"multi"` map to
+/// `"auto"`.  Returns `None` when the language is not one STT supports.
+pub fn normalize_language_code(input: &str) -> Option<String> {
+    let trimmed = input.trim();
+    let lower = trimmed.to_lowercase();
+    if lower.is_empty() || lower == AUTO || lower == "multi" {
+        return Some(AUTO.to_string());
+    }
+
+    if let Some(code) = lookup_name(&lower) {
+        return Some(code.to_string());
+    }
+
+    let base = lower.split('.').next().unwrap_or(&lower);
+    let mut subtags = base.split(['-', '_']);
+    let primary = subtags.next().unwrap_or("");
+    let rest: Vec<&str> = subtags.collect();
+    if rest.iter().any(|s| s.is_empty() || !s.chars().all(|c| c.is_ascii_alphanumeric())) {
+        return None;
+    }
+
+    let primary = match ALIASES.iter().find(|(alias, _)| *alias == primary) {
+        Some((_, target)) => target.split('-').next().unwrap_or(*target),
+        None => primary,
+    };
+
+    if primary == "zh" {
+        let traditional = rest.iter().any(|s| matches!(*s, "hant" | "tw" | "hk" | "mo"));
+        return Some(if traditional { "zh-TW" } else { "zh-CN" }.to_string());
+    }
+
+    if !WHISPER_LANGUAGES.iter().any(|(code, _)| *code == primary) {
+        return None;
+    }
+
+    let mut tag = primary.to_string();
+    for subtag in rest {
+        tag.push('-');
+        match subtag.len() {
+            // Region: "us" → "US"; UN M.49 numeric regions stay as-is.
+            2 if subtag.chars().all(|c| c.is_ascii_alphabetic()) => {
+                tag.push_str(&subtag.to_ascii_uppercase())
+            }
+            // Script: "latn" → "Latn".
+            4 => {
+                let mut chars = subtag.chars();
+                if let Some(first) = chars.next() {
+                    tag.push(first.to_ascii_uppercase());
+                    tag.push_str(chars.as_str());
+                }
+            }
+            _ => tag.push_str(subtag),
+        }
+    }
+    Some(tag)
+}
+
+/// Match a language name ("japanese", "日本語", "traditional chinese")
+/// against the table and aliases.
+fn lookup_name(lower: &str) -> Option<&'static str> {
+    if let Some((_, code)) = ALIASES.iter().find(|(alias, _)| *alias == lower) {
+        if alias_is_name(lower) {
+            return Some(*code);
+        }
+    }
+    WHISPER_LANGUAGES.iter().find_map(|(code, label)| {
+        let (native, english) = match label.split_once(" (") {
+            Some((native, english)) => (native, english.trim_end_matches(')')),
+            None => (*label, *label),
+        };
+        (native.to_lowercase() == lower || english.to_lowercase() == lower).then_some(*code)
+    })
+}
+
+/// Short aliases are codes and go through subtag parsing; only the longer
+/// ones are treated as whole-string names.
+fn alias_is_name(alias: &str) -> bool {
+    alias.chars().count() > 3 || !alias.is_ascii()
+}
+
+/// Languages `provider` accepts, with display names.  The first entry is
+/// always automatic detection.
+pub fn supported_languages(provider: &SttProvider) -> Vec<LanguageOption> {
+    let auto = LanguageOption { code: AUTO.to_string(), name: "Auto".to_string() };
+    let option = |(code, name): &(&str, &str)| LanguageOption {
+        code: code.to_string(),
+        name: name.to_string(),
+    };
+    let languages: Vec<LanguageOption> = match provider {
+        SttProvider::Azure => AZURE_LOCALES.iter().map(option).collect(),
+        SttProvider::Deepgram => WHISPER_LANGUAGES
+            .iter()
+            .filter(|(code, _)| DEEPGRAM_LANGUAGES.contains(code))
+            .map(option)
+            .collect(),
+        SttProvider::Groq | SttProvider::OpenAi | SttProvider::Custom => {
+            WHISPER_LANGUAGES.iter().map(option).collect()
+        }
+    };
+    std::iter::once(auto).chain(languages).collect()
+}
+
+/// Map a normalized code to the Azure locale it should be sent as:
+/// exact locale matches win, then the first locale for the language.
+/// Languages Azure does not list pass through unchanged.
+pub fn azure_locale(code: &str) -> String {
+    if let Some((locale, _)) = AZURE_LOCALES.iter().find(|(locale, _)| locale.eq_ignore_ascii_case(code)) {
+        return locale.to_string();
+    }
+    let primary = code.split('-').next().unwrap_or(code);
+    AZURE_LOCALES
+        .iter()
+        .find(|(locale, _)| locale.split('-').next() == Some(primary))
+        .map(|(locale, _)| locale.to_string())
+        .unwrap_or_else(|| code.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn norm(s: &str) -> Option<String> {
+        normalize_language_code(s)
+    }
+
+    #[test]
+    fn canonicalises_case_and_separators() {
+        assert_eq!(norm("EN").as_deref(), Some("en"));
+        assert_eq!(norm("en_us").as_deref(), Some("en-US"));
+        assert_eq!(norm(" pt-br ").as_deref(), Some("pt-BR"));
+        assert_eq!(norm("sr-latn").as_deref(), Some("sr-Latn"));
+        assert_eq!(norm("es-419").as_deref(), Some("es-419"));
+        assert_eq!(norm("en_US.UTF-8").as_deref(), Some("en-US"));
+    }
+
+    #[test]
+    fn chinese_resolves_by_script_or_region() {
+        assert_eq!(norm("zh").as_deref(), Some("zh-CN"));
+        assert_eq!(norm("zh-Hant").as_deref(), Some("zh-TW"));
+        assert_eq!(norm("zh_hk").as_deref(), Some("zh-TW"));
+        assert_eq!(norm("zh-Hans-CN").as_deref(), Some("zh-CN"));
+    }
+
+    #[test]
+    fn accepts_names_and_aliases() {
+        assert_eq!(norm("Japanese").as_deref(), Some("ja"));
+        assert_eq!(norm("日本語").as_deref(), Some("ja"));
+        assert_eq!(norm("traditional chinese").as_deref(), Some("zh-TW"));
+        assert_eq!(norm("Mandarin").as_deref(), Some("zh-CN"));
+        assert_eq!(norm("iw").as_deref(), Some("he"));
+        assert_eq!(norm("nb-NO").as_deref(), Some("no-NO"));
+    }
+
+    #[test]
+    fn auto_and_invalid() {
+        assert_eq!(norm("").as_deref(), Some("auto"));
+        assert_eq!(norm("AUTO").as_deref(), Some("auto"));
+        assert_eq!(norm("multi").as_deref(), Some("auto"));
+        assert_eq!(norm("xx"), None);
+        assert_eq!(norm("klingon"), None);
+        assert_eq!(norm("en--us"), None);
+    }
+
+    #[test]
+    fn provider_lists_start_with_auto() {
+        for provider in SttProvider::ALL {
+            let langs = supported_languages(&provider);
+            assert_eq!(langs[0].code, "auto");
+            assert!(langs.len() > 1);
+            for lang in &langs[1..] {
+                assert_eq!(norm(&lang.code).as_deref(), Some(lang.code.as_str()), "{provider:?}");
+            }
+        }
+        let deepgram = supported_languages(&SttProvider::Deepgram);
+        assert!(deepgram.iter().all(|l| l.code != "yo"));
+    }
+
+    #[test]
+    fn azure_locale_mapping() {
+        assert_eq!(azure_locale("en"), "en-US");
+        assert_eq!(azure_locale("en-GB"), "en-GB");
+        assert_eq!(azure_locale("zh-TW"), "zh-TW");
+        assert_eq!(azure_locale("ja"), "ja-JP");
+        assert_eq!(azure_locale("yo"), "yo");
+    }
+}
@@ -1,33 +1,43 @@
 mod audio;
 mod audio_devices;
 mod audio_import;
+mod capitalize;
 mod commands;
 #[cfg(feature = "diarization")]
 pub mod diarization;
 mod context_detect;
+mod continuous_session;
 mod credentials;
+mod dictation_queue;
+pub mod error;
+mod fillers;
 mod history;
 mod hotkey;
+mod languages;
+mod local_api;
 mod meeting_feeder;
 mod meeting_notes;
 mod permissions;
 pub mod platform;
 pub mod models;
 mod polisher;
+mod proxy;
+mod retry;
 mod qwen3_asr;
 pub mod settings;
 pub mod system_info;
 mod whisper_streaming;
 pub mod stt;
+mod text_normalize;
 mod transcribe;
 #[cfg(feature = "diarization")]
 mod vbx;
 pub mod whisper_models;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{
     atomic::{AtomicBool, AtomicU64, Ordering},
-    Arc, Condvar, Mutex,
+    Arc, Condvar, Mutex, RwLock,
 };
 use std::time::Instant;
 use tauri::{
@@ -40,12 +50,11 @@ use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut,
 use unicode_segmentation::UnicodeSegmentation;
 
 use commands::get_cached_api_key;
+use dictation_queue::{DictationQueue, QueuedDictation};
 use hotkey::{hotkey_display_label, parse_hotkey_string};
-use settings::{load_settings, models_dir, history_dir, audio_dir, logs_dir, Settings};
+use settings::{load_settings, models_dir, history_dir, audio_dir, logs_dir, EditScope, Settings};
 use stt::{SttConfig, SttMode};
 
-const MAX_RECORDING_SECS: u64 = 120;
-
 /// Convert Simplified Chinese → Traditional Chinese when the configured
 /// language targets a Traditional Chinese locale. No-op for all other locales.
 pub(crate) fn maybe_convert_zh(text: &str, language: &str) -> String {
@@ -63,6 +72,11 @@ pub(crate) fn maybe_convert_zh(text: &str, language: &str) -> String {
 pub struct AppState {
     pub is_recording: Arc<AtomicBool>,
     pub is_processing: AtomicBool,
+    /// When `is_processing` last went true; read by the processing watchdog.
+    pub processing_since: Mutex<Option<Instant>>,
+    /// Length of the audio the running pipeline transcribes, in milliseconds;
+    /// extends the watchdog's budget for long recordings.
+    pub processing_audio_ms: AtomicU64,
     pub buffer: Arc<Mutex<Vec<f32>>>,
     pub sample_rate: Mutex<Option<u32>>,
     pub settings: Mutex<Settings>,
@@ -74,14 +88,24 @@ pub struct AppState {
     pub test_mode: AtomicBool,
     pub voice_rule_mode: AtomicBool,
     pub last_hotkey_time: Mutex<Instant>,
-    pub http_client: reqwest::blocking::Client,
+    /// Time of the last hotkey press event, including repeats that were dropped.
+    pub last_hotkey_event: Mutex<Instant>,
+    /// Ids of shortcuts pressed and not yet released; a press of one of them is auto-repeat.
+    pub hotkeys_held: Mutex<HashSet<u32>>,
+    /// Shared client for cloud requests; read it through [`AppState::http_client`].
+    pub shared_http_client: RwLock<reqwest::blocking::Client>,
     pub api_key_cache: Mutex<HashMap<String, String>>,
     pub edit_mode: AtomicBool,
     pub edit_selected_text: Mutex<Option<String>>,
     pub edit_text_override: Mutex<Option<String>>,
     pub saved_clipboard: Mutex<Option<String>>,
     pub vad_ctx: Mutex<Option<transcribe::VadContextCache>>,
+    /// True while any model download is in flight (mirrors
+    /// `!downloads_in_flight.is_empty()`); guards model delete/switch.
     pub downloading: AtomicBool,
+    /// In-flight downloads keyed by target filename, so the same file is
+    /// never downloaded twice concurrently.
+    pub downloads_in_flight: Mutex<HashMap<String, commands::DownloadProgress>>,
     pub audio_thread: Mutex<Option<audio::AudioThreadControl>>,
     pub qwen3_asr_ctx: Mutex<Option<qwen3_asr::Qwen3AsrCache>>,
     /// Condvar/flag pair used by `warm_qwen3_asr` (producer) and
@@ -96,6 +120,35 @@ pub struct AppState {
     pub streaming_active: AtomicBool,
     pub streaming_cancelled: AtomicBool,
     pub streaming_result: Mutex<Option<String>>,
+    /// Confidence score of the most recent cloud transcription (verbose mode only).
+    /// Written by `do_stop_recording`, consumed when the history entry is saved.
+    pub last_stt_confidence: Mutex<Option<f64>>,
+    /// Cancellation token of the in-flight transcribe/polish pipeline.  Each
+    /// pipeline installs a fresh token so `cancel_processing` only ever
+    /// aborts the run that was active when it was invoked.
+    pub processing_cancel: Mutex<Option<Arc<AtomicBool>>>,
+    /// Set while a finished dictation waits out `paste_grace_ms`;
+    /// `cancel_recording` flips it to skip the paste.
+    pub paste_grace: Mutex<Option<Arc<AtomicBool>>>,
+    /// Escape is registered as the global cancel shortcut; see
+    /// [`arm_cancel_shortcut`].
+    pub cancel_shortcut_armed: Mutex<bool>,
+    /// Chunk worker of the continuous dictation in progress, if any.
+    pub continuous_session: Mutex<continuous_session::ContinuousSessions>,
+    /// Dictations recorded while another was processing (`queue_dictations`).
+    pub dictation_queue: Mutex<DictationQueue>,
+    /// The current recording started while the pipeline was busy; stopping
+    /// it queues the audio instead of running the pipeline directly.
+    pub recording_queued: AtomicBool,
+    /// A stop has claimed the current recording but its pipeline has not
+    /// taken the audio out of the buffer yet.  A queued recording must not
+    /// start before then.
+    pub stop_pending: AtomicBool,
+    /// One-shot polish model for the next dictation (`set_next_polish_model`).
+    /// Taken by the pipeline that stops it; never persisted.
+    pub next_polish_model: Mutex<Option<String>>,
+    /// `context-unavailable` was already sent this session.
+    pub context_warning_sent: AtomicBool,
     /// Condvar used to wake feeder threads early when `is_recording` is set to
     /// false. Replaces a fixed `thread::sleep(2000 ms)` with an interruptible
     /// `wait_timeout(2000 ms)`, eliminating up to 2 s of unnecessary latency
@@ -145,6 +198,14 @@ pub struct AppState {
     pub registered_edit_shortcut: Mutex<Option<Shortcut>>,
     /// Cached `Shortcut` for the meeting hotkey. Same rationale as above.
     pub registered_meeting_shortcut: Mutex<Option<Shortcut>>,
+    /// Cached Shift-variant of the primary hotkey ("paste raw"), registered
+    /// only while `paste_raw_on_modifier` is enabled.
+    pub registered_raw_shortcut: Mutex<Option<Shortcut>>,
+    /// Cached `Shortcut` for the repeat-paste hotkey. Same rationale as above.
+    pub registered_repeat_shortcut: Mutex<Option<Shortcut>>,
+    /// Set when the current recording was started or stopped with the
+    /// paste-raw shortcut; the pipeline then skips polish.
+    pub paste_raw_pending: AtomicBool,
     /// Set to `true` when we sent a Play/Pause media key at recording start.
     /// Cleared (and play/pause key sent again) when recording ends, so the
     /// user's music resumes automatically.  Guards against spuriously resuming
@@ -153,16 +214,49 @@ pub struct AppState {
     /// Timestamp of the last recording end. Used by the idle mic watcher to
     /// determine when to close the mic stream.
     pub last_recording_end: Mutex<Option<Instant>>,
+    /// True while the settings mic level monitor is running; keeps the idle
+    /// mic watcher from pausing the stream under it.
+    pub mic_monitor_active: AtomicBool,
+    /// Timestamp of the last use of the local models (dictation, import,
+    /// meeting, polish test or re-polish). Used by the idle model-unload
+    /// watcher to decide when to free Whisper/LLM memory.
+    pub last_model_use: Mutex<Option<Instant>>,
     /// True while an audio file import is running.
     pub import_active: AtomicBool,
     /// Set to true to cancel a running audio file import.
     pub import_cancelled: AtomicBool,
+    /// The running local HTTP API server, if enabled.
+    pub local_api: Mutex<Option<local_api::ServerHandle>>,
     /// Optional speaker diarization engine (WeSpeaker ONNX).
     /// Loaded at meeting start when diarization model files are present.
     #[cfg(feature = "diarization")]
     pub diarization_ctx: Mutex<Option<diarization::DiarizationEngine>>,
 }
 
+impl AppState {
+    /// The shared client for cloud STT and polish requests (cheap to clone).
+    pub fn http_client(&self) -> reqwest::blocking::Client {
+        match self.shared_http_client.read() {
+            Ok(client) => client.clone(),
+            Err(e) => e.into_inner().clone(),
+        }
+    }
+
+    /// Restart the idle model-unload clock: the local models were just used.
+    pub fn touch_model_use(&self) {
+        if let Ok(mut t) = self.last_model_use.lock() {
+            *t = Some(Instant::now());
+        }
+    }
+
+    /// Rebuild the shared client after the proxy setting changed.
+    pub fn rebuild_http_client(&self) {
+        if let Ok(mut client) = self.shared_http_client.write() {
+            *client = proxy::shared_client();
+        }
+    }
+}
+
 /// Emit a `"transcription-partial"` event to the overlay window.
 ///
 /// Used by all feeder loops (Qwen3-ASR normal/meeting, Whisper preview/meeting)
@@ -187,41 +281,125 @@ pub(crate) fn emit_transcription_partial(app: &AppHandle, text: &str) {
 ///
 /// Uses `NSScreen.mainScreen` (the screen with the active keyboard focus) so the
 /// capsule always appears on the same screen as the user's frontmost app.
-/// Falls back to the overlay's current monitor if the platform call is unavailable.
+/// Where the platform call is unavailable, falls back to the monitor under the
+/// cursor (see [`overlay_target_monitor`]).  Also sizes the window for the
+/// configured `overlay_style` and tells the overlay which style to render.
 fn center_overlay_bottom(overlay: &tauri::WebviewWindow) {
-    const WIN_W: f64 = 300.0;
-    const WIN_H: f64 = 40.0;
     const MARGIN_BOTTOM: f64 = 80.0;
 
+    let style = overlay
+        .state::<AppState>()
+        .settings
+        .lock()
+        .map(|s| s.overlay_style)
+        .unwrap_or_default();
+    let (win_w, win_h) = style.window_size();
+    let _ = overlay.set_size(tauri::LogicalSize::new(win_w, win_h));
+    let _ = overlay.emit("overlay-style", style);
+
     if let Some((sx, sy, sw, sh, scale)) = platform::focused_screen_logical_frame() {
-        let x = sx + (sw - WIN_W) / 2.0;
-        let y = sy + sh - WIN_H - MARGIN_BOTTOM;
+        let x = sx + (sw - win_w) / 2.0;
+        let y = sy + sh - win_h - MARGIN_BOTTOM;
         let _ = overlay.set_position(tauri::PhysicalPosition::new(
             (x * scale) as i32,
             (y * scale) as i32,
         ));
-    } else if let Ok(Some(monitor)) = overlay.current_monitor() {
+    } else if let Some(monitor) = overlay_target_monitor(overlay) {
+        let origin = monitor.position();
         let screen = monitor.size();
         let scale = monitor.scale_factor();
-        let x = (screen.width as f64 / scale - WIN_W) / 2.0;
-        let y = screen.height as f64 / scale - WIN_H - MARGIN_BOTTOM;
+        let x = (screen.width as f64 / scale - win_w) / 2.0;
+        let y = screen.height as f64 / scale - win_h - MARGIN_BOTTOM;
         let _ = overlay.set_position(tauri::PhysicalPosition::new(
-            (x * scale) as i32,
-            (y * scale) as i32,
+            origin.x + (x * scale) as i32,
+            origin.y + (y * scale) as i32,
         ));
     }
 }
 
+/// The monitor the overlay should appear on when the platform cannot report
+/// the focused screen: the one under the cursor, which is where the user is
+/// working.  `current_monitor()` is only a last resort because it reports
+/// the screen the overlay was last shown on, not the active one.
+fn overlay_target_monitor(overlay: &tauri::WebviewWindow) -> Option<tauri::Monitor> {
+    if let Ok(cursor) = overlay.cursor_position() {
+        if let Ok(Some(monitor)) = overlay.monitor_from_point(cursor.x, cursor.y) {
+            return Some(monitor);
+        }
+    }
+    overlay
+        .current_monitor()
+        .ok()
+        .flatten()
+        .or_else(|| overlay.primary_monitor().ok().flatten())
+}
+
 /// Restore original clipboard content from saved_clipboard.
 fn restore_clipboard(state: &AppState) {
     if let Ok(mut saved) = state.saved_clipboard.lock() {
         if let Some(original) = saved.take() {
-            std::thread::sleep(std::time::Duration::from_millis(200));
-            if let Ok(mut clipboard) = arboard::Clipboard::new() {
-                let _ = clipboard.set_text(&original);
-            }
+            restore_clipboard_text(&original);
+        }
+    }
+}
+
+/// Put `original` back on the clipboard after a short delay, so it lands
+/// after any copy/paste keystrokes still in flight.
+pub(crate) fn restore_clipboard_text(original: &str) {
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    if let Ok(mut clipboard) = arboard::Clipboard::new() {
+        let _ = clipboard.set_text(original);
+    }
+}
+
+/// Hold an auto-paste for `grace_ms` while the overlay shows `text`.
+/// Returns true if the user cancelled (or the pipeline was cancelled) in
+/// the meantime, in which case the caller should only copy.
+fn wait_paste_grace(
+    app: &AppHandle,
+    state: &AppState,
+    cancel: &AtomicBool,
+    text: &str,
+    grace_ms: u64,
+) -> bool {
+    let token = Arc::new(AtomicBool::new(false));
+    if let Ok(mut slot) = state.paste_grace.lock() {
+        *slot = Some(Arc::clone(&token));
+    }
+    if let Some(overlay) = app.get_webview_window("overlay") {
+        let _ = overlay.emit("paste-pending", serde_json::json!({ "text": text, "grace_ms": grace_ms }));
+    }
+    let deadline = Instant::now() + std::time::Duration::from_millis(grace_ms);
+    while Instant::now() < deadline && !token.load(Ordering::SeqCst) && !cancel.load(Ordering::SeqCst) {
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    if let Ok(mut slot) = state.paste_grace.lock() {
+        if slot.as_ref().is_some_and(|t| Arc::ptr_eq(t, &token)) {
+            *slot = None;
         }
     }
+    let skipped = token.load(Ordering::SeqCst);
+    if skipped {
+        tracing::info!("📋 Paste skipped during grace period; text left on clipboard");
+    }
+    skipped || cancel.load(Ordering::SeqCst)
+}
+
+/// Forward a queued corrupt-model notice from the polisher to the main window
+/// so it can offer a re-download.
+pub(crate) fn emit_polish_model_corrupt(app: &AppHandle) {
+    if let Some(notice) = polisher::take_corrupt_model_notice() {
+        let _ = app.emit("polish-model-corrupt", &notice);
+    }
+}
+
+/// Hide the overlay, telling it first that the session is over.
+///
+/// The terminal `"idle"` status gives the frontend one deterministic reset
+/// point, so a stale phase cannot survive a hide it did not notice.
+pub(crate) fn hide_overlay_idle(overlay: &tauri::WebviewWindow) {
+    let _ = overlay.emit("recording-status", "idle");
+    platform::hide_overlay(overlay);
 }
 
 /// Hide overlay after a delay (in ms). 0 means hide immediately.
@@ -231,21 +409,41 @@ fn hide_overlay_delayed(app: &AppHandle, delay_ms: u64) {
         if delay_ms > 0 {
             std::thread::sleep(std::time::Duration::from_millis(delay_ms));
         }
+        // A queued recording, or a queued dictation about to run, owns the overlay now.
+        let state = app_handle.state::<AppState>();
+        let queued_pending = state.dictation_queue.lock().map(|q| !q.is_empty()).unwrap_or(false);
+        if state.recording_queued.load(Ordering::SeqCst) || queued_pending {
+            return;
+        }
         let app_for_hide = app_handle.clone();
         let _ = app_handle.run_on_main_thread(move || {
             if let Some(overlay) = app_for_hide.get_webview_window("overlay") {
-                platform::hide_overlay(&overlay);
+                hide_overlay_idle(&overlay);
             }
         });
     });
 }
 
-/// Emit 'preparing' to reset overlay phase, then hide via delayed path.
+/// Hide the overlay after a dictation or edit result per `overlay_auto_hide_ms`.
+///
+/// `min_ms` keeps time-boxed affordances (the edit undo window) reachable when
+/// the configured delay is shorter.  A setting of 0 leaves the overlay up until
+/// the `dismiss_overlay` command hides it.
+fn hide_overlay_after_result(app: &AppHandle, state: &AppState, min_ms: u64) {
+    let delay_ms = state.settings.lock().map(|s| s.overlay_auto_hide_ms).unwrap_or(1500);
+    if delay_ms == 0 {
+        tracing::debug!("Overlay kept visible until dismissed");
+        return;
+    }
+    hide_overlay_delayed(app, delay_ms.max(min_ms));
+}
+
+/// Emit 'idle' to reset overlay phase, then hide via delayed path.
 fn reset_and_hide_overlay(app: &AppHandle) {
     if let Some(overlay) = app.get_webview_window("overlay") {
-        let _ = overlay.emit("recording-status", "preparing");
+        let _ = overlay.emit("recording-status", "idle");
     }
-    // Emit 'preparing' first so the WebView can process the phase reset before
+    // Emit 'idle' first so the WebView can process the phase reset before
     // the window becomes invisible.  hide_overlay_delayed(0) schedules the
     // actual hide on the main thread via a freshly spawned OS thread, which
     // gives the run loop one extra iteration to deliver the IPC message.
@@ -254,17 +452,623 @@ fn reset_and_hide_overlay(app: &AppHandle) {
     hide_overlay_delayed(app, 0);
 }
 
+/// Register Shift + primary hotkey as the "paste raw" shortcut when
+/// `paste_raw_on_modifier` is enabled, and cache it for the handler.
+///
+/// Global-shortcut events carry no live modifier state — the OS matches the
+/// exact key combination (on macOS via Carbon `RegisterEventHotKey`) — so a
+/// held Shift cannot be detected on the primary shortcut's event.  A second
+/// registered shortcut is the only portable way to tell the two apart.
+/// Call after the other shortcuts are (re-)registered.
+pub(crate) fn register_raw_paste_shortcut(app: &AppHandle, state: &AppState, settings: &Settings) {
+    let mut raw = None;
+    if settings.paste_raw_on_modifier {
+        if let Some(variant) = hotkey::shift_variant(&settings.hotkey) {
+            let conflicts = [&settings.edit_hotkey, &settings.meeting_hotkey, &settings.repeat_paste_hotkey]
+                .into_iter()
+                .flatten()
+                .any(|hk| hotkey::same_hotkey(hk, &variant));
+            if conflicts {
+                tracing::warn!("Paste-raw shortcut {} conflicts with another hotkey; not registered", variant);
+            } else if let Some(shortcut) = parse_hotkey_string(&variant) {
+                match app.global_shortcut().register(shortcut) {
+                    Ok(()) => {
+                        tracing::info!("{} paste-raw shortcut registered", hotkey_display_label(&variant));
+                        raw = Some(shortcut);
+                    }
+                    Err(e) => tracing::warn!("Failed to register paste-raw shortcut: {}", e),
+                }
+            }
+        } else {
+            tracing::warn!("Primary hotkey already uses Shift; paste-raw shortcut unavailable");
+        }
+    }
+    if let Ok(mut cached) = state.registered_raw_shortcut.lock() {
+        *cached = raw;
+    }
+}
+
+/// Register the repeat-paste hotkey if one is configured, and cache it for
+/// the handler.  Call after the other shortcuts are (re-)registered.
+pub(crate) fn register_repeat_paste_shortcut(app: &AppHandle, state: &AppState, settings: &Settings) {
+    let mut repeat = None;
+    if let Some(ref hk) = settings.repeat_paste_hotkey {
+        if let Some(shortcut) = parse_hotkey_string(hk) {
+            match app.global_shortcut().register(shortcut) {
+                Ok(()) => {
+                    tracing::info!("{} repeat-paste shortcut registered", hotkey_display_label(hk));
+                    repeat = Some(shortcut);
+                }
+                Err(e) => tracing::warn!("Failed to register repeat-paste shortcut: {}", e),
+            }
+        }
+    }
+    if let Ok(mut cached) = state.registered_repeat_shortcut.lock() {
+        *cached = repeat;
+    }
+}
+
+/// Escape cancels the dictation in progress.  The overlay never takes focus,
+/// so it is a global shortcut, registered only while there is something to
+/// cancel so Escape keeps working in other apps the rest of the time.
+fn cancel_shortcut() -> Shortcut {
+    Shortcut::new(None, Code::Escape)
+}
+
+/// Recording (outside a meeting), stopping, processing, waiting out the
+/// paste grace period or queued.
+fn dictation_cancellable(state: &AppState) -> bool {
+    (state.is_recording.load(Ordering::SeqCst) && !state.meeting_active.load(Ordering::SeqCst))
+        || state.stop_pending.load(Ordering::SeqCst)
+        || dictation_busy(state)
+}
+
+/// Register the cancel shortcut for the dictation that just started.  A
+/// watcher unregisters it once [`dictation_cancellable`] no longer holds.
+/// Skipped when Escape is one of the configured hotkeys.
+pub(crate) fn arm_cancel_shortcut(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let conflicts = state.settings.lock().map(|s| cancel_shortcut_conflicts(&s)).unwrap_or(true);
+    if conflicts {
+        return;
+    }
+    let Ok(mut armed) = state.cancel_shortcut_armed.lock() else { return };
+    if *armed {
+        return;
+    }
+    if let Err(e) = app.global_shortcut().register(cancel_shortcut()) {
+        tracing::warn!("Failed to register the cancel shortcut: {}", e);
+        return;
+    }
+    *armed = true;
+    drop(armed);
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let state = app.state::<AppState>();
+        loop {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            // Decided under the lock so a dictation starting now re-arms
+            // after the unregister instead of being left without Escape.
+            let Ok(mut armed) = state.cancel_shortcut_armed.lock() else { return };
+            if !dictation_cancellable(&state) {
+                if *armed {
+                    if let Err(e) = app.global_shortcut().unregister(cancel_shortcut()) {
+                        tracing::warn!("Failed to unregister the cancel shortcut: {}", e);
+                    }
+                    *armed = false;
+                }
+                return;
+            }
+        }
+    });
+}
+
+/// Whether Escape is one of the hotkeys configured in `settings`.
+fn cancel_shortcut_conflicts(settings: &Settings) -> bool {
+    [
+        Some(&settings.hotkey),
+        settings.edit_hotkey.as_ref(),
+        settings.meeting_hotkey.as_ref(),
+        settings.repeat_paste_hotkey.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    .any(|hk| parse_hotkey_string(hk) == Some(cancel_shortcut()))
+}
+
+/// Register the cancel shortcut again after every shortcut was dropped
+/// (hotkey change, wake), if a dictation had it armed.  Disarmed instead
+/// when Escape is now a configured hotkey, so the watcher leaves it alone.
+pub(crate) fn restore_cancel_shortcut(app: &AppHandle, settings: &Settings) {
+    let state = app.state::<AppState>();
+    let Ok(mut armed) = state.cancel_shortcut_armed.lock() else { return };
+    if !*armed {
+        return;
+    }
+    if cancel_shortcut_conflicts(settings) {
+        *armed = false;
+        return;
+    }
+    if let Err(e) = app.global_shortcut().register(cancel_shortcut()) {
+        tracing::warn!("Failed to restore the cancel shortcut: {}", e);
+        *armed = false;
+    }
+}
+
+/// The cancel shortcut was pressed: stop the recording (or skip the pending
+/// paste) if there is one, otherwise abort the pipeline.
+fn cancel_from_shortcut(app: &AppHandle, state: &AppState) {
+    let paste_pending = state.paste_grace.lock().map(|slot| slot.is_some()).unwrap_or(false);
+    if state.is_recording.load(Ordering::SeqCst) || paste_pending {
+        commands::cancel_recording(app.clone(), app.state());
+    } else if state.is_processing.load(Ordering::SeqCst) {
+        if let Err(e) = commands::cancel_processing(app.clone(), app.state()) {
+            tracing::debug!("Cancel shortcut: {}", e);
+        }
+    }
+}
+
+/// Put the newest history transcript back on the clipboard and paste it.
+/// No recording, no STT: for filling the same text into several fields.
+fn repeat_last_paste(plain_only: bool) {
+    let Some(text) = history::latest_text(&history_dir()) else {
+        tracing::info!("Repeat paste: history is empty");
+        return;
+    };
+    if let Err(e) = platform::set_clipboard_text(&text, plain_only) {
+        tracing::error!("Clipboard error: {}", e);
+        return;
+    }
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    if permissions::accessibility_trusted() && platform::simulate_paste() {
+        tracing::info!("📋 Repeat-pasted last transcript ({} graphemes)", text.graphemes(true).count());
+    } else {
+        tracing::info!("📋 Last transcript copied to clipboard (paste unavailable)");
+    }
+}
+
+/// Start a dictation the way the primary hotkey does, for the local HTTP API.
+/// Edit mode, live previews and model pre-warming are hotkey-only.
+pub(crate) fn start_recording_from_api(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    if state.meeting_active.load(Ordering::SeqCst) {
+        return Err("meeting_mode_active".to_string());
+    }
+    if state.is_recording.load(Ordering::SeqCst) {
+        return Err("already_recording".to_string());
+    }
+    if state.is_processing.load(Ordering::SeqCst) {
+        return Err("busy".to_string());
+    }
+
+    let captured_ctx = state
+        .context_override
+        .lock()
+        .ok()
+        .and_then(|ctx| ctx.clone())
+        .unwrap_or_else(context_detect::detect_frontmost_app);
+    let preferred_device = state.settings.lock().ok().and_then(|s| s.mic_device.clone());
+
+    if let Some(overlay) = app.get_webview_window("overlay") {
+        let _ = overlay.emit("recording-status", "preparing");
+        center_overlay_bottom(&overlay);
+        platform::show_overlay(&overlay);
+    }
+    if platform::is_now_playing() {
+        platform::pause_now_playing();
+        state.media_paused_by_sumi.store(true, Ordering::SeqCst);
+    }
+
+    if let Err(e) = audio::do_start_recording(
+        &state.is_recording,
+        &state.mic_available,
+        &state.reconnecting,
+        &state.sample_rate,
+        &state.buffer,
+        &state.is_recording,
+        &state.audio_thread,
+        preferred_device,
+    ) {
+        tracing::error!("Failed to start recording from local API: {}", e);
+        if state.media_paused_by_sumi.swap(false, Ordering::SeqCst) {
+            platform::resume_now_playing();
+        }
+        if let Some(overlay) = app.get_webview_window("overlay") {
+            let _ = overlay.emit("recording-status", "error");
+        }
+        hide_overlay_delayed(app, 1500);
+        return Err(e);
+    }
+    tracing::info!("🎙️ Recording started from local API (app: {:?})", captured_ctx.app_name);
+    report_mic_fallback(app, &state);
+
+    if let Ok(mut ctx) = state.captured_context.lock() {
+        *ctx = Some(captured_ctx);
+    }
+    if let Some(main_win) = app.get_webview_window("main") {
+        let _ = main_win.emit("hotkey-activated", true);
+    }
+    if let Some(overlay) = app.get_webview_window("overlay") {
+        let _ = overlay.emit("recording-status", "recording");
+        let _ = overlay.emit("recording-max-duration", max_recording_secs(&state));
+    }
+    arm_cancel_shortcut(app);
+    spawn_audio_level_monitor(app.clone(), AudioMonitorMode::Normal);
+    Ok(())
+}
+
+/// Install a fresh cancellation token for the pipeline that is about to run
+/// on `audio_secs` of audio.
+fn begin_processing(state: &AppState, audio_secs: f64) -> Arc<AtomicBool> {
+    let token = Arc::new(AtomicBool::new(false));
+    if let Ok(mut slot) = state.processing_cancel.lock() {
+        *slot = Some(Arc::clone(&token));
+    }
+    state
+        .processing_audio_ms
+        .store((audio_secs * 1000.0) as u64, Ordering::SeqCst);
+    token
+}
+
+/// Seconds of audio waiting in the recording buffer.
+fn buffered_audio_secs(state: &AppState) -> f64 {
+    let sample_rate = state.sample_rate.lock().ok().and_then(|r| *r).unwrap_or(44100);
+    let len = state.buffer.lock().map(|b| b.len()).unwrap_or(0);
+    len as f64 / sample_rate.max(1) as f64
+}
+
+/// Clear the processing state so the next hotkey press is accepted again.
+fn reset_processing(app: &AppHandle, state: &AppState) {
+    // Make a pipeline that is still running discard its result.
+    if let Ok(mut slot) = state.processing_cancel.lock() {
+        if let Some(token) = slot.take() {
+            token.store(true, Ordering::SeqCst);
+        }
+    }
+    state.edit_mode.store(false, Ordering::SeqCst);
+    state.stop_pending.store(false, Ordering::SeqCst);
+    state.is_processing.store(false, Ordering::SeqCst);
+    if let Ok(mut since) = state.processing_since.lock() {
+        *since = None;
+    }
+    reset_and_hide_overlay(app);
+}
+
+/// Held by a pipeline thread for its whole run: if the thread unwinds, the
+/// processing state is reset instead of leaving every later hotkey press
+/// ignored.  A continuous-session chunk holds none of that state; its
+/// worker logs the panic and moves on.
+struct PanicGuard<'a> {
+    app: &'a AppHandle,
+    chunk: bool,
+}
+
+impl Drop for PanicGuard<'_> {
+    fn drop(&mut self) {
+        if std::thread::panicking() && !self.chunk {
+            tracing::error!("Processing pipeline panicked — resetting state");
+            reset_processing(self.app, &self.app.state::<AppState>());
+        }
+    }
+}
+
+/// Remove `token` from the shared slot unless a newer pipeline replaced it.
+fn end_processing(state: &AppState, token: &Arc<AtomicBool>) {
+    if let Ok(mut slot) = state.processing_cancel.lock() {
+        if slot.as_ref().is_some_and(|t| Arc::ptr_eq(t, token)) {
+            *slot = None;
+        }
+    }
+}
+
+/// Exit of a pipeline stopped by `cancel_processing`.  The command already
+/// hid the overlay; the pipeline releases `is_processing` only here, once it
+/// has actually stopped.  A continuous-session chunk never held it.
+fn end_cancelled_processing(state: &AppState, token: &Arc<AtomicBool>, is_chunk: bool) {
+    end_processing(state, token);
+    if !is_chunk {
+        state.is_processing.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Tell the main window when the chosen input device was missing and the
+/// stream opened on the system default instead.  The setting is kept so the
+/// device is used again once it is reconnected.
+pub(crate) fn report_mic_fallback(app: &AppHandle, state: &AppState) {
+    if let Some((requested, actual)) = audio::take_device_fallback(&state.audio_thread) {
+        tracing::warn!("Input device {:?} unavailable, recording from {:?}", requested, actual);
+        let _ = app.emit(
+            "mic-device-changed",
+            serde_json::json!({ "requested": requested, "actual": actual }),
+        );
+    }
+}
+
+/// Stop the recording in progress from a hotkey press (or, in push-to-talk
+/// mode, a release).  The raw-paste hotkey also skips polishing.
+fn stop_recording_from_hotkey(app: &AppHandle, state: &AppState, raw: bool) {
+    if raw {
+        state.paste_raw_pending.store(true, Ordering::SeqCst);
+    }
+    if state.edit_mode.load(Ordering::SeqCst) {
+        stop_edit_and_replace(app);
+    } else {
+        stop_transcribe_and_paste(app);
+    }
+}
+
+/// `Settings::max_recording_secs`, also sent to the overlay as
+/// `recording-max-duration` for its elapsed-time indicator.
+fn max_recording_secs(state: &AppState) -> u64 {
+    state
+        .settings
+        .lock()
+        .map(|s| s.max_recording_secs)
+        .unwrap_or(120)
+}
+
+/// The app context captured when the current recording started.
+fn captured_context(state: &AppState) -> context_detect::AppContext {
+    state
+        .captured_context
+        .lock()
+        .ok()
+        .and_then(|c| c.clone())
+        .unwrap_or_default()
+}
+
+/// Tell the main window, once per session, that no app context could be
+/// detected while prompt rules depend on it; usually a missing Accessibility
+/// permission.  Without the notice rules silently never match.
+fn warn_if_context_unavailable(
+    app: &AppHandle,
+    state: &AppState,
+    context: &context_detect::AppContext,
+    polish_config: &polisher::PolishConfig,
+) {
+    if !context.is_empty() || !polisher::uses_app_context(polish_config) {
+        return;
+    }
+    if state.context_warning_sent.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tracing::warn!("App context detection returned nothing; prompt rules cannot match");
+    let _ = app.emit("context-unavailable", ());
+}
+
+/// Resolve the STT language for this recording.
+///
+/// Matches prompt rules against the context captured at hotkey press so a
+/// rule's `stt_language_override` applies before transcription. The cloud
+/// language is updated too, since cloud providers read it from `stt_config`.
+fn resolve_stt_language(
+    context: &context_detect::AppContext,
+    polish_config: &polisher::PolishConfig,
+    stt_config: &mut SttConfig,
+) -> String {
+    match polisher::stt_language_override(polish_config, context) {
+        Some(lang) => {
+            tracing::info!("STT language override from prompt rule: {:?} → {:?}", stt_config.language, lang);
+            stt_config.cloud.language = lang.clone();
+            lang
+        }
+        None => stt_config.language.clone(),
+    }
+}
+
+/// Audio that a run of the dictation pipeline transcribes.
+enum Capture {
+    /// Stop the recording and take everything it captured.
+    StopRecording,
+    /// A stretch of a continuous session cut at a pause; recording goes on.
+    Chunk { samples: Vec<f32>, sample_rate: u32 },
+    /// A recording that waited in the dictation queue.
+    Queued(QueuedDictation),
+}
+
+/// How long a chunk's result stays on the overlay before it returns to
+/// the recording state.
+const CHUNK_RESULT_LINGER_MS: u64 = 600;
+
+/// Only transcripts this recent are quoted by `context_from_history`; older
+/// ones belong to a different dictation.
+const HISTORY_CONTEXT_MAX_AGE: std::time::Duration = std::time::Duration::from_secs(30 * 60);
+
+/// Cut the audio recorded since the last pause and queue it for the session
+/// worker, starting one on the first chunk.  Chunks never take
+/// `is_processing`, so the hotkey stays live and a second press ends the
+/// session.
+fn commit_continuous_chunk(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let Ok(mut sessions) = state.continuous_session.lock() else {
+        return;
+    };
+    // Checked under the session lock: once a stop has claimed the pipeline,
+    // whatever is left in the buffer belongs to its final stretch.
+    if state.is_processing.load(Ordering::SeqCst) || !state.is_recording.load(Ordering::SeqCst) {
+        return;
+    }
+    let Some((samples, sample_rate)) = audio::take_recorded_chunk(&state) else {
+        return;
+    };
+    tracing::info!(
+        "Continuous dictation: committing {:.2}s chunk",
+        samples.len() as f64 / sample_rate as f64
+    );
+    if let Some(overlay) = app.get_webview_window("overlay") {
+        let _ = overlay.emit("recording-status", "transcribing");
+    }
+    let worker_app = app.clone();
+    sessions.commit(samples, sample_rate, move |samples, sample_rate, cancel| {
+        run_dictation_pipeline(&worker_app, Capture::Chunk { samples, sample_rate }, Arc::clone(cancel));
+    });
+}
+
+/// Close the continuous session, if any, and wait until its queued chunks
+/// have been pasted (and a cancelled session's worker has exited).
+fn finish_continuous_session(state: &AppState) {
+    let workers = state.continuous_session.lock().map(|mut s| s.finish()).unwrap_or_default();
+    for worker in workers {
+        let _ = worker.join();
+    }
+}
+
+/// Whether a new dictation would have to wait: the pipeline is running or
+/// earlier recordings are still queued.
+fn dictation_busy(state: &AppState) -> bool {
+    let processing = state.is_processing.load(Ordering::SeqCst);
+    state
+        .dictation_queue
+        .lock()
+        .map(|q| q.has_work_ahead(processing))
+        .unwrap_or(processing)
+}
+
+/// Drop every dictation still waiting in the queue; called when the user
+/// cancels, so nothing recorded before the cancel pastes afterwards.
+pub(crate) fn clear_dictation_queue(state: &AppState) {
+    if let Ok(mut queue) = state.dictation_queue.lock() {
+        let dropped = queue.clear();
+        if dropped > 0 {
+            tracing::info!("Dropped {} queued dictation(s)", dropped);
+        }
+    }
+}
+
+/// Whether a press while busy may start a queued recording.  Continuous
+/// sessions paste chunks on their own worker and are never queued.
+fn queue_dictations_enabled(state: &AppState) -> bool {
+    state
+        .settings
+        .lock()
+        .map(|s| s.queue_dictations && s.session_mode == settings::SessionMode::Single)
+        .unwrap_or(false)
+}
+
+/// Stop a recording that began while the pipeline was busy and queue its
+/// audio behind the dictations ahead of it.  Returns false when nothing is
+/// ahead any more, so the caller can take the normal stop path.
+fn enqueue_dictation(app: &AppHandle, state: &AppState) -> bool {
+    let Ok(mut queue) = state.dictation_queue.lock() else {
+        return false;
+    };
+    if !queue.has_work_ahead(state.is_processing.load(Ordering::SeqCst)) {
+        return false;
+    }
+    if state
+        .is_recording
+        .compare_exchange(true, false, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return false;
+    }
+    if let Ok(mut t) = state.last_recording_end.lock() {
+        *t = Some(Instant::now());
+    }
+    if state.media_paused_by_sumi.swap(false, Ordering::SeqCst) {
+        platform::resume_now_playing();
+    }
+    let paste_raw = state.paste_raw_pending.swap(false, Ordering::SeqCst);
+    let (samples, sample_rate) = audio::take_recorded_chunk(state).unwrap_or_default();
+    let context = state
+        .captured_context
+        .lock()
+        .ok()
+        .and_then(|c| c.clone())
+        .unwrap_or_default();
+    let polish_model = state.next_polish_model.lock().ok().and_then(|mut m| m.take());
+    let job = QueuedDictation { samples, sample_rate, context, paste_raw, polish_model };
+    let spawn_worker = match queue.push(job) {
+        dictation_queue::Push::Dropped => {
+            tracing::info!("Queued recording captured no audio, dropping it");
+            return true;
+        }
+        dictation_queue::Push::Queued { spawn_worker } => spawn_worker,
+    };
+    tracing::info!("⏳ Dictation queued ({} waiting)", queue.len());
+    if let Some(overlay) = app.get_webview_window("overlay") {
+        let _ = overlay.emit("recording-status", "transcribing");
+    }
+    if spawn_worker {
+        let worker_app = app.clone();
+        std::thread::spawn(move || run_dictation_queue(&worker_app));
+    }
+    true
+}
+
+/// Run queued dictations one at a time, each once the pipeline is free, so
+/// their pastes land in the order they were recorded.
+fn run_dictation_queue(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    loop {
+        while state
+            .is_processing
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        let job = state.dictation_queue.lock().ok().and_then(|mut queue| queue.next_job());
+        let Some(job) = job else {
+            state.is_processing.store(false, Ordering::SeqCst);
+            return;
+        };
+        if let Ok(mut since) = state.processing_since.lock() {
+            *since = Some(Instant::now());
+        }
+        if let Some(overlay) = app.get_webview_window("overlay") {
+            let _ = overlay.emit("recording-status", "transcribing");
+        }
+        let cancel = begin_processing(&state, job.samples.len() as f64 / job.sample_rate.max(1) as f64);
+        // A panicking job is reset by its `PanicGuard`; keep draining the queue.
+        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            run_dictation_pipeline(app, Capture::Queued(job), cancel)
+        }));
+    }
+}
+
+/// Put the overlay back into recording after a chunk, unless the session
+/// has ended since (the final stop then owns the overlay).
+fn resume_continuous_overlay(app: &AppHandle, state: &AppState, linger_ms: u64) {
+    std::thread::sleep(std::time::Duration::from_millis(linger_ms));
+    if state.is_recording.load(Ordering::SeqCst) && !state.is_processing.load(Ordering::SeqCst) {
+        if let Some(overlay) = app.get_webview_window("overlay") {
+            let _ = overlay.emit("recording-status", "recording");
+        }
+    }
+}
+
+/// What became of a stop request.
+pub(crate) enum StopOutcome {
+    /// The pipeline runs on this thread; it yields the saved history entry id.
+    Running(std::thread::JoinHandle<Option<String>>),
+    /// The recording waits in the dictation queue.
+    Queued,
+    /// Another stop already claimed the pipeline.
+    Busy,
+}
+
 /// Shared logic: stop recording, transcribe, copy/paste, and hide the overlay.
-fn stop_transcribe_and_paste(app: &AppHandle) {
+pub(crate) fn stop_transcribe_and_paste(app: &AppHandle) -> StopOutcome {
     let state = app.state::<AppState>();
+    if state.recording_queued.swap(false, Ordering::SeqCst) && enqueue_dictation(app, &state) {
+        return StopOutcome::Queued;
+    }
     if state
         .is_processing
         .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
         .is_err()
     {
         tracing::info!("stop_transcribe_and_paste: already processing, skipping");
-        return;
+        return StopOutcome::Busy;
     }
+    state.stop_pending.store(true, Ordering::SeqCst);
+    if let Ok(mut since) = state.processing_since.lock() {
+        *since = Some(Instant::now());
+    }
+    // Installed before the thread starts so a cancel during STT setup finds it.
+    let cancel = begin_processing(&state, buffered_audio_secs(&state));
 
     if let Some(overlay) = app.get_webview_window("overlay") {
         let _ = overlay.emit("recording-status", "transcribing");
@@ -281,254 +1085,498 @@ fn stop_transcribe_and_paste(app: &AppHandle) {
     tracing::info!("⏹️ Stopping recording...");
 
     let app_handle = app.clone();
-    std::thread::spawn(move || {
-        let pipeline_start = Instant::now();
-        let state = app_handle.state::<AppState>();
+    StopOutcome::Running(std::thread::spawn(move || {
+        run_dictation_pipeline(&app_handle, Capture::StopRecording, cancel)
+    }))
+}
 
-        let (auto_paste, polish_config, retention_days, mut stt_config) = state
-            .settings
-            .lock()
-            .map(|s| (s.auto_paste, s.polish.clone(), s.history_retention_days, s.stt.clone()))
-            .unwrap_or((true, polisher::PolishConfig::default(), 0, SttConfig::default()));
+/// Transcribe `capture`, polish, paste and save it to history.  `cancel` is
+/// the token the caller installed with [`begin_processing`] (a chunk's own
+/// token is not shared).  Returns the id of the history entry it saved.
+fn run_dictation_pipeline(app_handle: &AppHandle, capture: Capture, cancel: Arc<AtomicBool>) -> Option<String> {
+    let state = app_handle.state::<AppState>();
+    let is_chunk = matches!(capture, Capture::Chunk { .. });
+    if !is_chunk {
+        // Chunks from a continuous session paste before the final stretch.
+        finish_continuous_session(&state);
+    }
+    let pipeline_start = Instant::now();
+    let _guard = PanicGuard { app: app_handle, chunk: is_chunk };
 
-        if stt_config.mode == SttMode::Cloud {
-            let key = get_cached_api_key(&state.api_key_cache, stt_config.cloud.provider.as_key());
-            if !key.is_empty() {
-                stt_config.cloud.api_key = key;
-            }
-        }
+    let (auto_paste, verify_target, polish_config, history_limits, audio_format, mut stt_config, filler_words, normalize_numbers, smart_capitalize) = state
+        .settings
+        .lock()
+        .map(|s| (
+            s.auto_paste,
+            s.verify_target_on_paste,
+            s.polish.clone(),
+            s.history_limits(),
+            s.history_audio_format,
+            s.stt.clone(),
+            s.remove_fillers.then(|| s.effective_filler_words()),
+            s.normalize_numbers,
+            s.smart_capitalize,
+        ))
+        .unwrap_or((true, false, polisher::PolishConfig::default(), history::HistoryLimits::default(), history::AudioFormat::default(), SttConfig::default(), None, false, false));
 
-        let stt_language = stt_config.language.clone();
-        let dictionary_terms = polish_config.dictionary.enabled_terms();
+    if stt_config.mode == SttMode::Cloud {
+        let key = get_cached_api_key(&state.api_key_cache, stt_config.cloud.provider.as_key());
+        if !key.is_empty() {
+            stt_config.cloud.api_key = key;
+        }
+    }
 
-        let stop_result = audio::do_stop_recording(
+    // Snapshot the recording's context, raw-paste choice and one-shot polish
+    // model now: a queued recording may replace them while this one is
+    // still processing.
+    let (context, paste_raw, polish_model) = match &capture {
+        Capture::Queued(job) => (job.context.clone(), job.paste_raw, job.polish_model.clone()),
+        // The raw-paste choice and model hold for every chunk of a continuous
+        // session; the final stop consumes the model.
+        Capture::Chunk { .. } => (
+            captured_context(&state),
+            state.paste_raw_pending.load(Ordering::SeqCst),
+            state.next_polish_model.lock().ok().and_then(|m| m.clone()),
+        ),
+        Capture::StopRecording => (
+            captured_context(&state),
+            state.paste_raw_pending.swap(false, Ordering::SeqCst),
+            state.next_polish_model.lock().ok().and_then(|mut m| m.take()),
+        ),
+    };
+    warn_if_context_unavailable(app_handle, &state, &context, &polish_config);
+    let stt_language = resolve_stt_language(&context, &polish_config, &mut stt_config);
+    let dictionary_terms = polish_config.dictionary_terms();
+
+    let stop_result = match capture {
+        Capture::StopRecording => {
+            let result = audio::do_stop_recording(
+                &state,
+                &stt_config,
+                &stt_language,
+                &dictionary_terms,
+                Some(&cancel),
+            );
+            state.stop_pending.store(false, Ordering::SeqCst);
+            if let Ok(mut t) = state.last_recording_end.lock() {
+                *t = Some(Instant::now());
+            }
+            // Resume media paused at recording start.
+            if state.media_paused_by_sumi.swap(false, Ordering::SeqCst) {
+                platform::resume_now_playing();
+            }
+            result
+        }
+        Capture::Chunk { samples, sample_rate } => audio::transcribe_capture(
             &state,
             &stt_config,
             &stt_language,
             &dictionary_terms,
-        );
-        if let Ok(mut t) = state.last_recording_end.lock() {
-            *t = Some(Instant::now());
-        }
-        // Resume media paused at recording start.
-        if state.media_paused_by_sumi.swap(false, Ordering::SeqCst) {
-            platform::resume_now_playing();
-        }
-        match stop_result {
-            Ok((text, samples_16k)) => {
-                let transcribe_elapsed = pipeline_start.elapsed();
-                tracing::info!("[timing] stop→transcribed: {:.0?} | len: {} graphemes", transcribe_elapsed, text.graphemes(true).count());
-
-                // Voice Rule Mode
-                if state.voice_rule_mode.compare_exchange(true, false, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
-                    tracing::info!("Voice rule mode: emitting transcript to main window");
-                    if let Some(main_win) = app_handle.get_webview_window("main") {
-                        let _ = main_win.emit("voice-rule-transcript", &text);
-                    }
-                    state.is_processing.store(false, Ordering::SeqCst);
-                    reset_and_hide_overlay(&app_handle);
-                    return;
+            samples,
+            sample_rate,
+            None,
+            Some(&cancel),
+        ),
+        Capture::Queued(job) => audio::transcribe_capture(
+            &state,
+            &stt_config,
+            &stt_language,
+            &dictionary_terms,
+            job.samples,
+            job.sample_rate,
+            None,
+            Some(&cancel),
+        ),
+    };
+    if cancel.load(Ordering::SeqCst) {
+        tracing::info!("Pipeline cancelled after transcription");
+        end_cancelled_processing(&state, &cancel, is_chunk);
+        return None;
+    }
+    let mut saved_entry = None;
+    match stop_result {
+        Ok((text, samples_16k, word_timings)) => {
+            let transcribe_elapsed = pipeline_start.elapsed();
+            tracing::info!("[timing] stop→transcribed: {:.0?} | len: {} graphemes", transcribe_elapsed, text.graphemes(true).count());
+
+            // Voice Rule Mode
+            if state.voice_rule_mode.compare_exchange(true, false, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
+                tracing::info!("Voice rule mode: emitting transcript to main window");
+                if let Some(main_win) = app_handle.get_webview_window("main") {
+                    let _ = main_win.emit("voice-rule-transcript", &text);
+                }
+                end_processing(&state, &cancel);
+                if is_chunk {
+                    // The session keeps recording and owns the overlay.
+                    resume_continuous_overlay(app_handle, &state, 0);
+                    return None;
                 }
+                state.is_processing.store(false, Ordering::SeqCst);
+                reset_and_hide_overlay(app_handle);
+                return None;
+            }
+
+            let raw_text = text.clone();
+            let audio_duration_secs = samples_16k.len() as f64 / 16000.0;
 
-                let raw_text = text.clone();
-                let audio_duration_secs = samples_16k.len() as f64 / 16000.0;
+            let grapheme_count = text.graphemes(true).count();
+            let stt_secs = transcribe_elapsed.as_secs_f64();
+            let chars_per_sec = if stt_secs > 0.0 {
+                grapheme_count as f64 / stt_secs
+            } else {
+                0.0
+            };
+            tracing::info!(
+                "[stats] STT output: {} graphemes in {:.2}s = {:.1} graphemes/sec",
+                grapheme_count, stt_secs, chars_per_sec
+            );
 
-                let grapheme_count = text.graphemes(true).count();
-                let stt_secs = transcribe_elapsed.as_secs_f64();
-                let chars_per_sec = if stt_secs > 0.0 {
-                    grapheme_count as f64 / stt_secs
+            // AI Polishing
+            let mut polish_config = polish_config;
+            polish_config.cancel = Some(Arc::clone(&cancel));
+            if let Some(model) = &polish_model {
+                if polisher::apply_model_override(&mut polish_config, model) {
+                    tracing::info!("Using one-shot polish model: {}", model);
                 } else {
-                    0.0
-                };
-                tracing::info!(
-                    "[stats] STT output: {} graphemes in {:.2}s = {:.1} graphemes/sec",
-                    grapheme_count, stt_secs, chars_per_sec
+                    tracing::warn!("Ignoring one-shot polish model {:?}: not a local model", model);
+                }
+            }
+            let show_reasoning = state.settings.lock().map(|s| s.overlay_show_reasoning).unwrap_or(false);
+            if polish_config.reasoning && show_reasoning {
+                if let Some(overlay) = app_handle.get_webview_window("overlay") {
+                    polish_config.reasoning_sink = Some(polisher::ReasoningSink(Arc::new(move |reasoning: &str| {
+                        let _ = overlay.emit("polish-reasoning", reasoning);
+                    })));
+                }
+            }
+            if polish_config.mode == polisher::PolishMode::Cloud && polish_config.cloud.stream {
+                if let Some(overlay) = app_handle.get_webview_window("overlay") {
+                    polish_config.stream_sink = Some(polisher::ReasoningSink(Arc::new(move |partial: &str| {
+                        let _ = overlay.emit("polish-stream", partial);
+                    })));
+                }
+            }
+            if polish_config.enabled && polish_config.context_from_history > 0 {
+                polish_config.recent_transcripts = history::recent_texts(
+                    &history_dir(),
+                    polish_config.context_from_history,
+                    HISTORY_CONTEXT_MAX_AGE,
                 );
-
-                // AI Polishing
-                let mut polish_config = polish_config;
-                if polish_config.enabled && polish_config.mode == polisher::PolishMode::Cloud {
-                    let key = get_cached_api_key(&state.api_key_cache, polish_config.cloud.provider.as_key());
-                    if !key.is_empty() {
-                        polish_config.cloud.api_key = key;
-                    }
+            }
+            if polish_config.enabled && polish_config.mode == polisher::PolishMode::Cloud {
+                let key = get_cached_api_key(&state.api_key_cache, polish_config.cloud.provider.as_key());
+                if !key.is_empty() {
+                    polish_config.cloud.api_key = key;
                 }
-                let stt_elapsed_ms = transcribe_elapsed.as_millis() as u64;
+            }
+            let stt_elapsed_ms = transcribe_elapsed.as_millis() as u64;
 
-                let history_context = state
-                    .captured_context
-                    .lock()
-                    .ok()
-                    .and_then(|c| c.clone())
-                    .unwrap_or_default();
+            let auto_paste = polisher::effective_auto_paste(&polish_config, &context, auto_paste);
 
-                let (final_text, reasoning, polish_elapsed_ms) = if polish_config.enabled {
-                    let model_dir = models_dir();
-                    if polisher::is_polish_ready(&model_dir, &polish_config) {
+            if paste_raw && polish_config.enabled {
+                tracing::info!("Paste-raw shortcut used — skipping polish");
+            }
+            // Set when a rate limit forced the raw-text fallback; reported once
+            // the raw text is out, so the paste status does not hide it.
+            let mut polish_rate_limited: Option<Option<u64>> = None;
+            let (final_text, reasoning, polish_elapsed_ms, polish_fallback) = if polish_config.enabled && !paste_raw {
+                let model_dir = models_dir();
+                if polisher::is_polish_ready(&model_dir, &polish_config) {
+                    if let Some(overlay) = app_handle.get_webview_window("overlay") {
+                        let _ = overlay.emit("recording-status", "polishing");
+                    }
+                    let mode_label = match polish_config.mode {
+                        polisher::PolishMode::Cloud => format!("Cloud ({})", polish_config.cloud.model_id),
+                        polisher::PolishMode::Local => format!("Local ({})", polish_config.model.display_name()),
+                    };
+                    let polish_start = Instant::now();
+                    let result = polisher::polish_text(
+                        &state.llm_model,
+                        &model_dir,
+                        &polish_config,
+                        &context,
+                        &text,
+                        &state.http_client(),
+                    );
+                    emit_polish_model_corrupt(app_handle);
+                    // The stream showed the reply before the empty and
+                    // hallucination checks; replace it with the text used.
+                    if polish_config.stream_sink.is_some() {
                         if let Some(overlay) = app_handle.get_webview_window("overlay") {
-                            let _ = overlay.emit("recording-status", "polishing");
+                            let _ = overlay.emit("polish-stream", &result.text);
                         }
-                        let mode_label = match polish_config.mode {
-                            polisher::PolishMode::Cloud => format!("Cloud ({})", polish_config.cloud.model_id),
-                            polisher::PolishMode::Local => format!("Local ({})", polish_config.model.display_name()),
-                        };
-                        let context = state
-                            .captured_context
-                            .lock()
-                            .ok()
-                            .and_then(|mut c| c.take())
-                            .unwrap_or_default();
-
-                        let polish_start = Instant::now();
-                        let result = polisher::polish_text(
-                            &state.llm_model,
-                            &model_dir,
-                            &polish_config,
-                            &context,
-                            &text,
-                            &state.http_client,
-                        );
-                        let p_elapsed = polish_start.elapsed().as_millis() as u64;
-                        tracing::info!("[timing] polish ({}): {:.0?} | len: {} graphemes", mode_label, polish_start.elapsed(), result.text.graphemes(true).count());
-                        (result.text, result.reasoning, Some(p_elapsed))
+                    }
+                    let p_elapsed = polish_start.elapsed().as_millis() as u64;
+                    tracing::info!("[timing] polish ({}): {:.0?} | len: {} graphemes", mode_label, polish_start.elapsed(), result.text.graphemes(true).count());
+                    if let Some(reason) = &result.fallback_reason {
+                        // The raw text goes out as if polish were off, so
+                        // history and number normalization treat it that way.
+                        tracing::info!("Polish fell back to raw text ({}): {}", mode_label, reason);
+                        if let Some(error::SumiError::RateLimited { retry_after_secs, .. }) = result.error {
+                            polish_rate_limited = Some(retry_after_secs);
+                        }
+                        (result.text, result.reasoning, None, Some(reason.clone()))
                     } else {
-                        tracing::warn!("Polish enabled but not ready (model missing or no API key), skipping");
-                        (text, None, None)
+                        (result.text, result.reasoning, Some(p_elapsed), None)
                     }
                 } else {
-                    (text, None, None)
-                };
-                let text = final_text;
-                let text = crate::maybe_convert_zh(&text, &stt_language);
-
-                if let Some(main_win) = app_handle.get_webview_window("main") {
-                    let _ = main_win.emit("transcription-result", &text);
+                    tracing::warn!("Polish enabled but not ready (model missing or no API key), skipping");
+                    (text, None, None, None)
                 }
+            } else {
+                (text, None, None, None)
+            };
+            if cancel.load(Ordering::SeqCst) {
+                tracing::info!("Pipeline cancelled during polish; discarding result");
+                end_cancelled_processing(&state, &cancel, is_chunk);
+                return None;
+            }
+            let text = match filler_words {
+                // Deterministic cleanup stands in for the LLM when it did not run.
+                Some(ref words) if polish_elapsed_ms.is_none() && !paste_raw => {
+                    fillers::remove_fillers(&final_text, words)
+                }
+                _ => final_text,
+            };
+            let text = if normalize_numbers && polish_elapsed_ms.is_none() && !paste_raw {
+                text_normalize::normalize_numbers(&text, &stt_language)
+            } else {
+                text
+            };
+            let text = if smart_capitalize && polish_elapsed_ms.is_none() && !paste_raw {
+                capitalize::smart_capitalize(&text, &dictionary_terms)
+            } else {
+                text
+            };
+            // Translate mode only takes effect when the polish step actually ran.
+            let translated_to = polish_elapsed_ms
+                .and(polish_config.translation_target())
+                .map(str::to_string);
+            let text = crate::maybe_convert_zh(&text, translated_to.as_deref().unwrap_or(&stt_language));
+
+            if let Some(main_win) = app_handle.get_webview_window("main") {
+                let _ = main_win.emit("transcription-result", &text);
+            }
 
-                let clipboard_ok = match arboard::Clipboard::new() {
-                    Ok(mut clipboard) => {
-                        if let Err(e) = clipboard.set_text(&text) {
-                            tracing::error!("Clipboard error: {}", e);
-                            false
-                        } else {
-                            true
-                        }
+            // History keeps the text as transcribed; only the pasted copy is adjusted.
+            let trailing = state.settings.lock().map(|s| s.trailing_behavior).unwrap_or_default();
+            let paste_text = trailing.apply(&text);
+            let (plain_only, insertion_method, keystroke_delay_ms, paste_grace_ms) = state
+                .settings
+                .lock()
+                .map(|s| (s.plain_text_only, s.insertion_method, s.keystroke_delay_ms, s.paste_grace_ms))
+                .unwrap_or((true, settings::InsertionMethod::default(), 0, 0));
+            // Still recording, so Escape would end the session, not the grace period.
+            let paste_grace_ms = if is_chunk { 0 } else { paste_grace_ms };
+
+            let auto_paste = auto_paste
+                && !(paste_grace_ms > 0 && wait_paste_grace(app_handle, &state, &cancel, &paste_text, paste_grace_ms));
+            if cancel.load(Ordering::SeqCst) {
+                tracing::info!("Pipeline cancelled during paste grace period; discarding result");
+                end_cancelled_processing(&state, &cancel, is_chunk);
+                return None;
+            }
+
+            // Direct insertion skips the clipboard entirely; anything it
+            // can't handle goes down the regular paste path below.  Typing
+            // that stops part-way can't be pasted over without duplicating
+            // text, so the full text is only copied for the user.
+            let mut typing_incomplete = false;
+            let inserted = auto_paste
+                && insertion_method != settings::InsertionMethod::ClipboardPaste
+                && (!verify_target
+                    || context_detect::is_same_app(&context, &context_detect::detect_frontmost_identity()))
+                && match insertion_method {
+                    settings::InsertionMethod::Accessibility => {
+                        permissions::accessibility_trusted() && platform::insert_text_accessibility(&paste_text)
                     }
-                    Err(e) => {
-                        tracing::error!("Clipboard init error: {}", e);
-                        false
+                    settings::InsertionMethod::TypeKeystrokes => {
+                        let typed = platform::type_text(&paste_text, keystroke_delay_ms, &cancel);
+                        typing_incomplete = typed > 0 && typed < paste_text.graphemes(true).count();
+                        typed > 0
                     }
+                    settings::InsertionMethod::ClipboardPaste => false,
                 };
+            if cancel.load(Ordering::SeqCst) {
+                tracing::info!("Pipeline cancelled while typing; discarding the rest");
+                end_cancelled_processing(&state, &cancel, is_chunk);
+                return None;
+            }
+            if typing_incomplete {
+                tracing::warn!("Keystroke typing stopped part-way; copying the full text");
+                if let Err(e) = platform::set_clipboard_text(&paste_text, plain_only) {
+                    tracing::error!("Clipboard error: {}", e);
+                }
+                if let Some(overlay) = app_handle.get_webview_window("overlay") {
+                    let _ = overlay.emit("recording-status", "copied_typing_incomplete");
+                }
+            } else if inserted {
+                tracing::info!("⌨️ Inserted at cursor via {:?}", insertion_method);
+                if let Some(overlay) = app_handle.get_webview_window("overlay") {
+                    let _ = overlay.emit("recording-status", "pasted");
+                }
+            } else if insertion_method != settings::InsertionMethod::ClipboardPaste && auto_paste {
+                tracing::info!("{:?} insertion unavailable for the focused element; pasting instead", insertion_method);
+            }
 
-                if clipboard_ok {
-                    std::thread::sleep(std::time::Duration::from_millis(100));
-
-                    if auto_paste {
-                        let pasted = platform::simulate_paste();
-                        if pasted {
-                            tracing::info!("📋 Auto-pasted at cursor");
-                            if let Some(overlay) = app_handle.get_webview_window("overlay") {
-                                let _ = overlay.emit("recording-status", "pasted");
-                            }
-                        } else {
-                            tracing::info!("📋 Copied to clipboard (paste simulation failed)");
-                            if let Some(overlay) = app_handle.get_webview_window("overlay") {
-                                let _ = overlay.emit("recording-status", "copied");
-                            }
+            let clipboard_ok = !inserted && match platform::set_clipboard_text(&paste_text, plain_only) {
+                Ok(()) => true,
+                Err(e) => {
+                    tracing::error!("Clipboard error: {}", e);
+                    false
+                }
+            };
+
+            if clipboard_ok {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+
+                let target_changed = auto_paste && verify_target && {
+                    let current = context_detect::detect_frontmost_identity();
+                    let changed = !context_detect::is_same_app(&context, &current);
+                    if changed {
+                        tracing::warn!(
+                            "Paste target changed ({:?} → {:?}); copying instead of pasting",
+                            context.app_name, current.app_name
+                        );
+                    }
+                    changed
+                };
+                if target_changed {
+                    if let Some(overlay) = app_handle.get_webview_window("overlay") {
+                        let _ = overlay.emit("recording-status", "copied_target_changed");
+                    }
+                } else if auto_paste && !permissions::accessibility_trusted() {
+                    tracing::warn!("📋 Accessibility not granted; copied to clipboard instead of pasting");
+                    if let Some(overlay) = app_handle.get_webview_window("overlay") {
+                        let _ = overlay.emit("recording-status", "needs-accessibility");
+                    }
+                    permissions::prompt_accessibility_once();
+                } else if auto_paste {
+                    let pasted = platform::simulate_paste();
+                    if pasted {
+                        tracing::info!("📋 Auto-pasted at cursor");
+                        if let Some(overlay) = app_handle.get_webview_window("overlay") {
+                            let _ = overlay.emit("recording-status", "pasted");
                         }
                     } else {
-                        tracing::info!("📋 Copied to clipboard (auto-paste disabled)");
+                        tracing::info!("📋 Copied to clipboard (paste simulation failed)");
                         if let Some(overlay) = app_handle.get_webview_window("overlay") {
                             let _ = overlay.emit("recording-status", "copied");
                         }
                     }
+                } else {
+                    tracing::info!("📋 Copied to clipboard (auto-paste disabled)");
+                    if let Some(overlay) = app_handle.get_webview_window("overlay") {
+                        let _ = overlay.emit("recording-status", "copied");
+                    }
                 }
+            }
 
-                let total_elapsed_ms = pipeline_start.elapsed().as_millis() as u64;
-                tracing::info!("[timing] total pipeline: {:.0?}", pipeline_start.elapsed());
-
-                // Save to history
-                {
-                    let entry_id = history::generate_id();
-                    let stt_model = match stt_config.mode {
-                        SttMode::Cloud => {
-                            format!("{} (Cloud/{})", stt_config.cloud.model_id, stt_config.cloud.provider.as_key())
-                        }
-                        SttMode::Local => match stt_config.local_engine {
-                            stt::LocalSttEngine::Whisper => stt_config.whisper_model.display_name().to_string(),
-                            stt::LocalSttEngine::Qwen3Asr => stt_config.qwen3_asr_model.display_name().to_string(),
-                        },
-                    };
-                    let polish_model_name = if polish_elapsed_ms.is_some() {
-                        match polish_config.mode {
-                            polisher::PolishMode::Cloud => {
-                                format!("{} (Cloud/{})", polish_config.cloud.model_id, polish_config.cloud.provider.as_key())
-                            }
-                            polisher::PolishMode::Local => {
-                                format!("{} (Local)", polish_config.model.display_name())
-                            }
-                        }
-                    } else {
-                        "None".to_string()
-                    };
-                    let has_audio = history::save_audio_wav(&audio_dir(), &entry_id, &samples_16k);
-                    let word_count = history::count_words(&text) as u64;
-                    let entry = history::HistoryEntry {
-                        id: entry_id,
-                        timestamp: std::time::SystemTime::now()
-                            .duration_since(std::time::SystemTime::UNIX_EPOCH)
-                            .unwrap_or_default()
-                            .as_millis() as i64,
-                        text: text.clone(),
-                        raw_text,
-                        reasoning,
-                        stt_model,
-                        polish_model: polish_model_name,
-                        duration_secs: audio_duration_secs,
-                        has_audio,
-                        stt_elapsed_ms,
-                        polish_elapsed_ms,
-                        total_elapsed_ms,
-                        app_name: history_context.app_name.clone(),
-                        bundle_id: history_context.bundle_id.clone(),
-                        chars_per_sec,
-                        word_count,
-                    };
-                    history::add_entry(&history_dir(), &audio_dir(), entry, retention_days);
-                    tracing::info!("📝 History entry saved (audio={})", has_audio);
+            if let Some(retry_after_secs) = polish_rate_limited {
+                if let Some(overlay) = app_handle.get_webview_window("overlay") {
+                    let _ = overlay.emit("rate-limited", serde_json::json!({ "retry_after_secs": retry_after_secs }));
                 }
             }
-            Err(ref e) if e == "no_speech" => {
-                tracing::info!("No speech detected, skipping (took {:.0?})", pipeline_start.elapsed());
-                if state.voice_rule_mode.compare_exchange(true, false, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
-                    if let Some(main_win) = app_handle.get_webview_window("main") {
-                        let _ = main_win.emit("voice-rule-transcript", "");
+
+            let total_elapsed_ms = pipeline_start.elapsed().as_millis() as u64;
+            tracing::info!("[timing] total pipeline: {:.0?}", pipeline_start.elapsed());
+
+            // Save to history
+            {
+                let entry_id = history::generate_id();
+                let stt_model = match stt_config.mode {
+                    SttMode::Cloud => {
+                        format!("{} (Cloud/{})", stt_config.cloud.model_id, stt_config.cloud.provider.as_key())
                     }
+                    SttMode::Local => match stt_config.local_engine {
+                        stt::LocalSttEngine::Whisper => stt_config.whisper_model.display_name().to_string(),
+                        stt::LocalSttEngine::Qwen3Asr => stt_config.qwen3_asr_model.display_name().to_string(),
+                    },
+                };
+                let polish_model_name = if polish_elapsed_ms.is_some() {
+                    polish_config.history_model_name()
+                } else {
+                    "None".to_string()
+                };
+                let bit_depth = state.settings.lock().map(|s| s.history_audio_bit_depth).unwrap_or_default();
+                let has_audio = history::save_audio(&audio_dir(), &entry_id, &samples_16k, audio_format, bit_depth);
+                let word_count = history::count_words(&text) as u64;
+                let entry = history::HistoryEntry {
+                    id: entry_id,
+                    timestamp: std::time::SystemTime::now()
+                        .duration_since(std::time::SystemTime::UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_millis() as i64,
+                    text: text.clone(),
+                    raw_text,
+                    reasoning,
+                    stt_model,
+                    polish_model: polish_model_name,
+                    duration_secs: audio_duration_secs,
+                    has_audio,
+                    stt_elapsed_ms,
+                    polish_elapsed_ms,
+                    total_elapsed_ms,
+                    app_name: context.app_name.clone(),
+                    bundle_id: context.bundle_id.clone(),
+                    chars_per_sec,
+                    word_count,
+                    confidence: state.last_stt_confidence.lock().ok().and_then(|mut c| c.take()),
+                    audio_format,
+                    source_language: translated_to.as_ref().map(|_| stt_language.clone()),
+                    target_language: translated_to,
+                    tags: Vec::new(),
+                    word_timings,
+                    app_context: Some(context.clone()),
+                    fallback_reason: polish_fallback,
+                };
+                saved_entry = Some(entry.id.clone());
+                history::add_entry(&history_dir(), &audio_dir(), entry, history_limits);
+                tracing::info!("📝 History entry saved (audio={})", has_audio);
+            }
+        }
+        Err(error::SumiError::NoSpeech(_)) => {
+            tracing::info!("No speech detected, skipping (took {:.0?})", pipeline_start.elapsed());
+            if state.voice_rule_mode.compare_exchange(true, false, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
+                if let Some(main_win) = app_handle.get_webview_window("main") {
+                    let _ = main_win.emit("voice-rule-transcript", "");
                 }
-                // Release immediately and hide overlay — nothing to display
-                state.is_processing.store(false, Ordering::SeqCst);
-                reset_and_hide_overlay(&app_handle);
-                return;
             }
-            Err(e) => {
-                tracing::error!("Transcription error: {} (after {:.0?})", e, pipeline_start.elapsed());
-                if let Some(overlay) = app_handle.get_webview_window("overlay") {
-                    let _ = overlay.emit("recording-status", "error");
+            end_processing(&state, &cancel);
+            if is_chunk {
+                resume_continuous_overlay(app_handle, &state, 0);
+                return None;
+            }
+            // Release immediately and hide overlay — nothing to display
+            state.is_processing.store(false, Ordering::SeqCst);
+            reset_and_hide_overlay(app_handle);
+            return None;
+        }
+        Err(e) => {
+            tracing::error!("Transcription error: {} (after {:.0?})", e, pipeline_start.elapsed());
+            if let Some(overlay) = app_handle.get_webview_window("overlay") {
+                match e {
+                    error::SumiError::RateLimited { retry_after_secs, .. } => {
+                        let _ = overlay.emit("rate-limited", serde_json::json!({ "retry_after_secs": retry_after_secs }));
+                    }
+                    _ => {
+                        let _ = overlay.emit("recording-status", "error");
+                    }
                 }
-                state.voice_rule_mode.store(false, Ordering::SeqCst);
             }
+            state.voice_rule_mode.store(false, Ordering::SeqCst);
         }
+    }
 
-        state.is_processing.store(false, Ordering::SeqCst);
+    end_processing(&state, &cancel);
+    if is_chunk {
+        resume_continuous_overlay(app_handle, &state, CHUNK_RESULT_LINGER_MS);
+        return saved_entry;
+    }
+    state.is_processing.store(false, Ordering::SeqCst);
 
-        std::thread::sleep(std::time::Duration::from_millis(1500));
-        let app_for_hide = app_handle.clone();
-        let _ = app_handle.run_on_main_thread(move || {
-            if let Some(overlay) = app_for_hide.get_webview_window("overlay") {
-                platform::hide_overlay(&overlay);
-            }
-        });
-    });
+    hide_overlay_after_result(app_handle, &state, 0);
+    saved_entry
 }
 
 /// Edit-by-voice pipeline: stop recording, transcribe instruction, edit text, replace.
@@ -542,8 +1590,13 @@ fn stop_edit_and_replace(app: &AppHandle) {
         tracing::info!("stop_edit_and_replace: already processing, skipping");
         return;
     }
+    state.stop_pending.store(true, Ordering::SeqCst);
+    if let Ok(mut since) = state.processing_since.lock() {
+        *since = Some(Instant::now());
+    }
 
     state.edit_mode.store(false, Ordering::SeqCst);
+    let cancel = begin_processing(&state, buffered_audio_secs(&state));
 
     if let Some(overlay) = app.get_webview_window("overlay") {
         let _ = overlay.emit("recording-status", "transcribing");
@@ -555,6 +1608,7 @@ fn stop_edit_and_replace(app: &AppHandle) {
     std::thread::spawn(move || {
         let pipeline_start = Instant::now();
         let state = app_handle.state::<AppState>();
+        let _guard = PanicGuard { app: &app_handle, chunk: false };
 
         let (polish_config, mut stt_config) = state
             .settings
@@ -581,14 +1635,15 @@ fn stop_edit_and_replace(app: &AppHandle) {
             if let Some(overlay) = app_handle.get_webview_window("overlay") {
                 let _ = overlay.emit("recording-status", "error");
             }
+            state.stop_pending.store(false, Ordering::SeqCst);
             state.is_processing.store(false, Ordering::SeqCst);
             restore_clipboard(&state);
-            hide_overlay_delayed(&app_handle, 1500);
+            hide_overlay_after_result(&app_handle, &state, 0);
             return;
         }
 
-        let edit_stt_language = stt_config.language.clone();
-        let edit_dict_terms = polish_config.dictionary.enabled_terms();
+        let edit_stt_language = resolve_stt_language(&captured_context(&state), &polish_config, &mut stt_config);
+        let edit_dict_terms = polish_config.dictionary_terms();
 
         // The edit path never spawns a live-preview feeder. Defensively clear
         // any residual streaming state from a previous normal recording so a
@@ -603,7 +1658,9 @@ fn stop_edit_and_replace(app: &AppHandle) {
             &stt_config,
             &edit_stt_language,
             &edit_dict_terms,
+            Some(&cancel),
         );
+        state.stop_pending.store(false, Ordering::SeqCst);
         if let Ok(mut t) = state.last_recording_end.lock() {
             *t = Some(Instant::now());
         }
@@ -611,8 +1668,13 @@ fn stop_edit_and_replace(app: &AppHandle) {
         if state.media_paused_by_sumi.swap(false, Ordering::SeqCst) {
             platform::resume_now_playing();
         }
+        if cancel.load(Ordering::SeqCst) {
+            tracing::info!("Edit-by-voice cancelled after transcription");
+            end_cancelled_processing(&state, &cancel, false);
+            return;
+        }
         match stop_result {
-            Ok((instruction, _samples)) => {
+            Ok((instruction, _samples, _timings)) => {
                 tracing::info!("Edit instruction received: {} graphemes", instruction.graphemes(true).count());
 
                 if let Some(overlay) = app_handle.get_webview_window("overlay") {
@@ -620,6 +1682,7 @@ fn stop_edit_and_replace(app: &AppHandle) {
                 }
 
                 let mut polish_config = polish_config;
+                polish_config.cancel = Some(Arc::clone(&cancel));
                 if polish_config.mode == polisher::PolishMode::Cloud {
                     let key = get_cached_api_key(
                         &state.api_key_cache,
@@ -638,18 +1701,26 @@ fn stop_edit_and_replace(app: &AppHandle) {
                     }
                     state.is_processing.store(false, Ordering::SeqCst);
                     restore_clipboard(&state);
-                    hide_overlay_delayed(&app_handle, 1500);
+                    hide_overlay_after_result(&app_handle, &state, 0);
                     return;
                 }
 
-                match polisher::edit_text_by_instruction(
+                let edit_result = polisher::edit_text_by_instruction(
                     &state.llm_model,
                     &model_dir,
                     &polish_config,
                     &selected_text,
                     &instruction,
-                    &state.http_client,
-                ) {
+                    &state.http_client(),
+                );
+                emit_polish_model_corrupt(&app_handle);
+                if cancel.load(Ordering::SeqCst) {
+                    tracing::info!("Edit-by-voice cancelled during LLM edit");
+                    end_cancelled_processing(&state, &cancel, false);
+                    return;
+                }
+                end_processing(&state, &cancel);
+                match edit_result {
                     Ok(edited_text) => {
                         tracing::info!(
                             "Edit result: {} graphemes (took {:.0?})",
@@ -657,10 +1728,8 @@ fn stop_edit_and_replace(app: &AppHandle) {
                             pipeline_start.elapsed()
                         );
 
-                        let clipboard_ok = match arboard::Clipboard::new() {
-                            Ok(mut clipboard) => clipboard.set_text(&edited_text).is_ok(),
-                            Err(_) => false,
-                        };
+                        let plain_only = state.settings.lock().map(|s| s.plain_text_only).unwrap_or(true);
+                        let clipboard_ok = platform::set_clipboard_text(&edited_text, plain_only).is_ok();
 
                         if clipboard_ok {
                             std::thread::sleep(std::time::Duration::from_millis(100));
@@ -675,7 +1744,7 @@ fn stop_edit_and_replace(app: &AppHandle) {
                         }
 
                         state.is_processing.store(false, Ordering::SeqCst);
-                        hide_overlay_delayed(&app_handle, 5500);
+                        hide_overlay_after_result(&app_handle, &state, 5500);
                     }
                     Err(e) => {
                         tracing::error!("Edit-by-voice LLM error: {}", e);
@@ -684,12 +1753,13 @@ fn stop_edit_and_replace(app: &AppHandle) {
                         }
                         state.is_processing.store(false, Ordering::SeqCst);
                         restore_clipboard(&state);
-                        hide_overlay_delayed(&app_handle, 1500);
+                        hide_overlay_after_result(&app_handle, &state, 0);
                     }
                 }
             }
-            Err(ref e) if e == "no_speech" => {
+            Err(error::SumiError::NoSpeech(_)) => {
                 tracing::info!("Edit-by-voice: no speech detected");
+                end_processing(&state, &cancel);
                 state.is_processing.store(false, Ordering::SeqCst);
                 restore_clipboard(&state);
                 reset_and_hide_overlay(&app_handle);
@@ -699,9 +1769,10 @@ fn stop_edit_and_replace(app: &AppHandle) {
                 if let Some(overlay) = app_handle.get_webview_window("overlay") {
                     let _ = overlay.emit("recording-status", "error");
                 }
+                end_processing(&state, &cancel);
                 state.is_processing.store(false, Ordering::SeqCst);
                 restore_clipboard(&state);
-                hide_overlay_delayed(&app_handle, 1500);
+                hide_overlay_after_result(&app_handle, &state, 0);
             }
         }
     });
@@ -715,6 +1786,37 @@ fn stop_edit_and_replace(app: &AppHandle) {
 static LOG_GUARD: std::sync::Mutex<Option<tracing_appender::non_blocking::WorkerGuard>> =
     std::sync::Mutex::new(None);
 
+type LogFilterReload =
+    Box<dyn Fn(tracing_subscriber::EnvFilter) -> Result<(), String> + Send + Sync>;
+
+/// Swaps the installed subscriber's filter; set once logging is initialised.
+static LOG_FILTER_RELOAD: std::sync::OnceLock<LogFilterReload> = std::sync::OnceLock::new();
+
+/// Filter for `level`.  The level applies to this crate only; dependencies
+/// (reqwest, hyper, ONNX Runtime's BFCArena spam, ...) stay at WARN+ so
+/// debug/trace shows Sumi's own logs.
+fn log_filter(level: settings::LogLevel) -> tracing_subscriber::EnvFilter {
+    tracing_subscriber::EnvFilter::new(format!("warn,sumi={}", level.directive()))
+}
+
+fn install_log_filter_reload<S: Send + Sync + 'static>(
+    handle: tracing_subscriber::reload::Handle<tracing_subscriber::EnvFilter, S>,
+) {
+    let _ = LOG_FILTER_RELOAD.set(Box::new(move |filter| {
+        handle.reload(filter).map_err(|e| e.to_string())
+    }));
+}
+
+/// Apply the `log_level` setting to the running logger.
+pub(crate) fn apply_log_level(level: settings::LogLevel) {
+    if let Some(reload) = LOG_FILTER_RELOAD.get() {
+        match reload(log_filter(level)) {
+            Ok(()) => tracing::info!("Log level set to {}", level.directive()),
+            Err(e) => tracing::warn!("Failed to change log level: {}", e),
+        }
+    }
+}
+
 /// Delete `sumi.log*` files in `log_dir` that have not been written to within `keep_days` days.
 /// Also removes the legacy non-rotating `sumi.log` (written by older app versions).
 #[cfg(not(debug_assertions))]
@@ -852,30 +1954,59 @@ pub fn run() {
             commands::start_recording,
             commands::stop_recording,
             commands::cancel_recording,
+            commands::cancel_processing,
             commands::set_test_mode,
             commands::set_voice_rule_mode,
             commands::set_context_override,
             commands::set_edit_text_override,
             commands::get_settings,
             commands::save_settings,
+            commands::set_stt_mode,
+            commands::set_stt_provider,
+            commands::set_polish_mode,
             commands::update_hotkey,
             commands::reset_settings,
+            commands::restart_onboarding,
+            commands::export_settings,
+            commands::import_settings,
             commands::get_default_prompt,
             commands::get_default_prompt_rules,
+            commands::get_default_filler_words,
+            commands::list_supported_languages,
             commands::test_polish,
+            commands::test_edit,
+            commands::preview_system_prompt,
+            commands::match_prompt_rule,
+            commands::set_next_polish_model,
+            commands::get_current_context,
+            commands::list_cloud_models,
             commands::get_mic_status,
             commands::check_model_status,
             commands::download_model,
             commands::check_llm_model_status,
             commands::download_llm_model,
+            commands::cancel_download,
             commands::save_api_key,
             commands::get_api_key,
+            commands::list_stored_credentials,
+            commands::clear_all_credentials,
             commands::get_history_stats,
+            commands::get_usage_stats,
             commands::get_history,
             commands::get_history_page,
+            commands::set_entry_tags,
+            commands::get_history_by_tag,
             commands::delete_history_entry,
             commands::clear_all_history,
+            commands::get_history_trash,
+            commands::restore_history_entry,
+            commands::empty_history_trash,
+            commands::verify_history_db,
+            commands::repair_history_db,
             commands::export_history_audio,
+            commands::export_history_srt,
+            commands::repolish_history_entry,
+            commands::copy_entry_raw,
             commands::get_history_storage_path,
             commands::get_app_icon,
             permissions::check_permissions,
@@ -883,12 +2014,15 @@ pub fn run() {
             commands::generate_rule_from_description,
             commands::update_edit_hotkey,
             commands::trigger_undo,
+            commands::dismiss_overlay,
             commands::list_polish_models,
             commands::switch_polish_model,
             commands::download_polish_model,
             commands::list_whisper_models,
             commands::get_system_info,
+            commands::get_diagnostics,
             commands::get_whisper_model_recommendation,
+            commands::benchmark_whisper_threads,
             commands::switch_whisper_model,
             commands::download_whisper_model,
             commands::check_vad_model_status,
@@ -896,7 +2030,13 @@ pub fn run() {
             commands::copy_image_to_clipboard,
             commands::is_dev_mode,
             commands::set_mic_device,
+            commands::set_channel_mode,
+            commands::set_capture_mode,
+            commands::start_mic_monitor,
+            commands::get_current_input_level,
+            commands::stop_mic_monitor,
             commands::export_diagnostic_log,
+            commands::get_log_path,
             commands::list_qwen3_asr_models,
             commands::switch_qwen3_asr_model,
             commands::download_qwen3_asr_model,
@@ -911,6 +2051,8 @@ pub fn run() {
             commands::download_segmentation_model,
             commands::delete_segmentation_model,
             commands::update_meeting_hotkey,
+            commands::update_repeat_hotkey,
+            commands::set_paste_raw_on_modifier,
             commands::list_meeting_notes,
             commands::get_meeting_note,
             commands::rename_meeting_note,
@@ -934,24 +2076,23 @@ pub fn run() {
                 let log_dir = logs_dir();
                 let _ = std::fs::create_dir_all(&log_dir);
 
-                // Filter: INFO for everything, WARN+ for ORT when diarization is enabled
-                // (suppresses BFCArena spam from ONNX Runtime).
-                #[cfg(feature = "diarization")]
-                let ort_filter = tracing_subscriber::EnvFilter::new("info,ort::logging=warn");
-                #[cfg(not(feature = "diarization"))]
-                let ort_filter = tracing_subscriber::EnvFilter::new("info");
+                // Start at the default level; the `log_level` setting is
+                // applied through the reload handle once settings are loaded.
+                let filter = log_filter(settings::LogLevel::default());
 
                 #[cfg(debug_assertions)]
                 {
                     // Dev: write to stderr so `cargo tauri dev` shows logs in the terminal.
-                    if let Err(e) = tracing_subscriber::fmt()
+                    let builder = tracing_subscriber::fmt()
                         .with_writer(std::io::stderr)
                         .with_ansi(true)
                         .with_target(false)
-                        .with_env_filter(ort_filter)
-                        .try_init()
-                    {
-                        eprintln!("[Sumi] Logger init failed: {}", e);
+                        .with_env_filter(filter)
+                        .with_filter_reloading();
+                    let reload = builder.reload_handle();
+                    match builder.try_init() {
+                        Ok(()) => install_log_filter_reload(reload),
+                        Err(e) => eprintln!("[Sumi] Logger init failed: {}", e),
                     }
                 }
 
@@ -961,14 +2102,16 @@ pub fn run() {
                     cleanup_old_logs(&log_dir, 7);
                     let file_appender = tracing_appender::rolling::daily(&log_dir, "sumi.log");
                     let (non_blocking, guard) = tracing_appender::non_blocking(file_appender);
-                    match tracing_subscriber::fmt()
+                    let builder = tracing_subscriber::fmt()
                         .with_writer(non_blocking)
                         .with_ansi(false)
                         .with_target(false)
-                        .with_env_filter(ort_filter)
-                        .try_init()
-                    {
+                        .with_env_filter(filter)
+                        .with_filter_reloading();
+                    let reload = builder.reload_handle();
+                    match builder.try_init() {
                         Ok(()) => {
+                            install_log_filter_reload(reload);
                             // Guard must stay alive until process exit so the background
                             // writer thread keeps running and flushes all logs on drop.
                             *LOG_GUARD.lock().unwrap_or_else(|e| e.into_inner()) = Some(guard);
@@ -992,16 +2135,33 @@ pub fn run() {
             // Load settings, then apply locale defaults.
             let mut settings = load_settings();
             settings::apply_locale_defaults(&mut settings);
+            if settings.log_level != settings::LogLevel::default() {
+                apply_log_level(settings.log_level);
+            }
             let hotkey_str = settings.hotkey.clone();
 
             // Migrate legacy JSON history to SQLite, then run schema migrations
             history::migrate_from_json(&history_dir(), &audio_dir());
             history::init_db(&history_dir());
+            history::purge_trash(&history_dir(), &audio_dir(), settings.history_trash_retention_days);
 
             // Init meeting notes schema & recover notes stuck from a previous crash
             meeting_notes::init_db(&history_dir());
             meeting_notes::recover_stuck_notes(&history_dir(), &audio_dir());
 
+            // Integrity-check the history DB off the main thread; unclean
+            // shutdowns can leave it damaged.  The settings window offers repair.
+            {
+                let handle = app.handle().clone();
+                std::thread::spawn(move || {
+                    let health = history::verify_db(&history_dir());
+                    if !health.ok {
+                        tracing::warn!("History DB failed integrity check: {:?}", health.problems);
+                        let _ = handle.emit("history-db-unhealthy", &health);
+                    }
+                });
+            }
+
             // Remove obsolete model files in the background (non-blocking).
             {
                 let dir = models_dir();
@@ -1026,14 +2186,13 @@ pub fn run() {
             let sample_rate: Option<u32> = None;
             let audio_thread_init: Option<audio::AudioThreadControl> = None;
 
-            let http_client = reqwest::blocking::Client::builder()
-                .timeout(std::time::Duration::from_secs(60))
-                .build()
-                .unwrap_or_else(|_| reqwest::blocking::Client::new());
+            let http_client = proxy::shared_client();
 
             app.manage(AppState {
                 is_recording,
                 is_processing: AtomicBool::new(false),
+                processing_since: Mutex::new(None),
+                processing_audio_ms: AtomicU64::new(0),
                 buffer,
                 sample_rate: Mutex::new(sample_rate),
                 settings: Mutex::new(settings.clone()),
@@ -1045,7 +2204,9 @@ pub fn run() {
                 test_mode: AtomicBool::new(false),
                 voice_rule_mode: AtomicBool::new(false),
                 last_hotkey_time: Mutex::new(Instant::now() - std::time::Duration::from_secs(1)),
-                http_client,
+                last_hotkey_event: Mutex::new(Instant::now() - std::time::Duration::from_secs(1)),
+                hotkeys_held: Mutex::new(HashSet::new()),
+                shared_http_client: RwLock::new(http_client),
                 api_key_cache: Mutex::new(HashMap::new()),
                 edit_mode: AtomicBool::new(false),
                 edit_selected_text: Mutex::new(None),
@@ -1053,6 +2214,7 @@ pub fn run() {
                 saved_clipboard: Mutex::new(None),
                 vad_ctx: Mutex::new(None),
                 downloading: AtomicBool::new(false),
+                downloads_in_flight: Mutex::new(HashMap::new()),
                 audio_thread: Mutex::new(audio_thread_init),
                 qwen3_asr_ctx: Mutex::new(None),
                 qwen3_ready_cv: Condvar::new(),
@@ -1062,6 +2224,16 @@ pub fn run() {
                 streaming_active: AtomicBool::new(false),
                 streaming_cancelled: AtomicBool::new(false),
                 streaming_result: Mutex::new(None),
+                last_stt_confidence: Mutex::new(None),
+                processing_cancel: Mutex::new(None),
+                paste_grace: Mutex::new(None),
+                cancel_shortcut_armed: Mutex::new(false),
+                continuous_session: Mutex::new(continuous_session::ContinuousSessions::default()),
+                dictation_queue: Mutex::new(DictationQueue::default()),
+                recording_queued: AtomicBool::new(false),
+                stop_pending: AtomicBool::new(false),
+                next_polish_model: Mutex::new(None),
+                context_warning_sent: AtomicBool::new(false),
                 feeder_stop_cv: Condvar::new(),
                 feeder_stop_mu: Mutex::new(()),
                 meeting_active: AtomicBool::new(false),
@@ -1082,14 +2254,46 @@ pub fn run() {
                 registered_meeting_shortcut: Mutex::new(
                     settings.meeting_hotkey.as_deref().and_then(parse_hotkey_string),
                 ),
+                registered_raw_shortcut: Mutex::new(None),
+                registered_repeat_shortcut: Mutex::new(None),
+                paste_raw_pending: AtomicBool::new(false),
                 media_paused_by_sumi: AtomicBool::new(false),
                 last_recording_end: Mutex::new(None),
+                mic_monitor_active: AtomicBool::new(false),
+                last_model_use: Mutex::new(Some(Instant::now())),
                 import_active: AtomicBool::new(false),
                 import_cancelled: AtomicBool::new(false),
+                local_api: Mutex::new(None),
                 #[cfg(feature = "diarization")]
                 diarization_ctx: Mutex::new(None),
             });
 
+            // Start the local HTTP API if the user opted in.
+            {
+                let local_api_config = app.state::<AppState>().settings.lock().map(|s| s.local_api.clone());
+                if let Ok(config) = local_api_config {
+                    local_api::apply(app.handle(), &config);
+                }
+            }
+
+            // Re-register global shortcuts after sleep/wake: macOS sometimes
+            // stops delivering them until they are registered again.
+            {
+                let app_for_wake = app.handle().clone();
+                platform::add_wake_listener(move || {
+                    let app = app_for_wake.clone();
+                    // Off the main thread: the shortcut plugin dispatches
+                    // registration to it and waits for the result.
+                    std::thread::spawn(move || {
+                        std::thread::sleep(std::time::Duration::from_secs(1));
+                        match commands::reregister_hotkeys(&app) {
+                            Ok(()) => tracing::info!("Hotkeys re-registered after wake"),
+                            Err(e) => tracing::warn!("Failed to re-register hotkeys after wake: {}", e),
+                        }
+                    });
+                });
+            }
+
             // Register a CoreAudio listener for default-input-device changes.
             //
             // Handles two scenarios:
@@ -1225,12 +2429,21 @@ pub fn run() {
                 }
             }
 
-            // Auto-show settings when active whisper model is missing
+            // Auto-show settings for first-run onboarding, or when the
+            // configured STT engine has no model or API key to work with.
             {
-                let active_model = app.state::<AppState>().settings.lock()
-                    .map(|s| s.stt.whisper_model.clone())
+                let state = app.state::<AppState>();
+                let (onboarding_completed, mut stt_config) = state.settings.lock()
+                    .map(|s| (s.onboarding_completed, s.stt.clone()))
                     .unwrap_or_default();
-                if !models_dir().join(active_model.filename()).exists() {
+                if stt_config.mode == SttMode::Cloud {
+                    stt_config.cloud.api_key =
+                        get_cached_api_key(&state.api_key_cache, stt_config.cloud.provider.as_key());
+                }
+                if !onboarding_completed {
+                    show_settings_window(app.handle());
+                } else if !stt::is_stt_ready(&stt_config) {
+                    tracing::info!("Configured STT engine is not ready; opening settings");
                     show_settings_window(app.handle());
                 }
             }
@@ -1275,6 +2488,7 @@ pub fn run() {
                         if model_dir.join(polish_model.filename()).exists() {
                             if let Err(e) = polisher::warm_llm_cache(&state.llm_model, &model_dir, &polish_model) {
                                 tracing::error!("LLM pre-warm failed: {}", e);
+                                emit_polish_model_corrupt(&app_handle);
                             }
                         }
                     }
@@ -1339,9 +2553,10 @@ pub fn run() {
                             continue;
                         }
 
-                        // Don't close while recording, meeting, or reconnecting.
+                        // Don't close while recording, meeting, monitoring, or reconnecting.
                         if state.is_recording.load(Ordering::SeqCst)
                             || state.meeting_active.load(Ordering::SeqCst)
+                            || state.mic_monitor_active.load(Ordering::SeqCst)
                             || state.reconnecting.load(Ordering::SeqCst)
                             || !state.mic_available.load(Ordering::SeqCst)
                         {
@@ -1371,6 +2586,7 @@ pub fn run() {
                         if let Ok(at) = state.audio_thread.lock() {
                             if state.is_recording.load(Ordering::SeqCst)
                                 || state.meeting_active.load(Ordering::SeqCst)
+                                || state.mic_monitor_active.load(Ordering::SeqCst)
                                 || state.reconnecting.load(Ordering::SeqCst)
                             {
                                 continue;
@@ -1413,6 +2629,111 @@ pub fn run() {
                 });
             }
 
+            // Processing watchdog: a pipeline that hangs (a dead panicking
+            // thread is handled by `PanicGuard`) would leave every later hotkey
+            // press ignored.  Force the flag back once it has run for
+            // `max_processing_secs` plus twice the length of its audio, so a
+            // long recording that is still transcribing is left alone.
+            {
+                let app_handle = app.handle().clone();
+                std::thread::spawn(move || loop {
+                    std::thread::sleep(std::time::Duration::from_secs(5));
+                    let state = app_handle.state::<AppState>();
+                    let max_secs = state.settings.lock().map(|s| s.max_processing_secs).unwrap_or(0);
+                    if max_secs == 0 || !state.is_processing.load(Ordering::SeqCst) {
+                        continue;
+                    }
+                    let stuck_for = state
+                        .processing_since
+                        .lock()
+                        .ok()
+                        .and_then(|t| t.map(|i| i.elapsed()));
+                    let audio_ms = state.processing_audio_ms.load(Ordering::SeqCst);
+                    let budget = std::time::Duration::from_secs(max_secs as u64)
+                        + std::time::Duration::from_millis(audio_ms.saturating_mul(2));
+                    match stuck_for {
+                        Some(e) if e >= budget => {}
+                        _ => continue,
+                    }
+                    tracing::warn!(
+                        "Processing watchdog: pipeline still busy after {:.0?} — resetting state",
+                        budget
+                    );
+                    reset_processing(&app_handle, &state);
+                });
+            }
+
+            // Idle model-unload watcher: drops the cached Whisper context and
+            // LLM after a configurable idle period so local mode does not pin
+            // several GB of RAM/VRAM while the user is not dictating.  Both
+            // caches are lazily re-initialised on the next transcription.
+            {
+                let app_handle = app.handle().clone();
+                std::thread::spawn(move || {
+                    tracing::info!("Idle model-unload watcher started (poll interval: 30s)");
+                    loop {
+                        std::thread::sleep(std::time::Duration::from_secs(30));
+                        let state = app_handle.state::<AppState>();
+
+                        let unload_minutes = state
+                            .settings
+                            .lock()
+                            .map(|s| s.idle_unload_minutes)
+                            .unwrap_or(0);
+                        if unload_minutes == 0 {
+                            continue;
+                        }
+
+                        if state.is_recording.load(Ordering::SeqCst)
+                            || state.is_processing.load(Ordering::SeqCst)
+                            || state.meeting_active.load(Ordering::SeqCst)
+                            || state.import_active.load(Ordering::SeqCst)
+                            || state.model_switching.load(Ordering::SeqCst)
+                        {
+                            continue;
+                        }
+
+                        let timeout = std::time::Duration::from_secs(unload_minutes as u64 * 60);
+                        let elapsed = state
+                            .last_model_use
+                            .lock()
+                            .ok()
+                            .and_then(|t| t.map(|i| i.elapsed()));
+                        match elapsed {
+                            None => continue, // already unloaded
+                            Some(e) if e < timeout => continue,
+                            _ => {}
+                        }
+
+                        // try_lock: if a context is busy it is in use, so skip
+                        // it this round rather than blocking the watcher.
+                        let mut unloaded = false;
+                        let mut all_checked = true;
+                        match state.whisper_ctx.try_lock() {
+                            Ok(mut ctx) => unloaded |= ctx.take().is_some(),
+                            Err(_) => all_checked = false,
+                        }
+                        match state.llm_model.try_lock() {
+                            Ok(mut llm) => unloaded |= llm.take().is_some(),
+                            Err(_) => all_checked = false,
+                        }
+                        if unloaded {
+                            tracing::info!(
+                                "Idle model-unload timeout ({} min) — released Whisper/LLM caches",
+                                unload_minutes
+                            );
+                        }
+                        // Keep the timestamp while a busy cache is left, so the
+                        // next round tries it again.
+                        if all_checked {
+                            if let Ok(mut t) = state.last_model_use.lock() {
+                                *t = None;
+                            }
+                        }
+                    }
+                });
+            }
+
             // System Tray
             let settings_i =
                 MenuItem::with_id(app, "settings", "Settings...", true, None::<&str>)?;
@@ -1484,12 +2805,17 @@ pub fn run() {
                 app.handle().plugin(
                     tauri_plugin_global_shortcut::Builder::new()
                         .with_handler(move |app, shortcut, event| {
-                            if event.state() != ShortcutState::Pressed {
+                            let state = app.state::<AppState>();
+
+                            if *shortcut == cancel_shortcut()
+                                && state.cancel_shortcut_armed.lock().map(|armed| *armed).unwrap_or(false)
+                            {
+                                if event.state() == ShortcutState::Pressed {
+                                    cancel_from_shortcut(app, &state);
+                                }
                                 return;
                             }
 
-                            let state = app.state::<AppState>();
-
                             let is_edit_hotkey = state.registered_edit_shortcut
                                 .lock()
                                 .ok()
@@ -1500,6 +2826,35 @@ pub fn run() {
                                 .ok()
                                 .and_then(|g| g.as_ref().map(|s| s == shortcut))
                                 .unwrap_or(false);
+                            let is_raw_hotkey = state.registered_raw_shortcut
+                                .lock()
+                                .ok()
+                                .and_then(|g| g.as_ref().map(|s| s == shortcut))
+                                .unwrap_or(false);
+                            let is_repeat_hotkey = state.registered_repeat_shortcut
+                                .lock()
+                                .ok()
+                                .and_then(|g| g.as_ref().map(|s| s == shortcut))
+                                .unwrap_or(false);
+                            let push_to_talk = !is_meeting_hotkey
+                                && !is_repeat_hotkey
+                                && state.settings.lock().map(|s| s.hotkey_mode).unwrap_or_default()
+                                    == settings::HotkeyMode::PushToTalk;
+
+                            if event.state() != ShortcutState::Pressed {
+                                if let Ok(mut held) = state.hotkeys_held.lock() {
+                                    held.remove(&shortcut.id());
+                                }
+                                // Push-to-talk: letting go of a dictation hotkey ends the recording.
+                                if push_to_talk
+                                    && !state.test_mode.load(Ordering::SeqCst)
+                                    && !state.meeting_active.load(Ordering::SeqCst)
+                                    && state.is_recording.load(Ordering::SeqCst)
+                                {
+                                    stop_recording_from_hotkey(app, &state, is_raw_hotkey);
+                                }
+                                return;
+                            }
 
                             if state.test_mode.load(Ordering::SeqCst) {
                                 if let Some(main_win) = app.get_webview_window("main") {
@@ -1508,19 +2863,54 @@ pub fn run() {
                                 return;
                             }
 
-                            // Debounce
+                            // Debounce: drop auto-repeat from a held key, then
+                            // presses inside `hotkey_debounce_ms` of the last one.
+                            // Push-to-talk skips the latter so quick taps register.
                             {
                                 let now = Instant::now();
+                                let debounce = if push_to_talk {
+                                    std::time::Duration::ZERO
+                                } else {
+                                    std::time::Duration::from_millis(
+                                        state.settings.lock().map(|s| s.hotkey_debounce_ms).unwrap_or(300),
+                                    )
+                                };
+                                let held = state
+                                    .hotkeys_held
+                                    .lock()
+                                    .map(|mut held| !held.insert(shortcut.id()))
+                                    .unwrap_or(false);
+                                let since_event = state
+                                    .last_hotkey_event
+                                    .lock()
+                                    .map(|mut t| {
+                                        let d = now.duration_since(*t);
+                                        *t = now;
+                                        d
+                                    })
+                                    .unwrap_or(std::time::Duration::MAX);
                                 if let Ok(mut last) = state.last_hotkey_time.lock() {
-                                    if now.duration_since(*last) < std::time::Duration::from_millis(300) {
-                                        return;
+                                    match hotkey::classify_press(held, since_event, now.duration_since(*last), debounce) {
+                                        hotkey::PressKind::Accept => *last = now,
+                                        hotkey::PressKind::Repeat | hotkey::PressKind::Bounce => return,
                                     }
-                                    *last = now;
                                 }
                             }
 
-                            if state.is_processing.load(Ordering::SeqCst) {
-                                return;
+                            // While busy, presses are ignored unless queued mode lets the
+                            // primary hotkey record the next dictation (or stop it).
+                            let busy = dictation_busy(&state);
+                            if busy {
+                                let press = dictation_queue::BusyPress {
+                                    recording: state.is_recording.load(Ordering::SeqCst),
+                                    recording_queued: state.recording_queued.load(Ordering::SeqCst),
+                                    primary: !is_edit_hotkey && !is_meeting_hotkey && !is_repeat_hotkey,
+                                    stop_pending: state.stop_pending.load(Ordering::SeqCst),
+                                    queue_enabled: queue_dictations_enabled(&state),
+                                };
+                                if !press.accepted() {
+                                    return;
+                                }
                             }
 
                             if state.model_switching.load(Ordering::SeqCst) {
@@ -1542,6 +2932,15 @@ pub fn run() {
                                 return;
                             }
 
+                            // Repeat-paste hotkey: paste the last transcript again, never records.
+                            if is_repeat_hotkey {
+                                if !state.is_recording.load(Ordering::SeqCst) {
+                                    let plain_only = state.settings.lock().map(|s| s.plain_text_only).unwrap_or(true);
+                                    std::thread::spawn(move || repeat_last_paste(plain_only));
+                                }
+                                return;
+                            }
+
                             // Block regular recording while meeting is finalizing (worker still running).
                             if state.meeting_stopping.load(Ordering::SeqCst) {
                                 return;
@@ -1556,6 +2955,8 @@ pub fn run() {
 
                             if !is_recording {
                                 // Start Recording
+                                state.paste_raw_pending.store(is_raw_hotkey, Ordering::SeqCst);
+                                state.recording_queued.store(busy, Ordering::SeqCst);
 
                                 // For edit hotkey: check polish readiness before anything else
                                 if is_edit_hotkey {
@@ -1604,64 +3005,78 @@ pub fn run() {
                                             .ok()
                                             .and_then(|mut cb| cb.get_text().ok());
                                         if let Ok(mut saved) = state.saved_clipboard.lock() {
-                                            *saved = original_clipboard;
+                                            *saved = original_clipboard.clone();
                                         }
 
-                                        // Record change count before copy (macOS/Windows)
-                                        let change_count_before = platform::clipboard_change_count();
+                                        // Clipboard scope edits what is already on the clipboard;
+                                        // the others copy the selection (after Cmd/Ctrl+A for SelectAll).
+                                        let edit_scope = state.settings.lock()
+                                            .map(|s| s.edit_scope)
+                                            .unwrap_or_default();
+                                        let selected = match edit_scope {
+                                            EditScope::Clipboard => original_clipboard.unwrap_or_default(),
+                                            EditScope::Selection | EditScope::SelectAll => {
+                                                // Record change count before copy (macOS/Windows)
+                                                let change_count_before = platform::clipboard_change_count();
+
+                                                // On platforms without change count (Linux), write a sentinel
+                                                // so we can detect whether Ctrl+C actually fired
+                                                let sentinel_str: Option<String> = if change_count_before.is_none() {
+                                                    let s = format!("__sumi_sentinel_{}__",
+                                                        std::time::SystemTime::now()
+                                                            .duration_since(std::time::UNIX_EPOCH)
+                                                            .unwrap_or_default()
+                                                            .as_nanos());
+                                                    if let Ok(mut cb) = arboard::Clipboard::new() {
+                                                        let _ = cb.set_text(&s);
+                                                    }
+                                                    std::thread::sleep(std::time::Duration::from_millis(30));
+                                                    Some(s)
+                                                } else {
+                                                    None
+                                                };
 
-                                        // On platforms without change count (Linux), write a sentinel
-                                        // so we can detect whether Ctrl+C actually fired
-                                        let sentinel_str: Option<String> = if change_count_before.is_none() {
-                                            let s = format!("__sumi_sentinel_{}__",
-                                                std::time::SystemTime::now()
-                                                    .duration_since(std::time::UNIX_EPOCH)
-                                                    .unwrap_or_default()
-                                                    .as_nanos());
-                                            if let Ok(mut cb) = arboard::Clipboard::new() {
-                                                let _ = cb.set_text(&s);
-                                            }
-                                            std::thread::sleep(std::time::Duration::from_millis(30));
-                                            Some(s)
-                                        } else {
-                                            None
-                                        };
+                                                if edit_scope == EditScope::SelectAll {
+                                                    platform::simulate_select_all();
+                                                    std::thread::sleep(std::time::Duration::from_millis(50));
+                                                }
+                                                platform::simulate_copy();
+                                                std::thread::sleep(std::time::Duration::from_millis(100));
+
+                                                // Determine whether the clipboard was actually updated
+                                                let clipboard_changed = match change_count_before {
+                                                    Some(before) => {
+                                                        // macOS / Windows: compare sequence numbers
+                                                        platform::clipboard_change_count()
+                                                            .map(|after| after != before)
+                                                            .unwrap_or(false)
+                                                    }
+                                                    None => {
+                                                        // Linux / fallback: check the clipboard differs from sentinel
+                                                        let current = arboard::Clipboard::new()
+                                                            .ok()
+                                                            .and_then(|mut cb| cb.get_text().ok())
+                                                            .unwrap_or_default();
+                                                        let sentinel = sentinel_str.as_deref().unwrap_or("");
+                                                        !current.is_empty() && current != sentinel
+                                                    }
+                                                };
 
-                                        platform::simulate_copy();
-                                        std::thread::sleep(std::time::Duration::from_millis(100));
+                                                if !clipboard_changed {
+                                                    tracing::info!("Edit-by-voice: no text selected, aborting");
+                                                    restore_clipboard(&state);
+                                                    return;
+                                                }
 
-                                        // Determine whether the clipboard was actually updated
-                                        let clipboard_changed = match change_count_before {
-                                            Some(before) => {
-                                                // macOS / Windows: compare sequence numbers
-                                                platform::clipboard_change_count()
-                                                    .map(|after| after != before)
-                                                    .unwrap_or(false)
-                                            }
-                                            None => {
-                                                // Linux / fallback: check the clipboard differs from sentinel
-                                                let current = arboard::Clipboard::new()
+                                                arboard::Clipboard::new()
                                                     .ok()
                                                     .and_then(|mut cb| cb.get_text().ok())
-                                                    .unwrap_or_default();
-                                                let sentinel = sentinel_str.as_deref().unwrap_or("");
-                                                !current.is_empty() && current != sentinel
+                                                    .unwrap_or_default()
                                             }
                                         };
 
-                                        if !clipboard_changed {
-                                            tracing::info!("Edit-by-voice: no text selected, aborting");
-                                            restore_clipboard(&state);
-                                            return;
-                                        }
-
-                                        let selected = arboard::Clipboard::new()
-                                            .ok()
-                                            .and_then(|mut cb| cb.get_text().ok())
-                                            .unwrap_or_default();
-
                                         if selected.is_empty() {
-                                            tracing::warn!("Edit-by-voice: clipboard empty after copy, aborting");
+                                            tracing::warn!("Edit-by-voice: nothing to edit ({:?} scope), aborting", edit_scope);
                                             restore_clipboard(&state);
                                             return;
                                         }
@@ -1713,6 +3128,7 @@ pub fn run() {
                                     Ok(()) => {
                                         tracing::info!("🎙️ Recording started (app: {:?}, bundle: {:?}, url: {:?})",
                                             captured_ctx.app_name, captured_ctx.bundle_id, captured_ctx.url);
+                                        report_mic_fallback(app, &state);
 
                                         // Recording-start warm: load models in parallel with the user speaking.
                                         // If startup pre-warm already finished, the guard in each warm function
@@ -1767,13 +3183,19 @@ pub fn run() {
                                         // ── Live-preview feeder (Qwen3-ASR non-edit mode only) ──
                                         // Read all feeder-relevant settings in a single lock acquisition
                                         // so that no race can occur between computing should_stream and
-                                        // reading feeder_model/feeder_lang.
-                                        let stream_config = if !is_edit_hotkey {
+                                        // reading feeder_model/feeder_lang.  Continuous sessions cut the
+                                        // buffer at every pause, which the feeders cannot follow, and a
+                                        // queued recording must leave the feeder state to the pipeline
+                                        // that is still running.
+                                        let stream_config = if !is_edit_hotkey && !busy {
                                             state.settings.lock().ok().and_then(|s| {
-                                                if s.stt.mode == SttMode::Local
+                                                if s.session_mode == settings::SessionMode::Single
+                                                    && s.stt.mode == SttMode::Local
                                                     && s.stt.local_engine == stt::LocalSttEngine::Qwen3Asr
                                                 {
-                                                    Some((s.stt.qwen3_asr_model.clone(), s.stt.language.clone()))
+                                                    let lang = polisher::stt_language_override(&s.polish, &captured_ctx)
+                                                        .unwrap_or_else(|| s.stt.language.clone());
+                                                    Some((s.stt.qwen3_asr_model.clone(), lang))
                                                 } else {
                                                     None
                                                 }
@@ -1806,9 +3228,10 @@ pub fn run() {
                                         }
 
                                         // ── Live-preview feeder (Whisper non-edit mode only) ──
-                                        let whisper_preview_config = if !is_edit_hotkey {
+                                        let whisper_preview_config = if !is_edit_hotkey && !busy {
                                             state.settings.lock().ok().and_then(|s| {
-                                                if s.stt.mode == SttMode::Local
+                                                if s.session_mode == settings::SessionMode::Single
+                                                    && s.stt.mode == SttMode::Local
                                                     && s.stt.local_engine == stt::LocalSttEngine::Whisper
                                                 {
                                                     Some(s.stt.language.clone())
@@ -1842,15 +3265,17 @@ pub fn run() {
                                         if let Some(overlay) = app.get_webview_window("overlay") {
                                             let rec_status = if is_edit_hotkey { "edit_recording" } else { "recording" };
                                             let _ = overlay.emit("recording-status", rec_status);
-                                            let _ = overlay.emit("recording-max-duration", MAX_RECORDING_SECS);
+                                            let _ = overlay.emit("recording-max-duration", max_recording_secs(&state));
                                             // overlay already shown in 'preparing' state above
                                         }
+                                        arm_cancel_shortcut(app);
 
                                         // Audio level monitoring thread
                                         spawn_audio_level_monitor(app.clone(), AudioMonitorMode::Normal);
                                     }
                                     Err(e) => {
                                         tracing::error!("Failed to start recording: {}", e);
+                                        state.recording_queued.store(false, Ordering::SeqCst);
                                         if is_edit_hotkey {
                                             state.edit_mode.store(false, Ordering::SeqCst);
                                             restore_clipboard(&state);
@@ -1867,12 +3292,9 @@ pub fn run() {
                                     }
                                 }
                             } else {
-                                // Stop Recording
-                                if state.edit_mode.load(Ordering::SeqCst) {
-                                    stop_edit_and_replace(app);
-                                } else {
-                                    stop_transcribe_and_paste(app);
-                                }
+                                // Stop Recording (in push-to-talk mode, only if the
+                                // release event never arrived).
+                                stop_recording_from_hotkey(app, &state, is_raw_hotkey);
                             }
                         })
                         .build(),
@@ -1895,6 +3317,9 @@ pub fn run() {
                         tracing::info!("{} meeting shortcut registered", hotkey_display_label(meeting_hk));
                     }
                 }
+
+                register_raw_paste_shortcut(app.handle(), &app.state::<AppState>(), &settings);
+                register_repeat_paste_shortcut(app.handle(), &app.state::<AppState>(), &settings);
             }
 
             Ok(())
@@ -1903,7 +3328,7 @@ pub fn run() {
         .expect("error while running tauri application");
 }
 
-fn show_settings_window(app: &AppHandle) {
+pub(crate) fn show_settings_window(app: &AppHandle) {
     if let Some(window) = app.get_webview_window("main") {
         let _ = window.set_size(tauri::LogicalSize::new(960.0, 720.0));
         let _ = window.center();
@@ -2019,7 +3444,7 @@ fn start_meeting_mode(app: &AppHandle) {
                 let app_inner = app_for_hide.clone();
                 let _ = app_for_hide.run_on_main_thread(move || {
                     if let Some(ov) = app_inner.get_webview_window("overlay") {
-                        platform::hide_overlay(&ov);
+                        crate::hide_overlay_idle(&ov);
                     }
                 });
             });
@@ -2027,6 +3452,7 @@ fn start_meeting_mode(app: &AppHandle) {
         return;
     }
     tracing::info!("🎙️ Meeting mode started (engine: {:?}, lang: {:?})", stt_mode, lang);
+    report_mic_fallback(app, &state);
     // Advance the session generation counter. The feeder captures this value
     // and aborts post-loop work if the counter has advanced past it, preventing
     // a zombie feeder from a timed-out previous session from corrupting state.
@@ -2346,7 +3772,8 @@ enum AudioMonitorMode {
 
 /// Compute per-bar RMS levels from the current audio buffer, applying adaptive
 /// gain (fast attack, slow decay) so the waveform fills the visual range on
-/// any platform or mic sensitivity.
+/// any platform or mic sensitivity.  Also returns the un-normalized RMS of the
+/// most recent bar, used for silence endpointing.
 ///
 /// The buffer lock is held only while copying the tail slice; all computation
 /// runs after the lock is released to minimise contention with the cpal callback.
@@ -2355,14 +3782,15 @@ fn compute_audio_levels(
     num_bars: usize,
     samples_per_bar: usize,
     peak_rms: &mut f32,
-) -> Vec<f32> {
+    gain: f32,
+) -> (Vec<f32>, f32) {
     // Copy only the tail we need, then release the lock before computing.
     let tail: Vec<f32> = {
         let Ok(buf) = buffer.lock() else {
-            return vec![0.0; num_bars];
+            return (vec![0.0; num_bars], 0.0);
         };
         if buf.is_empty() {
-            return vec![0.0; num_bars];
+            return (vec![0.0; num_bars], 0.0);
         }
         let total = num_bars * samples_per_bar;
         let start = buf.len().saturating_sub(total);
@@ -2383,18 +3811,18 @@ fn compute_audio_levels(
     // Left-pad with zeros so the waveform always has exactly `num_bars` bars,
     // with the most-recent audio on the right.
     let mut bars = vec![0.0f32; num_bars.saturating_sub(raw_rms.len())];
-    bars.extend(raw_rms.iter().map(|&rms| (rms / *peak_rms).min(1.0)));
-    bars
+    bars.extend(raw_rms.iter().map(|&rms| (rms / *peak_rms * gain).min(1.0)));
+    (bars, raw_rms.last().copied().unwrap_or(0.0))
 }
 
 /// Spawn the 50 ms audio-level monitor thread.
-/// `Normal` mode applies max-duration auto-stop and voice-rule-mode forwarding.
+/// `Normal` mode applies max-duration and silence auto-stop, and voice-rule-mode forwarding.
 /// `Meeting` mode calls `stop_meeting_mode` on dead-stream detection.
 fn spawn_audio_level_monitor(app: AppHandle, mode: AudioMonitorMode) {
     std::thread::spawn(move || {
         let state = app.state::<AppState>();
         let sr = state.sample_rate.lock().ok().and_then(|v| *v).unwrap_or(44100) as usize;
-        let recording_start = Instant::now();
+        let mut recording_start = Instant::now();
 
         const NUM_BARS: usize = 20;
         let samples_per_bar = sr / 20;
@@ -2402,13 +3830,34 @@ fn spawn_audio_level_monitor(app: AppHandle, mode: AudioMonitorMode) {
         // visual range regardless of platform mic level.
         let mut peak_rms: f32 = 0.01;
         let is_normal = matches!(mode, AudioMonitorMode::Normal);
+        let (auto_stop_silence_ms, visualizer_gain, session_mode) = state
+            .settings
+            .lock()
+            .map(|s| (s.auto_stop_silence_ms, settings::clamp_visualizer_gain(s.visualizer_gain), s.session_mode))
+            .unwrap_or((0, 1.0, settings::SessionMode::default()));
+        let max_recording_secs = max_recording_secs(&state);
+        // Edits and voice rules need the whole utterance at once.
+        let continuous = is_normal
+            && session_mode == settings::SessionMode::Continuous
+            && !state.edit_mode.load(Ordering::SeqCst)
+            && !state.voice_rule_mode.load(Ordering::SeqCst);
+        let auto_stop_silence_ms = if continuous && auto_stop_silence_ms == 0 {
+            settings::SessionMode::DEFAULT_PAUSE_MS
+        } else {
+            auto_stop_silence_ms
+        };
+        let mut endpointer = (is_normal && auto_stop_silence_ms > 0)
+            .then(|| audio::SilenceEndpointer::new(auto_stop_silence_ms));
+        let mut last_tick = Instant::now();
 
         while state.is_recording.load(Ordering::SeqCst) {
             let elapsed = recording_start.elapsed();
 
             // Dead-stream guard: if the buffer is still empty after 1.5 s the
-            // cpal callback is not running at all.
-            if elapsed.as_millis() >= 1500 {
+            // cpal callback is not running at all.  WASAPI loopback delivers
+            // no packets while nothing is playing, so silence is no sign of a
+            // dead stream there.
+            if elapsed.as_millis() >= 1500 && !audio::loopback_capture() {
                 let buf_empty = state.buffer.lock().map(|b| b.is_empty()).unwrap_or(false);
                 if buf_empty {
                     state.mic_available.store(false, Ordering::SeqCst);
@@ -2437,9 +3886,15 @@ fn spawn_audio_level_monitor(app: AppHandle, mode: AudioMonitorMode) {
                 }
             }
 
-            // Normal mode only: enforce max recording duration.
-            if is_normal && elapsed.as_secs() >= MAX_RECORDING_SECS {
-                tracing::info!("Max recording duration reached ({}s)", MAX_RECORDING_SECS);
+            // Normal mode only: enforce max recording duration.  A continuous
+            // session only cuts a chunk; the limit then applies per chunk.
+            if is_normal && elapsed.as_secs() >= max_recording_secs {
+                tracing::info!("Max recording duration reached ({}s)", max_recording_secs);
+                if continuous {
+                    commit_continuous_chunk(&app);
+                    recording_start = Instant::now();
+                    continue;
+                }
                 if state.edit_mode.load(Ordering::SeqCst) {
                     stop_edit_and_replace(&app);
                 } else {
@@ -2448,7 +3903,30 @@ fn spawn_audio_level_monitor(app: AppHandle, mode: AudioMonitorMode) {
                 return;
             }
 
-            let levels = compute_audio_levels(&state.buffer, NUM_BARS, samples_per_bar, &mut peak_rms);
+            let (levels, latest_rms) = compute_audio_levels(&state.buffer, NUM_BARS, samples_per_bar, &mut peak_rms, visualizer_gain);
+
+            // Normal mode only: stop once the speaker has gone quiet.
+            if let Some(ep) = endpointer.as_mut() {
+                let tick = last_tick.elapsed();
+                last_tick = Instant::now();
+                if ep.update(latest_rms, tick) {
+                    if continuous {
+                        // Keep recording; the dead-stream guard and the
+                        // duration limit restart with the next chunk.
+                        commit_continuous_chunk(&app);
+                        *ep = audio::SilenceEndpointer::new(auto_stop_silence_ms);
+                        recording_start = Instant::now();
+                        continue;
+                    }
+                    tracing::info!("Auto-stop: {} ms of silence after speech", auto_stop_silence_ms);
+                    if state.edit_mode.load(Ordering::SeqCst) {
+                        stop_edit_and_replace(&app);
+                    } else {
+                        stop_transcribe_and_paste(&app);
+                    }
+                    return;
+                }
+            }
 
             if let Some(ov) = app.get_webview_window("overlay") {
                 let _ = ov.emit("audio-levels", &levels);
@@ -0,0 +1,349 @@
+//! Opt-in local HTTP API so scripts and stream decks can drive dictation.
+//!
+//! Bound to 127.0.0.1 only and every request must carry
+//! `Authorization: Bearer <token>`.  Requests with an `Origin` header are
+//! refused so a web page cannot reach the server from the browser.
+//!
+//! | Route                | Effect                                            |
+//! |----------------------|---------------------------------------------------|
+//! | `GET /status`        | `{ recording, processing, meeting }`              |
+//! | `POST /record/start` | Same as pressing the dictation hotkey             |
+//! | `POST /record/stop`  | Runs the full pipeline, returns the transcript    |
+
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use crate::settings::history_dir;
+use crate::{history, AppState};
+
+pub const DEFAULT_PORT: u16 = 47821;
+
+/// How long `POST /record/stop` waits for the pipeline to finish.
+const STOP_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Upper bound on request line + headers; the API takes no request bodies.
+const MAX_HEADER_BYTES: usize = 8 * 1024;
+
+/// Connections served at once; more are turned away with 503.  A
+/// `/record/stop` holds its connection for the whole pipeline, so a few are
+/// plenty for scripts and stream decks.
+const MAX_CONNECTIONS: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LocalApiConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// Shared secret; the server refuses to start while it is empty.
+    #[serde(default)]
+    pub token: String,
+}
+
+fn default_port() -> u16 {
+    DEFAULT_PORT
+}
+
+impl Default for LocalApiConfig {
+    fn default() -> Self {
+        Self { enabled: false, port: DEFAULT_PORT, token: String::new() }
+    }
+}
+
+/// A running server; dropping it does not stop it, call [`ServerHandle::stop`].
+pub struct ServerHandle {
+    config: LocalApiConfig,
+    stop: Arc<AtomicBool>,
+}
+
+impl ServerHandle {
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // Wake the blocking accept() so the thread sees the flag.
+        let _ = TcpStream::connect(("127.0.0.1", self.config.port));
+    }
+}
+
+/// Start, restart or stop the server so it matches `config`.
+pub fn apply(app: &AppHandle, config: &LocalApiConfig) {
+    let state = app.state::<AppState>();
+    let Ok(mut slot) = state.local_api.lock() else { return };
+    if slot.as_ref().is_some_and(|h| &h.config == config) {
+        return;
+    }
+    if let Some(old) = slot.take() {
+        old.stop();
+        tracing::info!("Local API stopped");
+    }
+    if !config.enabled {
+        return;
+    }
+    if config.token.trim().is_empty() {
+        tracing::warn!("Local API enabled without a token; not starting");
+        return;
+    }
+    match start(app.clone(), config.clone()) {
+        Ok(handle) => *slot = Some(handle),
+        Err(e) => tracing::error!("Local API failed to start on port {}: {}", config.port, e),
+    }
+}
+
+fn start(app: AppHandle, config: LocalApiConfig) -> Result<ServerHandle, String> {
+    // A just-stopped server may still hold the port for a moment.
+    let mut attempts = 0;
+    let listener = loop {
+        match TcpListener::bind(("127.0.0.1", config.port)) {
+            Ok(l) => break l,
+            Err(_) if attempts < 10 => {
+                attempts += 1;
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            Err(e) => return Err(e.to_string()),
+        }
+    };
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread_stop = Arc::clone(&stop);
+    let token = config.token.clone();
+    std::thread::spawn(move || {
+        let active = Arc::new(AtomicUsize::new(0));
+        for stream in listener.incoming() {
+            if thread_stop.load(Ordering::SeqCst) {
+                break;
+            }
+            let Ok(mut stream) = stream else { continue };
+            let Some(slot) = ConnectionSlot::acquire(&active) else {
+                let _ = stream.set_write_timeout(Some(Duration::from_secs(1)));
+                respond(&mut stream, 503, &serde_json::json!({ "error": "too many connections" }));
+                continue;
+            };
+            let app = app.clone();
+            let token = token.clone();
+            std::thread::spawn(move || {
+                let _slot = slot;
+                handle_connection(&app, stream, &token);
+            });
+        }
+    });
+    tracing::info!("Local API listening on 127.0.0.1:{}", config.port);
+    Ok(ServerHandle { config, stop })
+}
+
+/// One of the [`MAX_CONNECTIONS`] connection slots, released on drop.
+struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl ConnectionSlot {
+    fn acquire(active: &Arc<AtomicUsize>) -> Option<Self> {
+        if active.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            active.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(Self(Arc::clone(active)))
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Request {
+    method: String,
+    path: String,
+    authorization: Option<String>,
+    has_origin: bool,
+}
+
+/// Parse the request line and the headers we care about.
+fn parse_request(head: &str) -> Option<Request> {
+    let mut lines = head.lines();
+    let mut parts = lines.next()?.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?;
+    let path = target.split('?').next().unwrap_or(target).to_string();
+    let mut authorization = None;
+    let mut has_origin = false;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else { continue };
+        match name.trim().to_ascii_lowercase().as_str() {
+            "authorization" => authorization = Some(value.trim().to_string()),
+            "origin" => has_origin = true,
+            _ => {}
+        }
+    }
+    Some(Request { method, path, authorization, has_origin })
+}
+
+/// Constant-time check of `Authorization: Bearer <token>`.
+fn is_authorized(authorization: Option<&str>, token: &str) -> bool {
+    let Some(given) = authorization.and_then(|a| a.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    let (a, b) = (given.trim().as_bytes(), token.as_bytes());
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn read_head(stream: &TcpStream) -> Option<String> {
+    let mut reader = BufReader::new(stream.try_clone().ok()?);
+    let mut head = String::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).ok()? == 0 {
+            break;
+        }
+        if line == "\r\n" || line == "\n" {
+            break;
+        }
+        head.push_str(&line);
+        if head.len() > MAX_HEADER_BYTES {
+            return None;
+        }
+    }
+    Some(head)
+}
+
+fn handle_connection(app: &AppHandle, mut stream: TcpStream, token: &str) {
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+    let Some(request) = read_head(&stream).as_deref().and_then(parse_request) else {
+        respond(&mut stream, 400, &serde_json::json!({ "error": "bad request" }));
+        return;
+    };
+    if request.has_origin {
+        respond(&mut stream, 403, &serde_json::json!({ "error": "browser requests are not allowed" }));
+        return;
+    }
+    if !is_authorized(request.authorization.as_deref(), token) {
+        respond(&mut stream, 401, &serde_json::json!({ "error": "unauthorized" }));
+        return;
+    }
+
+    let (status, body) = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/status") => (200, status_json(app)),
+        ("POST", "/record/start") => match crate::start_recording_from_api(app) {
+            Ok(()) => (200, serde_json::json!({ "recording": true })),
+            Err(e) => (409, serde_json::json!({ "error": e })),
+        },
+        ("POST", "/record/stop") => stop_and_collect(app),
+        (_, "/status" | "/record/start" | "/record/stop") => {
+            (405, serde_json::json!({ "error": "method not allowed" }))
+        }
+        _ => (404, serde_json::json!({ "error": "not found" })),
+    };
+    respond(&mut stream, status, &body);
+}
+
+fn status_json(app: &AppHandle) -> serde_json::Value {
+    let state = app.state::<AppState>();
+    serde_json::json!({
+        "recording": state.is_recording.load(Ordering::SeqCst),
+        "processing": state.is_processing.load(Ordering::SeqCst),
+        "meeting": state.meeting_active.load(Ordering::SeqCst),
+    })
+}
+
+/// Stop recording, wait for the pipeline and return the entry it saved.
+fn stop_and_collect(app: &AppHandle) -> (u16, serde_json::Value) {
+    let state = app.state::<AppState>();
+    if !state.is_recording.load(Ordering::SeqCst) {
+        return (409, serde_json::json!({ "error": "not_recording" }));
+    }
+    let pipeline = match crate::stop_transcribe_and_paste(app) {
+        crate::StopOutcome::Running(pipeline) => pipeline,
+        crate::StopOutcome::Queued => return (202, serde_json::json!({ "queued": true })),
+        crate::StopOutcome::Busy => return (409, serde_json::json!({ "error": "busy" })),
+    };
+
+    let started = Instant::now();
+    while !pipeline.is_finished() {
+        if started.elapsed() > STOP_TIMEOUT {
+            return (504, serde_json::json!({ "error": "timed out waiting for transcript" }));
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    // No entry means nothing was transcribed (silence, error or cancel).
+    let saved = pipeline.join().ok().flatten();
+    match saved.and_then(|id| history::get_entry(&history_dir(), &id)) {
+        Some(entry) => (
+            200,
+            serde_json::json!({
+                "id": entry.id,
+                "text": entry.text,
+                "raw_text": entry.raw_text,
+                "duration_secs": entry.duration_secs,
+            }),
+        ),
+        None => (200, serde_json::json!({ "text": "" })),
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: u16, body: &serde_json::Value) {
+    let reason = match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "Error",
+    };
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_request_line_and_headers() {
+        let req = parse_request(
+            "POST /record/stop?x=1 HTTP/1.1\r\nHost: 127.0.0.1\r\nAuthorization: Bearer abc\r\n",
+        )
+        .unwrap();
+        assert_eq!(req.method, "POST");
+        assert_eq!(req.path, "/record/stop");
+        assert_eq!(req.authorization.as_deref(), Some("Bearer abc"));
+        assert!(!req.has_origin);
+
+        let req = parse_request("GET /status HTTP/1.1\r\norigin: https://example.com\r\n").unwrap();
+        assert!(req.has_origin);
+        assert!(parse_request("").is_none());
+    }
+
+    #[test]
+    fn connections_beyond_the_limit_are_refused() {
+        let active = Arc::new(AtomicUsize::new(0));
+        let slots: Vec<_> = (0..MAX_CONNECTIONS).map(|_| ConnectionSlot::acquire(&active).unwrap()).collect();
+        assert!(ConnectionSlot::acquire(&active).is_none());
+        assert_eq!(active.load(Ordering::SeqCst), MAX_CONNECTIONS);
+        drop(slots);
+        assert_eq!(active.load(Ordering::SeqCst), 0);
+        assert!(ConnectionSlot::acquire(&active).is_some());
+    }
+
+    #[test]
+    fn token_must_match_exactly() {
+        assert!(is_authorized(Some("Bearer s3cret"), "s3cret"));
+        assert!(!is_authorized(Some("Bearer s3cre"), "s3cret"));
+        assert!(!is_authorized(Some("s3cret"), "s3cret"));
+        assert!(!is_authorized(None, "s3cret"));
+    }
+}
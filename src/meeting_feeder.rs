@@ -150,6 +150,7 @@ fn run_meeting_worker(
         // is handled gracefully by all three STT backends.
         let mut wal_segments: Vec<crate::meeting_notes::WalSegment> =
             transcribe(&samples, start_secs, end_secs, &prev_text);
+        state.touch_model_use();
         for seg in &mut wal_segments {
             seg.text = crate::maybe_convert_zh(&seg.text, language);
         }
@@ -153,6 +153,32 @@ mod inner {
     }
 }
 
+/// Whether synthetic key events (Cmd+V) will actually be delivered.
+/// Without Accessibility, macOS drops them silently.  Always true elsewhere.
+pub fn accessibility_trusted() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        inner::accessibility_trusted()
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        true
+    }
+}
+
+/// Open the Accessibility pane the first time a paste is blocked in this
+/// session; later failures only show the overlay hint.
+pub fn prompt_accessibility_once() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    static PROMPTED: AtomicBool = AtomicBool::new(false);
+    if PROMPTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    if let Err(e) = open_permission_settings("accessibility".to_string()) {
+        tracing::warn!("Failed to open accessibility settings: {}", e);
+    }
+}
+
 #[tauri::command]
 pub fn check_permissions() -> PermissionStatus {
     #[cfg(target_os = "macos")]
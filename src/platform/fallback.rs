@@ -20,6 +20,11 @@ pub unsafe fn simulate_copy() -> bool {
     false
 }
 
+/// Select-all simulation not available on this platform.
+pub unsafe fn simulate_select_all() -> bool {
+    false
+}
+
 /// Undo simulation not available on this platform.
 pub unsafe fn simulate_undo() -> bool {
     false
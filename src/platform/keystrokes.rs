@@ -0,0 +1,151 @@
+//! Per-character typing for targets that ignore clipboard paste (web
+//! canvases, games, remote desktop clients).
+//!
+//! Text goes out one grapheme cluster per key event, so combining marks,
+//! ZWJ emoji and surrogate pairs always arrive together, through the
+//! platform's Unicode keystroke path (`CGEventKeyboardSetUnicodeString`,
+//! `SendInput` with `KEYEVENTF_UNICODE`).  That path bypasses the keyboard
+//! layout and any IME, so CJK text is typed as-is.  Line breaks become real
+//! Return presses, which is what chat boxes and terminals expect.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Where the keystrokes go: the OS event queue, or a recorder in tests.
+pub trait KeySink {
+    /// Type one grapheme cluster.  Returns false if the OS rejected it.
+    fn text(&mut self, grapheme: &str) -> bool;
+    /// Press and release Return.
+    fn enter(&mut self) -> bool;
+}
+
+/// Type `text` into `sink`, pausing `delay` between keystrokes so slow
+/// targets do not drop characters.  Stops at the first rejected keystroke,
+/// or once `cancel` is set.  Returns the number of grapheme clusters typed,
+/// so the caller can tell a partial failure (some text already landed) from
+/// nothing typed at all.
+pub fn type_with(sink: &mut impl KeySink, text: &str, delay: Duration, cancel: &AtomicBool) -> usize {
+    let mut typed = 0usize;
+    for grapheme in text.graphemes(true) {
+        if typed > 0 && !delay.is_zero() {
+            std::thread::sleep(delay);
+        }
+        if cancel.load(Ordering::SeqCst) {
+            tracing::info!("Keystroke typing cancelled after {} characters", typed);
+            return typed;
+        }
+        let ok = match grapheme {
+            "\n" | "\r\n" | "\r" => sink.enter(),
+            g => sink.text(g),
+        };
+        if !ok {
+            if typed > 0 {
+                tracing::warn!("Keystroke typing stopped after {} characters", typed);
+            }
+            return typed;
+        }
+        typed += 1;
+    }
+    typed
+}
+
+/// Split `units` (one grapheme as UTF-16) into pieces of at most `max`
+/// units without separating a surrogate pair.  macOS accepts 20 units per
+/// key event; only extreme combining sequences ever need more than one.
+pub fn utf16_pieces(units: &[u16], max: usize) -> Vec<&[u16]> {
+    let mut pieces = Vec::new();
+    let mut rest = units;
+    while rest.len() > max {
+        let mut cut = max;
+        if cut > 1 && (0xD800..0xDC00).contains(&rest[cut - 1]) {
+            cut -= 1;
+        }
+        let (head, tail) = rest.split_at(cut);
+        pieces.push(head);
+        rest = tail;
+    }
+    if !rest.is_empty() {
+        pieces.push(rest);
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Dummy target: records each keystroke and rebuilds the typed text.
+    #[derive(Default)]
+    struct Recorder {
+        keys: Vec<String>,
+        fail_after: Option<usize>,
+    }
+
+    impl Recorder {
+        fn accept(&mut self, key: &str) -> bool {
+            if self.fail_after.is_some_and(|n| self.keys.len() >= n) {
+                return false;
+            }
+            self.keys.push(key.to_string());
+            true
+        }
+
+        fn typed(&self) -> String {
+            self.keys.iter().map(|k| if k == "⏎" { "\n" } else { k.as_str() }).collect()
+        }
+    }
+
+    impl KeySink for Recorder {
+        fn text(&mut self, grapheme: &str) -> bool {
+            self.accept(grapheme)
+        }
+        fn enter(&mut self) -> bool {
+            self.accept("⏎")
+        }
+    }
+
+    #[test]
+    fn types_graphemes_in_order() {
+        let mut sink = Recorder::default();
+        let text = "Hi 世界\ncafe\u{301} 👩‍💻!";
+        assert_eq!(type_with(&mut sink, text, Duration::ZERO, &AtomicBool::new(false)), 13);
+        assert_eq!(
+            sink.keys,
+            ["H", "i", " ", "世", "界", "⏎", "c", "a", "f", "e\u{301}", " ", "👩‍💻", "!"]
+        );
+        assert_eq!(sink.typed(), text);
+    }
+
+    #[test]
+    fn crlf_is_a_single_return() {
+        let mut sink = Recorder::default();
+        assert_eq!(type_with(&mut sink, "a\r\nb", Duration::ZERO, &AtomicBool::new(false)), 3);
+        assert_eq!(sink.keys, ["a", "⏎", "b"]);
+    }
+
+    #[test]
+    fn rejected_keystrokes_stop_typing() {
+        let mut sink = Recorder { fail_after: Some(0), ..Default::default() };
+        assert_eq!(type_with(&mut sink, "abc", Duration::ZERO, &AtomicBool::new(false)), 0);
+
+        let mut sink = Recorder { fail_after: Some(2), ..Default::default() };
+        assert_eq!(type_with(&mut sink, "abcd", Duration::ZERO, &AtomicBool::new(false)), 2);
+        assert_eq!(sink.typed(), "ab");
+        assert_eq!(type_with(&mut Recorder::default(), "", Duration::ZERO, &AtomicBool::new(false)), 0);
+    }
+
+    #[test]
+    fn cancel_stops_typing() {
+        let mut sink = Recorder::default();
+        assert_eq!(type_with(&mut sink, "abc", Duration::ZERO, &AtomicBool::new(true)), 0);
+        assert!(sink.keys.is_empty());
+    }
+
+    #[test]
+    fn utf16_pieces_keep_surrogate_pairs_together() {
+        let units: Vec<u16> = "a😀b".encode_utf16().collect(); // a, hi, lo, b
+        assert_eq!(utf16_pieces(&units, 2), vec![&units[..1], &units[1..3], &units[3..]]);
+        assert_eq!(utf16_pieces(&units, 20), vec![&units[..]]);
+    }
+}
@@ -274,6 +274,7 @@ extern "C" {
     ) -> *mut c_void;
     fn CGEventSetFlags(event: *mut c_void, flags: u64);
     fn CGEventPost(tap: u32, event: *mut c_void);
+    fn CGEventKeyboardSetUnicodeString(event: *mut c_void, length: usize, string: *const u16);
 }
 
 #[link(name = "CoreFoundation", kind = "framework")]
@@ -307,6 +308,64 @@ unsafe fn simulate_cmd_key(virtual_key: u16) -> bool {
     true
 }
 
+/// Posts Unicode keystrokes through CGEvent for [`type_text`].
+struct CgEventSink {
+    source: *mut c_void,
+}
+
+impl CgEventSink {
+    const HID_EVENT_TAP: u32 = 0;
+    const KVK_RETURN: u16 = 36;
+    /// Longest string a single keyboard event carries.
+    const MAX_UNITS: usize = 20;
+
+    /// Post a key down/up pair.  Flags are cleared so a modifier the user is
+    /// still holding (e.g. from the hotkey) does not turn text into shortcuts.
+    unsafe fn post(&self, virtual_key: u16, units: &[u16]) -> bool {
+        for key_down in [true, false] {
+            let event = CGEventCreateKeyboardEvent(self.source, virtual_key, key_down);
+            if event.is_null() {
+                return false;
+            }
+            CGEventSetFlags(event, 0);
+            if !units.is_empty() {
+                CGEventKeyboardSetUnicodeString(event, units.len(), units.as_ptr());
+            }
+            CGEventPost(Self::HID_EVENT_TAP, event);
+            CFRelease(event);
+        }
+        true
+    }
+}
+
+impl super::keystrokes::KeySink for CgEventSink {
+    fn text(&mut self, grapheme: &str) -> bool {
+        let units: Vec<u16> = grapheme.encode_utf16().collect();
+        super::keystrokes::utf16_pieces(&units, Self::MAX_UNITS)
+            .into_iter()
+            .all(|piece| unsafe { self.post(0, piece) })
+    }
+
+    fn enter(&mut self) -> bool {
+        unsafe { self.post(Self::KVK_RETURN, &[]) }
+    }
+}
+
+/// Type `text` as Unicode keystrokes, one grapheme per key event.  Returns
+/// the number of graphemes typed.
+pub fn type_text(text: &str, delay: std::time::Duration, cancel: &std::sync::atomic::AtomicBool) -> usize {
+    const COMBINED_STATE: i32 = 0;
+    unsafe {
+        let source = CGEventSourceCreate(COMBINED_STATE);
+        if source.is_null() {
+            return 0;
+        }
+        let typed = super::keystrokes::type_with(&mut CgEventSink { source }, text, delay, cancel);
+        CFRelease(source);
+        typed
+    }
+}
+
 /// Convert an NSString pointer to a Rust String.
 ///
 /// # Safety
@@ -539,6 +598,11 @@ pub unsafe fn simulate_cmd_v() -> bool { simulate_cmd_key(9) }
 /// # Safety
 /// Posts CGEvents; must be called from a context where CGEvent posting is allowed.
 pub unsafe fn simulate_cmd_c() -> bool { simulate_cmd_key(8) }
+/// Simulate Cmd+A (select all).
+///
+/// # Safety
+/// Posts CGEvents; must be called from a context where CGEvent posting is allowed.
+pub unsafe fn simulate_cmd_a() -> bool { simulate_cmd_key(0) }
 /// Simulate Cmd+Z (undo).
 ///
 /// # Safety
@@ -916,6 +980,134 @@ pub fn add_default_input_listener(callback: impl Fn() + Send + 'static) {
     }
 }
 
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {
+    static NSWorkspaceDidWakeNotification: *mut c_void;
+    static NSPasteboardTypeString: *mut c_void;
+    static _NSConcreteGlobalBlock: u8;
+}
+
+/// Replace the general pasteboard with `text` as `NSPasteboardTypeString`
+/// alone.  `clearContents` drops every type the previous owner declared
+/// (RTF, HTML, app-private data), so a rich target has nothing but plain
+/// text to choose from.  Returns false if the pasteboard rejected the write.
+pub fn set_clipboard_plain_text(text: &str) -> bool {
+    const NS_UTF8_STRING_ENCODING: u64 = 4;
+    unsafe {
+        let pb_cls = objc_getClass(c"NSPasteboard".as_ptr());
+        let str_cls = objc_getClass(c"NSString".as_ptr());
+        if pb_cls.is_null() || str_cls.is_null() { return false; }
+
+        type MsgSendObj = unsafe extern "C" fn(*mut c_void, *mut c_void) -> *mut c_void;
+        let send: MsgSendObj = std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        let pb = send(pb_cls, sel_registerName(c"generalPasteboard".as_ptr()));
+        if pb.is_null() { return false; }
+
+        // initWithBytes:length:encoding: takes a length, so interior NULs
+        // survive (stringWithUTF8String: would truncate at the first one).
+        type InitBytesFn = unsafe extern "C" fn(*mut c_void, *mut c_void, *const u8, usize, u64) -> *mut c_void;
+        let init_bytes: InitBytesFn = std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        let alloc = send(str_cls, sel_registerName(c"alloc".as_ptr()));
+        let nsstr = init_bytes(
+            alloc,
+            sel_registerName(c"initWithBytes:length:encoding:".as_ptr()),
+            text.as_ptr(),
+            text.len(),
+            NS_UTF8_STRING_ENCODING,
+        );
+        if nsstr.is_null() { return false; }
+
+        type ClearFn = unsafe extern "C" fn(*mut c_void, *mut c_void) -> i64;
+        let clear: ClearFn = std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        clear(pb, sel_registerName(c"clearContents".as_ptr()));
+
+        type SetStringFn = unsafe extern "C" fn(*mut c_void, *mut c_void, *mut c_void, *mut c_void) -> i8;
+        let set_string: SetStringFn = std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        let ok = set_string(
+            pb,
+            sel_registerName(c"setString:forType:".as_ptr()),
+            nsstr,
+            NSPasteboardTypeString,
+        ) != 0;
+
+        // The pasteboard copies the string; drop our +1 from alloc/init.
+        type ReleaseFn = unsafe extern "C" fn(*mut c_void, *mut c_void);
+        let release: ReleaseFn = std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        release(nsstr, sel_registerName(c"release".as_ptr()));
+        ok
+    }
+}
+
+/// Register a permanent observer for `NSWorkspaceDidWakeNotification`.
+///
+/// The `callback` runs on the main thread (queue `nil` delivers on the posting
+/// thread) and must not block; hand real work to another thread.
+pub fn add_wake_listener(callback: impl Fn() + Send + 'static) {
+    // Objective-C block layout for `^(NSNotification *note)`.
+    #[repr(C)]
+    struct BlockDesc { reserved: u64, size: u64 }
+    #[repr(C)]
+    struct Block {
+        isa: *const u8,
+        flags: i32,
+        reserved: i32,
+        invoke: unsafe extern "C" fn(*mut Block, *mut c_void),
+        descriptor: *const BlockDesc,
+        // Captured variables
+        callback: *const Box<dyn Fn() + Send>,
+    }
+
+    static DESC: BlockDesc = BlockDesc {
+        reserved: 0,
+        size: std::mem::size_of::<Block>() as u64,
+    };
+
+    unsafe extern "C" fn invoke(b: *mut Block, _note: *mut c_void) {
+        (*(*b).callback)();
+    }
+
+    // BLOCK_IS_GLOBAL: `_Block_copy` returns the block itself instead of
+    // copying it, so the leaked allocation below is what the centre keeps.
+    const BLOCK_IS_GLOBAL: i32 = 1 << 28;
+
+    let boxed: Box<dyn Fn() + Send> = Box::new(callback);
+    unsafe {
+        let block = Box::into_raw(Box::new(Block {
+            isa: &_NSConcreteGlobalBlock,
+            flags: BLOCK_IS_GLOBAL,
+            reserved: 0,
+            invoke,
+            descriptor: &DESC,
+            callback: Box::into_raw(Box::new(boxed)),
+        }));
+
+        let cls = objc_getClass(c"NSWorkspace".as_ptr());
+        if cls.is_null() { return; }
+        type MsgSendObj = unsafe extern "C" fn(*mut c_void, *mut c_void) -> *mut c_void;
+        let send: MsgSendObj = std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        let workspace = send(cls, sel_registerName(c"sharedWorkspace".as_ptr()));
+        if workspace.is_null() { return; }
+        let center = send(workspace, sel_registerName(c"notificationCenter".as_ptr()));
+        if center.is_null() { return; }
+
+        type AddObserverFn = unsafe extern "C" fn(
+            *mut c_void, *mut c_void, *mut c_void, *mut c_void, *mut c_void, *mut Block,
+        ) -> *mut c_void;
+        let add: AddObserverFn = std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        let observer = add(
+            center,
+            sel_registerName(c"addObserverForName:object:queue:usingBlock:".as_ptr()),
+            NSWorkspaceDidWakeNotification,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            block,
+        );
+        if observer.is_null() {
+            tracing::warn!("Failed to register NSWorkspace wake observer");
+        }
+    }
+}
+
 /// Returns the NSPasteboard changeCount, which increments each time the clipboard is written.
 /// Used to detect whether a Cmd+C actually updated the clipboard (avoids false negatives
 /// when the selected text is identical to the previously saved clipboard content).
@@ -935,3 +1127,137 @@ pub fn clipboard_change_count() -> Option<u32> {
         Some(count as u32)
     }
 }
+
+// ── Accessibility: direct text insertion ────────────────────────────
+
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn AXUIElementCreateSystemWide() -> *mut c_void;
+    fn AXUIElementCopyAttributeValue(element: *mut c_void, attribute: *const c_void, value: *mut *mut c_void) -> i32;
+    fn AXUIElementIsAttributeSettable(element: *mut c_void, attribute: *const c_void, settable: *mut u8) -> i32;
+    fn AXUIElementSetAttributeValue(element: *mut c_void, attribute: *const c_void, value: *const c_void) -> i32;
+    fn AXValueGetValue(value: *mut c_void, the_type: u32, value_ptr: *mut c_void) -> u8;
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    fn CFStringCreateWithBytes(
+        alloc: *const c_void,
+        bytes: *const u8,
+        num_bytes: isize,
+        encoding: u32,
+        is_external_representation: u8,
+    ) -> *mut c_void;
+    fn CFEqual(cf1: *const c_void, cf2: *const c_void) -> u8;
+}
+
+/// `CFRange`, as unpacked from an `AXValue` of type `kAXValueCFRangeType`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CfRange {
+    location: isize,
+    length: isize,
+}
+
+/// Owned CFString; released on drop.
+struct CfString(*mut c_void);
+
+impl CfString {
+    fn new(s: &str) -> Option<Self> {
+        const CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+        let ptr = unsafe {
+            CFStringCreateWithBytes(std::ptr::null(), s.as_ptr(), s.len() as isize, CF_STRING_ENCODING_UTF8, 0)
+        };
+        (!ptr.is_null()).then_some(Self(ptr))
+    }
+}
+
+impl Drop for CfString {
+    fn drop(&mut self) {
+        unsafe { CFRelease(self.0) }
+    }
+}
+
+const AX_ERROR_SUCCESS: i32 = 0;
+
+/// Copy `attr` of `element`; the caller releases the result.
+unsafe fn copy_ax_attribute(element: *mut c_void, attr: &CfString) -> Option<*mut c_void> {
+    let mut value: *mut c_void = std::ptr::null_mut();
+    let err = AXUIElementCopyAttributeValue(element, attr.0, &mut value);
+    (err == AX_ERROR_SUCCESS && !value.is_null()).then_some(value)
+}
+
+/// The element's `AXSelectedTextRange`, in UTF-16 code units.
+unsafe fn selected_range(element: *mut c_void, attr: &CfString) -> Option<CfRange> {
+    const AX_VALUE_CF_RANGE_TYPE: u32 = 4;
+    let value = copy_ax_attribute(element, attr)?;
+    let mut range = CfRange { location: 0, length: 0 };
+    let ok = AXValueGetValue(value, AX_VALUE_CF_RANGE_TYPE, &mut range as *mut CfRange as *mut c_void) != 0;
+    CFRelease(value);
+    ok.then_some(range)
+}
+
+/// Insert `text` at the cursor of the focused UI element by setting its
+/// `AXSelectedText`, which replaces the selection (or inserts at the caret)
+/// without touching the clipboard.
+///
+/// Some apps accept the write without changing anything, so the result is
+/// read back: the caret must end up right after the inserted text or, for
+/// elements without a selection range, `AXValue` must have changed.
+///
+/// Returns `false` when nothing is focused, the element does not expose a
+/// settable selection — e.g. non-text controls or apps with incomplete AX
+/// support — or the text did not land, so the caller can fall back to
+/// clipboard paste.
+pub fn insert_text_accessibility(text: &str) -> bool {
+    let (Some(focused_attr), Some(selected_attr), Some(range_attr), Some(value_attr), Some(value)) = (
+        CfString::new("AXFocusedUIElement"),
+        CfString::new("AXSelectedText"),
+        CfString::new("AXSelectedTextRange"),
+        CfString::new("AXValue"),
+        CfString::new(text),
+    ) else {
+        return false;
+    };
+    unsafe {
+        let system = AXUIElementCreateSystemWide();
+        if system.is_null() { return false; }
+        let mut focused: *mut c_void = std::ptr::null_mut();
+        let err = AXUIElementCopyAttributeValue(system, focused_attr.0, &mut focused);
+        CFRelease(system);
+        if err != AX_ERROR_SUCCESS || focused.is_null() { return false; }
+
+        let mut settable: u8 = 0;
+        let is_settable = AXUIElementIsAttributeSettable(focused, selected_attr.0, &mut settable) == AX_ERROR_SUCCESS
+            && settable != 0;
+        if !is_settable {
+            CFRelease(focused);
+            return false;
+        }
+
+        let range_before = selected_range(focused, &range_attr);
+        let value_before = if range_before.is_none() { copy_ax_attribute(focused, &value_attr) } else { None };
+        let set = AXUIElementSetAttributeValue(focused, selected_attr.0, value.0) == AX_ERROR_SUCCESS;
+
+        let inserted = set && match (range_before, value_before) {
+            (Some(before), _) => {
+                let expected = CfRange { location: before.location + text.encode_utf16().count() as isize, length: 0 };
+                selected_range(focused, &range_attr) == Some(expected)
+            }
+            (None, Some(before)) => copy_ax_attribute(focused, &value_attr).is_some_and(|after| {
+                let equal = CFEqual(before, after) != 0;
+                CFRelease(after);
+                !equal
+            }),
+            (None, None) => false,
+        };
+        if let Some(before) = value_before {
+            CFRelease(before);
+        }
+        CFRelease(focused);
+        if set && !inserted {
+            tracing::info!("AX insert accepted but the text did not land");
+        }
+        inserted
+    }
+}
@@ -4,6 +4,7 @@ pub mod macos;
 pub mod windows;
 #[cfg(not(any(target_os = "macos", target_os = "windows")))]
 pub mod fallback;
+pub mod keystrokes;
 
 /// Hide the Dock icon (macOS) or equivalent.
 pub fn set_app_accessory_mode() {
@@ -85,6 +86,16 @@ pub fn simulate_copy() -> bool {
     { false }
 }
 
+/// Simulate select-all (Cmd+A on macOS, Ctrl+A on Windows).
+pub fn simulate_select_all() -> bool {
+    #[cfg(target_os = "macos")]
+    { unsafe { macos::simulate_cmd_a() } }
+    #[cfg(target_os = "windows")]
+    { unsafe { windows::simulate_select_all() } }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    { false }
+}
+
 /// Simulate undo (Cmd+Z on macOS, Ctrl+Z on Windows).
 pub fn simulate_undo() -> bool {
     #[cfg(target_os = "macos")]
@@ -129,6 +140,62 @@ pub fn focused_screen_logical_frame() -> Option<(f64, f64, f64, f64, f64)> {
     { None }
 }
 
+/// Register a permanent callback for system wake from sleep.
+/// macOS only; no-op elsewhere.
+pub fn add_wake_listener(callback: impl Fn() + Send + 'static) {
+    #[cfg(target_os = "macos")]
+    macos::add_wake_listener(callback);
+    #[cfg(not(target_os = "macos"))]
+    { let _ = callback; }
+}
+
+/// Put `text` on the clipboard for pasting.
+///
+/// With `plain_only`, macOS clears every pasteboard type and writes the plain
+/// string type alone.  arboard already empties the clipboard and writes only
+/// Unicode text on Windows and Linux, so `plain_only` changes nothing there.
+pub fn set_clipboard_text(text: &str, plain_only: bool) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    if plain_only {
+        return if macos::set_clipboard_plain_text(text) {
+            Ok(())
+        } else {
+            Err("NSPasteboard rejected the text".to_string())
+        };
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = plain_only;
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .map_err(|e| e.to_string())
+}
+
+/// Insert `text` into the focused text field through the accessibility API,
+/// leaving the clipboard untouched.  macOS only; returns `false` elsewhere or
+/// when the focused element is not editable, and the caller pastes instead.
+pub fn insert_text_accessibility(text: &str) -> bool {
+    #[cfg(target_os = "macos")]
+    { macos::insert_text_accessibility(text) }
+    #[cfg(not(target_os = "macos"))]
+    { let _ = text; false }
+}
+
+/// Type `text` into the focused app as Unicode keystrokes, waiting
+/// `delay_ms` between them, for targets that ignore paste.  Leaves the
+/// clipboard alone.  Returns the number of grapheme clusters typed: 0 if
+/// nothing could be (and always on Linux/other), so the caller can paste
+/// instead; fewer than the text holds if typing stopped part-way or
+/// `cancel` was set.
+pub fn type_text(text: &str, delay_ms: u64, cancel: &std::sync::atomic::AtomicBool) -> usize {
+    let delay = std::time::Duration::from_millis(delay_ms);
+    #[cfg(target_os = "macos")]
+    { macos::type_text(text, delay, cancel) }
+    #[cfg(target_os = "windows")]
+    { windows::type_text(text, delay, cancel) }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    { let _ = (text, delay, cancel); 0 }
+}
+
 /// Returns the clipboard change sequence number if the platform supports it.
 /// macOS: NSPasteboard.changeCount, Windows: GetClipboardSequenceNumber.
 /// Returns None on Linux/other (caller falls back to sentinel approach).
@@ -2,7 +2,8 @@ use windows::Win32::Foundation::HWND;
 use windows::Win32::Graphics::Dwm::{DwmEnableBlurBehindWindow, DWM_BB_ENABLE, DWM_BLURBEHIND};
 use windows::Win32::System::DataExchange::GetClipboardSequenceNumber;
 use windows::Win32::UI::Input::KeyboardAndMouse::{
-    SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, VIRTUAL_KEY,
+    SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, KEYEVENTF_UNICODE,
+    VIRTUAL_KEY,
 };
 use windows::Win32::UI::WindowsAndMessaging::{
     GetWindowLongPtrW, SetWindowLongPtrW, SetWindowPos, ShowWindow, GWL_EXSTYLE, HWND_TOPMOST,
@@ -14,7 +15,9 @@ use windows::Win32::UI::WindowsAndMessaging::{
 const VK_CONTROL: u16 = 0x11;
 const VK_V: u16 = 0x56;
 const VK_C: u16 = 0x43;
+const VK_A: u16 = 0x41;
 const VK_Z: u16 = 0x5A;
+const VK_RETURN: u16 = 0x0D;
 
 /// Set app accessory mode — no-op on Windows (no Dock equivalent).
 pub fn set_accessory_policy() {}
@@ -83,6 +86,11 @@ pub unsafe fn simulate_copy() -> bool {
     send_key_combo(VK_CONTROL, VK_C)
 }
 
+/// Simulate Ctrl+A (select all) via SendInput.
+pub unsafe fn simulate_select_all() -> bool {
+    send_key_combo(VK_CONTROL, VK_A)
+}
+
 /// Simulate Ctrl+Z (undo) via SendInput.
 pub unsafe fn simulate_undo() -> bool {
     send_key_combo(VK_CONTROL, VK_Z)
@@ -147,6 +155,48 @@ unsafe fn send_key_combo(modifier: u16, key: u16) -> bool {
     SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) == 4
 }
 
+/// Sends Unicode keystrokes through SendInput for [`type_text`].
+struct SendInputSink;
+
+impl super::keystrokes::KeySink for SendInputSink {
+    /// All of a grapheme's UTF-16 units go in one SendInput batch, so
+    /// surrogate pairs and combining marks cannot be split by other input.
+    fn text(&mut self, grapheme: &str) -> bool {
+        let inputs: Vec<INPUT> = grapheme
+            .encode_utf16()
+            .flat_map(|unit| [make_unicode_input(unit, false), make_unicode_input(unit, true)])
+            .collect();
+        unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) as usize == inputs.len() }
+    }
+
+    fn enter(&mut self) -> bool {
+        let inputs = [make_key_input(VK_RETURN, false), make_key_input(VK_RETURN, true)];
+        unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) == 2 }
+    }
+}
+
+/// Type `text` as Unicode keystrokes (`KEYEVENTF_UNICODE`), one grapheme
+/// per batch.  Returns the number of graphemes typed.
+pub fn type_text(text: &str, delay: std::time::Duration, cancel: &std::sync::atomic::AtomicBool) -> usize {
+    super::keystrokes::type_with(&mut SendInputSink, text, delay, cancel)
+}
+
+fn make_unicode_input(unit: u16, key_up: bool) -> INPUT {
+    let flags = if key_up { KEYEVENTF_UNICODE | KEYEVENTF_KEYUP } else { KEYEVENTF_UNICODE };
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: VIRTUAL_KEY(0),
+                wScan: unit,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    }
+}
+
 fn make_key_input(vk: u16, key_up: bool) -> INPUT {
     let flags = if key_up { KEYEVENTF_KEYUP } else { Default::default() };
     INPUT {
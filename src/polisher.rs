@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use unicode_segmentation::UnicodeSegmentation;
 
 use candle_core::quantized::gguf_file;
@@ -9,6 +10,7 @@ use candle_core::{Device, Tensor};
 use candle_transformers::generation::{LogitsProcessor, Sampling};
 
 use crate::context_detect::AppContext;
+use crate::error::SumiError;
 
 // ── Config ──────────────────────────────────────────────────────────────────
 
@@ -28,12 +30,75 @@ pub struct PolishConfig {
         deserialize_with = "deserialize_prompt_rules"
     )]
     pub prompt_rules: HashMap<String, Vec<PromptRule>>,
+    /// Master switch for the dictionary prompt block.
     #[serde(default)]
     pub dictionary: DictionaryConfig,
+    /// Named term lists; every enabled one contributes to the prompt and to
+    /// STT biasing.
+    #[serde(default = "default_dictionaries")]
+    pub dictionaries: Vec<NamedDictionary>,
     /// Enable model reasoning / chain-of-thought (e.g. Qwen3 `<think>` blocks).
     /// When false, `/no_think` is prepended to suppress reasoning.
     #[serde(default)]
     pub reasoning: bool,
+    /// Keep markdown / SSML markup exactly as dictated.  When true, an extra
+    /// instruction tells the model not to add, remove or reformat structural
+    /// characters (`*`, `#`, `` ` ``, `<break/>`, …).
+    #[serde(default)]
+    pub preserve_markup: bool,
+    /// Translate the polished text into this language (BCP-47 code such as
+    /// `"en"` or `"zh-TW"`).  `None` keeps the spoken language.
+    #[serde(default)]
+    pub translate_to: Option<String>,
+    /// Compose the prompt from the matched rule, dictionary and app context.
+    /// When false only the base / custom prompt is sent, which keeps the
+    /// prompt short and local prompt-eval fast.
+    #[serde(default = "default_true")]
+    pub context_aware: bool,
+    /// Quote this many of the most recent transcripts (0 = off) in the polish
+    /// prompt, so terminology and style stay consistent across a dictation
+    /// split over several recordings.  Needs `context_aware`.
+    #[serde(default)]
+    pub context_from_history: u32,
+    /// Reject output longer than `raw × hallucination_ratio + hallucination_abs`
+    /// graphemes and paste the raw text instead.  A ratio of 0 disables the
+    /// length check; the repetition check always runs.
+    #[serde(default = "default_hallucination_ratio")]
+    pub hallucination_ratio: f32,
+    #[serde(default = "default_hallucination_abs")]
+    pub hallucination_abs: usize,
+    /// System prompt for edit-by-voice.  `None` (or blank) uses
+    /// [`DEFAULT_EDIT_SYSTEM_PROMPT`].
+    #[serde(default)]
+    pub edit_system_prompt: Option<String>,
+    /// Runtime-only cancellation token for the in-flight pipeline.  Set by the
+    /// caller before polishing; checked by the local generation loop and the
+    /// cloud request wait loop.
+    #[serde(skip)]
+    pub cancel: Option<Arc<AtomicBool>>,
+    /// Runtime-only receiver for reasoning streamed while a local model is
+    /// still inside its `<think>` block.  Only used when `reasoning` is on.
+    #[serde(skip)]
+    pub reasoning_sink: Option<ReasoningSink>,
+    /// Runtime-only receiver for the polished text as it streams in from a
+    /// cloud provider with `CloudConfig::stream` on.
+    #[serde(skip)]
+    pub stream_sink: Option<ReasoningSink>,
+    /// Runtime-only recent transcripts, newest first, filled in by the caller
+    /// when `context_from_history` is set.
+    #[serde(skip)]
+    pub recent_transcripts: Vec<String>,
+}
+
+/// Callback that receives the text generated so far, not just the newest
+/// token: the content of the open `<think>` block, or a streamed reply.
+#[derive(Clone)]
+pub struct ReasoningSink(pub Arc<dyn Fn(&str) + Send + Sync>);
+
+impl std::fmt::Debug for ReasoningSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ReasoningSink")
+    }
 }
 
 impl Default for PolishConfig {
@@ -46,11 +111,61 @@ impl Default for PolishConfig {
             cloud: CloudConfig::default(),
             prompt_rules: default_prompt_rules_map(),
             dictionary: DictionaryConfig::default(),
+            dictionaries: default_dictionaries(),
             reasoning: false,
+            preserve_markup: false,
+            translate_to: None,
+            context_aware: true,
+            context_from_history: 0,
+            hallucination_ratio: default_hallucination_ratio(),
+            hallucination_abs: default_hallucination_abs(),
+            edit_system_prompt: None,
+            cancel: None,
+            reasoning_sink: None,
+            stream_sink: None,
+            recent_transcripts: Vec::new(),
+        }
+    }
+}
+
+impl PolishConfig {
+    fn cancel_flag(&self) -> Option<&AtomicBool> {
+        self.cancel.as_deref()
+    }
+
+    /// Target language of translate mode, if one is set.
+    pub fn translation_target(&self) -> Option<&str> {
+        self.translate_to.as_deref().map(str::trim).filter(|l| !l.is_empty())
+    }
+
+    /// Enabled terms across all enabled dictionaries, in list order, with
+    /// case-insensitive duplicates dropped.
+    pub fn dictionary_terms(&self) -> Vec<String> {
+        let mut seen = std::collections::HashSet::new();
+        self.dictionaries
+            .iter()
+            .filter(|d| d.enabled)
+            .flat_map(|d| d.entries.iter())
+            .filter(|e| e.enabled)
+            .map(|e| e.term.trim())
+            .filter(|term| !term.is_empty() && seen.insert(term.to_lowercase()))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Model name recorded on history entries polished with this config.
+    pub fn history_model_name(&self) -> String {
+        match self.mode {
+            PolishMode::Cloud => format!("{} (Cloud/{})", self.cloud.model_id, self.cloud.provider.as_key()),
+            PolishMode::Local => format!("{} (Local)", self.model.display_name()),
         }
     }
 }
 
+fn is_cancelled(cancel: Option<&AtomicBool>) -> bool {
+    cancel.is_some_and(|c| c.load(Ordering::SeqCst))
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum PolishMode {
@@ -71,11 +186,27 @@ pub enum CloudProvider {
     OpenAi,
     Gemini,
     SambaNova,
+    /// Claude via the Messages API, which is not OpenAI-compatible; see
+    /// [`run_anthropic_inference`].  `model_id` is an Anthropic model name
+    /// or alias such as `claude-3-5-haiku-latest`.
+    Anthropic,
     Custom,
 }
 
 
 impl CloudProvider {
+    /// Every provider, for enumerating stored credentials.
+    pub const ALL: [CloudProvider; 8] = [
+        CloudProvider::GitHubModels,
+        CloudProvider::Groq,
+        CloudProvider::OpenRouter,
+        CloudProvider::OpenAi,
+        CloudProvider::Gemini,
+        CloudProvider::SambaNova,
+        CloudProvider::Anthropic,
+        CloudProvider::Custom,
+    ];
+
     /// Returns the snake_case identifier matching the serde serialization.
     pub fn as_key(&self) -> &'static str {
         match self {
@@ -85,6 +216,7 @@ impl CloudProvider {
             CloudProvider::OpenAi => "open_ai",
             CloudProvider::Gemini => "gemini",
             CloudProvider::SambaNova => "samba_nova",
+            CloudProvider::Anthropic => "anthropic",
             CloudProvider::Custom => "custom",
         }
     }
@@ -97,9 +229,51 @@ impl CloudProvider {
             CloudProvider::OpenAi => "https://api.openai.com/v1/chat/completions",
             CloudProvider::Gemini => "https://generativelanguage.googleapis.com/v1beta/openai/chat/completions",
             CloudProvider::SambaNova => "https://api.sambanova.ai/v1/chat/completions",
+            CloudProvider::Anthropic => "https://api.anthropic.com/v1/messages",
             CloudProvider::Custom => "",
         }
     }
+
+    /// Endpoint that lists the provider's model ids.  `None` for providers
+    /// without a usable one (see [`CloudProvider::curated_models`]); `Custom`
+    /// derives it from the user's chat completions URL.
+    pub fn models_endpoint(&self) -> Option<&'static str> {
+        match self {
+            CloudProvider::GitHubModels => Some("https://models.github.ai/catalog/models"),
+            CloudProvider::Groq => Some("https://api.groq.com/openai/v1/models"),
+            CloudProvider::OpenRouter => Some("https://openrouter.ai/api/v1/models"),
+            CloudProvider::OpenAi => Some("https://api.openai.com/v1/models"),
+            CloudProvider::SambaNova => Some("https://api.sambanova.ai/v1/models"),
+            CloudProvider::Gemini | CloudProvider::Anthropic | CloudProvider::Custom => None,
+        }
+    }
+
+    /// Static model list for providers whose models endpoint we don't query.
+    pub fn curated_models(&self) -> &'static [&'static str] {
+        match self {
+            CloudProvider::Gemini => &[
+                "gemini-2.5-flash",
+                "gemini-2.5-flash-lite",
+                "gemini-2.5-pro",
+                "gemini-2.0-flash",
+            ],
+            CloudProvider::Anthropic => &[
+                "claude-3-5-haiku-latest",
+                "claude-haiku-4-5",
+                "claude-sonnet-4-5",
+            ],
+            _ => &[],
+        }
+    }
+
+    /// Whether the provider's chat completions endpoint accepts
+    /// `response_format: {"type": "json_object"}`.
+    pub fn supports_json_mode(&self) -> bool {
+        matches!(
+            self,
+            CloudProvider::GitHubModels | CloudProvider::Groq | CloudProvider::OpenAi | CloudProvider::Gemini
+        )
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -113,6 +287,12 @@ pub struct CloudConfig {
     pub endpoint: String,
     #[serde(default)]
     pub model_id: String,
+    /// Ask for a streamed reply (`"stream": true`, server-sent events) so the
+    /// overlay can show the polish while it is generated.  Worth it for slow
+    /// self-hosted models such as Ollama; off for providers that answer fast.
+    /// Ignored for Anthropic.
+    #[serde(default)]
+    pub stream: bool,
 }
 
 
@@ -229,14 +409,6 @@ impl PolishModel {
         &[PolishModel::Phi4Mm, PolishModel::Ministral3B, PolishModel::Ministral14B, PolishModel::Qwen3_4B, PolishModel::Qwen3_8B]
     }
 
-    fn eos_token(&self) -> &'static str {
-        match self {
-            PolishModel::Phi4Mm | PolishModel::Unknown => "<|end|>",
-            PolishModel::Ministral3B | PolishModel::Ministral14B => "</s>",
-            PolishModel::Qwen3_4B | PolishModel::Qwen3_8B => "<|im_end|>",
-        }
-    }
-
     /// Filename for the external tokenizer JSON, if the GGUF tokenizer is not gpt2-compatible.
     /// Returns `None` for models whose tokenizer is embedded in the GGUF (gpt2/BPE type).
     pub fn tokenizer_filename(&self) -> Option<&'static str> {
@@ -382,12 +554,28 @@ pub struct PromptRule {
     /// Alternative match conditions (OR logic). Rule triggers if primary OR any alt matches.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub alt_matches: Vec<MatchCondition>,
+    /// STT language to use instead of `stt.language` when this rule matches the
+    /// captured context (e.g. "en" for terminals). "multi" means auto-detect.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stt_language_override: Option<String>,
+    /// Paste (`true`) or copy only (`false`) when this rule matches, instead
+    /// of the global `auto_paste` (e.g. never auto-paste into password managers).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_paste_override: Option<bool>,
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_hallucination_ratio() -> f32 {
+    3.0
+}
+
+fn default_hallucination_abs() -> usize {
+    200
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DictionaryEntry {
     pub term: String,
@@ -399,53 +587,133 @@ pub struct DictionaryEntry {
 pub struct DictionaryConfig {
     #[serde(default = "default_true")]
     pub enabled: bool,
-    #[serde(default)]
-    pub entries: Vec<DictionaryEntry>,
 }
 
 impl Default for DictionaryConfig {
     fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// A user-named term list (e.g. "Work", "Medical") that can be switched on
+/// and off as a unit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamedDictionary {
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub entries: Vec<DictionaryEntry>,
+}
+
+impl NamedDictionary {
+    pub fn new(name: &str, entries: Vec<DictionaryEntry>) -> Self {
         Self {
+            name: name.to_string(),
             enabled: true,
-            entries: Vec::new(),
+            entries,
         }
     }
 }
 
-impl DictionaryConfig {
-    pub fn enabled_terms(&self) -> Vec<String> {
-        self.entries
-            .iter()
-            .filter(|e| e.enabled && !e.term.is_empty())
-            .map(|e| e.term.clone())
-            .collect()
-    }
+/// Name of the dictionary created on first run and for migrated settings.
+pub const DEFAULT_DICTIONARY_NAME: &str = "Personal";
+
+fn default_dictionaries() -> Vec<NamedDictionary> {
+    vec![NamedDictionary::new(DEFAULT_DICTIONARY_NAME, Vec::new())]
 }
 
-/// Format a prompt using the correct chat template for local models.
+/// Chat template family for local models.
 ///
-/// Content (`user`) is placed BEFORE the instruction (`system`) within the user turn.
-/// This improves instruction-following for small models: tokens closer to the generation
-/// position carry higher effective attention weight, so the instruction lands last.
-/// Cloud models use a conventional system/user split instead (see `run_cloud_inference`).
-fn format_chat_prompt(model: &PolishModel, system: &str, user: &str) -> String {
-    match model {
-        PolishModel::Phi4Mm => format!(
-            "<|user|>\n{user}\n\n{system}<|end|>\n\
-             <|assistant|>\n"
-        ),
-        PolishModel::Ministral3B | PolishModel::Ministral14B => format!(
-            "<s>[INST] {user}\n\n{system} [/INST]"
-        ),
-        PolishModel::Qwen3_4B | PolishModel::Qwen3_8B => format!(
-            // Pre-fill empty <think></think> so the model skips thinking mode.
-            "<|im_start|>user\n{user}\n\n{system}<|im_end|>\n\
-             <|im_start|>assistant\n<think>\n\n</think>\n\n"
-        ),
-        PolishModel::Unknown => format!(
-            "<|user|>\n{user}\n\n{system}<|end|>\n\
-             <|assistant|>\n"
-        ),
+/// Picked from the GGUF `tokenizer.chat_template` when the file carries one, so
+/// a re-quantized or fine-tuned variant with a different template still gets
+/// the right turn markers; otherwise from the hardcoded per-model default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChatFormat {
+    Phi,
+    Mistral,
+    ChatMl,
+    /// ChatML with an empty `<think></think>` pre-fill (Qwen3 hybrid thinking).
+    ChatMlNoThink,
+    Llama3,
+    Gemma,
+}
+
+impl ChatFormat {
+    fn for_model(model: &PolishModel) -> Self {
+        match model {
+            PolishModel::Phi4Mm | PolishModel::Unknown => ChatFormat::Phi,
+            PolishModel::Ministral3B | PolishModel::Ministral14B => ChatFormat::Mistral,
+            PolishModel::Qwen3_4B | PolishModel::Qwen3_8B => ChatFormat::ChatMlNoThink,
+        }
+    }
+
+    /// Recognise the template family from the markers in a Jinja chat template.
+    /// Returns `None` for templates we cannot map, so the caller falls back.
+    fn from_template(template: &str) -> Option<Self> {
+        if template.contains("<|im_start|>") {
+            if template.contains("<think>") {
+                Some(ChatFormat::ChatMlNoThink)
+            } else {
+                Some(ChatFormat::ChatMl)
+            }
+        } else if template.contains("<|start_header_id|>") {
+            Some(ChatFormat::Llama3)
+        } else if template.contains("<start_of_turn>") {
+            Some(ChatFormat::Gemma)
+        } else if template.contains("[INST]") {
+            Some(ChatFormat::Mistral)
+        } else if template.contains("<|user|>") && template.contains("<|end|>") {
+            Some(ChatFormat::Phi)
+        } else {
+            None
+        }
+    }
+
+    fn eos_token(self) -> &'static str {
+        match self {
+            ChatFormat::Phi => "<|end|>",
+            ChatFormat::Mistral => "</s>",
+            ChatFormat::ChatMl | ChatFormat::ChatMlNoThink => "<|im_end|>",
+            ChatFormat::Llama3 => "<|eot_id|>",
+            ChatFormat::Gemma => "<end_of_turn>",
+        }
+    }
+
+    /// Format a prompt for local models.
+    ///
+    /// Content (`user`) is placed BEFORE the instruction (`system`) within the user turn.
+    /// This improves instruction-following for small models: tokens closer to the generation
+    /// position carry higher effective attention weight, so the instruction lands last.
+    /// Cloud models use a conventional system/user split instead (see `run_cloud_inference`).
+    fn format(self, system: &str, user: &str) -> String {
+        match self {
+            ChatFormat::Phi => format!(
+                "<|user|>\n{user}\n\n{system}<|end|>\n\
+                 <|assistant|>\n"
+            ),
+            ChatFormat::Mistral => format!(
+                "<s>[INST] {user}\n\n{system} [/INST]"
+            ),
+            ChatFormat::ChatMl => format!(
+                "<|im_start|>user\n{user}\n\n{system}<|im_end|>\n\
+                 <|im_start|>assistant\n"
+            ),
+            ChatFormat::ChatMlNoThink => format!(
+                // Pre-fill empty <think></think> so the model skips thinking mode.
+                "<|im_start|>user\n{user}\n\n{system}<|im_end|>\n\
+                 <|im_start|>assistant\n<think>\n\n</think>\n\n"
+            ),
+            ChatFormat::Llama3 => format!(
+                "<|begin_of_text|><|start_header_id|>user<|end_header_id|>\n\n\
+                 {user}\n\n{system}<|eot_id|>\
+                 <|start_header_id|>assistant<|end_header_id|>\n\n"
+            ),
+            ChatFormat::Gemma => format!(
+                "<start_of_turn>user\n{user}\n\n{system}<end_of_turn>\n\
+                 <start_of_turn>model\n"
+            ),
+        }
     }
 }
 
@@ -481,6 +749,10 @@ pub struct LlmModelCache {
     tokenizer: tokenizers::Tokenizer,
     device: Device,
     loaded_path: PathBuf,
+    chat_format: ChatFormat,
+    /// `tokenizer.ggml.eos_token_id` from the GGUF, when present; stops
+    /// generation alongside the chat format's end-of-turn token.
+    eos_token_id: Option<u32>,
 }
 
 // All candle types and tokenizers::Tokenizer are Send.
@@ -689,6 +961,8 @@ pub fn default_prompt_rules_for_lang(lang: Option<&str>) -> Vec<PromptRule> {
             prompt: email_prompt,
             enabled: true,
             icon: None,
+            stt_language_override: None,
+            auto_paste_override: None,
             alt_matches: vec![],
         },
         // ── AI CLI tools (detected via terminal subprocess enrichment) ──
@@ -699,6 +973,8 @@ pub fn default_prompt_rules_for_lang(lang: Option<&str>) -> Vec<PromptRule> {
             prompt: ai_cli_prompt.clone(),
             enabled: true,
             icon: None,
+            stt_language_override: None,
+            auto_paste_override: None,
             alt_matches: vec![],
         },
         PromptRule {
@@ -708,6 +984,8 @@ pub fn default_prompt_rules_for_lang(lang: Option<&str>) -> Vec<PromptRule> {
             prompt: ai_cli_prompt.clone(),
             enabled: true,
             icon: None,
+            stt_language_override: None,
+            auto_paste_override: None,
             alt_matches: vec![],
         },
         PromptRule {
@@ -717,6 +995,8 @@ pub fn default_prompt_rules_for_lang(lang: Option<&str>) -> Vec<PromptRule> {
             prompt: ai_cli_prompt,
             enabled: true,
             icon: None,
+            stt_language_override: None,
+            auto_paste_override: None,
             alt_matches: vec![],
         },
         // ── Code editors & terminals ──
@@ -727,6 +1007,8 @@ pub fn default_prompt_rules_for_lang(lang: Option<&str>) -> Vec<PromptRule> {
             prompt: code_editor_prompt.clone(),
             enabled: true,
             icon: None,
+            stt_language_override: None,
+            auto_paste_override: None,
             alt_matches: vec![],
         },
         PromptRule {
@@ -736,6 +1018,8 @@ pub fn default_prompt_rules_for_lang(lang: Option<&str>) -> Vec<PromptRule> {
             prompt: code_editor_prompt.clone(),
             enabled: true,
             icon: None,
+            stt_language_override: None,
+            auto_paste_override: None,
             alt_matches: vec![],
         },
         PromptRule {
@@ -745,6 +1029,8 @@ pub fn default_prompt_rules_for_lang(lang: Option<&str>) -> Vec<PromptRule> {
             prompt: code_editor_prompt.clone(),
             enabled: true,
             icon: None,
+            stt_language_override: None,
+            auto_paste_override: None,
             alt_matches: vec![],
         },
         PromptRule {
@@ -754,6 +1040,8 @@ pub fn default_prompt_rules_for_lang(lang: Option<&str>) -> Vec<PromptRule> {
             prompt: code_editor_prompt.clone(),
             enabled: true,
             icon: None,
+            stt_language_override: None,
+            auto_paste_override: None,
             alt_matches: vec![],
         },
         PromptRule {
@@ -763,6 +1051,8 @@ pub fn default_prompt_rules_for_lang(lang: Option<&str>) -> Vec<PromptRule> {
             prompt: code_editor_prompt,
             enabled: true,
             icon: None,
+            stt_language_override: None,
+            auto_paste_override: None,
             alt_matches: vec![],
         },
         // ── Notes & docs ──
@@ -773,6 +1063,8 @@ pub fn default_prompt_rules_for_lang(lang: Option<&str>) -> Vec<PromptRule> {
             prompt: notion_prompt,
             enabled: true,
             icon: None,
+            stt_language_override: None,
+            auto_paste_override: None,
             alt_matches: vec![MatchCondition {
                 match_type: MatchType::AppName,
                 match_value: "Notion".to_string(),
@@ -786,6 +1078,8 @@ pub fn default_prompt_rules_for_lang(lang: Option<&str>) -> Vec<PromptRule> {
             prompt: chat_prompt.clone(),
             enabled: true,
             icon: None,
+            stt_language_override: None,
+            auto_paste_override: None,
             alt_matches: vec![MatchCondition {
                 match_type: MatchType::Url,
                 match_value: "web.whatsapp.com".to_string(),
@@ -798,6 +1092,8 @@ pub fn default_prompt_rules_for_lang(lang: Option<&str>) -> Vec<PromptRule> {
             prompt: chat_prompt.clone(),
             enabled: true,
             icon: None,
+            stt_language_override: None,
+            auto_paste_override: None,
             alt_matches: vec![MatchCondition {
                 match_type: MatchType::Url,
                 match_value: "web.telegram.org".to_string(),
@@ -810,6 +1106,8 @@ pub fn default_prompt_rules_for_lang(lang: Option<&str>) -> Vec<PromptRule> {
             prompt: slack_prompt,
             enabled: true,
             icon: None,
+            stt_language_override: None,
+            auto_paste_override: None,
             alt_matches: vec![MatchCondition {
                 match_type: MatchType::Url,
                 match_value: "app.slack.com".to_string(),
@@ -822,6 +1120,8 @@ pub fn default_prompt_rules_for_lang(lang: Option<&str>) -> Vec<PromptRule> {
             prompt: chat_prompt.clone(),
             enabled: true,
             icon: None,
+            stt_language_override: None,
+            auto_paste_override: None,
             alt_matches: vec![MatchCondition {
                 match_type: MatchType::Url,
                 match_value: "discord.com".to_string(),
@@ -834,6 +1134,8 @@ pub fn default_prompt_rules_for_lang(lang: Option<&str>) -> Vec<PromptRule> {
             prompt: chat_prompt,
             enabled: true,
             icon: None,
+            stt_language_override: None,
+            auto_paste_override: None,
             alt_matches: vec![],
         },
         // ── Developer platforms ──
@@ -844,6 +1146,8 @@ pub fn default_prompt_rules_for_lang(lang: Option<&str>) -> Vec<PromptRule> {
             prompt: github_prompt,
             enabled: true,
             icon: None,
+            stt_language_override: None,
+            auto_paste_override: None,
             alt_matches: vec![],
         },
         // ── Social media ──
@@ -854,6 +1158,8 @@ pub fn default_prompt_rules_for_lang(lang: Option<&str>) -> Vec<PromptRule> {
             prompt: twitter_prompt,
             enabled: true,
             icon: None,
+            stt_language_override: None,
+            auto_paste_override: None,
             alt_matches: vec![],
         },
     ]
@@ -891,10 +1197,82 @@ fn extract_think_tags(text: &str) -> (String, Option<String>) {
     (text.to_string(), None)
 }
 
+/// Content of a `<think>` block that is still open in partial model output.
+/// `None` when the output does not open with `<think>` or the block closed.
+fn open_think_block(partial: &str) -> Option<&str> {
+    let rest = partial.trim_start().strip_prefix("<think>")?;
+    if rest.contains("</think>") {
+        None
+    } else {
+        Some(rest.trim())
+    }
+}
+
+/// Decode and stream reasoning every this many generated tokens.
+const REASONING_STREAM_EVERY: usize = 4;
+
 /// Result of AI polishing, containing the cleaned text and optional reasoning.
 pub struct PolishResult {
     pub text: String,
     pub reasoning: Option<String>,
+    /// Why the polished output was discarded in favour of the raw text.
+    pub fallback_reason: Option<String>,
+    /// The error behind the fallback when the polish request itself failed,
+    /// e.g. a rate limit the overlay reports.
+    pub error: Option<SumiError>,
+}
+
+impl PolishResult {
+    fn raw(raw_text: &str, reasoning: Option<String>, reason: Option<String>) -> Self {
+        Self { text: raw_text.to_string(), reasoning, fallback_reason: reason, error: None }
+    }
+}
+
+/// Smallest number of times an n-gram must repeat to count as a loop.
+const LOOP_MIN_REPEATS: usize = 4;
+
+/// Share of the output a repeated n-gram must cover to count as a loop.
+const LOOP_MIN_COVERAGE: f64 = 0.3;
+
+/// Why `polished` looks like a local-model failure rather than an edit of
+/// `raw`, or `None` when it passes.
+///
+/// Two checks: output far longer than the input, and a verbatim n-gram loop
+/// ("and then and then and then…") that the speaker did not say.  Tokens are
+/// UAX#29 words, so each Han/Kana character is its own token; n-grams are
+/// longer for such text since single characters repeat naturally.
+fn hallucination_reason(raw: &str, polished: &str, ratio: f32, abs: usize) -> Option<String> {
+    let raw_chars = raw.graphemes(true).count();
+    let polished_chars = polished.graphemes(true).count();
+    if ratio > 0.0 && polished_chars as f64 > raw_chars as f64 * ratio as f64 + abs as f64 {
+        return Some(format!("output too long ({} vs {} graphemes)", polished_chars, raw_chars));
+    }
+
+    let tokens = |s: &str| s.unicode_words().map(str::to_lowercase).collect::<Vec<_>>();
+    let polished_tokens = tokens(polished);
+    let cjk_tokens = polished_tokens
+        .iter()
+        .filter(|t| t.chars().next().is_some_and(crate::transcribe::is_cjk))
+        .count();
+    let n = if cjk_tokens * 2 > polished_tokens.len() { 6 } else { 3 };
+    if polished_tokens.len() < n * LOOP_MIN_REPEATS {
+        return None;
+    }
+
+    let mut counts: HashMap<&[String], usize> = HashMap::new();
+    for gram in polished_tokens.windows(n) {
+        *counts.entry(gram).or_default() += 1;
+    }
+    let (gram, repeats) = counts.into_iter().max_by_key(|(_, c)| *c)?;
+    let coverage = (repeats * n) as f64 / polished_tokens.len() as f64;
+    if repeats < LOOP_MIN_REPEATS || coverage < LOOP_MIN_COVERAGE {
+        return None;
+    }
+    let raw_repeats = tokens(raw).windows(n).filter(|w| *w == gram).count();
+    if repeats <= raw_repeats * 2 {
+        return None; // The speaker really did repeat it.
+    }
+    Some(format!("repetition loop (\"{}\" ×{})", gram.join(" "), repeats))
 }
 
 /// Format app context information into a single descriptive line.
@@ -930,7 +1308,9 @@ fn matches_condition(
     }
 }
 
-fn find_matching_rule<'a>(rules: &[&'a PromptRule], context: &AppContext) -> Option<&'a str> {
+/// The first enabled rule matching `context`, with the index of the
+/// `alt_matches` condition that matched (`None` for the primary condition).
+fn find_matching_rule<'a>(rules: &[&'a PromptRule], context: &AppContext) -> Option<(&'a PromptRule, Option<usize>)> {
     let app_lower = context.app_name.to_lowercase();
     let url_lower = context.url.to_lowercase();
 
@@ -938,41 +1318,104 @@ fn find_matching_rule<'a>(rules: &[&'a PromptRule], context: &AppContext) -> Opt
         if !rule.enabled {
             continue;
         }
-        let matched = matches_condition(
+        let matched = if matches_condition(
             &rule.match_type,
             &rule.match_value,
             &app_lower,
             &url_lower,
             &context.bundle_id,
-        ) || rule.alt_matches.iter().any(|alt| {
-            matches_condition(
-                &alt.match_type,
-                &alt.match_value,
-                &app_lower,
-                &url_lower,
-                &context.bundle_id,
-            )
-        });
-        if matched {
-            tracing::info!("Prompt rule matched: \"{}\"", rule.name);
-            return Some(&rule.prompt);
+        ) {
+            Some(None)
+        } else {
+            rule.alt_matches
+                .iter()
+                .position(|alt| {
+                    matches_condition(
+                        &alt.match_type,
+                        &alt.match_value,
+                        &app_lower,
+                        &url_lower,
+                        &context.bundle_id,
+                    )
+                })
+                .map(Some)
+        };
+        if let Some(alt_index) = matched {
+            tracing::debug!("Prompt rule matched: \"{}\"", rule.name);
+            return Some((rule, alt_index));
         }
     }
-    tracing::info!("No prompt rule matched (app: {:?}, url: {:?})", context.app_name, context.url);
+    tracing::debug!("No prompt rule matched (app: {:?}, url: {:?})", context.app_name, context.url);
     None
 }
 
-/// Format dictionary entries into a prompt block for the AI model.
-fn format_dictionary_prompt(dictionary: &DictionaryConfig) -> String {
-    if !dictionary.enabled {
+/// Find the first enabled rule matching `context`, searching all language keys.
+pub fn matching_rule<'a>(config: &'a PolishConfig, context: &AppContext) -> Option<&'a PromptRule> {
+    let all_rules: Vec<&PromptRule> = config.prompt_rules.values()
+        .flat_map(|rules| rules.iter())
+        .collect();
+    find_matching_rule(&all_rules, context).map(|(rule, _)| rule)
+}
+
+/// The rule [`matching_rule`] would pick for `context`, for the rules editor.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MatchedRule {
+    pub name: String,
+    /// Index into the rule's `alt_matches` of the condition that matched;
+    /// `None` when the primary condition did.
+    pub alt_index: Option<usize>,
+}
+
+/// Whether polishing depends on the detected app: polish and context-aware
+/// prompts are on and at least one prompt rule is enabled.
+pub fn uses_app_context(config: &PolishConfig) -> bool {
+    config.enabled
+        && config.context_aware
+        && config.prompt_rules.values().flatten().any(|rule| rule.enabled)
+}
+
+/// Which rule would fire for `context`, and through which condition.
+pub fn match_prompt_rule(config: &PolishConfig, context: &AppContext) -> Option<MatchedRule> {
+    let all_rules: Vec<&PromptRule> = config.prompt_rules.values()
+        .flat_map(|rules| rules.iter())
+        .collect();
+    find_matching_rule(&all_rules, context).map(|(rule, alt_index)| MatchedRule {
+        name: rule.name.clone(),
+        alt_index,
+    })
+}
+
+/// STT language requested by the rule matching `context`, if any.
+///
+/// Runs before transcription so a rule can steer STT (e.g. force English in
+/// code editors). "multi" is normalised to "auto" (auto-detect).
+pub fn stt_language_override(config: &PolishConfig, context: &AppContext) -> Option<String> {
+    let lang = matching_rule(config, context)?
+        .stt_language_override
+        .as_deref()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())?;
+    if lang.eq_ignore_ascii_case("multi") {
+        Some("auto".to_string())
+    } else {
+        Some(lang.to_string())
+    }
+}
+
+/// Whether to auto-paste into `context`: the matched rule's override, else `global`.
+pub fn effective_auto_paste(config: &PolishConfig, context: &AppContext, global: bool) -> bool {
+    matching_rule(config, context)
+        .and_then(|rule| rule.auto_paste_override)
+        .unwrap_or(global)
+}
+
+/// Format the terms of all enabled dictionaries into a prompt block for the
+/// AI model.
+fn format_dictionary_prompt(config: &PolishConfig) -> String {
+    if !config.dictionary.enabled {
         return String::new();
     }
-    let active: Vec<&str> = dictionary
-        .entries
-        .iter()
-        .filter(|e| e.enabled && !e.term.is_empty())
-        .map(|e| e.term.as_str())
-        .collect();
+    let active = config.dictionary_terms();
     if active.is_empty() {
         return String::new();
     }
@@ -984,9 +1427,58 @@ fn format_dictionary_prompt(dictionary: &DictionaryConfig) -> String {
     block
 }
 
+/// Human-readable name for a BCP-47 language code, for use in prompts.
+pub(crate) fn language_name(bcp47: &str) -> Option<&'static str> {
+    Some(match bcp47 {
+        "zh-TW" => "繁體中文 (Traditional Chinese)",
+        "zh-CN" | "zh" => "简体中文 (Simplified Chinese)",
+        "en" => "English",
+        "ja" => "日本語 (Japanese)",
+        "ko" => "한국어 (Korean)",
+        "es" => "Español (Spanish)",
+        "fr" => "Français (French)",
+        "de" => "Deutsch (German)",
+        "pt" => "Português (Portuguese)",
+        "it" => "Italiano (Italian)",
+        "ru" => "Русский (Russian)",
+        "ar" => "العربية (Arabic)",
+        "hi" => "हिन्दी (Hindi)",
+        "th" => "ไทย (Thai)",
+        "vi" => "Tiếng Việt (Vietnamese)",
+        "id" => "Bahasa Indonesia (Indonesian)",
+        "ms" => "Bahasa Melayu (Malay)",
+        "nl" => "Nederlands (Dutch)",
+        "pl" => "Polski (Polish)",
+        "tr" => "Türkçe (Turkish)",
+        "uk" => "Українська (Ukrainian)",
+        "sv" => "Svenska (Swedish)",
+        "da" => "Dansk (Danish)",
+        "fi" => "Suomi (Finnish)",
+        "no" => "Norsk (Norwegian)",
+        "cs" => "Čeština (Czech)",
+        "ro" => "Română (Romanian)",
+        "hu" => "Magyar (Hungarian)",
+        "el" => "Ελληνικά (Greek)",
+        "he" => "עברית (Hebrew)",
+        _ => return None,
+    })
+}
+
+fn translation_instruction(target: &str) -> String {
+    let name = language_name(target).unwrap_or(target);
+    format!(
+        "Translation: after cleaning up the speech, translate it into {name}. \
+Output only the {name} text, never the original."
+    )
+}
+
+const PRESERVE_MARKUP_INSTRUCTION: &str = "Markup: never add or remove markdown or SSML markers. \
+Copy structural characters such as *, _, #, `, >, -, [ ], and <tags> verbatim from the speech.";
+
 /// Build the instruction block for polishing (appended after user input in the user message).
 ///
 /// Composition: base instructions (or custom override) + matched rule context
+/// + markup preservation (if enabled) + translation target (if set)
 /// + dictionary block + app context info.
 fn build_instructions(config: &PolishConfig, context: &AppContext) -> String {
     // 1. Base instructions (or custom_prompt override)
@@ -995,18 +1487,34 @@ fn build_instructions(config: &PolishConfig, context: &AppContext) -> String {
     let mut instructions = resolve_prompt(base);
 
     // 2. Append matched rule's context prompt (search all language keys)
-    let all_rules: Vec<&PromptRule> = config.prompt_rules.values()
-        .flat_map(|rules| rules.iter())
-        .collect();
-    if let Some(rule_prompt) = find_matching_rule(&all_rules, context) {
+    if config.context_aware {
+        if let Some(rule) = matching_rule(config, context) {
+            instructions.push_str("\n\n");
+            instructions.push_str(&rule.prompt);
+        }
+    }
+
+    // 3. Ask the model to leave markup untouched
+    if config.preserve_markup {
         instructions.push_str("\n\n");
-        instructions.push_str(rule_prompt);
+        instructions.push_str(PRESERVE_MARKUP_INSTRUCTION);
     }
 
-    // 3. Append dictionary block
-    instructions.push_str(&format_dictionary_prompt(&config.dictionary));
+    // 4. Translate into the configured output language
+    if let Some(target) = config.translation_target() {
+        instructions.push_str("\n\n");
+        instructions.push_str(&translation_instruction(target));
+    }
+
+    // Fast mode stops here: no dictionary or app context.
+    if !config.context_aware {
+        return instructions;
+    }
+
+    // 5. Append dictionary block
+    instructions.push_str(&format_dictionary_prompt(config));
 
-    // 4. Append app context info
+    // 6. Append app context info
     let context_line = format_app_context(context);
     if !context_line.is_empty() {
         instructions.push_str("\n\n");
@@ -1016,6 +1524,83 @@ fn build_instructions(config: &PolishConfig, context: &AppContext) -> String {
     instructions
 }
 
+/// Characters of earlier transcripts quoted by `context_from_history`.
+const HISTORY_CONTEXT_MAX_CHARS: usize = 800;
+/// Longest single earlier transcript quoted; longer ones keep their ending,
+/// which is closest to what is being dictated now.
+const HISTORY_CONTEXT_ENTRY_CHARS: usize = 240;
+
+/// Block quoting earlier transcripts (given newest first) oldest first.  The
+/// newest entries are kept when the budget runs out.
+fn format_history_context(recent: &[String]) -> String {
+    let mut kept = Vec::new();
+    let mut used = 0;
+    for text in recent {
+        let text = text.trim();
+        let count = text.chars().count();
+        if count == 0 {
+            continue;
+        }
+        let snippet = if count > HISTORY_CONTEXT_ENTRY_CHARS {
+            let tail: String = text.chars().skip(count - HISTORY_CONTEXT_ENTRY_CHARS).collect();
+            format!("…{}", tail)
+        } else {
+            text.to_string()
+        };
+        used += snippet.chars().count();
+        if used > HISTORY_CONTEXT_MAX_CHARS {
+            break;
+        }
+        kept.push(snippet);
+    }
+    if kept.is_empty() {
+        return String::new();
+    }
+    kept.reverse();
+    let mut block = String::from(
+        "Earlier in this session (reference for terminology and style only; do not repeat it):\n<previous>\n",
+    );
+    for snippet in kept {
+        block.push_str(&snippet);
+        block.push('\n');
+    }
+    block.push_str("</previous>\n\n");
+    block
+}
+
+/// System message sent with every polish request.
+pub const POLISH_SYSTEM_PROMPT: &str = "You are a speech-to-text post-processor.";
+
+/// User message for polishing `raw_text` in `context`.
+fn compose_user_message(config: &PolishConfig, context: &AppContext, raw_text: &str) -> String {
+    let instructions = build_instructions(config, context);
+
+    // User message: input first, instructions after (later tokens get higher attention weight).
+    let mut user_text = String::new();
+    // /no_think is a Qwen3-specific directive to suppress chain-of-thought.
+    // Only send it for local inference; cloud providers don't understand it.
+    if !config.reasoning && config.mode == PolishMode::Local {
+        user_text.push_str("/no_think\n");
+    }
+    if config.context_aware {
+        user_text.push_str(&format_history_context(&config.recent_transcripts));
+    }
+    user_text.push_str(&format!("<speech>\n{}\n</speech>\n\n", raw_text));
+    user_text.push_str(&instructions);
+    user_text
+}
+
+/// The exact system and user messages a polish of `raw_text` in `context`
+/// would send — shows which rule matched and what dictionary and context
+/// blocks were appended.
+pub fn preview_prompt(config: &PolishConfig, context: &AppContext, raw_text: &str) -> String {
+    format!(
+        "[system]\n{}\n\n[user]\n{}",
+        POLISH_SYSTEM_PROMPT,
+        compose_user_message(config, context, raw_text)
+    )
+}
+
 /// Polish transcribed text using a local LLM.
 ///
 /// This function is meant to be called from a background thread.
@@ -1031,7 +1616,7 @@ pub fn polish_text(
     client: &reqwest::blocking::Client,
 ) -> PolishResult {
     if raw_text.trim().is_empty() {
-        return PolishResult { text: raw_text.to_string(), reasoning: None };
+        return PolishResult::raw(raw_text, None, None);
     }
 
     match polish_text_inner(llm_cache, model_dir, config, context, raw_text, client) {
@@ -1044,26 +1629,22 @@ pub fn polish_text(
                 .replace("</speech>", "");
             let polished = polished.trim().to_string();
 
-            // Safety: if output is empty or suspiciously long, use original
+            // Safety: if output is empty or looks hallucinated, use original
             if polished.is_empty() {
                 tracing::warn!("Polish returned empty, using original");
-                return PolishResult { text: raw_text.to_string(), reasoning };
+                return PolishResult::raw(raw_text, reasoning, Some("empty output".to_string()));
             }
-            let raw_chars = raw_text.graphemes(true).count();
-            let polished_chars = polished.graphemes(true).count();
-            if polished_chars > raw_chars * 3 + 200 {
-                tracing::warn!(
-                    "Polish output too long ({} vs {} graphemes), likely hallucination — using original",
-                    polished_chars,
-                    raw_chars
-                );
-                return PolishResult { text: raw_text.to_string(), reasoning };
+            if let Some(reason) =
+                hallucination_reason(raw_text, &polished, config.hallucination_ratio, config.hallucination_abs)
+            {
+                tracing::warn!("Polish output rejected: {}, likely hallucination — using original", reason);
+                return PolishResult::raw(raw_text, reasoning, Some(reason));
             }
-            PolishResult { text: polished, reasoning }
+            PolishResult { text: polished, reasoning, fallback_reason: None, error: None }
         }
         Err(e) => {
             tracing::error!("Polish error: {} — using original text", e);
-            PolishResult { text: raw_text.to_string(), reasoning: None }
+            PolishResult { error: Some(e.clone()), ..PolishResult::raw(raw_text, None, Some(e.to_string())) }
         }
     }
 }
@@ -1075,55 +1656,59 @@ fn polish_text_inner(
     context: &AppContext,
     raw_text: &str,
     client: &reqwest::blocking::Client,
-) -> Result<String, String> {
-    let system_prompt = "You are a speech-to-text post-processor.";
-    let instructions = build_instructions(config, context);
-
-    // User message: input first, instructions after (later tokens get higher attention weight).
-    let mut user_text = String::new();
-    // /no_think is a Qwen3-specific directive to suppress chain-of-thought.
-    // Only send it for local inference; cloud providers don't understand it.
-    if !config.reasoning && config.mode == PolishMode::Local {
-        user_text.push_str("/no_think\n");
-    }
-    user_text.push_str(&format!("<speech>\n{}\n</speech>\n\n", raw_text));
-    user_text.push_str(&instructions);
+) -> Result<String, SumiError> {
+    let system_prompt = POLISH_SYSTEM_PROMPT;
+    let user_text = compose_user_message(config, context, raw_text);
 
     match config.mode {
-        PolishMode::Cloud => run_cloud_inference(&config.cloud, system_prompt, &user_text, client, None),
-        PolishMode::Local => run_llm_inference(llm_cache, model_dir, config, system_prompt, &user_text, None),
+        PolishMode::Cloud => run_cloud_inference(&config.cloud, system_prompt, &user_text, client, None, false, config.cancel_flag(), config.stream_sink.as_ref()),
+        PolishMode::Local => run_llm_inference(llm_cache, model_dir, config, system_prompt, &user_text, None)
+            .map_err(SumiError::from),
     }
 }
 
 /// Run cloud LLM inference via an OpenAI-compatible chat completions API.
+///
+/// With `json_mode`, providers that support it are asked for a JSON object
+/// response (`response_format`); others fall back to prompt-only JSON.
+/// With `cloud.stream` and a `stream_sink`, the reply is streamed and the
+/// sink sees the visible text so far after every chunk.
+#[allow(clippy::too_many_arguments)]
 fn run_cloud_inference(
     cloud: &CloudConfig,
     system_prompt: &str,
     raw_text: &str,
     client: &reqwest::blocking::Client,
     max_tokens: Option<u32>,
-) -> Result<String, String> {
+    json_mode: bool,
+    cancel: Option<&AtomicBool>,
+    stream_sink: Option<&ReasoningSink>,
+) -> Result<String, SumiError> {
     if cloud.api_key.is_empty() {
-        return Err("Cloud API key is not set".to_string());
+        return Err(SumiError::MissingApiKey("Cloud API key is not set".to_string()));
     }
 
     let endpoint = if cloud.endpoint.is_empty() {
         cloud.provider.default_endpoint().to_string()
     } else {
-        validate_custom_endpoint(&cloud.endpoint)?;
+        validate_custom_endpoint(&cloud.endpoint).map_err(SumiError::Config)?;
         cloud.endpoint.clone()
     };
 
     if endpoint.is_empty() {
-        return Err("Cloud API endpoint is not set".to_string());
+        return Err(SumiError::Config("Cloud API endpoint is not set".to_string()));
     }
 
     let model_id = if cloud.model_id.is_empty() {
-        return Err("Cloud model ID is not set".to_string());
+        return Err(SumiError::Config("Cloud model ID is not set".to_string()));
     } else {
         &cloud.model_id
     };
 
+    if cloud.provider == CloudProvider::Anthropic {
+        return run_anthropic_inference(cloud, &endpoint, system_prompt, raw_text, client, max_tokens, cancel);
+    }
+
     let mut body = serde_json::json!({
         "model": model_id,
         "messages": [
@@ -1136,36 +1721,56 @@ fn run_cloud_inference(
     if !model_id.contains("gpt-5") {
         body["temperature"] = serde_json::json!(0.1);
     }
+    if json_mode && cloud.provider.supports_json_mode() {
+        body["response_format"] = serde_json::json!({ "type": "json_object" });
+    }
+    let stream_sink = stream_sink.filter(|_| cloud.stream).cloned();
+    let streaming = stream_sink.is_some();
+    if streaming {
+        body["stream"] = serde_json::json!(true);
+    }
 
     tracing::info!("Cloud polish: {} via {}", model_id, sanitize_url_for_log(&endpoint));
     let start = std::time::Instant::now();
 
     let body_str = serde_json::to_string(&body).map_err(|e| format!("Serialize body: {}", e))?;
 
-    let resp = client
-        .post(&endpoint)
-        .header("Authorization", format!("Bearer {}", cloud.api_key))
-        .header("Content-Type", "application/json")
-        .body(body_str)
-        .send()
-        .map_err(|e| format!("Cloud API request failed: {}", e))?;
-
-    let status = resp.status();
-    let resp_text = resp.text().map_err(|e| format!("Read response: {}", e))?;
+    let client = client.clone();
+    let auth = format!("Bearer {}", cloud.api_key);
+    let request = move || {
+        client
+            .post(&endpoint)
+            .header("Authorization", &auth)
+            .header("Content-Type", "application/json")
+            .body(body_str.clone())
+    };
+    let (status, headers, resp_text) = match stream_sink {
+        Some(sink) => run_cancellable(cancel, move |abandoned| send_streaming(request, &sink, abandoned))?,
+        None => send_cancellable("Cloud API", request, cancel)?,
+    };
 
     if !status.is_success() {
         let preview = truncate_for_error(&resp_text, 200);
-        return Err(format!("Cloud API returned HTTP {}: {}", status, preview));
+        return Err(SumiError::from_status("Cloud API", status, &headers, preview));
     }
 
-    let json: serde_json::Value =
-        serde_json::from_str(&resp_text).map_err(|e| format!("Parse response JSON: {}", e))?;
+    if streaming {
+        tracing::info!(
+            "Cloud polish streamed: {:.0?}, {} graphemes",
+            start.elapsed(),
+            resp_text.graphemes(true).count()
+        );
+        return Ok(resp_text.trim().to_string());
+    }
+
+    let json: serde_json::Value = serde_json::from_str(&resp_text)
+        .map_err(|e| SumiError::Provider(format!("Parse response JSON: {}", e)))?;
 
     let content = json["choices"][0]["message"]["content"]
         .as_str()
         .ok_or_else(|| {
             let preview = truncate_for_error(&resp_text, 200);
-            format!("Unexpected response format: {}", preview)
+            SumiError::Provider(format!("Unexpected response format: {}", preview))
         })?;
 
     tracing::info!(
@@ -1177,6 +1782,279 @@ fn run_cloud_inference(
     Ok(content.trim().to_string())
 }
 
+/// `anthropic-version` header sent with every Messages API request.
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// [`run_cloud_inference`] for Anthropic's Messages API: the key goes in
+/// `x-api-key`, the system prompt is a top-level field, and the reply comes
+/// back as content blocks.  Replies are never streamed and there is no JSON
+/// mode; callers that want JSON already ask for it in the prompt.
+fn run_anthropic_inference(
+    cloud: &CloudConfig,
+    endpoint: &str,
+    system_prompt: &str,
+    raw_text: &str,
+    client: &reqwest::blocking::Client,
+    max_tokens: Option<u32>,
+    cancel: Option<&AtomicBool>,
+) -> Result<String, SumiError> {
+    let body = serde_json::json!({
+        "model": cloud.model_id,
+        "system": system_prompt,
+        "messages": [
+            { "role": "user", "content": raw_text }
+        ],
+        "max_tokens": max_tokens.unwrap_or(8192),
+        "temperature": 0.1
+    });
+
+    tracing::info!("Cloud polish: {} via {}", cloud.model_id, sanitize_url_for_log(endpoint));
+    let start = std::time::Instant::now();
+
+    let body_str = serde_json::to_string(&body).map_err(|e| format!("Serialize body: {}", e))?;
+    let client = client.clone();
+    let endpoint = endpoint.to_string();
+    let api_key = cloud.api_key.clone();
+    let request = move || {
+        client
+            .post(&endpoint)
+            .header("x-api-key", &api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .header("Content-Type", "application/json")
+            .body(body_str.clone())
+    };
+    let (status, headers, resp_text) = send_cancellable("Cloud API", request, cancel)?;
+
+    if !status.is_success() {
+        let preview = truncate_for_error(&resp_text, 200);
+        return Err(SumiError::from_status("Cloud API", status, &headers, preview));
+    }
+
+    let json: serde_json::Value = serde_json::from_str(&resp_text)
+        .map_err(|e| SumiError::Provider(format!("Parse response JSON: {}", e)))?;
+    let content = anthropic_reply_text(&json).ok_or_else(|| {
+        let preview = truncate_for_error(&resp_text, 200);
+        SumiError::Provider(format!("Unexpected response format: {}", preview))
+    })?;
+
+    tracing::info!(
+        "Cloud polish done: {:.0?}, {} graphemes",
+        start.elapsed(),
+        content.graphemes(true).count()
+    );
+
+    Ok(content.trim().to_string())
+}
+
+/// Text of a Messages API reply: its `text` content blocks joined (normally
+/// just `content[0].text`).  `None` when there are none.
+fn anthropic_reply_text(json: &serde_json::Value) -> Option<String> {
+    let texts: Vec<&str> = json["content"]
+        .as_array()?
+        .iter()
+        .filter(|block| block["type"] == "text")
+        .filter_map(|block| block["text"].as_str())
+        .collect();
+    (!texts.is_empty()).then(|| texts.concat())
+}
+
+/// Timeout for the model-list request; it backs a settings dropdown.
+const LIST_MODELS_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Model ids offered by `provider`, sorted, for the settings model picker.
+pub fn list_cloud_models(
+    provider: &CloudProvider,
+    api_key: &str,
+    endpoint: &str,
+    client: &reqwest::blocking::Client,
+) -> Result<Vec<String>, SumiError> {
+    let curated = provider.curated_models();
+    if !curated.is_empty() {
+        return Ok(curated.iter().map(|m| m.to_string()).collect());
+    }
+    let url = match provider.models_endpoint() {
+        Some(url) => url.to_string(),
+        None => {
+            validate_custom_endpoint(endpoint).map_err(SumiError::Config)?;
+            let base = endpoint
+                .trim_end_matches('/')
+                .strip_suffix("/chat/completions")
+                .ok_or_else(|| {
+                    SumiError::Config("Endpoint must end with /chat/completions to list models".to_string())
+                })?;
+            format!("{}/models", base)
+        }
+    };
+
+    let client = client.clone();
+    let auth = (!api_key.is_empty()).then(|| format!("Bearer {}", api_key));
+    let request = move || {
+        let request = client.get(&url).timeout(LIST_MODELS_TIMEOUT);
+        match &auth {
+            Some(auth) => request.header("Authorization", auth),
+            None => request,
+        }
+    };
+    let (status, headers, body) = send_cancellable("Cloud API", request, None)?;
+    if !status.is_success() {
+        let preview = truncate_for_error(&body, 200);
+        return Err(SumiError::from_status("Model list", status, &headers, preview));
+    }
+    let json: serde_json::Value = serde_json::from_str(&body)
+        .map_err(|e| SumiError::Provider(format!("Parse model list: {}", e)))?;
+    Ok(parse_model_ids(&json))
+}
+
+/// Model ids from an OpenAI-style `{"data": [{"id": ...}]}` body or a bare
+/// array of `{"id": ...}` objects (GitHub Models catalog).
+fn parse_model_ids(json: &serde_json::Value) -> Vec<String> {
+    let items = json["data"].as_array().or_else(|| json.as_array());
+    let mut ids: Vec<String> = items
+        .into_iter()
+        .flatten()
+        .filter_map(|m| m["id"].as_str())
+        .map(str::to_string)
+        .collect();
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+/// Status, headers (for rate-limit hints) and body of a cloud reply.
+pub(crate) type CloudResponse = (reqwest::StatusCode, reqwest::header::HeaderMap, String);
+
+/// Send the request built by `request` (retrying transient failures, see
+/// [`crate::retry`]) and read its body.  With a cancel token, the request
+/// runs on a helper thread and is abandoned (its result dropped) as soon as
+/// the token fires, so a hung provider cannot pin the pipeline until the
+/// client timeout.  `context` names the service in logs and errors.
+pub(crate) fn send_cancellable(
+    context: &'static str,
+    request: impl FnMut() -> reqwest::blocking::RequestBuilder + Send + 'static,
+    cancel: Option<&AtomicBool>,
+) -> Result<CloudResponse, SumiError> {
+    fn send(
+        context: &str,
+        request: impl FnMut() -> reqwest::blocking::RequestBuilder,
+        abandoned: &AtomicBool,
+    ) -> Result<CloudResponse, SumiError> {
+        let resp = crate::retry::send_with_retry(context, Some(abandoned), request)?;
+        let status = resp.status();
+        let headers = resp.headers().clone();
+        let text = resp
+            .text()
+            .map_err(|e| SumiError::Network(format!("Read response: {}", e)))?;
+        Ok((status, headers, text))
+    }
+
+    run_cancellable(cancel, move |abandoned| send(context, request, abandoned))
+}
+
+/// Run `job` (a blocking request) and return its result.  With a cancel
+/// token, the job runs on a helper thread that is abandoned as soon as the
+/// token fires; the job sees that through its `abandoned` flag.
+fn run_cancellable(
+    cancel: Option<&AtomicBool>,
+    job: impl FnOnce(&AtomicBool) -> Result<CloudResponse, SumiError> + Send + 'static,
+) -> Result<CloudResponse, SumiError> {
+    let abandoned = Arc::new(AtomicBool::new(false));
+    let Some(cancel) = cancel else {
+        return job(&abandoned);
+    };
+    let (tx, rx) = std::sync::mpsc::channel();
+    let flag = Arc::clone(&abandoned);
+    std::thread::spawn(move || {
+        let _ = tx.send(job(&flag));
+    });
+    loop {
+        if cancel.load(Ordering::SeqCst) {
+            abandoned.store(true, Ordering::SeqCst);
+            tracing::info!("Cloud request abandoned (cancelled)");
+            return Err(SumiError::cancelled());
+        }
+        match rx.recv_timeout(std::time::Duration::from_millis(100)) {
+            Ok(result) => return result,
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                return Err(SumiError::Other("Cloud API request thread exited unexpectedly".to_string()));
+            }
+        }
+    }
+}
+
+/// Send a streamed chat completion request and assemble the reply from its
+/// server-sent events, passing the visible text so far to `sink` after each
+/// chunk.  Stops reading once `abandoned` is set.  On an error status the
+/// body is returned whole for the caller's error handling.
+fn send_streaming(
+    request: impl FnMut() -> reqwest::blocking::RequestBuilder,
+    sink: &ReasoningSink,
+    abandoned: &AtomicBool,
+) -> Result<CloudResponse, SumiError> {
+    use std::io::BufRead;
+
+    let resp = crate::retry::send_with_retry("Cloud API", Some(abandoned), request)?;
+    let status = resp.status();
+    let headers = resp.headers().clone();
+    if !status.is_success() {
+        let text = resp
+            .text()
+            .map_err(|e| SumiError::Network(format!("Read response: {}", e)))?;
+        return Ok((status, headers, text));
+    }
+    let mut content = String::new();
+    for line in std::io::BufReader::new(resp).lines() {
+        if abandoned.load(Ordering::SeqCst) {
+            break;
+        }
+        let line = line.map_err(|e| SumiError::Network(format!("Read response stream: {}", e)))?;
+        match sse_content_delta(&line).map_err(SumiError::Provider)? {
+            Some(delta) if !delta.is_empty() => {
+                content.push_str(&delta);
+                if let Some(visible) = visible_stream_text(&content) {
+                    (sink.0)(visible);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok((status, headers, content))
+}
+
+/// Text added by one line of a streamed OpenAI-compatible completion
+/// (`data: {"choices":[{"delta":{"content":"…"}}]}`).  `None` for blank
+/// lines, comments, `[DONE]` and chunks without content; an error object
+/// sent mid-stream becomes `Err`.
+fn sse_content_delta(line: &str) -> Result<Option<String>, String> {
+    let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+        return Ok(None);
+    };
+    if data.is_empty() || data == "[DONE]" {
+        return Ok(None);
+    }
+    let json: serde_json::Value =
+        serde_json::from_str(data).map_err(|e| format!("Parse stream chunk: {}", e))?;
+    if let Some(error) = json.get("error") {
+        let message = error["message"].as_str().map(str::to_string).unwrap_or_else(|| error.to_string());
+        return Err(format!("Stream error: {}", message));
+    }
+    Ok(json["choices"][0]["delta"]["content"].as_str().map(str::to_string))
+}
+
+/// The part of a partial reply worth showing: nothing while a leading
+/// `<think>` block is still open, only what follows it once it closed.
+fn visible_stream_text(partial: &str) -> Option<&str> {
+    if open_think_block(partial).is_some() {
+        return None;
+    }
+    let text = match partial.find("</think>") {
+        Some(end) => &partial[end + "</think>".len()..],
+        None => partial,
+    };
+    let text = text.trim();
+    (!text.is_empty()).then_some(text)
+}
+
 /// Run LLM inference with the given system prompt and user text.
 /// Handles model loading/caching, tokenization, and sampling.
 fn run_llm_inference(
@@ -1206,7 +2084,7 @@ fn run_llm_inference(
     let cache_ref = cache.as_mut().ok_or("LLM not loaded")?;
 
     // Format prompt
-    let formatted = format_chat_prompt(&config.model, system_prompt, raw_text);
+    let formatted = cache_ref.chat_format.format(system_prompt, raw_text);
 
     // Tokenize
     let tokenize_start = std::time::Instant::now();
@@ -1225,11 +2103,19 @@ fn run_llm_inference(
         return Err("Empty tokenization result".to_string());
     }
 
-    // Resolve EOS token
-    let eos_token_id = cache_ref
+    // Stop tokens: the template's end-of-turn (Phi-4-mini `<|end|>`, Llama 3
+    // `<|eot_id|>`) and the GGUF's EOS, which often differ.
+    let eos_name = cache_ref.chat_format.eos_token();
+    let mut stop_tokens: Vec<u32> = cache_ref
         .tokenizer
-        .token_to_id(config.model.eos_token())
-        .ok_or_else(|| format!("EOS token '{}' not found", config.model.eos_token()))?;
+        .token_to_id(eos_name)
+        .into_iter()
+        .chain(cache_ref.eos_token_id)
+        .collect();
+    stop_tokens.dedup();
+    if stop_tokens.is_empty() {
+        return Err(format!("EOS token '{}' not found", eos_name));
+    }
 
     // Clear KV cache for fresh inference
     cache_ref.model.clear_kv_cache();
@@ -1263,14 +2149,31 @@ fn run_llm_inference(
     let max_tokens: usize = max_tokens.unwrap_or(8192);
     let gen_start = std::time::Instant::now();
     let mut output_token_ids: Vec<u32> = Vec::new();
+    let reasoning_sink = config.reasoning_sink.as_ref().filter(|_| config.reasoning);
+    let mut streaming_reasoning = reasoning_sink.is_some();
 
     for i in 0..max_tokens {
-        if next_token == eos_token_id {
+        if stop_tokens.contains(&next_token) {
             break;
         }
+        if is_cancelled(config.cancel_flag()) {
+            tracing::info!("LLM generation cancelled after {} tokens", i);
+            return Err("cancelled".to_string());
+        }
 
         output_token_ids.push(next_token);
 
+        if streaming_reasoning && i % REASONING_STREAM_EVERY == 0 {
+            if let (Some(sink), Ok(partial)) = (reasoning_sink, cache_ref.tokenizer.decode(&output_token_ids, true)) {
+                match open_think_block(&partial) {
+                    Some(reasoning) if !reasoning.is_empty() => (sink.0)(reasoning),
+                    Some(_) => {}
+                    // Block closed, or the output never opened one.
+                    None => streaming_reasoning = partial.trim_start().len() < "<think>".len(),
+                }
+            }
+        }
+
         let input = Tensor::new(&[next_token], &cache_ref.device)
             .and_then(|t| t.unsqueeze(0))
             .map_err(|e| format!("Token tensor: {}", e))?;
@@ -1313,18 +2216,55 @@ pub fn polish_with_prompt(
     raw_text: &str,
     client: &reqwest::blocking::Client,
     max_tokens: Option<u32>,
-) -> Result<String, String> {
+) -> Result<String, SumiError> {
+    run_with_prompt(llm_cache, model_dir, config, system_prompt, raw_text, client, max_tokens, false)
+}
+
+/// Like [`polish_with_prompt`], but requests a JSON object response from
+/// cloud providers that support structured output.  The caller still has to
+/// validate the result: local models and other providers only get the prompt.
+pub fn generate_json_with_prompt(
+    llm_cache: &Mutex<Option<LlmModelCache>>,
+    model_dir: &std::path::Path,
+    config: &PolishConfig,
+    system_prompt: &str,
+    raw_text: &str,
+    client: &reqwest::blocking::Client,
+) -> Result<String, SumiError> {
+    run_with_prompt(llm_cache, model_dir, config, system_prompt, raw_text, client, None, true)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_with_prompt(
+    llm_cache: &Mutex<Option<LlmModelCache>>,
+    model_dir: &std::path::Path,
+    config: &PolishConfig,
+    system_prompt: &str,
+    raw_text: &str,
+    client: &reqwest::blocking::Client,
+    max_tokens: Option<u32>,
+    json_mode: bool,
+) -> Result<String, SumiError> {
     let raw_output = match config.mode {
-        PolishMode::Cloud => run_cloud_inference(&config.cloud, system_prompt, raw_text, client, max_tokens)?,
+        PolishMode::Cloud => run_cloud_inference(&config.cloud, system_prompt, raw_text, client, max_tokens, json_mode, config.cancel_flag(), None)?,
         PolishMode::Local => run_llm_inference(llm_cache, model_dir, config, system_prompt, raw_text, max_tokens.map(|t| t as usize))?,
     };
     let (cleaned, _) = extract_think_tags(&raw_output);
     Ok(cleaned.trim().to_string())
 }
 
-/// Build the system prompt for edit-by-instruction mode.
-fn build_edit_system_prompt() -> &'static str {
-    "You are a text editing assistant."
+/// Built-in system prompt for edit-by-instruction mode.
+pub const DEFAULT_EDIT_SYSTEM_PROMPT: &str = "You are a text editing assistant.";
+
+/// Build the system prompt for edit-by-instruction mode: the user's prompt if
+/// set, else the built-in one.
+fn build_edit_system_prompt(config: &PolishConfig) -> &str {
+    config
+        .edit_system_prompt
+        .as_deref()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .unwrap_or(DEFAULT_EDIT_SYSTEM_PROMPT)
 }
 
 /// Edit text by applying a voice instruction using the LLM.
@@ -1346,7 +2286,7 @@ pub fn edit_text_by_instruction(
         return Err("Instruction is empty".to_string());
     }
 
-    let system_prompt = build_edit_system_prompt();
+    let system_prompt = build_edit_system_prompt(config);
 
     // User message: input first, instructions after.
     let mut user_text = String::new();
@@ -1362,7 +2302,7 @@ pub fn edit_text_by_instruction(
     ));
 
     let raw_output = match config.mode {
-        PolishMode::Cloud => run_cloud_inference(&config.cloud, system_prompt, &user_text, client, None)?,
+        PolishMode::Cloud => run_cloud_inference(&config.cloud, system_prompt, &user_text, client, None, false, config.cancel_flag(), None)?,
         PolishMode::Local => run_llm_inference(llm_cache, model_dir, config, system_prompt, &user_text, None)?,
     };
 
@@ -1426,6 +2366,27 @@ pub fn validate_gguf_file(path: &std::path::Path, expected_model: &PolishModel)
     Ok(())
 }
 
+/// Swap in a one-shot model for a single polish: a model ID in cloud mode,
+/// or a local model key such as `"qwen3_8b"` in local mode.  Returns false
+/// (leaving `config` untouched) for an unknown local key.
+pub fn apply_model_override(config: &mut PolishConfig, model: &str) -> bool {
+    match config.mode {
+        PolishMode::Cloud => {
+            config.cloud.model_id = model.to_string();
+            true
+        }
+        PolishMode::Local => {
+            match serde_json::from_value::<PolishModel>(serde_json::Value::String(model.to_string())) {
+                Ok(local) if local != PolishModel::Unknown => {
+                    config.model = local;
+                    true
+                }
+                _ => false,
+            }
+        }
+    }
+}
+
 /// Check if polishing is ready to run (either local model exists or cloud API key is set).
 pub fn is_polish_ready(model_dir: &std::path::Path, config: &PolishConfig) -> bool {
     match config.mode {
@@ -1485,8 +2446,112 @@ pub fn warm_llm_cache(
     Ok(())
 }
 
+/// Consecutive decode failures of one file before it is reported as corrupt.
+pub const CORRUPT_AFTER_FAILURES: u32 = 2;
+
+/// A local model that passed [`validate_gguf_file`] but repeatedly failed to
+/// decode; the UI asks the user whether to re-download it.
+#[derive(Debug, Clone, Serialize)]
+pub struct CorruptModelNotice {
+    pub model: PolishModel,
+    pub failures: u32,
+    pub error: String,
+}
+
+/// Why [`load_llm`] failed.  Only `Corrupt` counts towards a
+/// [`CorruptModelNotice`]; a missing tokenizer or a device out of memory
+/// says nothing about the file itself.
+enum LoadError {
+    /// The GGUF header or tensor data could not be decoded.
+    Corrupt(String),
+    Other(String),
+}
+
+impl LoadError {
+    /// Classify a weight-loading error; allocation failures are not the
+    /// file's fault.
+    fn weights(label: &str, e: candle_core::Error) -> Self {
+        let message = format!("Load {}: {}", label, e);
+        if is_resource_error(&e) {
+            LoadError::Other(message)
+        } else {
+            LoadError::Corrupt(message)
+        }
+    }
+}
+
+/// Whether a weight-loading error comes from the machine rather than the
+/// file: the host out of memory, or the CUDA/Metal device failing to take
+/// the tensors (GGUF decoding itself never touches the device).
+fn is_resource_error(e: &candle_core::Error) -> bool {
+    use candle_core::Error;
+    match e {
+        Error::WithBacktrace { inner, .. } | Error::Context { inner, .. } | Error::WithPath { inner, .. } => {
+            is_resource_error(inner)
+        }
+        Error::Io(io) => io.kind() == std::io::ErrorKind::OutOfMemory,
+        Error::Cuda(_) | Error::Metal(_) => true,
+        _ => false,
+    }
+}
+
+impl From<String> for LoadError {
+    fn from(message: String) -> Self {
+        LoadError::Other(message)
+    }
+}
+
+static LOAD_FAILURES: OnceLock<Mutex<HashMap<PathBuf, u32>>> = OnceLock::new();
+static CORRUPT_NOTICE: Mutex<Option<CorruptModelNotice>> = Mutex::new(None);
+
+fn load_failures() -> &'static Mutex<HashMap<PathBuf, u32>> {
+    LOAD_FAILURES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Forget past load failures of `model_path`, e.g. before re-downloading it.
+pub fn clear_load_failures(model_path: &std::path::Path) {
+    if let Ok(mut failures) = load_failures().lock() {
+        failures.remove(model_path);
+    }
+}
+
+/// Count a failed decode and, past [`CORRUPT_AFTER_FAILURES`], queue a
+/// corrupt-model notice.  The file is never removed here; re-downloading is
+/// up to the user.  Returns the consecutive failure count.
+fn record_load_failure(model_path: &std::path::Path, polish_model: &PolishModel, error: &str) -> u32 {
+    let failures = match load_failures().lock() {
+        Ok(mut map) => {
+            let count = map.entry(model_path.to_path_buf()).or_insert(0);
+            *count += 1;
+            *count
+        }
+        Err(_) => return 0,
+    };
+    if failures < CORRUPT_AFTER_FAILURES {
+        return failures;
+    }
+    tracing::warn!("{} failed to decode {} times in a row; it may be corrupt", model_path.display(), failures);
+    if let Ok(mut notice) = CORRUPT_NOTICE.lock() {
+        *notice = Some(CorruptModelNotice {
+            model: polish_model.clone(),
+            failures,
+            error: error.to_string(),
+        });
+    }
+    failures
+}
+
+/// Take the pending corrupt-model notice, if a load has crossed the
+/// threshold since the last call.
+pub fn take_corrupt_model_notice() -> Option<CorruptModelNotice> {
+    CORRUPT_NOTICE.lock().ok().and_then(|mut n| n.take())
+}
+
 /// Shared helper: ensure the LLM is loaded into `llm_cache`, reloading only
 /// when the cached path differs from `model_path` (or the cache is empty).
+///
+/// A failed load leaves the cache empty; decode failures are counted against
+/// the file, see [`record_load_failure`].
 fn ensure_llm_loaded(
     llm_cache: &Mutex<Option<LlmModelCache>>,
     model_path: &std::path::Path,
@@ -1499,65 +2564,116 @@ fn ensure_llm_loaded(
         None => true,
     };
     if needs_reload {
-        let load_start = std::time::Instant::now();
-        tracing::info!("Loading LLM: {} ...", display_name);
+        if *polish_model == PolishModel::Unknown {
+            return Err("Unknown polish model — please select a model in Settings → Polish".to_string());
+        }
+        // Malformed tensor data can make candle panic rather than error.
+        let loaded = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            load_llm(model_path, display_name, polish_model)
+        }))
+        .unwrap_or_else(|_| Err(LoadError::Corrupt(format!("Loading {} panicked", display_name))));
+        match loaded {
+            Ok(loaded) => {
+                *cache = Some(loaded);
+                clear_load_failures(model_path);
+            }
+            Err(LoadError::Corrupt(e)) => {
+                *cache = None;
+                record_load_failure(model_path, polish_model, &e);
+                return Err(e);
+            }
+            Err(LoadError::Other(e)) => {
+                *cache = None;
+                return Err(e);
+            }
+        }
+    }
+    Ok(())
+}
 
-        let device = Device::new_metal(0)
+/// Read `model_path` and build its weights, tokenizer and chat format.
+fn load_llm(
+    model_path: &std::path::Path,
+    display_name: &str,
+    polish_model: &PolishModel,
+) -> Result<LlmModelCache, LoadError> {
+    let load_start = std::time::Instant::now();
+    tracing::info!("Loading LLM: {} ...", display_name);
+
+    let device = if crate::settings::force_cpu() {
+        Device::Cpu
+    } else {
+        Device::new_metal(0)
             .or_else(|_| Device::new_cuda(0))
-            .unwrap_or(Device::Cpu);
-        tracing::debug!("LLM device: {:?}", device);
-
-        let mut file = std::fs::File::open(model_path)
-            .map_err(|e| format!("Cannot open model: {}", e))?;
-        let content = gguf_file::Content::read(&mut file)
-            .map_err(|e| format!("Read GGUF: {}", e))?;
-
-        // Load tokenizer — from external JSON for models with non-gpt2 GGUF tokenizers
-        // (e.g. Phi-3.5-mini uses SentencePiece/llama type), else from GGUF metadata.
-        let tokenizer = {
-            let tok_filename = polish_model.tokenizer_filename()
-                .ok_or("Model has no tokenizer configured")?;
-            let tok_path = model_path.parent().unwrap_or(model_path).join(tok_filename);
-            tokenizers::Tokenizer::from_file(&tok_path)
-                .map_err(|e| format!("Load tokenizer from {}: {}", tok_path.display(), e))?
-        };
+            .unwrap_or(Device::Cpu)
+    };
+    tracing::debug!("LLM device: {:?}", device);
+
+    let mut file = std::fs::File::open(model_path)
+        .map_err(|e| format!("Cannot open model: {}", e))?;
+    let content = gguf_file::Content::read(&mut file)
+        .map_err(|e| LoadError::Corrupt(format!("Read GGUF: {}", e)))?;
+
+    // Prefer the template shipped in the GGUF over our defaults; its EOS id
+    // becomes an extra stop token.
+    let chat_format = content
+        .metadata
+        .get("tokenizer.chat_template")
+        .and_then(|v| v.to_string().ok())
+        .and_then(|t| ChatFormat::from_template(t))
+        .unwrap_or_else(|| ChatFormat::for_model(polish_model));
+    let eos_token_id = content
+        .metadata
+        .get("tokenizer.ggml.eos_token_id")
+        .and_then(|v| v.to_u32().ok());
+    tracing::debug!("LLM chat format: {:?}, GGUF EOS id: {:?}", chat_format, eos_token_id);
+
+    // Load tokenizer — from external JSON for models with non-gpt2 GGUF tokenizers
+    // (e.g. Phi-3.5-mini uses SentencePiece/llama type), else from GGUF metadata.
+    let tokenizer = {
+        let tok_filename = polish_model.tokenizer_filename()
+            .ok_or_else(|| "Model has no tokenizer configured".to_string())?;
+        let tok_path = model_path.parent().unwrap_or(model_path).join(tok_filename);
+        tokenizers::Tokenizer::from_file(&tok_path)
+            .map_err(|e| format!("Load tokenizer from {}: {}", tok_path.display(), e))?
+    };
 
-        // Load model weights (consumes content; file reader is positioned at tensor data)
-        let model = match polish_model {
-            PolishModel::Phi4Mm => QuantizedModel::Phi4Mm(
-                crate::models::phi4::ModelWeights::from_gguf(content, &mut file, &device)
-                    .map_err(|e| format!("Load Phi4Mm: {}", e))?,
-            ),
-            PolishModel::Ministral3B => QuantizedModel::Ministral3B(
-                crate::models::mistral3::ModelWeights::from_gguf(content, &mut file, &device)
-                    .map_err(|e| format!("Load Ministral3B: {}", e))?,
-            ),
-            PolishModel::Ministral14B => QuantizedModel::Ministral3B(
-                crate::models::mistral3::ModelWeights::from_gguf(content, &mut file, &device)
-                    .map_err(|e| format!("Load Ministral14B: {}", e))?,
-            ),
-            PolishModel::Qwen3_4B => QuantizedModel::Qwen3(
-                candle_transformers::models::quantized_qwen3::ModelWeights::from_gguf(content, &mut file, &device)
-                    .map_err(|e| format!("Load Qwen3_4B: {}", e))?,
-            ),
-            PolishModel::Qwen3_8B => QuantizedModel::Qwen3(
-                candle_transformers::models::quantized_qwen3::ModelWeights::from_gguf(content, &mut file, &device)
-                    .map_err(|e| format!("Load Qwen3_8B: {}", e))?,
-            ),
-            PolishModel::Unknown => {
-                return Err("Unknown polish model — please select a model in Settings → Polish".to_string());
-            }
-        };
+    // Load model weights (consumes content; file reader is positioned at tensor data)
+    let model = match polish_model {
+        PolishModel::Phi4Mm => QuantizedModel::Phi4Mm(
+            crate::models::phi4::ModelWeights::from_gguf(content, &mut file, &device)
+                .map_err(|e| LoadError::weights("Phi4Mm", e))?,
+        ),
+        PolishModel::Ministral3B => QuantizedModel::Ministral3B(
+            crate::models::mistral3::ModelWeights::from_gguf(content, &mut file, &device)
+                .map_err(|e| LoadError::weights("Ministral3B", e))?,
+        ),
+        PolishModel::Ministral14B => QuantizedModel::Ministral3B(
+            crate::models::mistral3::ModelWeights::from_gguf(content, &mut file, &device)
+                .map_err(|e| LoadError::weights("Ministral14B", e))?,
+        ),
+        PolishModel::Qwen3_4B => QuantizedModel::Qwen3(
+            candle_transformers::models::quantized_qwen3::ModelWeights::from_gguf(content, &mut file, &device)
+                .map_err(|e| LoadError::weights("Qwen3_4B", e))?,
+        ),
+        PolishModel::Qwen3_8B => QuantizedModel::Qwen3(
+            candle_transformers::models::quantized_qwen3::ModelWeights::from_gguf(content, &mut file, &device)
+                .map_err(|e| LoadError::weights("Qwen3_8B", e))?,
+        ),
+        PolishModel::Unknown => {
+            return Err("Unknown polish model — please select a model in Settings → Polish".to_string().into());
+        }
+    };
 
-        *cache = Some(LlmModelCache {
-            model,
-            tokenizer,
-            device,
-            loaded_path: model_path.to_path_buf(),
-        });
-        tracing::info!("LLM loaded (took {:.0?})", load_start.elapsed());
-    }
-    Ok(())
+    tracing::info!("LLM loaded (took {:.0?})", load_start.elapsed());
+    Ok(LlmModelCache {
+        model,
+        tokenizer,
+        device,
+        loaded_path: model_path.to_path_buf(),
+        chat_format,
+        eos_token_id,
+    })
 }
 
 /// Extract only the host from a URL for safe logging (strips path, query params, credentials).
@@ -1665,3 +2781,396 @@ pub fn validate_custom_endpoint(url_str: &str) -> Result<(), String> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── hallucination_reason ──
+
+    #[test]
+    fn hallucination_length_guard_is_configurable() {
+        let raw = "hello there";
+        let long = (0..30).map(|i| format!("w{}", i)).collect::<Vec<_>>().join(" ");
+        assert!(hallucination_reason(raw, &long, 3.0, 20).unwrap().contains("too long"));
+        assert_eq!(hallucination_reason(raw, &long, 3.0, 200), None);
+        // Ratio 0 turns the length check off.
+        assert_eq!(hallucination_reason(raw, &long, 0.0, 0), None);
+    }
+
+    #[test]
+    fn hallucination_detects_repetition_loops() {
+        let raw = "Send the report to Alice and then call Bob";
+        let looped = format!("Send the report to Alice and then {}", "call Bob and then ".repeat(6));
+        let reason = hallucination_reason(raw, &looped, 0.0, 0).unwrap();
+        assert!(reason.contains("repetition"), "{}", reason);
+        assert_eq!(hallucination_reason(raw, "Send the report to Alice, and then call Bob.", 3.0, 200), None);
+    }
+
+    #[test]
+    fn hallucination_allows_repetition_the_speaker_said() {
+        let raw = "go team go team go team go team go team";
+        assert_eq!(hallucination_reason(raw, "Go team, go team, go team, go team, go team!", 3.0, 200), None);
+    }
+
+    #[test]
+    fn hallucination_detects_cjk_loops() {
+        let raw = "我們明天開會討論預算";
+        let looped = format!("我們明天開會討論預算。{}", "然後我們再討論一下".repeat(5));
+        assert!(hallucination_reason(raw, &looped, 0.0, 0).unwrap().contains("repetition"));
+        assert_eq!(hallucination_reason(raw, "我們明天開會，討論預算。", 3.0, 200), None);
+    }
+
+    fn matched_rule_name(context: &AppContext) -> Option<String> {
+        let rules = default_prompt_rules();
+        let refs: Vec<&PromptRule> = rules.iter().collect();
+        find_matching_rule(&refs, context).map(|(r, _)| r.name.clone())
+    }
+
+    fn desktop(app_name: &str, bundle_id: &str) -> AppContext {
+        let mut ctx = AppContext {
+            app_name: app_name.to_string(),
+            bundle_id: bundle_id.to_string(),
+            ..Default::default()
+        };
+        crate::context_detect::enrich_web_app_wrapper(&mut ctx);
+        ctx
+    }
+
+    #[test]
+    fn slack_desktop_matches_slack_rule() {
+        let ctx = desktop("Slack", "com.tinyspeck.slackmacgap");
+        assert_eq!(matched_rule_name(&ctx).as_deref(), Some("Slack"));
+    }
+
+    #[test]
+    fn notion_desktop_matches_url_rule() {
+        let ctx = desktop("Notion", "notion.id");
+        assert_eq!(ctx.url, "notion.so");
+        assert_eq!(matched_rule_name(&ctx).as_deref(), Some("Notion"));
+    }
+
+    #[test]
+    fn slack_in_browser_matches_via_url() {
+        let ctx = AppContext {
+            app_name: "Safari".to_string(),
+            bundle_id: "com.apple.Safari".to_string(),
+            url: "https://app.slack.com/client/T0/C0".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(matched_rule_name(&ctx).as_deref(), Some("Slack"));
+    }
+
+    #[test]
+    fn stt_language_override_follows_matched_rule() {
+        let mut config = PolishConfig::default();
+        let terminal = AppContext {
+            app_name: "Terminal".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(stt_language_override(&config, &terminal), None);
+
+        for rule in config.prompt_rules.values_mut().flatten() {
+            if rule.name == "Terminal" {
+                rule.stt_language_override = Some("multi".to_string());
+            }
+        }
+        assert_eq!(stt_language_override(&config, &terminal).as_deref(), Some("auto"));
+
+        let slack = desktop("Slack", "com.tinyspeck.slackmacgap");
+        assert_eq!(stt_language_override(&config, &slack), None);
+    }
+
+    #[test]
+    fn auto_paste_override_wins_over_global() {
+        let mut config = PolishConfig::default();
+        let terminal = AppContext {
+            app_name: "Terminal".to_string(),
+            ..Default::default()
+        };
+        assert!(effective_auto_paste(&config, &terminal, true));
+
+        for rule in config.prompt_rules.values_mut().flatten() {
+            if rule.name == "Terminal" {
+                rule.auto_paste_override = Some(false);
+            }
+        }
+        assert!(!effective_auto_paste(&config, &terminal, true));
+
+        let slack = desktop("Slack", "com.tinyspeck.slackmacgap");
+        assert!(effective_auto_paste(&config, &slack, true));
+        assert!(!effective_auto_paste(&config, &slack, false));
+    }
+
+    #[test]
+    fn open_think_block_streams_until_closed() {
+        assert_eq!(open_think_block("<think>\nThe user wants"), Some("The user wants"));
+        assert_eq!(open_think_block("<think>"), Some(""));
+        assert_eq!(open_think_block("<think>done</think>\nHello"), None);
+        assert_eq!(open_think_block("Hello world"), None);
+    }
+
+    #[test]
+    fn sse_lines_yield_content_deltas() {
+        let chunk = r#"data: {"choices":[{"index":0,"delta":{"content":"Hel"}}]}"#;
+        assert_eq!(sse_content_delta(chunk), Ok(Some("Hel".to_string())));
+        assert_eq!(sse_content_delta(r#"data:{"choices":[{"delta":{"role":"assistant"}}]}"#), Ok(None));
+        assert_eq!(sse_content_delta("data: [DONE]"), Ok(None));
+        assert_eq!(sse_content_delta(": keep-alive"), Ok(None));
+        assert_eq!(sse_content_delta(""), Ok(None));
+        let err = sse_content_delta(r#"data: {"error":{"message":"model not found"}}"#).unwrap_err();
+        assert!(err.contains("model not found"));
+    }
+
+    #[test]
+    fn anthropic_reply_joins_text_blocks() {
+        let reply = serde_json::json!({
+            "type": "message",
+            "role": "assistant",
+            "content": [{ "type": "text", "text": "Hi Sam,\n\nThanks for the notes." }],
+            "stop_reason": "end_turn"
+        });
+        assert_eq!(anthropic_reply_text(&reply).as_deref(), Some("Hi Sam,\n\nThanks for the notes."));
+        let mixed = serde_json::json!({
+            "content": [
+                { "type": "thinking", "thinking": "Tidy it up." },
+                { "type": "text", "text": "Hello " },
+                { "type": "text", "text": "world." }
+            ]
+        });
+        assert_eq!(anthropic_reply_text(&mixed).as_deref(), Some("Hello world."));
+        assert_eq!(anthropic_reply_text(&serde_json::json!({ "content": [] })), None);
+        assert_eq!(anthropic_reply_text(&serde_json::json!({ "error": { "type": "overloaded_error" } })), None);
+    }
+
+    #[test]
+    fn streamed_text_hides_open_reasoning() {
+        assert_eq!(visible_stream_text("<think>\nThe user wants"), None);
+        assert_eq!(visible_stream_text("<think>ok</think>\nHello"), Some("Hello"));
+        assert_eq!(visible_stream_text("<think>ok</think>\n"), None);
+        assert_eq!(visible_stream_text("Hello wor"), Some("Hello wor"));
+    }
+
+    #[test]
+    fn preserve_markup_adds_instruction() {
+        let mut config = PolishConfig::default();
+        let ctx = AppContext::default();
+        assert!(!build_instructions(&config, &ctx).contains(PRESERVE_MARKUP_INSTRUCTION));
+
+        config.preserve_markup = true;
+        assert!(build_instructions(&config, &ctx).contains(PRESERVE_MARKUP_INSTRUCTION));
+    }
+
+    #[test]
+    fn translate_to_names_target_language() {
+        let mut config = PolishConfig::default();
+        let ctx = AppContext::default();
+        config.translate_to = Some("  ".to_string());
+        assert!(!build_instructions(&config, &ctx).contains("Translation:"));
+
+        config.translate_to = Some("en".to_string());
+        assert!(build_instructions(&config, &ctx).contains("translate it into English."));
+        config.translate_to = Some("tlh".to_string());
+        assert!(build_instructions(&config, &ctx).contains("translate it into tlh."));
+    }
+
+    #[test]
+    fn chat_format_detected_from_gguf_template() {
+        let qwen3 = "{% for m in messages %}<|im_start|>{{ m.role }}\n{{ m.content }}<|im_end|>{% endfor %}<think>\n\n</think>";
+        assert_eq!(ChatFormat::from_template(qwen3), Some(ChatFormat::ChatMlNoThink));
+        assert_eq!(ChatFormat::from_template("<|im_start|>user"), Some(ChatFormat::ChatMl));
+        assert_eq!(ChatFormat::from_template("{{ bos_token }}[INST] {{ m }} [/INST]"), Some(ChatFormat::Mistral));
+        assert_eq!(ChatFormat::from_template("<|user|>\n{{ m }}<|end|>"), Some(ChatFormat::Phi));
+        assert_eq!(ChatFormat::from_template("<|start_header_id|>user<|end_header_id|>"), Some(ChatFormat::Llama3));
+        assert_eq!(ChatFormat::from_template("{{ unknown }}"), None);
+        assert_eq!(ChatFormat::for_model(&PolishModel::Qwen3_4B), ChatFormat::ChatMlNoThink);
+    }
+
+    #[test]
+    fn fast_mode_skips_rules_dictionary_and_context() {
+        let mut config = PolishConfig::default();
+        config.dictionary.enabled = true;
+        config.dictionaries[0].entries.push(DictionaryEntry { term: "Sumi".to_string(), enabled: true });
+        let ctx = desktop("Terminal", "com.apple.Terminal");
+        let full = build_instructions(&config, &ctx);
+        assert!(full.contains("Sumi"));
+        assert!(full.contains("App: Terminal"));
+
+        config.context_aware = false;
+        let fast = build_instructions(&config, &ctx);
+        assert!(!fast.contains("Sumi"));
+        assert!(!fast.contains("App: Terminal"));
+        assert!(fast.len() < full.len());
+    }
+
+    #[test]
+    fn repeated_load_failures_flag_but_keep_model() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bad.gguf");
+        std::fs::write(&path, b"GGUF").unwrap();
+        let model = PolishModel::Qwen3_4B;
+
+        assert_eq!(record_load_failure(&path, &model, "arch mismatch"), 1);
+        assert!(take_corrupt_model_notice().is_none());
+
+        assert_eq!(record_load_failure(&path, &model, "arch mismatch"), CORRUPT_AFTER_FAILURES);
+        assert!(take_corrupt_model_notice().is_some());
+
+        record_load_failure(&path, &model, "arch mismatch");
+        assert_eq!(take_corrupt_model_notice().unwrap().failures, CORRUPT_AFTER_FAILURES + 1);
+        assert!(path.exists());
+
+        clear_load_failures(&path);
+        assert_eq!(record_load_failure(&path, &model, "arch mismatch"), 1);
+    }
+
+    #[test]
+    fn edit_system_prompt_falls_back_to_builtin() {
+        let mut config = PolishConfig::default();
+        assert_eq!(build_edit_system_prompt(&config), DEFAULT_EDIT_SYSTEM_PROMPT);
+        config.edit_system_prompt = Some("  ".to_string());
+        assert_eq!(build_edit_system_prompt(&config), DEFAULT_EDIT_SYSTEM_PROMPT);
+        config.edit_system_prompt = Some("你是文字編輯助理，保留原有格式。\n".to_string());
+        assert_eq!(build_edit_system_prompt(&config), "你是文字編輯助理，保留原有格式。");
+    }
+
+    #[test]
+    fn dictionary_prompt_merges_enabled_dictionaries() {
+        let term = |t: &str| DictionaryEntry { term: t.to_string(), enabled: true };
+        let mut config = PolishConfig::default();
+        config.dictionaries = vec![
+            NamedDictionary::new("Work", vec![term("Kubernetes"), term("TSMC")]),
+            NamedDictionary::new(
+                "Names",
+                vec![term("tsmc"), term("Jay Chou"), DictionaryEntry { term: "Old".to_string(), enabled: false }],
+            ),
+            NamedDictionary { enabled: false, ..NamedDictionary::new("Medical", vec![term("ibuprofen")]) },
+        ];
+        assert_eq!(config.dictionary_terms(), vec!["Kubernetes", "TSMC", "Jay Chou"]);
+        let block = format_dictionary_prompt(&config);
+        assert!(block.contains("• Jay Chou"));
+        assert!(!block.contains("ibuprofen"));
+
+        config.dictionary.enabled = false;
+        assert!(format_dictionary_prompt(&config).is_empty());
+    }
+
+    #[test]
+    fn preview_prompt_shows_matched_rule_and_dictionary() {
+        let mut config = PolishConfig::default();
+        config.dictionary.enabled = true;
+        config.dictionaries[0].entries.push(DictionaryEntry { term: "Sumi".to_string(), enabled: true });
+        config.prompt_rules = HashMap::from([(
+            "en".to_string(),
+            vec![PromptRule {
+                name: "Notes".to_string(),
+                match_type: MatchType::AppName,
+                match_value: "Notes".to_string(),
+                prompt: "Write tidy bullet points.".to_string(),
+                enabled: true,
+                icon: None,
+                alt_matches: Vec::new(),
+                stt_language_override: None,
+                auto_paste_override: None,
+            }],
+        )]);
+        let preview = preview_prompt(&config, &desktop("Notes", "com.apple.Notes"), "hello");
+        assert!(preview.starts_with(&format!("[system]\n{}", POLISH_SYSTEM_PROMPT)));
+        assert!(preview.contains("<speech>\nhello\n</speech>"));
+        assert!(preview.contains("Write tidy bullet points."));
+        assert!(preview.contains("• Sumi"));
+        assert!(preview.contains("App: Notes"));
+
+        let unmatched = preview_prompt(&config, &desktop("Mail", "com.apple.mail"), "hello");
+        assert!(!unmatched.contains("Write tidy bullet points."));
+    }
+
+    #[test]
+    fn history_context_keeps_newest_entries_within_budget() {
+        let mut config = PolishConfig::default();
+        config.recent_transcripts = vec![
+            "newest".to_string(),
+            "x".repeat(HISTORY_CONTEXT_ENTRY_CHARS + 50),
+            "y".repeat(HISTORY_CONTEXT_MAX_CHARS),
+            "oldest".to_string(),
+        ];
+        let ctx = AppContext::default();
+        let message = compose_user_message(&config, &ctx, "hello");
+        let block = &message[message.find("<previous>").unwrap()..message.find("<speech>").unwrap()];
+        assert!(block.find('…').unwrap() < block.find("newest").unwrap());
+        assert!(!block.contains("yyy"));
+        assert!(!block.contains("oldest"));
+
+        config.context_aware = false;
+        assert!(!compose_user_message(&config, &ctx, "hello").contains("<previous>"));
+    }
+
+    #[test]
+    fn match_prompt_rule_reports_primary_or_alt_condition() {
+        let mut config = PolishConfig::default();
+        config.prompt_rules = HashMap::from([("en".to_string(), default_prompt_rules())]);
+        let web = AppContext {
+            app_name: "Arc".to_string(),
+            url: "https://www.notion.so/team".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            match_prompt_rule(&config, &web),
+            Some(MatchedRule { name: "Notion".to_string(), alt_index: None })
+        );
+        let app = AppContext { app_name: "Notion".to_string(), ..Default::default() };
+        assert_eq!(
+            match_prompt_rule(&config, &app),
+            Some(MatchedRule { name: "Notion".to_string(), alt_index: Some(0) })
+        );
+        assert_eq!(match_prompt_rule(&config, &desktop("Calculator", "com.apple.calculator")), None);
+    }
+
+    #[test]
+    fn app_context_matters_only_with_enabled_rules() {
+        let mut config = PolishConfig { enabled: true, context_aware: true, ..Default::default() };
+        assert!(uses_app_context(&config));
+        config.context_aware = false;
+        assert!(!uses_app_context(&config));
+        config.context_aware = true;
+        for rule in config.prompt_rules.values_mut().flatten() {
+            rule.enabled = false;
+        }
+        assert!(!uses_app_context(&config));
+    }
+
+    #[test]
+    fn model_override_targets_the_active_mode() {
+        let mut cloud = PolishConfig { mode: PolishMode::Cloud, ..Default::default() };
+        assert!(apply_model_override(&mut cloud, "gpt-5"));
+        assert_eq!(cloud.cloud.model_id, "gpt-5");
+
+        let mut local = PolishConfig { mode: PolishMode::Local, ..Default::default() };
+        assert!(apply_model_override(&mut local, "qwen3_8b"));
+        assert_eq!(local.model, PolishModel::Qwen3_8B);
+        assert!(!apply_model_override(&mut local, "gpt-5"));
+        assert_eq!(local.model, PolishModel::Qwen3_8B);
+    }
+
+    #[test]
+    fn model_ids_parse_from_openai_and_catalog_shapes() {
+        let openai = serde_json::json!({ "object": "list", "data": [{ "id": "b" }, { "id": "a" }, { "object": "x" }] });
+        assert_eq!(parse_model_ids(&openai), vec!["a", "b"]);
+        let catalog = serde_json::json!([{ "id": "openai/gpt-4.1" }, { "id": "openai/gpt-4.1" }]);
+        assert_eq!(parse_model_ids(&catalog), vec!["openai/gpt-4.1"]);
+        assert!(parse_model_ids(&serde_json::json!({ "error": "nope" })).is_empty());
+    }
+
+    #[test]
+    fn only_resource_failures_spare_the_model_file() {
+        let oom = candle_core::Error::Io(std::io::ErrorKind::OutOfMemory.into()).context("tensor blk.0");
+        assert!(matches!(LoadError::weights("Qwen3_4B", oom), LoadError::Other(_)));
+        let device = candle_core::Error::Cuda("CUDA_ERROR_OUT_OF_MEMORY".into()).bt();
+        assert!(matches!(LoadError::weights("Qwen3_4B", device), LoadError::Other(_)));
+        // A decode error that merely mentions allocation is still the file's.
+        let bad = candle_core::Error::Msg("invalid tensor allocation size in header".into());
+        assert!(matches!(LoadError::weights("Qwen3_4B", bad), LoadError::Corrupt(_)));
+        let truncated = candle_core::Error::Io(std::io::ErrorKind::UnexpectedEof.into());
+        assert!(matches!(LoadError::weights("Qwen3_4B", truncated), LoadError::Corrupt(_)));
+    }
+}
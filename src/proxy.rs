@@ -0,0 +1,119 @@
+//! Outbound HTTP proxy: the user's `Settings::http_proxy`, falling back to
+//! reqwest's system proxy (`HTTPS_PROXY` / `HTTP_PROXY` / `ALL_PROXY`,
+//! `NO_PROXY`, and the macOS system settings).
+//!
+//! Every reqwest client is built through [`client_builder`].
+
+use reqwest::Url;
+use std::sync::RwLock;
+
+static HTTP_PROXY: RwLock<Option<Url>> = RwLock::new(None);
+
+/// Check a user-entered proxy URL.  Blank means "no proxy"; otherwise the
+/// URL must be `http://` or `https://` with a host, and is returned trimmed.
+pub fn validate_proxy_url(raw: &str) -> Result<Option<String>, String> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Ok(None);
+    }
+    let url = Url::parse(raw).map_err(|e| format!("Invalid proxy URL \"{}\": {}", raw, e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!(
+            "Unsupported proxy scheme \"{}\" (use http:// or https://)",
+            url.scheme()
+        ));
+    }
+    if url.host_str().is_none_or(str::is_empty) {
+        return Err(format!("Proxy URL \"{}\" has no host", raw));
+    }
+    Ok(Some(raw.to_string()))
+}
+
+/// Mirror `Settings::http_proxy` for outbound requests.  Called once after
+/// `load_settings()` and whenever the user changes the setting.  A value that
+/// fails validation is ignored so a hand-edited settings file cannot break
+/// networking.
+pub fn set_http_proxy(proxy: Option<&str>) {
+    let url = match proxy.map(validate_proxy_url) {
+        Some(Ok(Some(url))) => Url::parse(&url).ok(),
+        Some(Err(e)) => {
+            tracing::warn!("Ignoring http_proxy setting: {}", e);
+            None
+        }
+        _ => None,
+    };
+    if let Ok(mut guard) = HTTP_PROXY.write() {
+        *guard = url;
+    }
+}
+
+/// Hosts that never go through the configured proxy: a local Ollama server
+/// and the local API.
+const LOOPBACK_HOSTS: &str = "localhost,127.0.0.1,::1";
+
+/// `reqwest::blocking::Client::builder()` with the proxy applied.  Without a
+/// configured proxy the builder is left alone, so reqwest's own system proxy
+/// lookup (environment variables, `NO_PROXY`, the macOS system settings)
+/// stays in effect.  The proxy is fixed when the client is built; the shared
+/// client is rebuilt by `AppState::rebuild_http_client` when the setting
+/// changes.
+pub fn client_builder() -> reqwest::blocking::ClientBuilder {
+    let builder = reqwest::blocking::Client::builder();
+    let Some(url) = HTTP_PROXY.read().ok().and_then(|g| g.clone()) else {
+        return builder;
+    };
+    match reqwest::Proxy::all(url) {
+        Ok(proxy) => {
+            let no_proxy = no_proxy_list(std::env::var("NO_PROXY").or_else(|_| std::env::var("no_proxy")).ok());
+            builder.proxy(proxy.no_proxy(reqwest::NoProxy::from_string(&no_proxy)))
+        }
+        Err(e) => {
+            tracing::warn!("Ignoring http_proxy setting: {}", e);
+            builder
+        }
+    }
+}
+
+/// The client shared through `AppState` for cloud STT and polish requests.
+pub fn shared_client() -> reqwest::blocking::Client {
+    client_builder()
+        .timeout(std::time::Duration::from_secs(60))
+        .build()
+        .unwrap_or_else(|_| reqwest::blocking::Client::new())
+}
+
+/// Bypass list for the configured proxy: loopback hosts plus `NO_PROXY`.
+fn no_proxy_list(env: Option<String>) -> String {
+    match env.as_deref().map(str::trim) {
+        Some(env) if !env.is_empty() => format!("{},{}", LOOPBACK_HOSTS, env),
+        _ => LOOPBACK_HOSTS.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_accepts_http_and_rejects_others() {
+        assert_eq!(validate_proxy_url("  "), Ok(None));
+        assert_eq!(
+            validate_proxy_url(" http://proxy.corp:8080 "),
+            Ok(Some("http://proxy.corp:8080".to_string()))
+        );
+        assert!(validate_proxy_url("https://user:pw@proxy.corp").is_ok());
+        assert!(validate_proxy_url("socks5://proxy.corp:1080").is_err());
+        assert!(validate_proxy_url("proxy.corp:8080").is_err());
+        assert!(validate_proxy_url("not a url").is_err());
+    }
+
+    #[test]
+    fn no_proxy_list_keeps_loopback_and_env() {
+        assert_eq!(no_proxy_list(None), LOOPBACK_HOSTS);
+        assert_eq!(no_proxy_list(Some("  ".to_string())), LOOPBACK_HOSTS);
+        assert_eq!(
+            no_proxy_list(Some("corp.local, 10.0.0.0/8".to_string())),
+            format!("{},corp.local, 10.0.0.0/8", LOOPBACK_HOSTS)
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! Retries for cloud STT / polish requests on flaky connections.
+//!
+//! A request is sent again when it never got a response (connection reset,
+//! refused, DNS) or the provider answered 429 / 500 / 502 / 503, waiting
+//! 250 ms, 500 ms, 1 s, … in between.  A 429's `Retry-After` replaces the
+//! backoff when it is short enough to wait out.  Timeouts and other 4xx
+//! replies (bad key, bad model) are returned at once: repeating them only
+//! makes the user wait longer for the same error.
+//!
+//! The retry count mirrors `Settings::cloud_max_retries`, set once after
+//! `load_settings()` and whenever the user saves settings.
+
+use crate::error::SumiError;
+use reqwest::blocking::{RequestBuilder, Response};
+use reqwest::StatusCode;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::time::{Duration, SystemTime};
+
+pub const DEFAULT_MAX_RETRIES: u8 = 3;
+
+static MAX_RETRIES: AtomicU8 = AtomicU8::new(DEFAULT_MAX_RETRIES);
+
+/// Delay before the first retry; doubles on each further one.
+const BASE_DELAY: Duration = Duration::from_millis(250);
+/// Upper bound for the doubling backoff.
+const MAX_BACKOFF: Duration = Duration::from_secs(4);
+/// Longest `Retry-After` worth waiting out mid-dictation.  Anything longer
+/// is reported as a rate limit so the overlay can show when to come back.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(10);
+
+pub fn set_max_retries(retries: u8) {
+    MAX_RETRIES.store(retries, Ordering::Relaxed);
+}
+
+/// Send the request built by `request`, retrying transient failures:
+/// connection and request errors (but not timeouts) and retryable statuses.
+/// The closure is called once per attempt because multipart bodies cannot be
+/// replayed.  Non-retryable statuses, and the last retryable one, come back
+/// as `Ok` for the caller's usual status handling.  Retrying stops early
+/// once `abandoned` is set.
+pub fn send_with_retry(
+    context: &str,
+    abandoned: Option<&AtomicBool>,
+    mut request: impl FnMut() -> RequestBuilder,
+) -> Result<Response, SumiError> {
+    let max_retries = MAX_RETRIES.load(Ordering::Relaxed) as u32;
+    let mut retry = 0;
+    loop {
+        let result = request().send();
+        let delay = match &result {
+            _ if retry >= max_retries => None,
+            Ok(resp) => status_delay(resp.status(), resp.headers(), retry),
+            Err(e) if e.is_timeout() => None,
+            Err(e) if e.is_connect() || e.is_request() => Some(backoff(retry)),
+            Err(_) => None,
+        };
+        let Some(delay) = delay else {
+            return result.map_err(|e| SumiError::from_request(context, e));
+        };
+        if abandoned.is_some_and(|flag| flag.load(Ordering::SeqCst)) {
+            return Err(SumiError::cancelled());
+        }
+        match &result {
+            Ok(resp) => tracing::warn!("{} returned HTTP {}, retrying in {:?}", context, resp.status(), delay),
+            Err(e) => tracing::warn!("{} request failed ({}), retrying in {:?}", context, e, delay),
+        }
+        drop(result);
+        std::thread::sleep(delay);
+        retry += 1;
+    }
+}
+
+/// How long to wait before retrying a reply with `status`, or `None` if it
+/// should not be retried.
+fn status_delay(status: StatusCode, headers: &reqwest::header::HeaderMap, retry: u32) -> Option<Duration> {
+    match status.as_u16() {
+        429 => match crate::error::retry_after_secs(headers, SystemTime::now()) {
+            Some(secs) => Some(Duration::from_secs(secs)).filter(|wait| *wait <= MAX_RETRY_AFTER),
+            None => Some(backoff(retry)),
+        },
+        500 | 502 | 503 => Some(backoff(retry)),
+        _ => None,
+    }
+}
+
+fn backoff(retry: u32) -> Duration {
+    BASE_DELAY.saturating_mul(1u32 << retry.min(16)).min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderMap, HeaderValue};
+
+    #[test]
+    fn backoff_doubles_up_to_cap() {
+        let ms = |retry| backoff(retry).as_millis();
+        assert_eq!([ms(0), ms(1), ms(2)], [250, 500, 1000]);
+        assert_eq!(ms(10), 4000);
+        assert_eq!(ms(255), 4000);
+    }
+
+    #[test]
+    fn only_throttling_and_server_errors_are_retried() {
+        let none = HeaderMap::new();
+        let delay = |code: u16| status_delay(StatusCode::from_u16(code).unwrap(), &none, 1);
+        for code in [429, 500, 502, 503] {
+            assert_eq!(delay(code), Some(Duration::from_millis(500)), "HTTP {}", code);
+        }
+        for code in [200, 400, 401, 403, 404, 422, 504] {
+            assert_eq!(delay(code), None, "HTTP {}", code);
+        }
+    }
+
+    #[test]
+    fn rate_limit_honours_short_retry_after() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("2"));
+        assert_eq!(status_delay(StatusCode::TOO_MANY_REQUESTS, &headers, 0), Some(Duration::from_secs(2)));
+        headers.insert("retry-after", HeaderValue::from_static("60"));
+        assert_eq!(status_delay(StatusCode::TOO_MANY_REQUESTS, &headers, 0), None);
+    }
+}
@@ -1,12 +1,20 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{OnceLock, RwLock};
 
 use crate::polisher;
 use crate::stt::SttConfig;
 
+/// Current on-disk schema version of `settings.json`.  Bump this and add a
+/// step to [`migrate_settings`] whenever a field is renamed or reshaped.
+pub const SETTINGS_SCHEMA_VERSION: u32 = 2;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
+    /// Schema version the file was written with. Missing = 0 (pre-versioning).
+    #[serde(default)]
+    pub schema_version: u32,
     pub hotkey: String,
     pub auto_paste: bool,
     #[serde(default)]
@@ -14,6 +22,17 @@ pub struct Settings {
     /// 0 = keep forever, otherwise number of days to retain history entries.
     #[serde(default)]
     pub history_retention_days: u32,
+    /// 0 = unlimited, otherwise keep only the newest N history entries.
+    #[serde(default)]
+    pub history_max_entries: u32,
+    /// 0 = unlimited, otherwise cap stored dictation audio at this many bytes
+    /// (oldest recordings are dropped first; their text is kept).
+    #[serde(default)]
+    pub history_max_audio_bytes: u64,
+    /// Days a deleted history entry stays in the trash before it is purged.
+    /// 0 = keep until the trash is emptied.
+    #[serde(default = "default_trash_retention_days")]
+    pub history_trash_retention_days: u32,
     /// UI language override. None = auto-detect from system.
     #[serde(default)]
     pub language: Option<String>,
@@ -22,35 +41,408 @@ pub struct Settings {
     /// Optional hotkey for "Edit by Voice" — select text, speak editing instruction.
     #[serde(default)]
     pub edit_hotkey: Option<String>,
+    /// When true, Shift + the primary hotkey records and pastes the raw
+    /// transcript, skipping AI polish.
+    #[serde(default)]
+    pub paste_raw_on_modifier: bool,
     /// Whether the onboarding wizard has been completed. `false` triggers the setup overlay.
     #[serde(default)]
     pub onboarding_completed: bool,
     /// Preferred microphone input device name. None = use system default.
     #[serde(default)]
     pub mic_device: Option<String>,
+    /// How a multi-channel input device is folded to mono. Default averages all channels.
+    #[serde(default)]
+    pub channel_mode: crate::audio::ChannelMode,
+    /// Record from the microphone or loop back system audio (Windows only).
+    #[serde(default)]
+    pub capture_mode: crate::audio::CaptureMode,
+    /// Fixed input gain in dB applied to each recording before transcription.
+    #[serde(default)]
+    pub input_gain_db: f32,
+    /// Level in dBFS below which the start and end of a recording are
+    /// trimmed as silence; raise it in noisy rooms.
+    #[serde(default = "default_silence_threshold_db")]
+    pub silence_threshold_db: f32,
+    /// Peak-normalize each recording to -3 dBFS before transcription.
+    #[serde(default)]
+    pub auto_gain: bool,
     /// Optional hotkey for meeting transcription mode. None = disabled.
     #[serde(default)]
     pub meeting_hotkey: Option<String>,
+    /// Optional hotkey that pastes the most recent transcript again without
+    /// recording. None = disabled.
+    #[serde(default)]
+    pub repeat_paste_hotkey: Option<String>,
     /// Idle mic timeout in seconds. 0 = never close (always-on).
     /// When > 0, the mic stream is closed after this many seconds of inactivity
     /// to prevent CoreAudio DSP (echo cancellation, AGC) from affecting other apps.
     #[serde(default = "default_idle_mic_timeout_secs")]
     pub idle_mic_timeout_secs: u32,
+    /// Minutes of inactivity after which the local Whisper and LLM models are
+    /// unloaded to free memory. 0 = never unload. The next dictation reloads them lazily.
+    #[serde(default)]
+    pub idle_unload_minutes: u32,
+    /// Stop recording automatically after this many milliseconds of silence
+    /// following speech. 0 = disabled (a second hotkey press is required).
+    #[serde(default)]
+    pub auto_stop_silence_ms: u32,
+    /// Whether a dictation ends at the first pause or keeps going.
+    #[serde(default)]
+    pub session_mode: SessionMode,
+    /// Let the hotkey record the next dictation while the previous one is
+    /// still transcribing; queued dictations paste in order.  Off = presses
+    /// are ignored while busy.  Single-utterance sessions only.
+    #[serde(default)]
+    pub queue_dictations: bool,
+    /// Encoding for dictation audio kept with history entries.
+    /// `none` disables audio retention entirely.
+    #[serde(default)]
+    pub history_audio_format: crate::history::AudioFormat,
+    /// Sample encoding when `history_audio_format` is `wav`: `"16"`, `"24"`
+    /// or `"32f"` (32-bit float).
+    #[serde(default)]
+    pub history_audio_bit_depth: crate::history::WavBitDepth,
     /// When true, the raw audio of each meeting is archived as a WAV file alongside
     /// the transcript.  Defaults to false (opt-in, privacy-sensitive feature).
     #[serde(default)]
     pub record_meeting_audio: bool,
+    /// How long the overlay lingers after a dictation or edit result, in ms.
+    /// 0 = keep it visible until the user dismisses it.
+    #[serde(default = "default_overlay_auto_hide_ms")]
+    pub overlay_auto_hide_ms: u64,
+    /// Size and level of detail of the recording overlay.
+    #[serde(default)]
+    pub overlay_style: OverlayStyle,
+    /// Stream the local model's `<think>` reasoning into the overlay while
+    /// polishing (requires `polish.reasoning`).
+    #[serde(default)]
+    pub overlay_show_reasoning: bool,
+    /// Multiplier for the overlay waveform bars.  Raise it when a quiet mic
+    /// barely moves the bars, lower it when they sit at the top.
+    #[serde(default = "default_visualizer_gain")]
+    pub visualizer_gain: f32,
+    /// Strip filler words ("um", "嗯", …) from the transcript when AI polish
+    /// does not run.
+    #[serde(default)]
+    pub remove_fillers: bool,
+    /// Filler words to strip. Empty = the defaults for `stt.language`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filler_words: Vec<String>,
+    /// Rewrite spelled-out numbers, amounts and dates as digits when AI
+    /// polish does not run.  Applies to English and Chinese transcripts.
+    #[serde(default)]
+    pub normalize_numbers: bool,
+    /// Capitalize sentence starts when AI polish does not run, leaving
+    /// code-like tokens and dictionary terms as transcribed.
+    #[serde(default)]
+    pub smart_capitalize: bool,
+    /// Run Whisper and the local LLM on the CPU only.  Escape hatch for
+    /// machines whose GPU drivers crash or misbehave.
+    #[serde(default)]
+    pub force_cpu: bool,
+    /// Whisper decode threads.  `None` = auto: performance cores only on
+    /// Apple Silicon, all logical cores elsewhere.
+    #[serde(default)]
+    pub whisper_threads: Option<usize>,
+    /// Prefer the Apple Neural Engine: downloading a Whisper model also
+    /// fetches its Core ML encoder, which whisper.cpp then loads instead of
+    /// running the encoder on Metal.  Ignored unless built with the `coreml`
+    /// feature.
+    #[serde(default)]
+    pub whisper_coreml: bool,
+    /// Proxy for all outbound requests (model downloads, cloud APIs), e.g.
+    /// `http://proxy.corp:8080`.  `None` falls back to the system proxy.
+    #[serde(default)]
+    pub http_proxy: Option<String>,
+    /// How many times a cloud STT / polish request is retried after a
+    /// connection error or HTTP 429 / 5xx.  0 disables retries; at most
+    /// [`MAX_CLOUD_RETRIES`].
+    #[serde(default = "default_cloud_max_retries")]
+    pub cloud_max_retries: u8,
+    /// Re-check the frontmost app right before auto-paste and only copy when
+    /// focus moved away from the app that was active when recording started.
+    #[serde(default)]
+    pub verify_target_on_paste: bool,
+    /// What the edit hotkey operates on when no override text is set.
+    #[serde(default)]
+    pub edit_scope: EditScope,
+    /// Seconds a transcription may run, on top of twice the recording's
+    /// length, before the watchdog assumes the pipeline hung and unblocks the
+    /// hotkey.  0 disables the watchdog.
+    #[serde(default = "default_max_processing_secs")]
+    pub max_processing_secs: u32,
+    /// A dictation stops and transcribes on its own after this many seconds
+    /// (a continuous session cuts a chunk instead).  Kept within
+    /// [`MAX_RECORDING_SECS_RANGE`] by [`clamp_max_recording_secs`].
+    #[serde(default = "default_max_recording_secs")]
+    pub max_recording_secs: u64,
+    /// Whitespace at the end of the pasted text.
+    #[serde(default)]
+    pub trailing_behavior: TrailingBehavior,
+    /// Minimum gap between two accepted hotkey presses.  OS auto-repeat from
+    /// a held key is dropped separately (see [`crate::hotkey::classify_press`]),
+    /// so this only limits deliberate double presses.  Releases are never
+    /// debounced, so anything acting on key-up always sees it.
+    #[serde(default = "default_hotkey_debounce_ms")]
+    pub hotkey_debounce_ms: u64,
+    /// Toggle or hold-to-record for the dictation hotkeys.
+    #[serde(default)]
+    pub hotkey_mode: HotkeyMode,
+    /// Write pasted text as the plain-string clipboard type only, so rich
+    /// editors (Notion, Google Docs) cannot pick up stale styled data.
+    #[serde(default = "default_plain_text_only")]
+    pub plain_text_only: bool,
+    /// How auto-paste puts text into the target app.
+    #[serde(default)]
+    pub insertion_method: InsertionMethod,
+    /// Pause between keystrokes with `InsertionMethod::TypeKeystrokes`, for
+    /// targets that drop characters typed too fast.  At most
+    /// [`MAX_KEYSTROKE_DELAY_MS`].
+    #[serde(default)]
+    pub keystroke_delay_ms: u64,
+    /// Show the result for this long before auto-pasting; cancelling within
+    /// the window leaves the text on the clipboard only.  0 pastes at once.
+    #[serde(default)]
+    pub paste_grace_ms: u64,
+    /// Minimum level of Sumi's own entries in `logs/sumi.log` (dependencies
+    /// stay at warn); applied live.
+    #[serde(default)]
+    pub log_level: LogLevel,
+    /// Opt-in HTTP server on 127.0.0.1 for scripting dictation.
+    #[serde(default)]
+    pub local_api: crate::local_api::LocalApiConfig,
     /// Custom root directory for all mutable data (models, history, audio).
     /// `None` means the default `~/.sumi/` location.
     /// `config/` always stays at `~/.sumi/config/` so the app can find this setting on next launch.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub data_root: Option<PathBuf>,
+    /// Fields this build does not know about (written by a newer version).
+    /// Kept so that saving from an older binary does not discard them.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Recording overlay presentation.  The window is resized to match on every
+/// show and the style is emitted to the overlay as `overlay-style`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlayStyle {
+    /// Capsule with status label, waveform and timer.
+    #[default]
+    Full,
+    /// Narrow capsule with icons/waveform only.
+    Compact,
+    /// A small colored dot — minimal distraction.
+    DotOnly,
+}
+
+impl OverlayStyle {
+    /// Logical (width, height) of the overlay window.
+    pub fn window_size(self) -> (f64, f64) {
+        match self {
+            OverlayStyle::Full => (300.0, 40.0),
+            OverlayStyle::Compact => (140.0, 32.0),
+            OverlayStyle::DotOnly => (24.0, 24.0),
+        }
+    }
+}
+
+/// Source text for edit-by-voice.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditScope {
+    /// Copy the current selection; abort when nothing is selected.
+    #[default]
+    Selection,
+    /// Use the text already on the clipboard without copying.
+    Clipboard,
+    /// Select all in the focused field, then copy (the result replaces everything).
+    SelectAll,
+}
+
+/// Minimum severity written to the log.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    /// `EnvFilter` directive for this level.
+    pub fn directive(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warn => "warn",
+            Self::Info => "info",
+            Self::Debug => "debug",
+            Self::Trace => "trace",
+        }
+    }
+}
+
+/// How transcripts reach the focused app when auto-paste is on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InsertionMethod {
+    /// Write the clipboard and simulate Cmd/Ctrl+V.
+    #[default]
+    ClipboardPaste,
+    /// Set the focused field's selected text via the accessibility API
+    /// (macOS), leaving the clipboard alone.  Falls back to clipboard paste
+    /// when the field is not editable that way.
+    Accessibility,
+    /// Type the text as Unicode keystrokes (macOS, Windows), for canvases,
+    /// games and remote desktops that ignore paste.  Leaves the clipboard
+    /// alone; falls back to clipboard paste where typing is unavailable.
+    TypeKeystrokes,
+}
+
+/// How the dictation hotkeys start and stop a recording.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyMode {
+    /// The first press starts recording, the next one stops it.
+    #[default]
+    Toggle,
+    /// Record while the key is held; releasing it stops and transcribes.
+    /// The meeting and repeat-paste hotkeys still toggle.
+    PushToTalk,
+}
+
+/// How long a dictation started with the primary hotkey lasts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionMode {
+    /// One recording per hotkey press (or until `auto_stop_silence_ms`).
+    #[default]
+    Single,
+    /// Keep recording across pauses: each pause transcribes and pastes what
+    /// was said so far, and the session ends on the next hotkey press.
+    Continuous,
+}
+
+impl SessionMode {
+    /// Pause that commits a chunk when `auto_stop_silence_ms` is off.
+    pub const DEFAULT_PAUSE_MS: u32 = 1200;
+}
+
+/// What to do with whitespace at the end of a transcript before it is pasted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrailingBehavior {
+    /// Strip trailing spaces and newlines (safe for single-line fields).
+    Trim,
+    /// End with one space so the next dictation continues the sentence.
+    AddSpace,
+    /// End with a newline, e.g. to send the message in chat apps.
+    AddNewline,
+    /// Paste exactly what the pipeline produced.
+    #[default]
+    AsIs,
+}
+
+impl TrailingBehavior {
+    pub fn apply(self, text: &str) -> String {
+        match self {
+            Self::Trim => text.trim_end().to_string(),
+            Self::AddSpace => format!("{} ", text.trim_end()),
+            Self::AddNewline => format!("{}\n", text.trim_end()),
+            Self::AsIs => text.to_string(),
+        }
+    }
 }
 
 fn default_idle_mic_timeout_secs() -> u32 {
     0
 }
 
+fn default_overlay_auto_hide_ms() -> u64 {
+    1500
+}
+
+fn default_visualizer_gain() -> f32 {
+    1.0
+}
+
+fn default_hotkey_debounce_ms() -> u64 {
+    300
+}
+
+fn default_plain_text_only() -> bool {
+    true
+}
+
+fn default_trash_retention_days() -> u32 {
+    30
+}
+
+fn default_max_processing_secs() -> u32 {
+    120
+}
+
+fn default_max_recording_secs() -> u64 {
+    120
+}
+
+fn default_silence_threshold_db() -> f32 {
+    crate::audio::DEFAULT_SILENCE_THRESHOLD_DB
+}
+
+fn default_cloud_max_retries() -> u8 {
+    crate::retry::DEFAULT_MAX_RETRIES
+}
+
+/// Allowed values for `Settings::max_recording_secs`.  0 would stop every
+/// recording the moment it starts.
+pub const MAX_RECORDING_SECS_RANGE: std::ops::RangeInclusive<u64> = 5..=600;
+
+/// Pull `secs` into [`MAX_RECORDING_SECS_RANGE`].
+pub fn clamp_max_recording_secs(secs: u64) -> u64 {
+    secs.clamp(*MAX_RECORDING_SECS_RANGE.start(), *MAX_RECORDING_SECS_RANGE.end())
+}
+
+/// Upper bound on `Settings::keystroke_delay_ms`; slower typing would keep
+/// a long dictation typing for minutes.
+pub const MAX_KEYSTROKE_DELAY_MS: u64 = 200;
+
+/// Upper bound on `Settings::cloud_max_retries`; with the doubling backoff,
+/// more would keep a dictation waiting on a dead connection for too long.
+pub const MAX_CLOUD_RETRIES: u8 = 5;
+
+/// Pull `retries` into 0–[`MAX_CLOUD_RETRIES`].
+pub fn clamp_cloud_max_retries(retries: u8) -> u8 {
+    retries.min(MAX_CLOUD_RETRIES)
+}
+
+/// Pull `ms` into 0–[`MAX_KEYSTROKE_DELAY_MS`].
+pub fn clamp_keystroke_delay_ms(ms: u64) -> u64 {
+    ms.min(MAX_KEYSTROKE_DELAY_MS)
+}
+
+/// Pull `gain` into 0.1–10; NaN and infinities fall back to the default.
+pub fn clamp_visualizer_gain(gain: f32) -> f32 {
+    if gain.is_finite() {
+        gain.clamp(0.1, 10.0)
+    } else {
+        default_visualizer_gain()
+    }
+}
+
+/// Pull `db` into -20–30 dB; NaN and infinities fall back to no gain.
+pub fn clamp_input_gain_db(db: f32) -> f32 {
+    if db.is_finite() {
+        db.clamp(-20.0, 30.0)
+    } else {
+        0.0
+    }
+}
+
 impl Default for Settings {
     fn default() -> Self {
         let (hotkey, edit_hotkey, meeting_hotkey) = if is_debug() {
@@ -67,19 +459,83 @@ impl Default for Settings {
             )
         };
         Self {
+            schema_version: SETTINGS_SCHEMA_VERSION,
             hotkey,
             auto_paste: true,
             polish: polisher::PolishConfig::default(),
             history_retention_days: 0,
+            history_max_entries: 0,
+            history_max_audio_bytes: 0,
+            history_trash_retention_days: default_trash_retention_days(),
             language: None,
             stt: SttConfig::default(),
             edit_hotkey,
+            paste_raw_on_modifier: false,
             onboarding_completed: false,
             mic_device: None,
+            channel_mode: crate::audio::ChannelMode::default(),
+            capture_mode: crate::audio::CaptureMode::default(),
+            input_gain_db: 0.0,
+            silence_threshold_db: default_silence_threshold_db(),
+            auto_gain: false,
             meeting_hotkey,
+            repeat_paste_hotkey: None,
             idle_mic_timeout_secs: default_idle_mic_timeout_secs(),
+            idle_unload_minutes: 0,
+            auto_stop_silence_ms: 0,
+            session_mode: SessionMode::default(),
+            queue_dictations: false,
+            history_audio_format: crate::history::AudioFormat::default(),
+            history_audio_bit_depth: crate::history::WavBitDepth::default(),
             record_meeting_audio: false,
+            overlay_auto_hide_ms: default_overlay_auto_hide_ms(),
+            overlay_style: OverlayStyle::default(),
+            overlay_show_reasoning: false,
+            visualizer_gain: default_visualizer_gain(),
+            remove_fillers: false,
+            filler_words: Vec::new(),
+            normalize_numbers: false,
+            smart_capitalize: false,
+            force_cpu: false,
+            whisper_threads: None,
+            whisper_coreml: false,
+            http_proxy: None,
+            cloud_max_retries: default_cloud_max_retries(),
+            verify_target_on_paste: false,
+            edit_scope: EditScope::default(),
+            max_processing_secs: default_max_processing_secs(),
+            max_recording_secs: default_max_recording_secs(),
+            trailing_behavior: TrailingBehavior::default(),
+            hotkey_debounce_ms: default_hotkey_debounce_ms(),
+            hotkey_mode: HotkeyMode::default(),
+            plain_text_only: default_plain_text_only(),
+            insertion_method: InsertionMethod::default(),
+            keystroke_delay_ms: 0,
+            paste_grace_ms: 0,
+            log_level: LogLevel::default(),
+            local_api: crate::local_api::LocalApiConfig::default(),
             data_root: None,
+            extra: serde_json::Map::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// Filler words to strip: the user's list, or the defaults for the STT language.
+    pub fn effective_filler_words(&self) -> Vec<String> {
+        if self.filler_words.is_empty() {
+            crate::fillers::default_filler_words(&self.stt.language)
+        } else {
+            self.filler_words.clone()
+        }
+    }
+    /// Retention and size caps applied to the history store on every insert.
+    pub fn history_limits(&self) -> crate::history::HistoryLimits {
+        crate::history::HistoryLimits {
+            retention_days: self.history_retention_days,
+            max_entries: self.history_max_entries,
+            max_audio_bytes: self.history_max_audio_bytes,
+            trash_retention_days: self.history_trash_retention_days,
         }
     }
 }
@@ -116,6 +572,40 @@ pub fn set_data_root(path: Option<PathBuf>) {
     }
 }
 
+// ── Runtime compute-device override ────────────────────────────────────────
+
+static FORCE_CPU: AtomicBool = AtomicBool::new(false);
+
+/// Mirror `Settings::force_cpu` for the model loaders.  Called once after
+/// `load_settings()` and whenever the user changes the setting; the caller
+/// drops the cached models so the next load picks it up.
+pub fn set_force_cpu(enabled: bool) {
+    FORCE_CPU.store(enabled, Ordering::SeqCst);
+}
+
+/// Whether Whisper and the local LLM must avoid the GPU.
+pub fn force_cpu() -> bool {
+    FORCE_CPU.load(Ordering::SeqCst)
+}
+
+static WHISPER_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Mirror `Settings::whisper_threads` for the decoders, capped at the
+/// machine's available parallelism.  Called once after `load_settings()` and
+/// whenever the user changes the setting.
+pub fn set_whisper_threads(threads: Option<usize>) {
+    let max = std::thread::available_parallelism().map_or(usize::MAX, |n| n.get());
+    WHISPER_THREADS.store(threads.map_or(0, |n| n.min(max)), Ordering::SeqCst);
+}
+
+/// User-chosen Whisper thread count, or `None` for auto.
+pub fn whisper_threads() -> Option<usize> {
+    match WHISPER_THREADS.load(Ordering::SeqCst) {
+        0 => None,
+        n => Some(n),
+    }
+}
+
 /// Root for all mutable data (models, history, audio).
 /// Returns the user-configured `data_root` if set, otherwise `base_dir()`.
 pub fn data_dir() -> PathBuf {
@@ -170,18 +660,121 @@ pub fn settings_path() -> PathBuf {
     config_dir().join("settings.json")
 }
 
+/// Upgrade a raw `settings.json` value to the current schema, then deserialize.
+///
+/// Each step rewrites the JSON in place so renamed/reshaped fields survive;
+/// anything unrecognised is carried through in [`Settings::extra`].
+pub fn migrate_settings(mut value: serde_json::Value) -> Result<Settings, String> {
+    let obj = value
+        .as_object_mut()
+        .ok_or_else(|| "settings root is not a JSON object".to_string())?;
+    let from_version = obj
+        .get("schema_version")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as u32;
+
+    if from_version > SETTINGS_SCHEMA_VERSION {
+        tracing::warn!(
+            "Settings schema v{} is newer than this build (v{}); unknown fields are preserved",
+            from_version, SETTINGS_SCHEMA_VERSION
+        );
+    }
+
+    // v0 → v1: prompt_rules was a flat list before per-language maps, and the
+    // STT language lived under stt.cloud.language.
+    if from_version < 1 {
+        if let Some(polish) = obj.get_mut("polish").and_then(|p| p.as_object_mut()) {
+            if let Some(list @ serde_json::Value::Array(_)) = polish.get("prompt_rules").cloned() {
+                polish.insert("prompt_rules".to_string(), serde_json::json!({ "auto": list }));
+            }
+        }
+        if let Some(stt) = obj.get_mut("stt").and_then(|s| s.as_object_mut()) {
+            let cloud_lang = stt
+                .get("cloud")
+                .and_then(|c| c.get("language"))
+                .and_then(|l| l.as_str())
+                .map(str::to_string);
+            if !stt.contains_key("language") {
+                if let Some(lang) = cloud_lang {
+                    stt.insert("language".to_string(), serde_json::Value::String(lang));
+                }
+            }
+        }
+    }
+
+    // v1 → v2: the single dictionary's entries move into a default named
+    // dictionary; `dictionary` keeps only the master switch.
+    if from_version < 2 {
+        if let Some(polish) = obj.get_mut("polish").and_then(|p| p.as_object_mut()) {
+            let legacy = polish
+                .get_mut("dictionary")
+                .and_then(|d| d.as_object_mut())
+                .and_then(|d| d.remove("entries"));
+            if let Some(entries) = legacy {
+                if !polish.contains_key("dictionaries") {
+                    polish.insert(
+                        "dictionaries".to_string(),
+                        serde_json::json!([{
+                            "name": polisher::DEFAULT_DICTIONARY_NAME,
+                            "enabled": true,
+                            "entries": entries,
+                        }]),
+                    );
+                }
+            }
+        }
+    }
+
+    let mut settings: Settings = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    settings.schema_version = settings.schema_version.max(SETTINGS_SCHEMA_VERSION);
+    Ok(settings)
+}
+
+/// Top-level keys whose values differ between two configurations, for
+/// summarising an import.
+pub fn changed_keys(old: &Settings, new: &Settings) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(new))) =
+        (serde_json::to_value(old), serde_json::to_value(new))
+    else {
+        return Vec::new();
+    };
+    let mut keys: Vec<String> = new
+        .iter()
+        .filter(|(k, v)| old.get(*k) != Some(*v))
+        .map(|(k, _)| k.clone())
+        .chain(old.keys().filter(|k| !new.contains_key(*k)).cloned())
+        .collect();
+    keys.sort();
+    keys
+}
+
+/// Copy an unreadable settings file aside so a parse failure never destroys it.
+fn backup_corrupt_settings(path: &std::path::Path) {
+    let backup = path.with_extension("json.bak");
+    match std::fs::copy(path, &backup) {
+        Ok(_) => tracing::warn!("Backed up unreadable settings to {}", backup.display()),
+        Err(e) => tracing::error!("Failed to back up settings to {}: {}", backup.display(), e),
+    }
+}
+
 /// Load settings from disk. Pure file I/O — no locale detection.
 pub fn load_settings() -> Settings {
     let path = settings_path();
     let mut settings = if path.exists() {
         match std::fs::read_to_string(&path) {
-            Ok(contents) => match serde_json::from_str(&contents) {
-                Ok(s) => s,
-                Err(e) => {
-                    tracing::warn!("Settings file corrupted ({}), using defaults", e);
-                    Settings::default()
+            Ok(contents) => {
+                let migrated = serde_json::from_str::<serde_json::Value>(&contents)
+                    .map_err(|e| e.to_string())
+                    .and_then(migrate_settings);
+                match migrated {
+                    Ok(s) => s,
+                    Err(e) => {
+                        tracing::warn!("Settings file corrupted ({}), using defaults", e);
+                        backup_corrupt_settings(&path);
+                        Settings::default()
+                    }
                 }
-            },
+            }
             Err(e) => {
                 tracing::warn!("Failed to read settings file ({}), using defaults", e);
                 Settings::default()
@@ -191,6 +784,11 @@ pub fn load_settings() -> Settings {
         Settings::default()
     };
     settings.stt.migrate_language();
+    settings.max_recording_secs = clamp_max_recording_secs(settings.max_recording_secs);
+    settings.input_gain_db = clamp_input_gain_db(settings.input_gain_db);
+    settings.keystroke_delay_ms = clamp_keystroke_delay_ms(settings.keystroke_delay_ms);
+    settings.cloud_max_retries = clamp_cloud_max_retries(settings.cloud_max_retries);
+    settings.silence_threshold_db = crate::audio::clamp_silence_threshold_db(settings.silence_threshold_db);
     // Migrate old local polish model names to new ones
     if settings.polish.model == polisher::PolishModel::Unknown {
         settings.polish.model = polisher::recommend_polish_model(settings.language.as_deref());
@@ -198,6 +796,13 @@ pub fn load_settings() -> Settings {
     }
     // Apply the persisted data_root so all subsequent path helpers use it.
     set_data_root(settings.data_root.clone());
+    set_force_cpu(settings.force_cpu);
+    set_whisper_threads(settings.whisper_threads);
+    crate::proxy::set_http_proxy(settings.http_proxy.as_deref());
+    crate::retry::set_max_retries(settings.cloud_max_retries);
+    crate::audio::set_channel_mode(settings.channel_mode.clone());
+    crate::audio::set_capture_mode(settings.capture_mode);
+    crate::audio::set_silence_threshold_db(settings.silence_threshold_db);
     settings
 }
 
@@ -271,6 +876,8 @@ mod tests {
         assert!(s.mic_device.is_none());
         assert!(!s.onboarding_completed);
         assert!(s.language.is_none());
+        assert_eq!(s.session_mode, SessionMode::Single);
+        assert_eq!(s.hotkey_mode, HotkeyMode::Toggle);
     }
 
     /// Config with unknown extra fields (forward compat: newer config opened
@@ -289,6 +896,38 @@ mod tests {
         assert!(!s.auto_paste);
     }
 
+    #[test]
+    fn max_recording_secs_is_clamped() {
+        assert_eq!(clamp_max_recording_secs(0), 5);
+        assert_eq!(clamp_max_recording_secs(300), 300);
+        assert_eq!(clamp_max_recording_secs(86_400), 600);
+        assert_eq!(Settings::default().max_recording_secs, 120);
+    }
+
+    #[test]
+    fn visualizer_gain_rejects_non_finite() {
+        assert_eq!(clamp_visualizer_gain(f32::NAN), 1.0);
+        assert_eq!(clamp_visualizer_gain(f32::INFINITY), 1.0);
+        assert_eq!(clamp_visualizer_gain(50.0), 10.0);
+    }
+
+    #[test]
+    fn input_gain_is_bounded_and_finite() {
+        assert_eq!(clamp_input_gain_db(f32::NAN), 0.0);
+        assert_eq!(clamp_input_gain_db(f32::NEG_INFINITY), 0.0);
+        assert_eq!(clamp_input_gain_db(-60.0), -20.0);
+        assert_eq!(clamp_input_gain_db(12.5), 12.5);
+        assert_eq!(clamp_input_gain_db(100.0), 30.0);
+    }
+
+    #[test]
+    fn trailing_behavior_normalises_end_of_text() {
+        assert_eq!(TrailingBehavior::Trim.apply("Hello.\n "), "Hello.");
+        assert_eq!(TrailingBehavior::AddSpace.apply("Hello. "), "Hello. ");
+        assert_eq!(TrailingBehavior::AddNewline.apply("Hello.  "), "Hello.\n");
+        assert_eq!(TrailingBehavior::AsIs.apply("Hello.\n"), "Hello.\n");
+    }
+
     /// Round-trip: serialize then deserialize should produce equivalent settings.
     #[test]
     fn settings_round_trip() {
@@ -316,6 +955,76 @@ mod tests {
         assert!(result.is_err());
     }
 
+    /// v0 files: flat prompt_rules list and cloud-only STT language are
+    /// lifted into the v1 shape, and the version is stamped.
+    #[test]
+    fn migrate_v0_reshapes_legacy_fields() {
+        let json = serde_json::json!({
+            "hotkey": "Alt+KeyZ",
+            "auto_paste": true,
+            "polish": { "enabled": true, "prompt_rules": [] },
+            "stt": { "cloud": { "language": "ja" } }
+        });
+        let s = migrate_settings(json).unwrap();
+        assert_eq!(s.schema_version, SETTINGS_SCHEMA_VERSION);
+        assert!(s.polish.prompt_rules.contains_key("auto"));
+        assert_eq!(s.stt.language, "ja");
+    }
+
+    /// Unknown fields from a newer build survive a load/save round trip.
+    #[test]
+    fn migrate_preserves_unknown_fields() {
+        let json = serde_json::json!({
+            "schema_version": 99,
+            "hotkey": "Alt+KeyZ",
+            "auto_paste": false,
+            "some_future_field": { "nested": 1 }
+        });
+        let s = migrate_settings(json).unwrap();
+        assert_eq!(s.schema_version, 99);
+        let out = serde_json::to_value(&s).unwrap();
+        assert_eq!(out["some_future_field"]["nested"], 1);
+    }
+
+    /// v1 files: the single dictionary becomes the default named one.
+    #[test]
+    fn migrate_v1_moves_dictionary_into_named_list() {
+        let json = serde_json::json!({
+            "schema_version": 1,
+            "hotkey": "Alt+KeyZ",
+            "auto_paste": true,
+            "polish": {
+                "dictionary": {
+                    "enabled": false,
+                    "entries": [{ "term": "Sumi", "enabled": true }]
+                }
+            }
+        });
+        let s = migrate_settings(json).unwrap();
+        assert!(!s.polish.dictionary.enabled);
+        assert_eq!(s.polish.dictionaries.len(), 1);
+        assert_eq!(s.polish.dictionaries[0].name, polisher::DEFAULT_DICTIONARY_NAME);
+        assert_eq!(s.polish.dictionaries[0].entries[0].term, "Sumi");
+        let out = serde_json::to_value(&s).unwrap();
+        assert!(out["polish"]["dictionary"].get("entries").is_none());
+    }
+
+    #[test]
+    fn migrate_rejects_non_object() {
+        assert!(migrate_settings(serde_json::json!([1, 2, 3])).is_err());
+    }
+
+    #[test]
+    fn changed_keys_lists_differing_top_level_fields() {
+        let old = Settings::default();
+        assert!(changed_keys(&old, &old.clone()).is_empty());
+
+        let mut new = old.clone();
+        new.auto_paste = !old.auto_paste;
+        new.polish.reasoning = !old.polish.reasoning;
+        assert_eq!(changed_keys(&old, &new), vec!["auto_paste", "polish"]);
+    }
+
     /// Corrupt JSON falls back to defaults in load_settings.
     /// (We can't call load_settings directly since it reads from disk,
     /// but we can verify the serde fallback behavior.)
@@ -1,7 +1,10 @@
 use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager};
+use unicode_segmentation::UnicodeSegmentation;
 
+use crate::error::SumiError;
 use crate::polisher::truncate_for_error;
 use crate::settings::models_dir;
 use crate::whisper_models::WhisperModel;
@@ -25,6 +28,20 @@ pub enum LocalSttEngine {
     Qwen3Asr,
 }
 
+/// How hard local Whisper searches for the best transcript.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WhisperQuality {
+    /// Greedy decoding; the fastest.
+    #[default]
+    Fast,
+    /// Beam search.
+    Balanced,
+    /// Beam search with the full temperature fallback ladder, retrying
+    /// segments that look like hallucinations or repetition loops.
+    Accurate,
+}
+
 // ── Qwen3-ASR model variants ──────────────────────────────────────────────────
 
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
@@ -117,6 +134,23 @@ pub fn is_qwen3_asr_downloaded(model: &Qwen3AsrModel) -> bool {
     model.required_files().iter().all(|f| dir.join(f).exists())
 }
 
+/// Check if transcription can run with the configured engine: the selected
+/// local model is on disk, or the cloud provider has an API key (and, for
+/// Azure and custom providers, an endpoint).  `config.cloud.api_key` must
+/// already be filled in from the credential store.
+pub fn is_stt_ready(config: &SttConfig) -> bool {
+    match config.mode {
+        SttMode::Cloud => {
+            !config.cloud.api_key.is_empty()
+                && (!config.cloud.provider.requires_endpoint() || !config.cloud.endpoint.trim().is_empty())
+        }
+        SttMode::Local => match config.local_engine {
+            LocalSttEngine::Whisper => config.whisper_model.local_path().exists(),
+            LocalSttEngine::Qwen3Asr => is_qwen3_asr_downloaded(&config.qwen3_asr_model),
+        },
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct Qwen3AsrModelInfo {
     pub id: Qwen3AsrModel,
@@ -162,6 +196,15 @@ pub enum SttProvider {
 }
 
 impl SttProvider {
+    /// Every provider, for enumerating stored credentials.
+    pub const ALL: [SttProvider; 5] = [
+        Self::Deepgram,
+        Self::Groq,
+        Self::OpenAi,
+        Self::Azure,
+        Self::Custom,
+    ];
+
     pub fn as_key(&self) -> &'static str {
         match self {
             Self::Deepgram => "stt_deepgram",
@@ -197,6 +240,18 @@ impl SttProvider {
         matches!(self, Self::Groq | Self::OpenAi | Self::Custom)
     }
 
+    /// Whether the provider takes WAV at the microphone's own sample rate.
+    /// Azure's short-audio REST API only accepts 16 kHz PCM.
+    pub fn accepts_native_rate(&self) -> bool {
+        !matches!(self, Self::Azure)
+    }
+
+    /// Whether the provider accepts FLAC uploads.  Azure's short-audio REST
+    /// API only takes WAV and OGG/Opus.
+    pub fn accepts_flac(&self) -> bool {
+        !matches!(self, Self::Azure)
+    }
+
     /// Whether the provider requires an endpoint URL from the user.
     pub fn requires_endpoint(&self) -> bool {
         matches!(self, Self::Azure | Self::Custom)
@@ -217,6 +272,27 @@ pub struct SttCloudConfig {
     /// Empty string means auto-detect (provider-dependent).
     #[serde(default = "default_stt_language")]
     pub language: String,
+    /// Request `verbose_json` from OpenAI-compatible providers so segment-level
+    /// `avg_logprob` / `no_speech_prob` can be used to score the transcript.
+    /// For Azure this requests `format=detailed` and uses the `NBest` confidence.
+    #[serde(default)]
+    pub verbose: bool,
+    /// Path to the transcript in a `Custom` provider's JSON response, e.g.
+    /// `results.transcript` or `segments[0].text`.  `None` reads `text`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_text_path: Option<String>,
+    /// Upload dictation at the device sample rate instead of downsampling to
+    /// 16 kHz first.  Ignored by providers that only accept 16 kHz.
+    #[serde(default)]
+    pub cloud_native_rate: bool,
+    /// Upload lossless FLAC instead of WAV, roughly halving the payload.
+    /// Off by default since `Custom` endpoints may not decode FLAC.
+    #[serde(default)]
+    pub compress_upload: bool,
+    /// Extra text sent as the `prompt` field to OpenAI-compatible providers
+    /// to bias spelling and vocabulary.  Dictionary terms are appended.
+    #[serde(default)]
+    pub stt_prompt: String,
 }
 
 fn default_stt_model_id() -> String {
@@ -235,10 +311,30 @@ impl Default for SttCloudConfig {
             endpoint: String::new(),
             model_id: default_stt_model_id(),
             language: default_stt_language(),
+            verbose: false,
+            response_text_path: None,
+            cloud_native_rate: false,
+            compress_upload: false,
+            stt_prompt: String::new(),
         }
     }
 }
 
+impl SttCloudConfig {
+    /// Switch to `provider` with its default model.  The endpoint is a URL
+    /// for `Custom` but a region for Azure, and the response path only
+    /// applies to `Custom`, so neither carries over to another provider.
+    pub fn switch_provider(&mut self, provider: SttProvider) {
+        if self.provider == provider {
+            return;
+        }
+        self.endpoint.clear();
+        self.response_text_path = None;
+        self.model_id = provider.default_model().to_string();
+        self.provider = provider;
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SttConfig {
     #[serde(default)]
@@ -257,6 +353,17 @@ pub struct SttConfig {
     /// Migrated from `cloud.language` for older settings files.
     #[serde(default = "default_stt_language")]
     pub language: String,
+    /// Local Whisper splits recordings longer than this into overlapping
+    /// 30 s windows instead of one `full()` call.  0 = never chunk.
+    #[serde(default = "default_chunk_threshold_secs")]
+    pub chunk_threshold_secs: u32,
+    /// Local Whisper decoding effort; slower settings are more accurate.
+    #[serde(default)]
+    pub whisper_quality: WhisperQuality,
+}
+
+fn default_chunk_threshold_secs() -> u32 {
+    60
 }
 
 impl Default for SttConfig {
@@ -268,6 +375,8 @@ impl Default for SttConfig {
             local_engine: LocalSttEngine::default(),
             qwen3_asr_model: Qwen3AsrModel::default(),
             language: default_stt_language(),
+            chunk_threshold_secs: default_chunk_threshold_secs(),
+            whisper_quality: WhisperQuality::default(),
         }
     }
 }
@@ -310,46 +419,376 @@ pub fn locale_to_stt_language(locale: &str) -> String {
     // Extract the language part (before _ or -)
     let lang = base.split(['_', '-']).next().unwrap_or(base);
 
-    const VALID: &[&str] = &[
-        "af", "am", "ar", "as", "az", "ba", "be", "bg", "bn", "bo", "br", "bs",
-        "ca", "cs", "cy", "da", "de", "el", "en", "es", "et", "eu", "fa", "fi",
-        "fo", "fr", "gl", "gu", "ha", "haw", "he", "hi", "hr", "ht", "hu", "hy",
-        "id", "is", "it", "ja", "jw", "ka", "kk", "km", "kn", "ko", "lb", "ln",
-        "lo", "lt", "lv", "mg", "mi", "mk", "ml", "mn", "mr", "ms", "mt", "my",
-        "ne", "nl", "nn", "no", "oc", "pa", "pl", "ps", "pt", "ro", "ru", "sa",
-        "sd", "si", "sk", "sl", "sn", "so", "sq", "sr", "su", "sv", "sw", "ta",
-        "te", "tg", "th", "tk", "tl", "tr", "tt", "uk", "ur", "uz", "vi", "yi",
-        "yo", "yue",
-    ];
-
-    if VALID.contains(&lang) {
+    // Whisper's languages, as offered for the OpenAI-compatible providers.
+    let known = crate::languages::supported_languages(&SttProvider::OpenAi)
+        .iter()
+        .any(|option| option.code == lang);
+    if known {
         lang.to_string()
     } else {
         "auto".to_string()
     }
 }
 
+/// Whether `model_id` belongs to OpenAI's GPT-4o transcription family
+/// (`gpt-4o-transcribe`, `gpt-4o-mini-transcribe`, …).
+fn is_gpt4o_transcribe_model(model_id: &str) -> bool {
+    model_id.starts_with("gpt-4o") && model_id.contains("transcribe")
+}
+
+/// Whisper only conditions on the last 224 prompt tokens.
+const CLOUD_PROMPT_MAX_TOKENS: usize = 224;
+
+/// Rough Whisper token count: about four ASCII characters per token, and
+/// up to two tokens for every other character (CJK, accented letters).
+fn estimate_prompt_tokens(text: &str) -> usize {
+    PromptTokens::default().with(text).total()
+}
+
+/// Running [`estimate_prompt_tokens`] count of a prompt built piece by piece.
+#[derive(Clone, Copy, Default)]
+struct PromptTokens {
+    ascii: usize,
+    other: usize,
+}
+
+impl PromptTokens {
+    fn with(mut self, text: &str) -> Self {
+        for ch in text.chars() {
+            if ch.is_ascii() {
+                self.ascii += 1;
+            } else {
+                self.other += 1;
+            }
+        }
+        self
+    }
+
+    fn total(self) -> usize {
+        self.ascii.div_ceil(4) + self.other * 2
+    }
+}
+
+/// The `prompt` biasing text for OpenAI-compatible providers, mirroring
+/// the local Whisper `initial_prompt`: the user's `stt_prompt` first, then
+/// dictionary terms at the tail where bias is strongest.  Capped at
+/// [`CLOUD_PROMPT_MAX_TOKENS`]: an over-long `stt_prompt` is cut at a
+/// grapheme boundary, and terms that do not fit are dropped.
+/// `None` for Deepgram and Azure, which have no prompt field, or when empty.
+pub fn cloud_bias_prompt(stt_cloud: &SttCloudConfig, dictionary_terms: &[String]) -> Option<String> {
+    if matches!(stt_cloud.provider, SttProvider::Deepgram | SttProvider::Azure) {
+        return None;
+    }
+
+    let mut prompt = String::new();
+    let mut tokens = PromptTokens::default();
+    for grapheme in stt_cloud.stt_prompt.trim().graphemes(true) {
+        let next = tokens.with(grapheme);
+        if next.total() > CLOUD_PROMPT_MAX_TOKENS {
+            break;
+        }
+        prompt.push_str(grapheme);
+        tokens = next;
+    }
+
+    let mut terms_added = 0;
+    for term in dictionary_terms.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        let sep = if prompt.is_empty() { "" } else if terms_added == 0 { " " } else { ", " };
+        let next = tokens.with(sep).with(term);
+        if next.total() > CLOUD_PROMPT_MAX_TOKENS {
+            break;
+        }
+        prompt.push_str(sep);
+        prompt.push_str(term);
+        tokens = next;
+        terms_added += 1;
+    }
+
+    if prompt.is_empty() { None } else { Some(prompt) }
+}
+
+/// The prompt for a chunk of a long transcription (meeting, file import):
+/// the text transcribed so far for continuity, then the bias prompt at the
+/// tail, which Whisper keeps when it drops the oldest prompt tokens.
+pub(crate) fn chunk_prompt(bias: Option<&str>, prev_text: &str) -> Option<String> {
+    match (prev_text.trim(), bias) {
+        ("", None) => None,
+        ("", Some(bias)) => Some(bias.to_string()),
+        (prev, None) => Some(prev.to_string()),
+        (prev, Some(bias)) => Some(format!("{prev} {bias}")),
+    }
+}
+
+/// Text fields of the OpenAI-compatible multipart request (everything except
+/// the audio file itself).
+///
+/// The `language` hint is model-aware: whisper-style models benefit from it,
+/// but GPT-4o transcribe models handle code-switching better without it, so
+/// it is omitted for them.  Those models also reject `verbose_json`.
+fn openai_form_fields(
+    model_id: &str,
+    language: &str,
+    prompt: Option<&str>,
+    verbose: bool,
+) -> Vec<(&'static str, String)> {
+    let is_4o = is_gpt4o_transcribe_model(model_id);
+    let response_format = if verbose && !is_4o { "verbose_json" } else { "json" };
+    let mut fields = vec![
+        ("model", model_id.to_string()),
+        ("response_format", response_format.to_string()),
+    ];
+
+    if !language.is_empty() && !is_4o {
+        let iso_lang = language.split('-').next().unwrap_or("");
+        if !iso_lang.is_empty() {
+            fields.push(("language", iso_lang.to_string()));
+        }
+    }
+
+    if let Some(p) = prompt {
+        if !p.is_empty() {
+            fields.push(("prompt", p.to_string()));
+        }
+    }
+    fields
+}
+
+/// Segment-averaged `no_speech_prob` above which a verbose transcript is
+/// treated as background noise and rejected with `no_speech`.
+const VERBOSE_NO_SPEECH_THRESHOLD: f64 = 0.6;
+
+/// Whether a verbose transcript with this averaged `no_speech_prob` is noise.
+fn is_verbose_no_speech(no_speech_prob: f64) -> bool {
+    no_speech_prob > VERBOSE_NO_SPEECH_THRESHOLD
+}
+
+/// Cloud STT output with optional quality metadata.
+#[derive(Debug, Clone, Default)]
+pub struct CloudSttResult {
+    pub text: String,
+    /// Overall confidence in `[0, 1]`, derived from the duration-weighted mean
+    /// of `exp(avg_logprob)` across `verbose_json` segments.  `None` when the
+    /// provider did not return segment data.
+    pub confidence: Option<f64>,
+    /// Per-word timings of the raw transcript.  Only Deepgram returns them.
+    pub word_timings: Option<Vec<WordTiming>>,
+}
+
+/// When one transcript word was spoken, in seconds from the start of the
+/// trimmed audio kept in history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WordTiming {
+    pub word: String,
+    pub start_secs: f64,
+    pub end_secs: f64,
+}
+
+/// Read the `words` array of a Deepgram alternative.  `smart_format` adds a
+/// `punctuated_word` that matches the transcript, preferred over the bare
+/// lowercase `word`.  Returns `None` when the response has no words.
+fn deepgram_word_timings(alternative: &serde_json::Value) -> Option<Vec<WordTiming>> {
+    let words: Vec<WordTiming> = alternative["words"]
+        .as_array()?
+        .iter()
+        .filter_map(|w| {
+            let word = w["punctuated_word"].as_str().or_else(|| w["word"].as_str())?;
+            Some(WordTiming {
+                word: word.to_string(),
+                start_secs: w["start"].as_f64()?,
+                end_secs: w["end"].as_f64()?,
+            })
+        })
+        .collect();
+    (!words.is_empty()).then_some(words)
+}
+
+/// Summarise `verbose_json` segments into `(confidence, no_speech_prob)`.
+///
+/// Both values are weighted by segment duration so a long confident segment
+/// is not outvoted by a short noisy one.  Returns `None` when the response
+/// carries no usable segments.
+fn summarize_verbose_segments(json: &serde_json::Value) -> Option<(f64, f64)> {
+    let segments = json["segments"].as_array()?;
+    let mut total_weight = 0.0;
+    let mut confidence_sum = 0.0;
+    let mut no_speech_sum = 0.0;
+    for seg in segments {
+        let Some(avg_logprob) = seg["avg_logprob"].as_f64() else { continue };
+        let no_speech = seg["no_speech_prob"].as_f64().unwrap_or(0.0);
+        let start = seg["start"].as_f64().unwrap_or(0.0);
+        let end = seg["end"].as_f64().unwrap_or(start);
+        // Zero-length segments still count, just minimally.
+        let weight = (end - start).max(0.01);
+        total_weight += weight;
+        confidence_sum += avg_logprob.exp().clamp(0.0, 1.0) * weight;
+        no_speech_sum += no_speech.clamp(0.0, 1.0) * weight;
+    }
+    if total_weight <= 0.0 {
+        return None;
+    }
+    Some((confidence_sum / total_weight, no_speech_sum / total_weight))
+}
+
+/// Resolve a dot/bracket path (`results.transcript`, `segments[0].text`)
+/// against `json` and return the string it points at.
+fn extract_text_at_path<'a>(json: &'a serde_json::Value, path: &str) -> Result<&'a str, String> {
+    let mut current = json;
+    for part in path.split('.') {
+        let (key, mut indices) = match part.find('[') {
+            Some(i) => (&part[..i], &part[i..]),
+            None => (part, ""),
+        };
+        if !key.is_empty() {
+            current = current
+                .get(key)
+                .ok_or_else(|| format!("Response text path \"{}\": key \"{}\" not found", path, key))?;
+        } else if indices.is_empty() {
+            return Err(format!("Response text path \"{}\" has an empty segment", path));
+        }
+        while !indices.is_empty() {
+            let close = indices
+                .find(']')
+                .filter(|_| indices.starts_with('['))
+                .ok_or_else(|| format!("Response text path \"{}\" has a malformed index", path))?;
+            let index: usize = indices[1..close]
+                .trim()
+                .parse()
+                .map_err(|_| format!("Response text path \"{}\" has a non-numeric index", path))?;
+            current = current
+                .get(index)
+                .ok_or_else(|| format!("Response text path \"{}\": index {} out of range", path, index))?;
+            indices = &indices[close + 1..];
+        }
+    }
+    current
+        .as_str()
+        .ok_or_else(|| format!("Response text path \"{}\" does not point to a string", path))
+}
+
+/// Largest sample count whose 16-bit PCM data still fits the u32 RIFF sizes.
+const MAX_WAV_SAMPLES: usize = (u32::MAX as usize - 36) / 2;
+const _: () = assert!(MAX_WAV_SAMPLES * 2 + 36 <= u32::MAX as usize);
+
+/// Encode mono f32 samples at `sample_rate` as an in-memory 16-bit PCM WAV file.
+///
+/// RIFF sizes are u32; longer buffers are rejected instead of letting the
+/// header wrap.  16-bit mono data is always even-length, so no pad byte.
+fn encode_wav(samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, SumiError> {
+    let num_samples = samples.len();
+    if num_samples > MAX_WAV_SAMPLES {
+        return Err(SumiError::Other(format!(
+            "Recording is too long to upload ({:.0} minutes)",
+            num_samples as f64 / sample_rate as f64 / 60.0
+        )));
+    }
+    let data_size = (num_samples * 2) as u32;
+    let file_size = 36 + data_size;
+    let mut buf = Vec::with_capacity(44 + data_size as usize);
+
+    buf.extend_from_slice(b"RIFF");
+    buf.extend_from_slice(&file_size.to_le_bytes());
+    buf.extend_from_slice(b"WAVE");
+    buf.extend_from_slice(b"fmt ");
+    buf.extend_from_slice(&16u32.to_le_bytes());
+    buf.extend_from_slice(&1u16.to_le_bytes());
+    buf.extend_from_slice(&1u16.to_le_bytes());
+    buf.extend_from_slice(&sample_rate.to_le_bytes());
+    buf.extend_from_slice(&(sample_rate * 2).to_le_bytes()); // byte rate, 16-bit mono
+    buf.extend_from_slice(&2u16.to_le_bytes());
+    buf.extend_from_slice(&16u16.to_le_bytes());
+    buf.extend_from_slice(b"data");
+    buf.extend_from_slice(&data_size.to_le_bytes());
+    for &s in samples {
+        buf.extend_from_slice(&to_pcm16(s).to_le_bytes());
+    }
+    Ok(buf)
+}
+
+fn to_pcm16(s: f32) -> i16 {
+    (s.clamp(-1.0, 1.0) * 32767.0) as i16
+}
+
+/// Encode mono f32 samples at `sample_rate` as an in-memory 16-bit FLAC file.
+/// Lossless against the 16-bit WAV that [`encode_wav`] would produce.
+fn encode_flac(samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, SumiError> {
+    use flacenc::component::BitRepr;
+    use flacenc::error::Verify;
+
+    let pcm: Vec<i32> = samples.iter().map(|&s| to_pcm16(s) as i32).collect();
+    let config = flacenc::config::Encoder::default()
+        .into_verified()
+        .map_err(|(_, e)| SumiError::Other(format!("Invalid FLAC encoder config: {:?}", e)))?;
+    let source = flacenc::source::MemSource::from_samples(&pcm, 1, 16, sample_rate as usize);
+    let stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+        .map_err(|e| SumiError::Other(format!("FLAC encoding failed: {:?}", e)))?;
+    let mut sink = flacenc::bitsink::ByteSink::new();
+    stream
+        .write(&mut sink)
+        .map_err(|e| SumiError::Other(format!("FLAC encoding failed: {:?}", e)))?;
+    Ok(sink.as_slice().to_vec())
+}
+
+/// Request body for a cloud STT upload.
+struct UploadAudio {
+    bytes: Vec<u8>,
+    mime: &'static str,
+    file_name: &'static str,
+}
+
+/// Encode `samples` for upload: FLAC when `compress_upload` is on and the
+/// provider accepts it, otherwise WAV.
+fn encode_upload(stt_cloud: &SttCloudConfig, samples: &[f32], sample_rate: u32) -> Result<UploadAudio, SumiError> {
+    if stt_cloud.compress_upload && stt_cloud.provider.accepts_flac() {
+        Ok(UploadAudio { bytes: encode_flac(samples, sample_rate)?, mime: "audio/flac", file_name: "audio.flac" })
+    } else {
+        Ok(UploadAudio { bytes: encode_wav(samples, sample_rate)?, mime: "audio/wav", file_name: "audio.wav" })
+    }
+}
+
 /// Transcribe audio via a cloud STT API.
 ///
 /// `prompt`: optional context text (e.g. previous transcript) for Groq/OpenAI
 /// compatible APIs. Ignored by Deepgram/Azure.
-pub fn run_cloud_stt(stt_cloud: &SttCloudConfig, samples_16k: &[f32], client: &reqwest::blocking::Client, prompt: Option<&str>) -> Result<String, String> {
+pub fn run_cloud_stt(stt_cloud: &SttCloudConfig, samples_16k: &[f32], client: &reqwest::blocking::Client, prompt: Option<&str>) -> Result<String, SumiError> {
+    run_cloud_stt_detailed(stt_cloud, samples_16k, 16000, client, prompt, None).map(|r| r.text)
+}
+
+/// Pick the highest-confidence `NBest` entry of an Azure `format=detailed`
+/// response, returning its `Display` text and confidence.
+fn best_azure_nbest(json: &serde_json::Value) -> Option<(String, Option<f64>)> {
+    let best = json["NBest"].as_array()?.iter().max_by(|a, b| {
+        let ca = a["Confidence"].as_f64().unwrap_or(0.0);
+        let cb = b["Confidence"].as_f64().unwrap_or(0.0);
+        ca.total_cmp(&cb)
+    })?;
+    let text = best["Display"].as_str()?.trim().to_string();
+    let confidence = best["Confidence"].as_f64().map(|c| c.clamp(0.0, 1.0));
+    Some((text, confidence))
+}
+
+/// Same as [`run_cloud_stt`], but also returns the confidence score when
+/// `stt_cloud.verbose` is enabled for an OpenAI-compatible provider or Azure,
+/// and word timings from Deepgram.
+/// `samples` are mono at `sample_rate`; callers only pass a rate other than
+/// 16 kHz when the provider [accepts it](SttProvider::accepts_native_rate).
+/// The upload is abandoned as soon as `cancel` fires.
+pub fn run_cloud_stt_detailed(stt_cloud: &SttCloudConfig, samples: &[f32], sample_rate: u32, client: &reqwest::blocking::Client, prompt: Option<&str>, cancel: Option<&AtomicBool>) -> Result<CloudSttResult, SumiError> {
     if stt_cloud.api_key.is_empty() {
-        return Err("Cloud STT API key is not set. Please configure it in Settings.".to_string());
+        return Err(SumiError::MissingApiKey("Cloud STT API key is not set. Please configure it in Settings.".to_string()));
     }
 
     let endpoint = if stt_cloud.provider == SttProvider::Azure {
         let region = stt_cloud.endpoint.trim();
         if region.is_empty() {
-            return Err("Azure region is not configured. Please set it in Settings.".to_string());
+            return Err(SumiError::Config("Azure region is not configured. Please set it in Settings.".to_string()));
         }
         // Azure region only allows lowercase letters, digits, and hyphens (e.g. "westus", "east-us-2").
         // Must not begin/end with a dash or contain consecutive dashes (RFC 952 DNS label rules).
         if !region.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
-            return Err("Azure region contains invalid characters. Use only lowercase letters, digits, and hyphens (e.g. \"westus\", \"east-us-2\").".to_string());
+            return Err(SumiError::Config("Azure region contains invalid characters. Use only lowercase letters, digits, and hyphens (e.g. \"westus\", \"east-us-2\").".to_string()));
         }
         if region.starts_with('-') || region.ends_with('-') || region.contains("--") {
-            return Err("Azure region must not begin or end with a hyphen, or contain consecutive hyphens.".to_string());
+            return Err(SumiError::Config("Azure region must not begin or end with a hyphen, or contain consecutive hyphens.".to_string()));
         }
         format!(
             "https://{}.stt.speech.microsoft.com/speech/recognition/conversation/cognitiveservices/v1",
@@ -357,15 +796,15 @@ pub fn run_cloud_stt(stt_cloud: &SttCloudConfig, samples_16k: &[f32], client: &r
         )
     } else if stt_cloud.provider == SttProvider::Custom {
         if stt_cloud.endpoint.is_empty() {
-            return Err("Cloud STT endpoint is not configured.".to_string());
+            return Err(SumiError::Config("Cloud STT endpoint is not configured.".to_string()));
         }
-        crate::polisher::validate_custom_endpoint(&stt_cloud.endpoint)?;
+        crate::polisher::validate_custom_endpoint(&stt_cloud.endpoint).map_err(SumiError::Config)?;
         stt_cloud.endpoint.clone()
     } else {
         let default_ep = stt_cloud.provider.default_endpoint();
         if default_ep.is_empty() {
             if !stt_cloud.endpoint.is_empty() {
-                crate::polisher::validate_custom_endpoint(&stt_cloud.endpoint)?;
+                crate::polisher::validate_custom_endpoint(&stt_cloud.endpoint).map_err(SumiError::Config)?;
             }
             stt_cloud.endpoint.clone()
         } else {
@@ -373,138 +812,132 @@ pub fn run_cloud_stt(stt_cloud: &SttCloudConfig, samples_16k: &[f32], client: &r
         }
     };
     if endpoint.is_empty() {
-        return Err("Cloud STT endpoint is not configured.".to_string());
+        return Err(SumiError::Config("Cloud STT endpoint is not configured.".to_string()));
     }
 
     let model_id = {
         let default = stt_cloud.provider.default_model();
         if default.is_empty() {
             stt_cloud.model_id.clone()
+        } else if stt_cloud.provider == SttProvider::OpenAi && is_gpt4o_transcribe_model(&stt_cloud.model_id) {
+            // OpenAI offers the 4o transcription family alongside whisper-1.
+            stt_cloud.model_id.clone()
         } else {
             default.to_string()
         }
     };
 
-    // Encode f32 samples → 16-bit PCM WAV in-memory
-    let wav_bytes = {
-        let num_samples = samples_16k.len();
-        let data_size = (num_samples * 2) as u32;
-        let file_size = 36 + data_size;
-        let mut buf = Vec::with_capacity(44 + data_size as usize);
-
-        buf.extend_from_slice(b"RIFF");
-        buf.extend_from_slice(&file_size.to_le_bytes());
-        buf.extend_from_slice(b"WAVE");
-        buf.extend_from_slice(b"fmt ");
-        buf.extend_from_slice(&16u32.to_le_bytes());
-        buf.extend_from_slice(&1u16.to_le_bytes());
-        buf.extend_from_slice(&1u16.to_le_bytes());
-        buf.extend_from_slice(&16000u32.to_le_bytes());
-        buf.extend_from_slice(&32000u32.to_le_bytes());
-        buf.extend_from_slice(&2u16.to_le_bytes());
-        buf.extend_from_slice(&16u16.to_le_bytes());
-        buf.extend_from_slice(b"data");
-        buf.extend_from_slice(&data_size.to_le_bytes());
-        for &s in samples_16k {
-            let clamped = s.clamp(-1.0, 1.0);
-            let val = (clamped * 32767.0) as i16;
-            buf.extend_from_slice(&val.to_le_bytes());
-        }
-        buf
-    };
+    let audio = encode_upload(stt_cloud, samples, sample_rate)?;
 
-    let language = if stt_cloud.language == "auto" { "" } else { &stt_cloud.language };
+    let language = match crate::languages::normalize_language_code(&stt_cloud.language) {
+        Some(code) if code == "auto" => String::new(),
+        Some(code) => code,
+        None => {
+            tracing::warn!("Unsupported STT language {:?}, falling back to auto-detect", stt_cloud.language);
+            String::new()
+        }
+    };
+    let language = language.as_str();
 
-    let resp = match stt_cloud.provider {
+    // The request closures are moved onto the helper thread that
+    // `send_cancellable` abandons on cancel, so they own what they send.
+    let client = client.clone();
+    let api_key = stt_cloud.api_key.clone();
+    let UploadAudio { bytes, mime, file_name } = audio;
+    let (status, headers, body) = match stt_cloud.provider {
         SttProvider::Deepgram => {
             let lang_param = if language.is_empty() { "multi".to_string() } else { language.to_string() };
-            client
-                .post(endpoint)
-                .query(&[
-                    ("model", model_id.as_str()),
-                    ("language", lang_param.as_str()),
-                    ("punctuate", "true"),
-                    ("smart_format", "true"),
-                ])
-                .header("Authorization", format!("Token {}", stt_cloud.api_key))
-                .header("Content-Type", "audio/wav")
-                .body(wav_bytes)
-                .send()
-                .map_err(|e| format!("Cloud STT request failed: {}", e))?
+            let query = [
+                ("model", model_id.clone()),
+                ("language", lang_param),
+                ("punctuate", "true".to_string()),
+                ("smart_format", "true".to_string()),
+            ];
+            crate::polisher::send_cancellable("Cloud STT", move || {
+                client
+                    .post(&endpoint)
+                    .query(&query)
+                    .header("Authorization", format!("Token {}", api_key))
+                    .header("Content-Type", mime)
+                    .body(bytes.clone())
+            }, cancel)?
         }
         SttProvider::Azure => {
-            let lang_param = if language.is_empty() { "en-US".to_string() } else { language.to_string() };
-            let url = format!("{}?language={}&format=simple", endpoint, lang_param);
-            client
-                .post(&url)
-                .header("Ocp-Apim-Subscription-Key", &stt_cloud.api_key)
-                .header("Content-Type", "audio/wav; codecs=audio/pcm; samplerate=16000")
-                .header("Accept", "application/json")
-                .body(wav_bytes)
-                .send()
-                .map_err(|e| format!("Cloud STT request failed: {}", e))?
+            let lang_param = if language.is_empty() { "en-US".to_string() } else { crate::languages::azure_locale(language) };
+            let format = if stt_cloud.verbose { "detailed" } else { "simple" };
+            let url = format!("{}?language={}&format={}", endpoint, lang_param, format);
+            crate::polisher::send_cancellable("Cloud STT", move || {
+                client
+                    .post(&url)
+                    .header("Ocp-Apim-Subscription-Key", &api_key)
+                    .header("Content-Type", "audio/wav; codecs=audio/pcm; samplerate=16000")
+                    .header("Accept", "application/json")
+                    .body(bytes.clone())
+            }, cancel)?
         }
         _ => {
-            let file_part = reqwest::blocking::multipart::Part::bytes(wav_bytes)
-                .file_name("audio.wav")
-                .mime_str("audio/wav")
-                .map_err(|e| format!("Failed to create multipart part: {}", e))?;
-
-            let mut form = reqwest::blocking::multipart::Form::new()
-                .part("file", file_part)
-                .text("model", model_id)
-                .text("response_format", "json");
-
-            if !language.is_empty() {
-                let iso_lang = language.split('-').next().unwrap_or("").to_string();
-                if !iso_lang.is_empty() {
-                    form = form.text("language", iso_lang);
-                }
-            }
+            // Each attempt rebuilds the form: a multipart body cannot be replayed.
+            let fields = openai_form_fields(&model_id, language, prompt, stt_cloud.verbose);
+            // Parsed once here: a `Part` cannot be rebuilt fallibly per attempt.
+            let content_type = reqwest::header::HeaderValue::from_str(mime)
+                .map_err(|e| SumiError::Other(format!("Invalid upload MIME type {:?}: {}", mime, e)))?;
 
-            if let Some(p) = prompt {
-                if !p.is_empty() {
-                    form = form.text("prompt", p.to_string());
+            crate::polisher::send_cancellable("Cloud STT", move || {
+                let part_headers =
+                    reqwest::header::HeaderMap::from_iter([(reqwest::header::CONTENT_TYPE, content_type.clone())]);
+                let file_part = reqwest::blocking::multipart::Part::bytes(bytes.clone())
+                    .file_name(file_name)
+                    .headers(part_headers);
+                let mut form = reqwest::blocking::multipart::Form::new().part("file", file_part);
+                for (name, value) in fields.clone() {
+                    form = form.text(name, value);
                 }
-            }
-
-            client
-                .post(&endpoint)
-                .header("Authorization", format!("Bearer {}", stt_cloud.api_key))
-                .multipart(form)
-                .send()
-                .map_err(|e| format!("Cloud STT request failed: {}", e))?
+                client
+                    .post(&endpoint)
+                    .header("Authorization", format!("Bearer {}", api_key))
+                    .multipart(form)
+            }, cancel)?
         }
     };
 
-    let status = resp.status();
-    let body = resp
-        .text()
-        .map_err(|e| format!("Failed to read Cloud STT response: {}", e))?;
-
     if !status.is_success() {
         let preview = truncate_for_error(&body, 200);
-        return Err(format!("Cloud STT returned HTTP {}: {}", status, preview));
+        return Err(SumiError::from_status("Cloud STT", status, &headers, preview));
     }
 
     let json: serde_json::Value = serde_json::from_str(&body)
         .map_err(|e| {
             let preview = truncate_for_error(&body, 200);
-            format!("Failed to parse Cloud STT response: {} — body: {}", e, preview)
+            SumiError::Provider(format!("Failed to parse Cloud STT response: {} — body: {}", e, preview))
         })?;
 
+    let mut confidence = None;
+    let mut word_timings = None;
     let text = match stt_cloud.provider {
         SttProvider::Deepgram => {
-            json["results"]["channels"]
+            let alternative = json["results"]["channels"]
                 .as_array()
                 .and_then(|ch| ch.first())
                 .and_then(|c| c["alternatives"].as_array())
-                .and_then(|alts| alts.first())
+                .and_then(|alts| alts.first());
+            word_timings = alternative.and_then(deepgram_word_timings);
+            alternative
                 .and_then(|a| a["transcript"].as_str())
                 .unwrap_or("")
                 .trim()
                 .to_string()
         }
+        SttProvider::Azure if stt_cloud.verbose => match best_azure_nbest(&json) {
+            Some((text, conf)) => {
+                if let Some(conf) = conf {
+                    tracing::info!("Cloud STT detailed (Azure): confidence={:.3}", conf);
+                }
+                confidence = conf;
+                text
+            }
+            // NoMatch / InitialSilenceTimeout responses carry no NBest.
+            None => String::new(),
+        },
         SttProvider::Azure => {
             json["DisplayText"]
                 .as_str()
@@ -513,18 +946,38 @@ pub fn run_cloud_stt(stt_cloud: &SttCloudConfig, samples_16k: &[f32], client: &r
                 .to_string()
         }
         _ => {
-            json["text"]
-                .as_str()
-                .unwrap_or("")
-                .trim()
-                .to_string()
+            if stt_cloud.verbose {
+                if let Some((conf, no_speech)) = summarize_verbose_segments(&json) {
+                    tracing::info!(
+                        "Cloud STT verbose: confidence={:.3}, no_speech_prob={:.3}",
+                        conf, no_speech
+                    );
+                    if is_verbose_no_speech(no_speech) {
+                        return Err(SumiError::no_speech());
+                    }
+                    confidence = Some(conf);
+                }
+            }
+            let custom_path = stt_cloud
+                .response_text_path
+                .as_deref()
+                .map(str::trim)
+                .filter(|p| !p.is_empty() && stt_cloud.provider == SttProvider::Custom);
+            match custom_path {
+                Some(path) => extract_text_at_path(&json, path).map_err(SumiError::Provider)?.trim().to_string(),
+                None => json["text"]
+                    .as_str()
+                    .unwrap_or("")
+                    .trim()
+                    .to_string(),
+            }
         }
     };
 
     if text.is_empty() {
-        Err("no_speech".to_string())
+        Err(SumiError::no_speech())
     } else {
-        Ok(text)
+        Ok(CloudSttResult { text, confidence, word_timings })
     }
 }
 
@@ -549,6 +1002,13 @@ pub(crate) fn run_cloud_meeting_feeder_loop(
     let mut cloud_config = cloud_config;
     let language_for_feeder = language.clone();
     cloud_config.language = language;
+    let dictionary_terms = app
+        .state::<crate::AppState>()
+        .settings
+        .lock()
+        .map(|s| s.polish.dictionary_terms())
+        .unwrap_or_default();
+    let bias = cloud_bias_prompt(&cloud_config, &dictionary_terms);
 
     let app_for_closure = app.clone();
     let transcribe: crate::meeting_feeder::MeetingTranscribeFn =
@@ -572,8 +1032,8 @@ pub(crate) fn run_cloud_meeting_feeder_loop(
         #[cfg(not(feature = "diarization"))]
         let sub_segs: Vec<(f64, f64, String)> = vec![(start_secs, end_secs, String::new())];
 
-        let prompt = if prev_text.is_empty() { None } else { Some(prev_text) };
-        let text = match run_cloud_stt(&cloud_config, samples, &state.http_client, prompt) {
+        let prompt = chunk_prompt(bias.as_deref(), prev_text);
+        let text = match run_cloud_stt(&cloud_config, samples, &state.http_client(), prompt.as_deref()) {
             Ok(t) => t,
             Err(e) => {
                 tracing::warn!("[cloud-meeting] transcription failed, skipping: {e}");
@@ -610,3 +1070,276 @@ pub(crate) fn run_cloud_meeting_feeder_loop(
     crate::meeting_feeder::run_meeting_feeder(app, session_id, "cloud-meeting", Some(120 * 16_000), language_for_feeder, transcribe);
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cloud_stt_ready_needs_key_and_required_endpoint() {
+        let mut config = SttConfig { mode: SttMode::Cloud, ..SttConfig::default() };
+        assert!(!is_stt_ready(&config));
+        config.cloud.api_key = "dg-key".to_string();
+        assert!(is_stt_ready(&config));
+
+        config.cloud.provider = SttProvider::Azure;
+        assert!(!is_stt_ready(&config));
+        config.cloud.endpoint = "westus".to_string();
+        assert!(is_stt_ready(&config));
+    }
+
+    // ── encode_wav: header must match the payload ──
+
+    #[test]
+    fn wav_header_parses_with_hound() {
+        let samples = [0.0, 0.5, -0.5, 1.5, -1.5];
+        let bytes = encode_wav(&samples, 16000).unwrap();
+        let reader = hound::WavReader::new(std::io::Cursor::new(&bytes)).unwrap();
+        let spec = reader.spec();
+        assert_eq!(spec.channels, 1);
+        assert_eq!(spec.sample_rate, 16000);
+        assert_eq!(spec.bits_per_sample, 16);
+        assert_eq!(reader.len() as usize, samples.len());
+        let decoded: Vec<i16> = reader.into_samples::<i16>().map(Result::unwrap).collect();
+        assert_eq!(decoded, vec![0, 16383, -16383, 32767, -32767]);
+        assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize, bytes.len() - 8);
+
+        let bytes = encode_wav(&samples, 48000).unwrap();
+        let reader = hound::WavReader::new(std::io::Cursor::new(&bytes)).unwrap();
+        assert_eq!(reader.spec().sample_rate, 48000);
+        assert_eq!(u32::from_le_bytes(bytes[28..32].try_into().unwrap()), 96000);
+    }
+
+    #[test]
+    fn flac_upload_decodes_to_the_same_pcm() {
+        use symphonia::core::audio::SampleBuffer;
+        use symphonia::core::codecs::DecoderOptions;
+        use symphonia::core::formats::FormatOptions;
+        use symphonia::core::io::MediaSourceStream;
+        use symphonia::core::meta::MetadataOptions;
+        use symphonia::core::probe::Hint;
+
+        let samples: Vec<f32> =
+            (0..16000).map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / 16000.0).sin() * 0.5).collect();
+        let flac = encode_flac(&samples, 16000).unwrap();
+        assert_eq!(&flac[..4], b"fLaC");
+        assert!(flac.len() < encode_wav(&samples, 16000).unwrap().len());
+
+        let mss = MediaSourceStream::new(Box::new(std::io::Cursor::new(flac)), Default::default());
+        let mut hint = Hint::new();
+        hint.with_extension("flac");
+        let mut format = symphonia::default::get_probe()
+            .format(&hint, mss, &FormatOptions::default(), &MetadataOptions::default())
+            .unwrap()
+            .format;
+        let params = format.default_track().unwrap().codec_params.clone();
+        assert_eq!(params.sample_rate, Some(16000));
+        let mut decoder = symphonia::default::get_codecs().make(&params, &DecoderOptions::default()).unwrap();
+        let mut decoded: Vec<i16> = Vec::new();
+        while let Ok(packet) = format.next_packet() {
+            let buf = decoder.decode(&packet).unwrap();
+            let mut out = SampleBuffer::<i16>::new(buf.frames() as u64, *buf.spec());
+            out.copy_interleaved_ref(buf);
+            decoded.extend_from_slice(out.samples());
+        }
+        let expected: Vec<i16> = samples.iter().map(|&s| to_pcm16(s)).collect();
+        assert_eq!(decoded, expected);
+    }
+
+    #[test]
+    fn compressed_upload_falls_back_to_wav_for_azure() {
+        let mut cfg = SttCloudConfig { compress_upload: true, ..Default::default() };
+        cfg.provider = SttProvider::Deepgram;
+        assert_eq!(encode_upload(&cfg, &[0.0; 160], 16000).unwrap().mime, "audio/flac");
+        cfg.provider = SttProvider::Azure;
+        let audio = encode_upload(&cfg, &[0.0; 160], 16000).unwrap();
+        assert_eq!((audio.mime, audio.file_name), ("audio/wav", "audio.wav"));
+    }
+
+    fn field<'a>(fields: &'a [(&'static str, String)], name: &str) -> Option<&'a str> {
+        fields.iter().find(|(n, _)| *n == name).map(|(_, v)| v.as_str())
+    }
+
+    // ── openai_form_fields: language hint is model-aware ──
+
+    #[test]
+    fn whisper_1_sends_iso_language() {
+        let fields = openai_form_fields("whisper-1", "zh-TW", None, false);
+        assert_eq!(field(&fields, "model"), Some("whisper-1"));
+        assert_eq!(field(&fields, "language"), Some("zh"));
+        assert_eq!(field(&fields, "response_format"), Some("json"));
+    }
+
+    #[test]
+    fn gpt4o_transcribe_omits_language_and_verbose() {
+        let fields = openai_form_fields("gpt-4o-transcribe", "zh-TW", Some("context"), true);
+        assert_eq!(field(&fields, "model"), Some("gpt-4o-transcribe"));
+        assert_eq!(field(&fields, "language"), None);
+        assert_eq!(field(&fields, "response_format"), Some("json"));
+        assert_eq!(field(&fields, "prompt"), Some("context"));
+    }
+
+    #[test]
+    fn bias_prompt_appends_terms_within_cap() {
+        let mut cfg = SttCloudConfig { provider: SttProvider::Groq, stt_prompt: " Meeting notes. ".into(), ..Default::default() };
+        let terms = vec!["Sumi".to_string(), "Tauri".to_string()];
+        assert_eq!(cloud_bias_prompt(&cfg, &terms).as_deref(), Some("Meeting notes. Sumi, Tauri"));
+
+        let many: Vec<String> = (0..500).map(|i| format!("term{i}")).collect();
+        let capped = cloud_bias_prompt(&cfg, &many).unwrap();
+        assert!(estimate_prompt_tokens(&capped) <= CLOUD_PROMPT_MAX_TOKENS);
+        assert!(capped.ends_with(|c: char| c.is_ascii_digit()));
+
+        cfg.provider = SttProvider::Deepgram;
+        assert_eq!(cloud_bias_prompt(&cfg, &terms), None);
+        cfg.provider = SttProvider::OpenAi;
+        cfg.stt_prompt.clear();
+        assert_eq!(cloud_bias_prompt(&cfg, &[]), None);
+    }
+
+    #[test]
+    fn verbose_segments_without_scores_summarize_to_none() {
+        assert_eq!(summarize_verbose_segments(&serde_json::json!({ "text": "hi" })), None);
+        assert_eq!(summarize_verbose_segments(&serde_json::json!({ "segments": [] })), None);
+        let unscored = serde_json::json!({ "segments": [{ "start": 0.0, "end": 1.0, "no_speech_prob": 0.9 }] });
+        assert_eq!(summarize_verbose_segments(&unscored), None);
+    }
+
+    #[test]
+    fn verbose_segments_are_weighted_by_duration() {
+        let json = serde_json::json!({ "segments": [
+            { "start": 0.0, "end": 3.0, "avg_logprob": 0.0, "no_speech_prob": 0.0 },
+            { "start": 3.0, "end": 4.0, "avg_logprob": -100.0, "no_speech_prob": 1.0 },
+            // Segments without a score are skipped.
+            { "start": 4.0, "end": 9.0, "no_speech_prob": 1.0 },
+        ] });
+        let (confidence, no_speech) = summarize_verbose_segments(&json).unwrap();
+        assert!((confidence - 0.75).abs() < 1e-9);
+        assert!((no_speech - 0.25).abs() < 1e-9);
+        assert!(!is_verbose_no_speech(no_speech));
+    }
+
+    #[test]
+    fn no_speech_rejection_is_strictly_above_the_threshold() {
+        assert!(!is_verbose_no_speech(VERBOSE_NO_SPEECH_THRESHOLD));
+        assert!(is_verbose_no_speech(VERBOSE_NO_SPEECH_THRESHOLD + 1e-9));
+        let noisy = serde_json::json!({ "segments": [
+            { "start": 0.0, "end": 1.0, "avg_logprob": -0.2, "no_speech_prob": 0.9 },
+            { "start": 1.0, "end": 2.0, "avg_logprob": -0.2, "no_speech_prob": 0.5 },
+        ] });
+        let (_, no_speech) = summarize_verbose_segments(&noisy).unwrap();
+        assert!(is_verbose_no_speech(no_speech));
+    }
+
+    #[test]
+    fn switching_provider_drops_provider_specific_settings() {
+        let mut cfg = SttCloudConfig {
+            provider: SttProvider::Custom,
+            endpoint: "https://stt.example.com/v1/transcribe".into(),
+            response_text_path: Some("results.transcript".into()),
+            model_id: "my-model".into(),
+            ..Default::default()
+        };
+        // A custom URL must not become the Azure region.
+        cfg.switch_provider(SttProvider::Azure);
+        assert_eq!(cfg.provider, SttProvider::Azure);
+        assert!(cfg.endpoint.is_empty());
+        assert_eq!(cfg.response_text_path, None);
+        assert_eq!(cfg.model_id, SttProvider::Azure.default_model());
+
+        cfg.endpoint = "westus".into();
+        cfg.switch_provider(SttProvider::Custom);
+        assert!(cfg.endpoint.is_empty());
+
+        // Re-selecting the same provider keeps what was configured.
+        cfg.endpoint = "https://stt.example.com".into();
+        cfg.switch_provider(SttProvider::Custom);
+        assert_eq!(cfg.endpoint, "https://stt.example.com");
+    }
+
+    #[test]
+    fn long_bias_prompt_is_cut_at_a_grapheme() {
+        // "é" as e + combining acute: two chars, one grapheme.
+        let cfg = SttCloudConfig { provider: SttProvider::Groq, stt_prompt: "e\u{301}".repeat(200), ..Default::default() };
+        let prompt = cloud_bias_prompt(&cfg, &["Sumi".to_string()]).unwrap();
+        assert!(estimate_prompt_tokens(&prompt) <= CLOUD_PROMPT_MAX_TOKENS);
+        assert!(prompt.starts_with("e\u{301}e\u{301}"));
+        assert!(prompt.ends_with("e\u{301} Sumi"));
+    }
+
+    #[test]
+    fn chunk_prompt_puts_bias_after_previous_text() {
+        assert_eq!(chunk_prompt(None, ""), None);
+        assert_eq!(chunk_prompt(Some("Sumi"), " ").as_deref(), Some("Sumi"));
+        assert_eq!(chunk_prompt(None, "so far").as_deref(), Some("so far"));
+        assert_eq!(chunk_prompt(Some("Sumi"), "so far").as_deref(), Some("so far Sumi"));
+    }
+
+    #[test]
+    fn auto_language_sends_no_hint() {
+        let fields = openai_form_fields("whisper-large-v3-turbo", "", None, true);
+        assert_eq!(field(&fields, "language"), None);
+        assert_eq!(field(&fields, "response_format"), Some("verbose_json"));
+    }
+
+    #[test]
+    fn deepgram_words_prefer_punctuated_form() {
+        let json = serde_json::json!({
+            "transcript": "Hello, world.",
+            "words": [
+                { "word": "hello", "punctuated_word": "Hello,", "start": 0.08, "end": 0.42 },
+                { "word": "world", "start": 0.5, "end": 0.91 },
+                { "word": "dropped", "start": 1.0 }
+            ]
+        });
+        let words = deepgram_word_timings(&json).unwrap();
+        assert_eq!(
+            words,
+            vec![
+                WordTiming { word: "Hello,".into(), start_secs: 0.08, end_secs: 0.42 },
+                WordTiming { word: "world".into(), start_secs: 0.5, end_secs: 0.91 },
+            ]
+        );
+        assert!(deepgram_word_timings(&serde_json::json!({ "words": [] })).is_none());
+        assert!(deepgram_word_timings(&serde_json::json!({ "transcript": "" })).is_none());
+    }
+
+    // ── extract_text_at_path: Custom provider response shapes ──
+
+    #[test]
+    fn azure_detailed_picks_highest_confidence() {
+        let json = serde_json::json!({
+            "RecognitionStatus": "Success",
+            "NBest": [
+                { "Confidence": 0.61, "Display": "Wreck a nice beach." },
+                { "Confidence": 0.93, "Display": " Recognize speech. " }
+            ]
+        });
+        let (text, conf) = best_azure_nbest(&json).unwrap();
+        assert_eq!(text, "Recognize speech.");
+        assert_eq!(conf, Some(0.93));
+        assert!(best_azure_nbest(&serde_json::json!({ "RecognitionStatus": "NoMatch" })).is_none());
+    }
+
+    #[test]
+    fn text_path_follows_keys_and_indices() {
+        let json = serde_json::json!({
+            "results": { "transcript": "hello" },
+            "segments": [{ "text": "first" }, { "text": "second" }],
+            "nested": [[{ "t": "deep" }]],
+        });
+        assert_eq!(extract_text_at_path(&json, "results.transcript"), Ok("hello"));
+        assert_eq!(extract_text_at_path(&json, "segments[1].text"), Ok("second"));
+        assert_eq!(extract_text_at_path(&json, "nested[0][0].t"), Ok("deep"));
+    }
+
+    #[test]
+    fn text_path_errors_are_descriptive() {
+        let json = serde_json::json!({ "results": { "count": 3 }, "items": [] });
+        assert!(extract_text_at_path(&json, "results.transcript").unwrap_err().contains("not found"));
+        assert!(extract_text_at_path(&json, "results.count").unwrap_err().contains("not point to a string"));
+        assert!(extract_text_at_path(&json, "items[0]").unwrap_err().contains("out of range"));
+        assert!(extract_text_at_path(&json, "items[x]").unwrap_err().contains("non-numeric"));
+        assert!(extract_text_at_path(&json, "results..count").unwrap_err().contains("empty segment"));
+    }
+}
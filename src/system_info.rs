@@ -11,6 +11,7 @@ pub struct SystemInfo {
     pub is_apple_silicon: bool,
     pub gpu_vram_bytes: u64,
     pub has_cuda: bool,
+    pub has_coreml: bool,
     pub os: String,
     pub arch: String,
     pub cpu_model: String,
@@ -24,6 +25,7 @@ pub fn detect_system_info() -> SystemInfo {
     let arch = std::env::consts::ARCH.to_string();
     let is_apple_silicon = cfg!(target_os = "macos") && arch == "aarch64";
     let has_cuda = cfg!(feature = "cuda");
+    let has_coreml = cfg!(all(target_os = "macos", feature = "coreml"));
 
     SystemInfo {
         total_ram_bytes,
@@ -31,6 +33,7 @@ pub fn detect_system_info() -> SystemInfo {
         is_apple_silicon,
         gpu_vram_bytes,
         has_cuda,
+        has_coreml,
         os: std::env::consts::OS.to_string(),
         arch,
         cpu_model: get_cpu_model(),
@@ -0,0 +1,612 @@
+//! Rule-based number normalization for unpolished transcripts.
+//!
+//! Whisper often spells numbers out ("three hundred twenty dollars",
+//! "二〇二五年") or mixes digits and words.  When AI polishing does not run,
+//! this module rewrites them as digits without an LLM:
+//!
+//! - English: cardinal runs, spoken years ("twenty twenty five" → 2025),
+//!   decimals, `$` / `€` / `%` suffixes and month + ordinal dates.  A lone
+//!   number below ten stays spelled out ("one of them").
+//! - Chinese: digit-by-digit years before 年, positional numbers with a unit
+//!   (三百二十 → 320), 百分之 percentages and 月 / 日 dates.  Numerals that
+//!   open idioms (一樣, 十分, 千萬, 萬一) are left alone.
+
+/// Rewrite spelled-out numbers in `text` for an STT language code
+/// ("en", "zh-TW", "auto", …).  Languages without a converter pass through.
+pub fn normalize_numbers(text: &str, language: &str) -> String {
+    let base = language.split(['-', '_']).next().unwrap_or("").to_ascii_lowercase();
+    match base.as_str() {
+        "en" => normalize_english(text),
+        "zh" => normalize_chinese(text),
+        // Auto-detect: the two converters touch disjoint scripts.
+        "auto" | "" => normalize_chinese(&normalize_english(text)),
+        _ => text.to_string(),
+    }
+}
+
+/// Digits with thousands separators from 10,000 up ("1500", "25,000").
+fn format_number(value: u64) -> String {
+    let digits = value.to_string();
+    if value < 10_000 {
+        return digits;
+    }
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+// ── English ─────────────────────────────────────────────────────────────────
+
+const UNITS: [&str; 10] = ["zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+const TEENS: [&str; 10] = [
+    "ten", "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen",
+];
+const TENS: [&str; 8] = ["twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety"];
+const ORDINALS: [&str; 19] = [
+    "first", "second", "third", "fourth", "fifth", "sixth", "seventh", "eighth", "ninth", "tenth",
+    "eleventh", "twelfth", "thirteenth", "fourteenth", "fifteenth", "sixteenth", "seventeenth",
+    "eighteenth", "nineteenth",
+];
+const MONTHS: [&str; 12] = [
+    "january", "february", "march", "april", "may", "june",
+    "july", "august", "september", "october", "november", "december",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Word {
+    Unit(u64),
+    Teen(u64),
+    Tens(u64),
+    Hundred,
+    Scale(u64),
+}
+
+fn number_word(core: &str) -> Option<Word> {
+    if let Some(i) = UNITS.iter().position(|w| *w == core) {
+        return Some(Word::Unit(i as u64));
+    }
+    if let Some(i) = TEENS.iter().position(|w| *w == core) {
+        return Some(Word::Teen(10 + i as u64));
+    }
+    if let Some(i) = TENS.iter().position(|w| *w == core) {
+        return Some(Word::Tens(20 + 10 * i as u64));
+    }
+    match core {
+        "hundred" => Some(Word::Hundred),
+        "thousand" => Some(Word::Scale(1_000)),
+        "million" => Some(Word::Scale(1_000_000)),
+        "billion" => Some(Word::Scale(1_000_000_000)),
+        _ => None,
+    }
+}
+
+/// Number words in one token; "twenty-five" yields two.
+fn number_words(core: &str) -> Option<Vec<Word>> {
+    match core.split_once('-') {
+        Some((tens, unit)) => match (number_word(tens)?, number_word(unit)?) {
+            (t @ Word::Tens(_), u @ Word::Unit(1..)) => Some(vec![t, u]),
+            _ => None,
+        },
+        None => number_word(core).map(|w| vec![w]),
+    }
+}
+
+fn ordinal_day(core: &str) -> Option<u64> {
+    match core {
+        "twentieth" => return Some(20),
+        "thirtieth" => return Some(30),
+        _ => {}
+    }
+    if let Some(i) = ORDINALS.iter().position(|w| *w == core) {
+        return Some(1 + i as u64);
+    }
+    let (tens, unit) = core.split_once('-')?;
+    compound_ordinal(tens, unit)
+}
+
+/// "twenty" + "first" → 21.
+fn compound_ordinal(tens: &str, unit: &str) -> Option<u64> {
+    match number_word(tens)? {
+        Word::Tens(t) => {
+            let u = ORDINALS[..9].iter().position(|w| *w == unit)? as u64 + 1;
+            Some(t + u)
+        }
+        _ => None,
+    }
+}
+
+/// A whitespace-separated token split into leading punctuation, the
+/// lower-cased word, and trailing punctuation.
+struct Token<'a> {
+    raw: &'a str,
+    lead: &'a str,
+    core: String,
+    trail: &'a str,
+}
+
+impl<'a> Token<'a> {
+    fn new(raw: &'a str) -> Self {
+        let start = raw.len() - raw.trim_start_matches(|c: char| !c.is_alphanumeric()).len();
+        let end = raw.trim_end_matches(|c: char| !c.is_alphanumeric()).len().max(start);
+        Token {
+            raw,
+            lead: &raw[..start],
+            core: raw[start..end].to_lowercase(),
+            trail: &raw[end..],
+        }
+    }
+}
+
+/// One number inside a run of number words, e.g. "twenty" and "twenty five"
+/// in "twenty twenty five".
+struct Group {
+    value: u64,
+    words: usize,
+    has_scale: bool,
+    first_tok: usize,
+    last_tok: usize,
+}
+
+fn normalize_english(text: &str) -> String {
+    text.lines().map(normalize_english_line).collect::<Vec<_>>().join("\n")
+}
+
+fn normalize_english_line(line: &str) -> String {
+    let tokens: Vec<Token> = line.split_whitespace().map(Token::new).collect();
+    let mut out: Vec<String> = Vec::with_capacity(tokens.len());
+    let mut changed = false;
+    let mut i = 0;
+    while i < tokens.len() {
+        if let Some((text, next)) = month_date(&tokens, i).or_else(|| number_run(&tokens, i)) {
+            out.push(text);
+            changed = true;
+            i = next;
+            continue;
+        }
+        out.push(tokens[i].raw.to_string());
+        i += 1;
+    }
+    if changed {
+        out.join(" ")
+    } else {
+        line.to_string()
+    }
+}
+
+/// "March fifth" / "March twenty-first" / "March twenty first" → "March 5".
+/// The month must be capitalised so the verb "may" is never touched.
+fn month_date(tokens: &[Token], i: usize) -> Option<(String, usize)> {
+    let month = &tokens[i];
+    if !MONTHS.contains(&month.core.as_str())
+        || !month.trail.is_empty()
+        || !month.raw.trim_start_matches(|c: char| !c.is_alphanumeric()).starts_with(char::is_uppercase)
+    {
+        return None;
+    }
+    let first = tokens.get(i + 1).filter(|t| t.lead.is_empty())?;
+    let (day, last) = match ordinal_day(&first.core) {
+        Some(day) => (day, i + 1),
+        None => {
+            let second = tokens.get(i + 2).filter(|t| t.lead.is_empty() && first.trail.is_empty())?;
+            (compound_ordinal(&first.core, &second.core)?, i + 2)
+        }
+    };
+    if !(1..=31).contains(&day) {
+        return None;
+    }
+    let text = format!("{} {}{}", month.raw, day, tokens[last].trail);
+    Some((text, last + 1))
+}
+
+/// Convert the run of number words starting at `start`.  Returns the
+/// replacement text and the index of the first unconsumed token, or `None`
+/// when there is nothing to rewrite.
+fn number_run(tokens: &[Token], start: usize) -> Option<(String, usize)> {
+    // Collect number words (with their token index) until the run breaks.
+    let mut words: Vec<(Word, usize)> = Vec::new();
+    let mut i = start;
+    while i < tokens.len() {
+        let t = &tokens[i];
+        if i > start && !t.lead.is_empty() {
+            break;
+        }
+        // "three hundred and twenty": skip an "and" between a scale and a number.
+        if t.core == "and" {
+            let after_scale = matches!(words.last(), Some((Word::Hundred | Word::Scale(_), _)));
+            let next_is_number = tokens.get(i + 1).is_some_and(|n| {
+                n.lead.is_empty()
+                    && matches!(
+                        number_words(&n.core).as_deref(),
+                        Some([Word::Unit(_) | Word::Teen(_) | Word::Tens(_), ..])
+                    )
+            });
+            if after_scale && next_is_number && tokens[i - 1].trail.is_empty() && t.trail.is_empty() {
+                i += 1;
+                continue;
+            }
+            break;
+        }
+        let Some(ws) = number_words(&t.core) else { break };
+        words.extend(ws.into_iter().map(|w| (w, i)));
+        i += 1;
+        if !t.trail.is_empty() {
+            break;
+        }
+    }
+
+    let groups = group_words(&words);
+    let last = groups.last()?;
+    let mut end = last.last_tok + 1;
+
+    // Spoken year: "twenty twenty five", "nineteen ninety nine".
+    let year = match groups.as_slice() {
+        [a, b] if !a.has_scale && !b.has_scale && (10..=99).contains(&a.value) && (10..=99).contains(&b.value) => {
+            Some(a.value * 100 + b.value)
+        }
+        _ => None,
+    };
+
+    // "three point five"
+    let mut decimals = String::new();
+    if year.is_none() && tokens[end - 1].trail.is_empty() && tokens.get(end).is_some_and(|t| t.core == "point" && t.trail.is_empty()) {
+        let mut j = end + 1;
+        while let Some(t) = tokens.get(j).filter(|t| t.lead.is_empty()) {
+            match number_word(&t.core) {
+                Some(Word::Unit(d)) => decimals.push(char::from(b'0' + d as u8)),
+                _ => break,
+            }
+            j += 1;
+            if !t.trail.is_empty() {
+                break;
+            }
+        }
+        if !decimals.is_empty() {
+            end = j;
+        }
+    }
+
+    // "dollars", "euros", "percent"
+    let (mut prefix, mut suffix) = ("", "");
+    if tokens[end - 1].trail.is_empty() {
+        if let Some(unit) = tokens.get(end).filter(|t| t.lead.is_empty()) {
+            match unit.core.as_str() {
+                "dollar" | "dollars" | "bucks" => prefix = "$",
+                "euro" | "euros" => prefix = "€",
+                "percent" => suffix = "%",
+                _ => {}
+            }
+            if !prefix.is_empty() || !suffix.is_empty() {
+                end += 1;
+            }
+        }
+    }
+    let marked = !decimals.is_empty() || !prefix.is_empty() || !suffix.is_empty();
+
+    let mut parts: Vec<String> = Vec::new();
+    let mut converted = false;
+    let last_index = groups.len() - 1;
+    for (gi, g) in groups.iter().enumerate() {
+        let is_last = gi == last_index;
+        let (value, first_tok, last_tok) = match year {
+            Some(y) if is_last => (y, groups[0].first_tok, g.last_tok),
+            Some(_) => continue,
+            None => (g.value, g.first_tok, g.last_tok),
+        };
+        // Spell-out rule: a lone word below ten stays as written.
+        if year.is_none() && g.words == 1 && g.value < 10 && !(is_last && marked) {
+            parts.extend(tokens[first_tok..=last_tok].iter().map(|t| t.raw.to_string()));
+            continue;
+        }
+        converted = true;
+        let lead = tokens[first_tok].lead;
+        let number = if year.is_some() { value.to_string() } else { format_number(value) };
+        if is_last {
+            let point = if decimals.is_empty() { "" } else { "." };
+            let trail = tokens[end - 1].trail;
+            parts.push(format!("{lead}{prefix}{number}{point}{decimals}{suffix}{trail}"));
+        } else {
+            parts.push(format!("{lead}{number}{}", tokens[last_tok].trail));
+        }
+    }
+    if !converted {
+        return None;
+    }
+    Some((parts.join(" "), end))
+}
+
+/// Split number words into numbers.  A word that cannot extend the current
+/// number ("twenty" after "twenty") starts a new one; a scale word that
+/// cannot attach ends the run.
+fn group_words(words: &[(Word, usize)]) -> Vec<Group> {
+    let mut groups: Vec<Group> = Vec::new();
+    let mut total = 0u64;
+    let mut current = 0u64;
+    let mut last: Option<Word> = None;
+    let mut last_scale: Option<u64> = None;
+    let mut count = 0;
+    let mut first_tok = 0;
+    let mut last_tok = 0;
+
+    for &(word, tok) in words {
+        let fits = match (word, last) {
+            (Word::Hundred | Word::Scale(_), None) => false,
+            (_, None) => true,
+            (Word::Unit(_), Some(Word::Tens(_) | Word::Hundred | Word::Scale(_))) => true,
+            (Word::Teen(_) | Word::Tens(_), Some(Word::Hundred | Word::Scale(_))) => true,
+            (Word::Hundred, Some(Word::Unit(_) | Word::Teen(_) | Word::Tens(_))) => current > 0 && current < 100,
+            (Word::Scale(s), Some(Word::Unit(_) | Word::Teen(_) | Word::Tens(_) | Word::Hundred)) => {
+                current > 0 && last_scale.is_none_or(|ls| s < ls)
+            }
+            _ => false,
+        };
+        if !fits {
+            if matches!(word, Word::Hundred | Word::Scale(_)) {
+                break;
+            }
+            if last.is_some() {
+                groups.push(Group { value: total + current, words: count, has_scale: last_scale.is_some(), first_tok, last_tok });
+            }
+            total = 0;
+            current = 0;
+            last_scale = None;
+            count = 0;
+            first_tok = tok;
+        }
+        match word {
+            Word::Unit(v) | Word::Teen(v) | Word::Tens(v) => current += v,
+            Word::Hundred => current *= 100,
+            Word::Scale(s) => {
+                total += current * s;
+                current = 0;
+                last_scale = Some(s);
+            }
+        }
+        if matches!(word, Word::Hundred) {
+            last_scale = last_scale.or(Some(100));
+        }
+        last = Some(word);
+        last_tok = tok;
+        count += 1;
+    }
+    if last.is_some() && count > 0 {
+        groups.push(Group { value: total + current, words: count, has_scale: last_scale.is_some(), first_tok, last_tok });
+    }
+    groups
+}
+
+// ── Chinese ─────────────────────────────────────────────────────────────────
+
+fn zh_digit(c: char) -> Option<u64> {
+    Some(match c {
+        '零' | '〇' => 0,
+        '一' => 1,
+        '二' | '兩' | '两' => 2,
+        '三' => 3,
+        '四' => 4,
+        '五' => 5,
+        '六' => 6,
+        '七' => 7,
+        '八' => 8,
+        '九' => 9,
+        _ => return None,
+    })
+}
+
+fn zh_unit(c: char) -> Option<u64> {
+    Some(match c {
+        '十' => 10,
+        '百' => 100,
+        '千' => 1_000,
+        _ => return None,
+    })
+}
+
+fn zh_big_unit(c: char) -> Option<u64> {
+    Some(match c {
+        '萬' | '万' => 10_000,
+        '億' | '亿' => 100_000_000,
+        _ => return None,
+    })
+}
+
+fn is_zh_numeral(c: char) -> bool {
+    zh_digit(c).is_some() || zh_unit(c).is_some() || zh_big_unit(c).is_some()
+}
+
+/// Value of a positional numeral ("三百二十", "一千零五", "兩萬", "十五").
+/// Colloquial trailing digits count in the next lower place ("三百五" = 350).
+fn parse_zh_positional(chars: &[char]) -> Option<u64> {
+    let mut total = 0u64;
+    let mut section = 0u64;
+    let mut number = 0u64;
+    let mut last_unit = 0u64;
+    let mut prev_digit = false;
+    for &c in chars {
+        if let Some(d) = zh_digit(c) {
+            // Two non-zero digits in a row are not positional ("三二").
+            if prev_digit && number != 0 && d != 0 {
+                return None;
+            }
+            number = d;
+            prev_digit = true;
+        } else if let Some(u) = zh_unit(c) {
+            let n = if number == 0 && u == 10 && !prev_digit { 1 } else { number };
+            if n == 0 {
+                return None;
+            }
+            section += n * u;
+            number = 0;
+            last_unit = u;
+            prev_digit = false;
+        } else if let Some(b) = zh_big_unit(c) {
+            section += number;
+            if section == 0 {
+                return None;
+            }
+            total += section * b;
+            section = 0;
+            number = 0;
+            last_unit = b;
+            prev_digit = false;
+        } else {
+            return None;
+        }
+    }
+    let n = chars.len();
+    if number > 0 && last_unit >= 100 && n >= 2 && !is_zero(chars[n - 2]) && zh_digit(chars[n - 2]).is_none() {
+        number *= last_unit / 10;
+    }
+    Some(total + section + number)
+}
+
+fn is_zero(c: char) -> bool {
+    matches!(c, '零' | '〇')
+}
+
+fn normalize_chinese(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        // 百分之二十 → 20%
+        if chars[i..].starts_with(&['百', '分', '之']) {
+            let run = numeral_run(&chars, i + 3);
+            if let Some(v) = parse_zh_positional(&chars[i + 3..run]).filter(|_| run > i + 3) {
+                out.push_str(&format!("{}%", v));
+                i = run;
+                continue;
+            }
+        }
+        if !is_zh_numeral(chars[i]) {
+            out.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        let end = numeral_run(&chars, i);
+        let run = &chars[i..end];
+        match convert_zh_run(run, chars.get(end).copied(), out.ends_with('月')) {
+            Some(digits) => out.push_str(&digits),
+            None => out.extend(run),
+        }
+        i = end;
+    }
+    out
+}
+
+fn numeral_run(chars: &[char], start: usize) -> usize {
+    let mut end = start;
+    while end < chars.len() && is_zh_numeral(chars[end]) {
+        end += 1;
+    }
+    end
+}
+
+/// Digits for one numeral run, given the character that follows it and
+/// whether it directly follows a converted month.
+fn convert_zh_run(run: &[char], next: Option<char>, after_month: bool) -> Option<String> {
+    let all_digits = run.iter().all(|c| zh_digit(*c).is_some());
+    // 二〇二五年 → 2025年
+    if all_digits && next == Some('年') && (2..=4).contains(&run.len()) {
+        return Some(run.iter().map(|c| char::from(b'0' + zh_digit(*c).unwrap_or(0) as u8)).collect());
+    }
+    // 三月 / 十二月, and the day right after it: 五日 / 二十號
+    let is_month = next == Some('月');
+    let is_day = after_month && matches!(next, Some('日' | '號' | '号'));
+    if is_month || is_day {
+        let max = if is_month { 12 } else { 31 };
+        return parse_zh_positional(run)
+            .filter(|v| (1..=max).contains(v))
+            .map(|v| v.to_string());
+    }
+    // Positional numbers need a unit and must open with a digit (十 only
+    // when a digit follows), which keeps 十分 / 千萬 / 萬一 intact.
+    let has_unit = run.iter().any(|c| zh_unit(*c).is_some() || zh_big_unit(*c).is_some());
+    let opens_with_digit = zh_digit(run[0]).is_some_and(|d| d > 0)
+        || (run[0] == '十' && run.get(1).is_some_and(|c| zh_digit(*c).is_some()));
+    if has_unit && opens_with_digit {
+        return parse_zh_positional(run).map(|v| v.to_string());
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── English ──
+
+    #[test]
+    fn converts_currency_amounts() {
+        assert_eq!(normalize_numbers("three hundred twenty dollars", "en"), "$320");
+        assert_eq!(normalize_numbers("It costs five dollars.", "en"), "It costs $5.");
+        assert_eq!(normalize_numbers("twenty percent off", "en"), "20% off");
+    }
+
+    #[test]
+    fn converts_spoken_years() {
+        assert_eq!(normalize_numbers("back in twenty twenty five", "en"), "back in 2025");
+        assert_eq!(normalize_numbers("Since nineteen ninety-nine,", "en"), "Since 1999,");
+        assert_eq!(normalize_numbers("two thousand and twenty", "en"), "2020");
+    }
+
+    #[test]
+    fn converts_large_numbers_and_decimals() {
+        assert_eq!(normalize_numbers("two million three hundred thousand users", "en"), "2,300,000 users");
+        assert_eq!(normalize_numbers("version three point five", "en"), "version 3.5");
+    }
+
+    #[test]
+    fn keeps_small_standalone_numbers() {
+        let text = "One of them said no one knows.";
+        assert_eq!(normalize_numbers(text, "en"), text);
+        assert_eq!(normalize_numbers("one hundred people", "en"), "100 people");
+    }
+
+    #[test]
+    fn converts_month_ordinals() {
+        assert_eq!(normalize_numbers("due March fifth", "en"), "due March 5");
+        assert_eq!(normalize_numbers("on July twenty-first.", "en"), "on July 21.");
+        assert_eq!(normalize_numbers("you may first check", "en"), "you may first check");
+    }
+
+    // ── Chinese ──
+
+    #[test]
+    fn converts_digit_by_digit_years() {
+        assert_eq!(normalize_numbers("二〇二五年的計畫", "zh-TW"), "2025年的計畫");
+        assert_eq!(normalize_numbers("二零二四年", "zh-CN"), "2024年");
+    }
+
+    #[test]
+    fn converts_positional_numbers() {
+        assert_eq!(normalize_numbers("三百二十塊", "zh-TW"), "320塊");
+        assert_eq!(normalize_numbers("一千零五個人", "zh-TW"), "1005個人");
+        assert_eq!(normalize_numbers("兩萬五", "zh-TW"), "25000");
+        assert_eq!(normalize_numbers("十五分鐘", "zh-TW"), "15分鐘");
+    }
+
+    #[test]
+    fn converts_dates_and_percentages() {
+        assert_eq!(normalize_numbers("三月五日開會", "zh-TW"), "3月5日開會");
+        assert_eq!(normalize_numbers("十二月二十號", "zh-TW"), "12月20號");
+        assert_eq!(normalize_numbers("成長百分之二十", "zh-TW"), "成長20%");
+    }
+
+    #[test]
+    fn leaves_numeral_idioms_alone() {
+        for text in ["十分重要", "千萬不要", "萬一失敗", "一樣好", "一起去"] {
+            assert_eq!(normalize_numbers(text, "zh-TW"), text);
+        }
+    }
+
+    #[test]
+    fn auto_handles_mixed_dictation_and_other_languages_pass_through() {
+        assert_eq!(normalize_numbers("預算 three hundred dollars 三百塊", "auto"), "預算 $300 300塊");
+        assert_eq!(normalize_numbers("three hundred", "ja"), "three hundred");
+    }
+}
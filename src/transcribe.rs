@@ -1,9 +1,10 @@
 use std::path::PathBuf;
-use std::sync::Mutex;
-use std::time::Instant;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
 use whisper_rs::{DtwMode, DtwModelPreset, DtwParameters, WhisperContext, WhisperContextParameters, WhisperVadContext, WhisperVadContextParams, WhisperVadParams};
 
 use crate::settings::models_dir;
+use crate::stt::WhisperQuality;
 use crate::whisper_models::WhisperModel;
 
 /// Cross-attention cache for Whisper DTW word-timestamp alignment.
@@ -33,10 +34,13 @@ unsafe impl Send for VadContextCache {}
 
 /// Filter audio samples through Silero VAD, returning only speech segments.
 /// The VAD context is lazily loaded on first call.
+///
+/// Also returns the offsets (into the filtered samples) where two speech
+/// segments were joined — natural cut points for chunked transcription.
 pub fn filter_with_vad(
     vad_cache: &Mutex<Option<VadContextCache>>,
     samples_16k: &[f32],
-) -> Result<Vec<f32>, String> {
+) -> Result<(Vec<f32>, Vec<usize>), String> {
     let model_path = crate::settings::vad_model_path();
     if !model_path.exists() {
         return Err("VAD model not downloaded".to_string());
@@ -95,6 +99,7 @@ pub fn filter_with_vad(
     tracing::info!("VAD found {} speech segment(s) (took {:.0?})", n, vad_start.elapsed());
 
     let mut speech_samples = Vec::new();
+    let mut boundaries = Vec::new();
     for seg in segments {
         // Timestamps are in centiseconds (1cs = 10ms)
         let start_sample = ((seg.start / 100.0) * 16000.0) as usize;
@@ -106,11 +111,14 @@ pub fn filter_with_vad(
                 seg.end / 100.0,
                 end_sample - start_sample,
             );
+            if !speech_samples.is_empty() {
+                boundaries.push(speech_samples.len());
+            }
             speech_samples.extend_from_slice(&samples_16k[start_sample..end_sample]);
         }
     }
 
-    Ok(speech_samples)
+    Ok((speech_samples, boundaries))
 }
 
 /// Check whether a 16 kHz audio chunk contains speech according to Silero VAD.
@@ -179,9 +187,11 @@ pub fn has_speech_vad(
 /// Resolve the path to a whisper GGML model file.
 /// Returns an error if the model hasn't been downloaded yet.
 pub fn whisper_model_path_for(model: &WhisperModel) -> Result<PathBuf, String> {
-    let model_path = models_dir().join(model.filename());
+    let model_path = model.local_path();
     if model_path.exists() {
         Ok(model_path)
+    } else if let WhisperModel::Custom { path } = model {
+        Err(format!("Custom Whisper model not found: {}", path.display()))
     } else {
         Err(format!(
             "Whisper model '{}' not downloaded. Please download it from Settings.",
@@ -213,8 +223,9 @@ pub fn warm_whisper_cache(
     let load_start = Instant::now();
     tracing::info!("Pre-warming Whisper model: {} ...", model.display_name());
 
+    let use_gpu = !crate::settings::force_cpu();
     let mut ctx_params = WhisperContextParameters::new();
-    ctx_params.use_gpu(true);
+    ctx_params.use_gpu(use_gpu);
     // DTW is always enabled so the loaded context is ready for meeting-mode word
     // timestamps without a model reload (a reload costs 5–10 s on first use).
     // Cost: ~128 MiB of Metal/GPU memory per loaded model, even for non-meeting sessions.
@@ -233,7 +244,8 @@ pub fn warm_whisper_cache(
         loaded_path: model_path,
     });
     tracing::info!(
-        "Whisper model pre-warmed with GPU enabled (took {:.0?})",
+        "Whisper model pre-warmed with GPU {} (took {:.0?})",
+        if use_gpu { "enabled" } else { "disabled" },
         load_start.elapsed()
     );
 
@@ -243,14 +255,21 @@ pub fn warm_whisper_cache(
 /// Transcribe 16 kHz mono f32 samples using the cached WhisperContext.
 /// The context is lazily loaded on first use, and automatically reloaded
 /// when the requested model differs from the currently loaded one.
+///
+/// Recordings longer than `chunk_threshold_secs` (0 = never) are split into
+/// overlapping windows, cut at `boundaries` (VAD segment joins) where possible,
+/// and the per-window transcripts stitched back together.
+#[allow(clippy::too_many_arguments)]
 pub fn transcribe_with_cached_whisper(
     whisper_cache: &Mutex<Option<WhisperContextCache>>,
     samples_16k: &[f32],
     model: &WhisperModel,
     language: &str,
     dictionary_terms: &[String],
+    chunk_threshold_secs: u32,
+    boundaries: &[usize],
+    quality: WhisperQuality,
 ) -> Result<String, String> {
-    use whisper_rs::{FullParams, SamplingStrategy};
 
     let model_path = whisper_model_path_for(model)?;
 
@@ -271,8 +290,9 @@ pub fn transcribe_with_cached_whisper(
             "Loading Whisper model: {} ...",
             model.display_name()
         );
+        let use_gpu = !crate::settings::force_cpu();
         let mut ctx_params = WhisperContextParameters::new();
-        ctx_params.use_gpu(true);
+        ctx_params.use_gpu(use_gpu);
         ctx_params.dtw_parameters(DtwParameters {
             mode: dtw_mode_for(model),
             dtw_mem_size: DTW_MEM_SIZE,
@@ -288,9 +308,16 @@ pub fn transcribe_with_cached_whisper(
             loaded_path: model_path.clone(),
         });
         tracing::info!(
-            "Whisper model loaded with GPU enabled (took {:.0?})",
+            "Whisper model loaded with GPU {} (took {:.0?})",
+            if use_gpu { "enabled" } else { "disabled" },
             load_start.elapsed()
         );
+        if cfg!(feature = "coreml") {
+            let coreml = model
+                .coreml_encoder_dir()
+                .is_some_and(|d| models_dir().join(d).is_dir());
+            tracing::info!("Core ML encoder: {}", if coreml { "in use" } else { "not found, encoder on GPU/CPU" });
+        }
     }
 
     let cache = cache_guard.as_ref().expect("Whisper context was just initialized above");
@@ -305,8 +332,6 @@ pub fn transcribe_with_cached_whisper(
         state_start.elapsed()
     );
 
-    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
-
     // Set language hint from STT config (BCP-47 → ISO 639-1 base code)
     // "auto" or empty means let Whisper auto-detect.
     let lang_hint = if language.is_empty() || language == "auto" {
@@ -314,7 +339,9 @@ pub fn transcribe_with_cached_whisper(
     } else {
         Some(language.split('-').next().unwrap_or(language))
     };
-    params.set_language(lang_hint);
+
+    let n_threads = whisper_thread_count();
+    tracing::info!("Whisper decode threads: {}", n_threads);
 
     // Build initial prompt for Whisper token biasing.
     //
@@ -368,28 +395,109 @@ pub fn transcribe_with_cached_whisper(
     }
 
     let prompt = prompt_parts.join(" ");
-    if !prompt.is_empty() {
-        params.set_initial_prompt(&prompt);
+
+    tracing::info!(
+        "[whisper] language={:?} (config: {:?}), quality={:?}, prompt={:?}",
+        lang_hint, language, quality, prompt
+    );
+
+    let chunked = chunk_threshold_secs > 0
+        && samples_16k.len() > chunk_threshold_secs as usize * WHISPER_SAMPLE_RATE;
+    if !chunked {
+        let params = whisper_full_params(lang_hint, &prompt, true, n_threads, quality);
+        return run_whisper_full(&mut wh_state, params, samples_16k);
     }
 
+    let chunks = plan_chunks(samples_16k, boundaries);
     tracing::info!(
-        "[whisper] language={:?} (config: {:?}), prompt={:?}",
-        lang_hint, language, prompt
+        "[whisper] chunked transcription: {:.1}s in {} window(s)",
+        samples_16k.len() as f64 / WHISPER_SAMPLE_RATE as f64,
+        chunks.len()
     );
+    let mut text = String::new();
+    for (i, range) in chunks.into_iter().enumerate() {
+        let (start, end) = (range.start, range.end);
+        // Multiple segments per window so boundaries follow the speech.
+        let params = whisper_full_params(lang_hint, &prompt, false, n_threads, quality);
+        let chunk_text = run_whisper_full(&mut wh_state, params, &samples_16k[range])?;
+        tracing::info!(
+            "[whisper] chunk {} ({:.1}s–{:.1}s): {} chars",
+            i,
+            start as f64 / WHISPER_SAMPLE_RATE as f64,
+            end as f64 / WHISPER_SAMPLE_RATE as f64,
+            chunk_text.chars().count()
+        );
+        text = stitch_transcripts(&text, &chunk_text);
+    }
+    Ok(text)
+}
+
+/// Beam width used by the beam-search quality levels.
+const WHISPER_BEAM_SIZE: i32 = 5;
+
+/// Decoder settings for a [`WhisperQuality`].
+#[derive(Debug, PartialEq)]
+struct DecodePlan {
+    /// `None` decodes greedily.
+    beam_size: Option<i32>,
+    /// Temperature step between quality-gate retries; each retry above 0
+    /// samples `best_of` candidates.
+    temperature_inc: f32,
+}
+
+fn decode_plan(quality: WhisperQuality) -> DecodePlan {
+    match quality {
+        // One retry at 0.6 keeps the compression-ratio, logprob and
+        // no-speech gates active without the cost of a full ladder.
+        WhisperQuality::Fast => DecodePlan { beam_size: None, temperature_inc: 0.6 },
+        WhisperQuality::Balanced => DecodePlan { beam_size: Some(WHISPER_BEAM_SIZE), temperature_inc: 0.6 },
+        // whisper.cpp's default ladder: retries at 0.2, 0.4, … 1.0.
+        WhisperQuality::Accurate => DecodePlan { beam_size: Some(WHISPER_BEAM_SIZE), temperature_inc: 0.2 },
+    }
+}
+
+/// Whisper decoding parameters shared by the single-shot and chunked paths.
+fn whisper_full_params<'a>(
+    lang_hint: Option<&'a str>,
+    prompt: &'a str,
+    single_segment: bool,
+    n_threads: usize,
+    quality: WhisperQuality,
+) -> whisper_rs::FullParams<'a, 'a> {
+    use whisper_rs::{FullParams, SamplingStrategy};
 
+    let plan = decode_plan(quality);
+    let strategy = match plan.beam_size {
+        Some(beam_size) => SamplingStrategy::BeamSearch { beam_size, patience: -1.0 },
+        None => SamplingStrategy::Greedy { best_of: 1 },
+    };
+    let mut params = FullParams::new(strategy);
+    params.set_language(lang_hint);
+    if !prompt.is_empty() {
+        params.set_initial_prompt(prompt);
+    }
     params.set_print_special(false);
     params.set_print_realtime(false);
     params.set_print_progress(false);
-    params.set_single_segment(true);
+    params.set_single_segment(single_segment);
     params.set_no_timestamps(true);
     params.set_no_context(true);
     // Re-enable whisper.cpp quality fallback: compression-ratio, logprob, and
-    // no-speech checks can trigger ONE retry at temperature 0.6.  Without this,
-    // all quality gates are bypassed and hallucinations on silence pass through.
-    params.set_temperature_inc(0.6);
+    // no-speech checks can trigger a retry at a higher temperature.  Without
+    // this, all quality gates are bypassed and hallucinations on silence pass
+    // through.
+    params.set_temperature_inc(plan.temperature_inc);
     params.set_no_speech_thold(0.5);
-    params.set_n_threads(num_cpus() as _);
+    params.set_n_threads(n_threads as _);
+    params
+}
 
+/// Run one `full()` pass and collect the text of all speech segments.
+fn run_whisper_full(
+    wh_state: &mut whisper_rs::WhisperState,
+    params: whisper_rs::FullParams<'_, '_>,
+    samples_16k: &[f32],
+) -> Result<String, String> {
     let infer_start = Instant::now();
     wh_state
         .full(params, samples_16k)
@@ -425,6 +533,136 @@ pub fn transcribe_with_cached_whisper(
     Ok(text.trim().to_string())
 }
 
+// ── Chunked transcription ────────────────────────────────────────────────────
+
+const WHISPER_SAMPLE_RATE: usize = 16_000;
+/// Window length for chunked transcription (Whisper's native context).
+const CHUNK_WINDOW_SAMPLES: usize = 30 * WHISPER_SAMPLE_RATE;
+/// Audio repeated at the start of each window so words cut at the seam are
+/// heard whole at least once; the duplicate text is removed when stitching.
+const CHUNK_OVERLAP_SAMPLES: usize = WHISPER_SAMPLE_RATE;
+/// Frame size used to find the quietest cut point when no VAD boundary fits.
+const CUT_FRAME_SAMPLES: usize = WHISPER_SAMPLE_RATE / 10;
+
+/// Split `samples` into windows of at most 30 s that overlap by 1 s.
+///
+/// Each window ends at the latest VAD boundary in its second half, falling
+/// back to the quietest 100 ms frame in its last third.
+fn plan_chunks(samples: &[f32], boundaries: &[usize]) -> Vec<std::ops::Range<usize>> {
+    let len = samples.len();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < len {
+        let limit = start + CHUNK_WINDOW_SAMPLES;
+        if limit >= len {
+            chunks.push(start..len);
+            break;
+        }
+        let end = boundaries
+            .iter()
+            .copied()
+            .filter(|&b| b > start + CHUNK_WINDOW_SAMPLES / 2 && b <= limit)
+            .max()
+            .unwrap_or_else(|| quietest_frame_end(samples, start + CHUNK_WINDOW_SAMPLES * 2 / 3, limit));
+        chunks.push(start..end);
+        start = end - CHUNK_OVERLAP_SAMPLES;
+    }
+    chunks
+}
+
+/// End offset of the lowest-energy frame within `from..to`.
+fn quietest_frame_end(samples: &[f32], from: usize, to: usize) -> usize {
+    let mut best = (f32::MAX, to);
+    let mut pos = from;
+    while pos + CUT_FRAME_SAMPLES <= to {
+        let energy = crate::audio::rms(&samples[pos..pos + CUT_FRAME_SAMPLES]);
+        if energy < best.0 {
+            best = (energy, pos + CUT_FRAME_SAMPLES);
+        }
+        pos += CUT_FRAME_SAMPLES;
+    }
+    best.1
+}
+
+/// Longest run of repeated units searched when removing the overlap
+/// (1 s of speech is at most a few words or CJK characters).
+const MAX_OVERLAP_UNITS: usize = 8;
+
+/// Append `next` to `prev`, dropping the words (or CJK characters) that both
+/// windows transcribed from the shared overlap audio.
+fn stitch_transcripts(prev: &str, next: &str) -> String {
+    let next = next.trim();
+    if prev.is_empty() {
+        return next.to_string();
+    }
+    if next.is_empty() {
+        return prev.to_string();
+    }
+
+    let prev_units = overlap_units(prev);
+    let next_units = overlap_units(next);
+    let max_k = MAX_OVERLAP_UNITS.min(prev_units.len()).min(next_units.len());
+    let overlap = (1..=max_k).rev().find(|&k| {
+        // A lone short unit ("a", "的") repeats by chance too often to trust.
+        (k > 1 || next_units[0].1.chars().count() >= 3)
+            && prev_units[prev_units.len() - k..]
+                .iter()
+                .zip(&next_units[..k])
+                .all(|((_, a), (_, b))| a == b)
+    });
+    let rest = match overlap {
+        Some(k) => {
+            let cut = next_units[k - 1].0.end;
+            next[cut..].trim_start_matches(|c: char| !c.is_alphanumeric() && !c.is_whitespace())
+                .trim_start()
+        }
+        None => next,
+    };
+    if rest.is_empty() {
+        return prev.to_string();
+    }
+
+    let glue = match (prev.chars().last(), rest.chars().next()) {
+        (Some(a), Some(b)) if is_cjk(a) && is_cjk(b) => "",
+        _ => " ",
+    };
+    format!("{}{}{}", prev, glue, rest)
+}
+
+/// Comparable units of `text` with their byte ranges: words for spaced
+/// scripts, single characters for CJK.  Case and punctuation are ignored.
+fn overlap_units(text: &str) -> Vec<(std::ops::Range<usize>, String)> {
+    let mut units = Vec::new();
+    let mut word: Option<(usize, String)> = None;
+    for (i, c) in text.char_indices() {
+        let end = i + c.len_utf8();
+        if is_cjk(c) {
+            if let Some((start, w)) = word.take() {
+                units.push((start..i, w));
+            }
+            units.push((i..end, c.to_string()));
+        } else if c.is_alphanumeric() {
+            let entry = word.get_or_insert_with(|| (i, String::new()));
+            entry.1.extend(c.to_lowercase());
+        } else if let Some((start, w)) = word.take() {
+            units.push((start..i, w));
+        }
+    }
+    if let Some((start, w)) = word {
+        units.push((start..text.len(), w));
+    }
+    units
+}
+
+/// Han and Kana — scripts written without spaces between words.
+pub(crate) fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{F900}'..='\u{FAFF}')
+}
+
 /// Return the number of available CPU cores.
 pub fn num_cpus() -> usize {
     std::thread::available_parallelism()
@@ -432,6 +670,82 @@ pub fn num_cpus() -> usize {
         .unwrap_or(4)
 }
 
+/// Threads for Whisper decoding: the `whisper_threads` setting if set,
+/// otherwise [`auto_whisper_threads`].
+pub fn whisper_thread_count() -> usize {
+    crate::settings::whisper_threads().unwrap_or_else(auto_whisper_threads)
+}
+
+/// Auto thread count.  On Apple Silicon only the performance cores are used:
+/// efficiency cores finish their share late and stall every barrier sync in
+/// the encoder.  Elsewhere all logical cores are used.
+pub fn auto_whisper_threads() -> usize {
+    static AUTO: OnceLock<usize> = OnceLock::new();
+    *AUTO.get_or_init(|| performance_cores().unwrap_or_else(num_cpus))
+}
+
+/// Logical CPUs in the highest performance level (`hw.perflevel0`), or
+/// `None` on Macs without heterogeneous cores.
+#[cfg(target_os = "macos")]
+fn performance_cores() -> Option<usize> {
+    let name = b"hw.perflevel0.logicalcpu\0";
+    let mut value: libc::c_int = 0;
+    let mut len = std::mem::size_of::<libc::c_int>();
+    let ret = unsafe {
+        libc::sysctlbyname(
+            name.as_ptr() as *const libc::c_char,
+            &mut value as *mut libc::c_int as *mut libc::c_void,
+            &mut len,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    (ret == 0 && value > 0).then_some(value as usize)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn performance_cores() -> Option<usize> {
+    None
+}
+
+/// Decode a fixed 10 s clip with a few candidate thread counts on the current
+/// model and return each count with its wall time, fastest first.
+///
+/// The clip is silence: the encoder always processes a padded 30 s window,
+/// so its cost — which is what the thread count affects — does not depend on
+/// the content, and silence keeps the decoder pass short and repeatable.
+pub fn benchmark_whisper_threads(
+    whisper_cache: &Mutex<Option<WhisperContextCache>>,
+    model: &WhisperModel,
+) -> Result<Vec<(usize, Duration)>, String> {
+    warm_whisper_cache(whisper_cache, model)?;
+    let cache_guard = whisper_cache.lock().unwrap_or_else(|e| e.into_inner());
+    let cache = cache_guard.as_ref().ok_or("Whisper model not loaded")?;
+
+    let cores = num_cpus();
+    let mut candidates = vec![auto_whisper_threads(), cores, cores / 2, 4, 2];
+    candidates.retain(|&n| n > 0 && n <= cores);
+    candidates.sort_unstable();
+    candidates.dedup();
+
+    let clip = vec![0.0f32; 10 * WHISPER_SAMPLE_RATE];
+    let mut runs = Vec::with_capacity(candidates.len());
+    for n in candidates {
+        let mut wh_state = cache
+            .ctx
+            .create_state()
+            .map_err(|e| format!("Failed to create whisper state: {}", e))?;
+        let params = whisper_full_params(None, "", true, n, WhisperQuality::Fast);
+        let start = Instant::now();
+        run_whisper_full(&mut wh_state, params, &clip)?;
+        let elapsed = start.elapsed();
+        tracing::info!("[whisper] benchmark: {} threads → {:.0?}", n, elapsed);
+        runs.push((n, elapsed));
+    }
+    runs.sort_by_key(|&(_, elapsed)| elapsed);
+    Ok(runs)
+}
+
 /// Map a WhisperModel to its DTW cross-attention preset.
 /// Fine-tuned variants of LargeV3Turbo share the same architecture and use the same preset.
 fn dtw_mode_for(model: &WhisperModel) -> DtwMode<'static> {
@@ -450,6 +764,8 @@ fn dtw_mode_for(model: &WhisperModel) -> DtwMode<'static> {
         WhisperModel::Base => DtwMode::ModelPreset {
             model_preset: DtwModelPreset::Base,
         },
+        // Unknown architecture — no alignment-head preset to pick.
+        WhisperModel::Custom { .. } => DtwMode::None,
     }
 }
 
@@ -532,3 +848,71 @@ pub fn extract_dtw_words(
 
     words
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: usize = WHISPER_SAMPLE_RATE;
+
+    // ── plan_chunks ──
+
+    #[test]
+    fn short_audio_is_a_single_chunk() {
+        let samples = vec![0.1; 20 * SECOND];
+        assert_eq!(plan_chunks(&samples, &[]), vec![0..20 * SECOND]);
+    }
+
+    #[test]
+    fn chunks_cut_at_vad_boundaries_and_overlap() {
+        let samples = vec![0.1; 70 * SECOND];
+        let boundaries = [10 * SECOND, 25 * SECOND, 50 * SECOND];
+        let chunks = plan_chunks(&samples, &boundaries);
+        assert_eq!(chunks[0], 0..25 * SECOND);
+        assert_eq!(chunks[1], 24 * SECOND..50 * SECOND);
+        assert_eq!(chunks[2], 49 * SECOND..70 * SECOND);
+    }
+
+    #[test]
+    fn chunks_fall_back_to_quietest_frame() {
+        let mut samples = vec![0.3; 45 * SECOND];
+        samples[27 * SECOND..27 * SECOND + CUT_FRAME_SAMPLES].fill(0.0);
+        let chunks = plan_chunks(&samples, &[]);
+        assert_eq!(chunks[0], 0..27 * SECOND + CUT_FRAME_SAMPLES);
+        assert!(chunks.iter().all(|c| c.len() <= CHUNK_WINDOW_SAMPLES));
+        assert_eq!(chunks.last().unwrap().end, samples.len());
+    }
+
+    // ── stitch_transcripts ──
+
+    #[test]
+    fn stitch_drops_repeated_words() {
+        assert_eq!(
+            stitch_transcripts("We should deploy the service tonight.", "service tonight. Then monitor it."),
+            "We should deploy the service tonight. Then monitor it."
+        );
+    }
+
+    #[test]
+    fn stitch_drops_repeated_cjk_characters() {
+        assert_eq!(stitch_transcripts("今天我們討論部署", "部署的時間"), "今天我們討論部署的時間");
+    }
+
+    #[test]
+    fn stitch_keeps_text_without_overlap() {
+        assert_eq!(stitch_transcripts("First part.", "a second part"), "First part. a second part");
+        assert_eq!(stitch_transcripts("", "hello"), "hello");
+    }
+
+    // ── decode_plan ──
+
+    #[test]
+    fn quality_levels_trade_speed_for_search() {
+        assert_eq!(decode_plan(WhisperQuality::Fast), DecodePlan { beam_size: None, temperature_inc: 0.6 });
+        assert_eq!(decode_plan(WhisperQuality::Balanced).beam_size, Some(WHISPER_BEAM_SIZE));
+        let accurate = decode_plan(WhisperQuality::Accurate);
+        assert_eq!(accurate.beam_size, Some(WHISPER_BEAM_SIZE));
+        assert!(accurate.temperature_inc < decode_plan(WhisperQuality::Balanced).temperature_inc);
+        assert_eq!(WhisperQuality::default(), WhisperQuality::Fast);
+    }
+}
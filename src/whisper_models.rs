@@ -1,3 +1,6 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
 use serde::{Deserialize, Serialize};
 
 use crate::settings::models_dir;
@@ -15,9 +18,14 @@ pub enum WhisperModel {
     Small,
     Base,
     LargeV3TurboZhTw,
+    /// A user-supplied GGML file outside the models directory (e.g. a
+    /// domain fine-tune).  Never downloaded or deleted by Sumi.
+    Custom { path: PathBuf },
 }
 
 impl WhisperModel {
+    /// File name inside the models directory.  Empty for [`Self::Custom`],
+    /// which lives elsewhere — use [`Self::local_path`] to locate any model.
     pub fn filename(&self) -> &'static str {
         match self {
             Self::LargeV3Turbo => "ggml-large-v3-turbo.bin",
@@ -26,6 +34,15 @@ impl WhisperModel {
             Self::Small => "ggml-small.bin",
             Self::Base => "ggml-base.bin",
             Self::LargeV3TurboZhTw => "ggml-large-v3-turbo-zh-TW.bin",
+            Self::Custom { .. } => "",
+        }
+    }
+
+    /// Where this model's GGML file lives on disk (whether or not it exists).
+    pub fn local_path(&self) -> PathBuf {
+        match self {
+            Self::Custom { path } => path.clone(),
+            _ => models_dir().join(self.filename()),
         }
     }
 
@@ -51,9 +68,30 @@ impl WhisperModel {
             Self::LargeV3TurboZhTw => Some(
                 "https://huggingface.co/Alkd/whisper-large-v3-turbo-zh-TW/resolve/main/ggml-model.bin",
             ),
+            Self::Custom { .. } => None,
         }
     }
 
+    /// Core ML encoder bundle whisper.cpp looks for next to the GGML file.
+    /// Quantized variants share the full model's encoder; fine-tunes without
+    /// a published encoder return `None`.
+    pub fn coreml_encoder_dir(&self) -> Option<&'static str> {
+        match self {
+            Self::LargeV3Turbo | Self::LargeV3TurboQ5 => Some("ggml-large-v3-turbo-encoder.mlmodelc"),
+            Self::Medium => Some("ggml-medium-encoder.mlmodelc"),
+            Self::Small => Some("ggml-small-encoder.mlmodelc"),
+            Self::Base => Some("ggml-base-encoder.mlmodelc"),
+            Self::LargeV3TurboZhTw | Self::Custom { .. } => None,
+        }
+    }
+
+    /// Zipped Core ML encoder published alongside the GGML models.
+    pub fn coreml_download_url(&self) -> Option<String> {
+        self.coreml_encoder_dir().map(|dir| {
+            format!("https://huggingface.co/ggerganov/whisper.cpp/resolve/main/{}.zip", dir)
+        })
+    }
+
     pub fn display_name(&self) -> &'static str {
         match self {
             Self::LargeV3Turbo => "Whisper Turbo",
@@ -62,6 +100,20 @@ impl WhisperModel {
             Self::Small => "Whisper Small",
             Self::Base => "Whisper Base",
             Self::LargeV3TurboZhTw => "Whisper Turbo TW",
+            Self::Custom { .. } => "Custom Whisper",
+        }
+    }
+
+    /// Name for diagnostics and bug reports: the display name, plus the file
+    /// name of a [`Self::Custom`] model so the report says which one it was.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Custom { path } => format!(
+                "{} ({})",
+                self.display_name(),
+                path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default()
+            ),
+            _ => self.display_name().to_string(),
         }
     }
 
@@ -73,6 +125,7 @@ impl WhisperModel {
             Self::Small => 488_000_000,
             Self::Base => 148_000_000,
             Self::LargeV3TurboZhTw => 1_600_000_000,
+            Self::Custom { .. } => 0,
         }
     }
 
@@ -91,6 +144,7 @@ impl WhisperModel {
             Self::Small => "Lightweight and fast",
             Self::Base => "Fastest, smallest footprint",
             Self::LargeV3TurboZhTw => "Best for Traditional Chinese",
+            Self::Custom { .. } => "Your own GGML model file",
         }
     }
 
@@ -104,6 +158,33 @@ impl WhisperModel {
     }
 }
 
+/// Magic number at the start of a legacy GGML file (`"ggml"` as a little-endian u32),
+/// the format whisper.cpp loads.
+const GGML_MAGIC: u32 = 0x6767_6d6c;
+/// Smaller than the tiny model by far; anything below this is not a real model.
+const MIN_GGML_MODEL_BYTES: u64 = 1024 * 1024;
+
+/// Cheap sanity check before switching to a custom model: the file exists,
+/// is plausibly sized, and starts with the GGML magic.  Loading it is still
+/// the final word, but this catches wrong picks without a slow failed load.
+pub fn validate_ggml_file(path: &Path) -> Result<(), String> {
+    let meta = std::fs::metadata(path).map_err(|e| format!("Cannot read model file: {}", e))?;
+    if !meta.is_file() {
+        return Err("Model path is not a file".to_string());
+    }
+    if meta.len() < MIN_GGML_MODEL_BYTES {
+        return Err(format!("Model file is too small ({} bytes) to be a Whisper model", meta.len()));
+    }
+    let mut magic = [0u8; 4];
+    std::fs::File::open(path)
+        .and_then(|mut f| f.read_exact(&mut magic))
+        .map_err(|e| format!("Cannot read model file: {}", e))?;
+    if u32::from_le_bytes(magic) != GGML_MAGIC {
+        return Err("Not a GGML Whisper model (bad file header)".to_string());
+    }
+    Ok(())
+}
+
 // ── WhisperModelInfo (for frontend serialization) ────────────────────────────
 
 #[derive(Debug, Clone, Serialize)]
@@ -116,12 +197,16 @@ pub struct WhisperModelInfo {
     pub downloaded: bool,
     pub file_size_on_disk: u64,
     pub is_active: bool,
+    /// The Core ML encoder for this model is unpacked in the models directory.
+    pub coreml_downloaded: bool,
 }
 
 impl WhisperModelInfo {
     pub fn from_model(model: &WhisperModel, active_model: &WhisperModel) -> Self {
         let dir = models_dir();
-        let path = dir.join(model.filename());
+        // Not `dir.join(filename)`: a custom model lives outside the models
+        // directory and has no file name there.
+        let path = model.local_path();
         let (downloaded, file_size_on_disk) = match std::fs::metadata(&path) {
             Ok(m) => (true, m.len()),
             Err(_) => (false, 0),
@@ -135,6 +220,7 @@ impl WhisperModelInfo {
             downloaded,
             file_size_on_disk,
             is_active: model == active_model,
+            coreml_downloaded: model.coreml_encoder_dir().is_some_and(|d| dir.join(d).is_dir()),
         }
     }
 }
@@ -178,3 +264,74 @@ pub fn recommend_model(system: &SystemInfo, settings_language: Option<&str>) ->
         WhisperModel::Base
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// whisper.cpp's `whisper_get_coreml_path_encoder`: drop the extension and
+    /// any `-qX_Y` quantization suffix, then append `-encoder.mlmodelc`.
+    fn whisper_cpp_encoder_path(filename: &str) -> String {
+        let mut stem = filename.rsplit_once('.').map_or(filename, |(s, _)| s);
+        if let Some((head, tail)) = stem.rsplit_once('-') {
+            let b = tail.as_bytes();
+            if b.len() == 4 && b[0] == b'q' && b[2] == b'_' {
+                stem = head;
+            }
+        }
+        format!("{}-encoder.mlmodelc", stem)
+    }
+
+    #[test]
+    fn coreml_encoder_dir_matches_whisper_cpp_lookup() {
+        for model in [
+            WhisperModel::LargeV3Turbo,
+            WhisperModel::LargeV3TurboQ5,
+            WhisperModel::Medium,
+            WhisperModel::Small,
+            WhisperModel::Base,
+        ] {
+            assert_eq!(
+                model.coreml_encoder_dir().map(str::to_string),
+                Some(whisper_cpp_encoder_path(model.filename())),
+                "{:?}",
+                model
+            );
+        }
+        assert_eq!(WhisperModel::LargeV3TurboZhTw.coreml_encoder_dir(), None);
+    }
+
+    #[test]
+    fn validate_ggml_file_checks_size_and_magic() {
+        let dir = std::env::temp_dir().join(format!("sumi-ggml-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut data = GGML_MAGIC.to_le_bytes().to_vec();
+        data.resize(MIN_GGML_MODEL_BYTES as usize, 0);
+        let good = dir.join("good.bin");
+        std::fs::write(&good, &data).unwrap();
+        assert!(validate_ggml_file(&good).is_ok());
+
+        data[..4].copy_from_slice(b"GGUF");
+        let bad_magic = dir.join("bad.bin");
+        std::fs::write(&bad_magic, &data).unwrap();
+        assert!(validate_ggml_file(&bad_magic).is_err());
+
+        let tiny = dir.join("tiny.bin");
+        std::fs::write(&tiny, GGML_MAGIC.to_le_bytes()).unwrap();
+        assert!(validate_ggml_file(&tiny).is_err());
+        assert!(validate_ggml_file(&dir.join("missing.bin")).is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn custom_model_serializes_with_path() {
+        let model = WhisperModel::Custom { path: PathBuf::from("/models/ft.bin") };
+        let json = serde_json::to_string(&model).unwrap();
+        assert_eq!(json, r#"{"custom":{"path":"/models/ft.bin"}}"#);
+        assert_eq!(model.local_path(), PathBuf::from("/models/ft.bin"));
+        assert_eq!(model.describe(), "Custom Whisper (ft.bin)");
+        assert_eq!(WhisperModel::Base.describe(), "Whisper Base");
+    }
+}
@@ -90,7 +90,7 @@ impl WhisperPreviewFeeder {
             params.set_no_context(true);
             params.set_temperature_inc(0.6);
             params.set_no_speech_thold(0.5);
-            params.set_n_threads(crate::transcribe::num_cpus() as _);
+            params.set_n_threads(crate::transcribe::whisper_thread_count() as _);
             wh_state
                 .full(params, &self.buffer)
                 .map_err(|e| format!("Whisper preview inference failed: {}", e))
@@ -287,7 +287,7 @@ pub(crate) fn transcribe_import_segments(
         params.set_no_context(true);
         params.set_temperature_inc(0.6);
         params.set_no_speech_thold(0.5);
-        params.set_n_threads(crate::transcribe::num_cpus() as _);
+        params.set_n_threads(crate::transcribe::whisper_thread_count() as _);
         wh_state
             .full(params, samples)
             .map_err(|e| format!("Whisper import inference failed: {}", e))?;
@@ -398,7 +398,7 @@ pub(crate) fn transcribe_meeting_chunk<'a>(
         params.set_no_context(true);
         params.set_temperature_inc(0.6);
         params.set_no_speech_thold(0.5);
-        params.set_n_threads(crate::transcribe::num_cpus() as _);
+        params.set_n_threads(crate::transcribe::whisper_thread_count() as _);
         wh_state
             .full(params, samples)
             .map_err(|e| format!("Whisper meeting inference failed: {}", e))